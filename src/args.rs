@@ -1,22 +1,34 @@
-use std::path::PathBuf;
+use std::{collections::HashSet, path::PathBuf};
 
 use clap::{*, builder::*};
 use error::ErrorKind;
 
-use crate::{board::corner_radius::CornerRadius, board_pos::{parse_board_pos, BoardPos}};
+use crate::{aliases::BoardIndex as Idx, board::corner_radius::CornerRadius, board_pos::{parse_board_pos, parse_board_pos_list, BoardPos}, leaper::{parse_piece, Leaper}, warnsdorff::CountedTours};
 
 use crate::board_size::{parse_board_size, BoardSize};
 
-// todo maybe: add "invert image" option to swap accessible and inaccessible squares
-
 /// Calculates a knight's tour on a board of the given size with the provided dimensions and starting position.
 #[derive(Parser, Clone, Debug)]
+#[command(after_long_help = "\
+Exit codes:
+  0  a tour was found (and written, unless --quiet is set)
+  1  a file could not be read or written, the starting position or --prefix-moves don't fit the board,
+     or the board is too large for --count-solutions
+  2  invalid arguments
+  3  no tour exists for the board, or none was found (even if --best-effort wrote the longest path instead)
+  4  a tour failed verification
+  5  the tour is open despite --fail-on-open, or closed despite --fail-on-closed
+
+Environment:
+  KT_NO_DEPRECATION_WARNINGS  if set, deprecated arguments are accepted without a warning
+  NO_COLOR                    if set to anything but an empty string, --color without a value doesn't color the board")]
 pub struct Args{
     #[command(flatten)]
     pub input: InputArgs,
 
-    /// If set, the program will not print the board and only output the time taken to reach a conclusion
-    #[arg(long, short, conflicts_with_all(["output_file", "output_format"]))]
+    /// If set, the program will not print the board and only output the time taken to reach a conclusion.
+    /// The board is still written to --output-file if one is given
+    #[arg(long, short)]
     pub quiet: bool,
 
     /// If set, the program will output the board to the specified file in the specified format.
@@ -28,30 +40,207 @@ pub struct Args{
     /// The format to use when outputting the board. See --output-file for more information
     /// 
     /// If set to auto, the program will choose the format based on the file extension of the output file
-    /// (svg for .svg, text otherwise)
-    #[arg(long, short = 'O', default_value = "auto", requires = "output_file")]
+    /// (svg for .svg, text otherwise). Without an output file, auto means text
+    #[arg(long, short = 'O', default_value = "auto")]
     pub output_format: OutputFormat,
 
+    /// Solves every board size listed in the given file, one per line in the same form as --board-size.
+    /// Empty lines and lines starting with '#' are ignored.
+    ///
+    /// Prints a summary table unless --output-dir is set. Solving many sizes in one invocation shares the cache
+    /// of partial solutions between them.
+    #[arg(long, conflicts_with_all(["board_size", "board_file", "output_file", "quiet"]))]
+    pub batch: Option<PathBuf>,
+
+    /// Writes the board for each size in the --batch file to this directory, named after the size (e.g. "12x9.svg")
+    /// and using --output-format (text unless svg is requested)
+    #[arg(long, requires = "batch")]
+    pub output_dir: Option<PathBuf>,
+
+    /// What to do when a file --output-file or --output-dir would write is already there. "error" and "skip" are checked
+    /// before solving, so no time is spent on a board that won't be written. With --batch, "error" checks every file first
+    /// and "skip" only leaves out the sizes whose files exist
+    #[arg(long, default_value = "clobber")]
+    pub overwrite_policy: OverwritePolicy,
+
+    #[command(flatten)]
+    pub checks: TourChecks,
+
+    #[command(flatten)]
+    pub svg_style: SvgStyle,
+
+    /// Makes the tour look less regular (particularly the seams between the sectors of divide and conquer)
+    /// by trying this many times to swap two of its moves for two others. The ends of the tour stay the same
+    #[arg(long, value_name = "ITERATIONS")]
+    pub shuffle: Option<usize>,
+
+    /// If set, an open tour is turned into a closed one where that only takes linking its ends or reversing
+    /// the last part of the tour. Whether that worked is reported on stderr
+    #[arg(long)]
+    pub prefer_closed: bool,
+
+    /// Reverses the solved tour if needed, so that it runs in a predictable direction
+    #[arg(long, default_value = "as-solved")]
+    pub canonical_direction: CanonicalDirection,
+
+    /// Colors the move numbers of the text board from blue for the first moves to red for the last ones, so that the way the tour
+    /// runs is visible at a glance. Without a value, colors are only used when stdout is a terminal and NO_COLOR isn't set
+    #[arg(long, value_name = "WHEN", num_args = 0..=1, default_value = "never", default_missing_value = "auto")]
+    pub color: ColorChoice,
+
+    /// If set, prints how long each phase of the solver took (and for divide and conquer, how long the sectors of each size took) to stderr.
+    /// SVG output also contains the table as a comment
+    #[arg(long)]
+    pub timings: bool,
+
+    /// How the elapsed time is written on the text board, the SVG title and with --quiet
+    #[arg(long, value_name = "FORMAT", default_value = "seconds")]
+    pub time_format: Precision,
+
+    /// If set, explains on stderr why divide and conquer cuts the board into the sectors it does:
+    /// how each side was split into segments, and which segments were cut up further and why. Ignored when using Warnsdorff
+    #[arg(long, conflicts_with = "batch")]
+    pub explain_partitions: bool,
+
+    /// Writes the board the solver is given, after reading --board-file or applying --corner-radius and the other options that shape it,
+    /// to this file before solving. Image files (by extension) show accessible squares as black pixels and inaccessible squares as white ones,
+    /// other files are text with '#' for accessible squares and spaces for inaccessible ones. Either can be passed back to --board-file
+    #[arg(long, value_name = "FILE", conflicts_with = "batch")]
+    pub emit_mask: Option<PathBuf>,
+
+    /// Shows how many accessible squares a move away from each accessible square there are before solving, which is where tours
+    /// have a hard time: squares with few of them are difficult to get into and out of again. SVG output draws the squares
+    /// beneath the tour, colored from red for none to green for eight, text output prints a digit per square before the tour
+    #[arg(long, conflicts_with = "batch")]
+    pub degree_map: bool,
+
+    /// Stops before solving, once --emit-mask and --explain-partitions are written.
+    /// With --degree-map, the map is written to --output-file (or stdout) instead of the tour. Otherwise the sectors divide and conquer
+    /// would solve the board in are, as a table of their offset, size, mode and merge direction, or drawn over the board for SVG output
+    #[arg(long, conflicts_with = "batch")]
+    pub dry_run: bool,
+
+    /// Counts the tours of the board instead of finding one, by trying every path, and prints the count and the time it took.
+    /// Without a value, every tour is counted, "closed" only counts the closed ones. Only boards with at most 40 accessible
+    /// squares are counted, larger ones would take practically forever
+    #[arg(
+        long,
+        value_name = "TOURS",
+        num_args = 0..=1,
+        default_missing_value = "all",
+        conflicts_with_all([
+            "batch", "output_file", "dry_run", "degree_map", "shuffle", "prefer_closed", "canonical_direction",
+            "verify", "fail_on_open", "fail_on_closed", "starting_pos", "prefix_moves", "resume", "ending_pos", "knights", "heatmap",
+        ])
+    )]
+    pub count_solutions: Option<CountedTours>,
+
+    /// If set, the program doesn't print the summary line it otherwise ends with on stderr.
+    /// The line consists of space separated key=value pairs, e.g. "result=solved size=8x8 solver=dnc closed=true duration_ms=1"
+    #[arg(long)]
+    pub no_summary: bool,
+
     /// If set, the program will print additional debug information. Specify up to three times for progressively more information
     #[arg(long, short, action = ArgAction::Count)]
     pub verbose: u8,
+
+    /// Collapses runs of identical lines of the debug output into the first of them and a "… repeated N times" line,
+    /// which keeps the output of -vvv manageable while Warnsdorff backtracks. Lines are compared per verbosity level.
+    /// Has no effect with the tracing feature, whose subscriber decides what is written
+    #[arg(long, requires = "verbose")]
+    pub debug_dedupe: bool,
+
+    /// If Warnsdorff gives up, runs out of moves to take back or proves that there is no tour, writes the furthest it got to this file:
+    /// the squares in the order they were visited, as --prefix-moves reads them, followed by an empty line
+    /// and the board as --emit-mask writes it. --resume continues the search from there. Divide and conquer writes the sector that failed last, even if solving it
+    /// another way worked out
+    #[arg(long, value_name = "FILE")]
+    pub failure_dump: Option<PathBuf>,
+
+    /// Writes an SVG image to this file showing how often Warnsdorff took a move back on each square, which is where the search struggled.
+    /// Squares it never backtracked from stay blank, the others are colored from yellow to red on a logarithmic scale.
+    /// The tour is drawn over them if one was found, the image is written either way
+    #[arg(long, value_name = "FILE", requires = "warnsdorff_base", conflicts_with_all(["batch", "dry_run", "knights"]))]
+    pub heatmap: Option<PathBuf>,
+
+    /// If Warnsdorff finds no tour, writes the longest path it got to instead of nothing. Boards known to have no tour are searched
+    /// all the same. The output says that the path is partial
+    /// and the summary line reads "result=partial ... complete=false". The program still exits with code 3
+    #[arg(long, requires = "warnsdorff_base", conflicts_with_all(["batch", "dry_run", "knights", "count_solutions"]))]
+    pub best_effort: bool,
+
+    /// Keeps the stretched sectors divide and conquer solves in this directory, one file per size and direction, so that later runs
+    /// of the same version don't have to solve them again. Files that were cut short, don't match their checksum, were written by another
+    /// version or don't hold a complete path keeping the moves merges rely on are ignored and solved again. The built in sectors are never
+    /// taken from the directory
+    #[arg(long, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
 }
 
 impl Args {
     pub fn parse() -> Self {
+        Self::parse_from(std::env::args_os())
+    }
+
+    fn built_command() -> Command {
         // unfortunately, arg groups that accept multiple options at once are not supported by the derive macro
         // except by moving all the options into a subcommand, which is not what we want here
         let mut builder = Self::command()
-            .group(ArgGroup::new("warnsdorff_base").args(vec!["use_warnsdorff", "board_file"]).multiple(true));
+            .group(ArgGroup::new("warnsdorff_base").args(vec!["use_warnsdorff", "board_file", "wrap"]).multiple(true));
         builder.build();
-        let matches = builder.get_matches();
+        builder
+    }
+
+    /// Parses the given arguments (starting with the program name), exiting with an error message if they are invalid
+    pub fn parse_from(args: impl IntoIterator<Item = impl Into<std::ffi::OsString> + Clone>) -> Self {
+        let matches = Self::built_command().get_matches_from(args);
+        if let Err(e) = check_starting_pos(&matches) {
+            e.exit();
+        }
         let mut res = Self::from_arg_matches(&matches).unwrap();
+        // clap leaves the options out altogether if none of them is given, even though they all have defaults
+        if res.input.warnsdorff.is_none() {
+            res.input.warnsdorff = Warnsdorff::from_arg_matches(&matches).ok();
+        }
+        let deprecated: Vec<_> = deprecations(&matches).collect();
+        if std::env::var_os("KT_NO_DEPRECATION_WARNINGS").is_none() {
+            for deprecation in &deprecated {
+                eprintln!("{deprecation}");
+            }
+        }
 
         if !res.input.use_warnsdorff && res.input.board_size.is_none() {
             res.input.board_size = Some(BoardSize::new(8, 8));
         }
 
+        if let (Some(size), Some(dead)) = (res.input.board_size, res.input.warnsdorff.as_ref().and_then(|w| w.dead_squares.as_ref())) {
+            let outside: Vec<_> = dead.0.iter().filter(|&&pos| !size.fits(pos)).map(|pos| pos.to_string()).collect();
+            if !outside.is_empty() {
+                Command::new("")
+                    .error(ErrorKind::ValueValidation, format!("'--dead-squares' lists squares outside the {size} board: {}.", outside.join(", ")))
+                    .exit();
+            }
+        }
+
         if let Some(ref warnsdorff) = res.input.warnsdorff {
+            if !warnsdorff.piece.is_knight() && !res.input.use_warnsdorff {
+                Command::new("")
+                    .error(ErrorKind::ArgumentConflict, format!("'--piece {}' requires '--use-warnsdorff', divide and conquer only finds knight's tours.", warnsdorff.piece))
+                    .exit();
+            }
+            // checked here rather than with `requires`, so that the message can say why
+            let seeded = res.input.use_warnsdorff || res.input.solve_order == SolveOrder::Random;
+            if warnsdorff.random_seed.is_some() && !seeded && !without_warnsdorff(&matches, &["random_seed"]) {
+                Command::new("")
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "'--random-seed' requires '--use-warnsdorff' or '--solve-order random', divide and conquer otherwise finds the same tour for every seed. Use '--shuffle' to vary its tours.",
+                    )
+                    .exit();
+            }
+            if warnsdorff.mode != SearchMode::Basic {
+                check_structured_mode(&matches, warnsdorff.mode, res.input.board_size.is_some());
+            }
             if warnsdorff.invert_image_mode && !matches!(warnsdorff.board_file_format, Some(BoardFileType::Image)) {
                 Command::new("")
                     .error(ErrorKind::ArgumentConflict, "'--invert-image-mode' requires '--board-file-format' to be 'image'.")
@@ -63,11 +252,164 @@ impl Args {
     }
 }
 
+/// '--wrap none' satisfies the requirements of --starting-pos without turning Warnsdorff on, and divide and conquer
+/// would ignore where the tour is asked to start
+fn check_starting_pos(matches: &ArgMatches) -> Result<(), clap::Error> {
+    let given = matches.value_source("starting_pos") == Some(parser::ValueSource::CommandLine);
+    if given && !matches.get_flag("use_warnsdorff") {
+        return Err(Command::new("").error(
+            ErrorKind::ArgumentConflict,
+            "'--starting-pos' requires '--use-warnsdorff', divide and conquer decides where its tours start.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Structured tours are searched on the plain board from a fixed start, so anything changing either would be ignored
+fn check_structured_mode(matches: &ArgMatches, mode: SearchMode, has_size: bool) {
+    let mode = mode.to_possible_value().unwrap();
+    if !has_size {
+        Command::new("")
+            .error(ErrorKind::MissingRequiredArgument, format!("'--mode {}' requires '--board-size'.", mode.get_name()))
+            .exit();
+    }
+
+    let ignored = [
+        "board_file", "corner_radius", "dead_squares", "starting_pos", "prefix_moves", "snap_start", "random_seed", "piece", "knights", "lookahead", "tie_break", "resume", "ending_pos", "backtrack_limit",
+    ];
+    let wraps = matches.get_one::<Wrap>("wrap").is_some_and(|&wrap| wrap != Wrap::None);
+    let given = ignored.iter().find(|&&id| matches.value_source(id) == Some(parser::ValueSource::CommandLine)).copied().or(wraps.then_some("wrap"));
+    if let Some(id) = given {
+        Command::new("")
+            .error(ErrorKind::ArgumentConflict, format!("'--mode {}' can't be combined with '--{}', structured tours start on A1 of the plain board.", mode.get_name(), id.replace('_', "-")))
+            .exit();
+    }
+}
+
+/// An argument combination that used to be accepted and still is until the version it is removed in,
+/// with a warning on stderr naming what to use instead
+#[derive(Debug)]
+pub struct Deprecation {
+    pub old: &'static str,
+    pub replacement: &'static str,
+    pub removal: &'static str,
+    applies: fn(&ArgMatches) -> bool,
+}
+
+impl std::fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "warning: {} is deprecated and will be rejected from version {} on. {}.", self.old, self.removal, self.replacement)
+    }
+}
+
+/// Every deprecated form there is. Entries are removed along with the forms they describe
+pub const DEPRECATIONS: &[Deprecation] = &[
+    Deprecation {
+        old: "'--prefix-moves', '--snap-start' or '--random-seed' with '--wrap none' instead of '--use-warnsdorff'",
+        replacement: "Add '--use-warnsdorff', divide and conquer ignores where the tour is asked to start",
+        removal: "0.2.0",
+        applies: |matches| without_warnsdorff(matches, &["prefix_moves", "snap_start", "random_seed"]),
+    },
+    Deprecation {
+        old: "'--lookahead', '--tie-break', '--exhaustive-limit' or '--knights' with '--wrap none' instead of '--use-warnsdorff'",
+        replacement: "Add '--use-warnsdorff', divide and conquer ignores how Warnsdorff is asked to search",
+        removal: "0.2.0",
+        applies: |matches| without_warnsdorff(matches, &["lookahead", "tie_break", "exhaustive_limit", "knights"]),
+    },
+];
+
+/// '--wrap none' counts as asking for Warnsdorff where options require it, even though it doesn't turn it on
+fn without_warnsdorff(matches: &ArgMatches, ids: &[&str]) -> bool {
+    let given = |id: &str| matches.value_source(id) == Some(parser::ValueSource::CommandLine);
+    given("wrap") && !matches.get_flag("use_warnsdorff") && ids.iter().any(|&id| given(id))
+}
+
+fn deprecations(matches: &ArgMatches) -> impl Iterator<Item = &'static Deprecation> + '_ {
+    DEPRECATIONS.iter().filter(|deprecation| (deprecation.applies)(matches))
+}
+
+/// Checks applied to the solved tour before it is written
+#[derive(Parser, Clone, Copy, Debug)]
+pub struct TourChecks {
+    /// If set, the solved tour is checked for illegal moves, inconsistent links and squares visited more than once
+    /// before it is written. The program exits with an error if the tour is invalid
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Exits with an error (code 5) if the solved tour is open. Implies --verify
+    #[arg(long, conflicts_with = "fail_on_closed")]
+    pub fail_on_open: bool,
+
+    /// Exits with an error (code 5) if the solved tour is closed. Implies --verify
+    #[arg(long)]
+    pub fail_on_closed: bool,
+}
+
+/// How the SVG output looks. Colors are used as given, so anything SVG understands works (e.g. "white", "#1e1e1e" or "rgb(30,30,30)")
+#[derive(Parser, Clone, Debug, PartialEq)]
+pub struct SvgStyle {
+    /// The color of the moves and the title. With several knights, only the path of the first one uses it
+    #[arg(long = "svg-stroke-color", value_name = "COLOR", default_value = "black", value_parser = parse_svg_value)]
+    pub stroke: String,
+
+    /// The width of the lines drawn for the moves
+    #[arg(long = "svg-stroke-width", value_name = "WIDTH", default_value = "1.5")]
+    pub stroke_width: f32,
+
+    /// The color of the lines between the squares
+    #[arg(long = "svg-grid-color", value_name = "COLOR", default_value = "gray", value_parser = parse_svg_value)]
+    pub grid_color: String,
+
+    /// Fills the whole image with this color. Without it, the image is transparent
+    #[arg(long = "svg-bg", value_name = "COLOR", value_parser = parse_svg_value)]
+    pub background: Option<String>,
+
+    /// The color of the squares the tour can't visit, e.g. those cut off by --corner-radius
+    #[arg(long = "svg-dead-color", value_name = "COLOR", default_value = "#333", value_parser = parse_svg_value)]
+    pub dead_color: String,
+
+    /// The font of the title
+    #[arg(long = "svg-font", value_name = "FONT", default_value = "Arial", value_parser = parse_svg_value)]
+    pub font_family: String,
+
+    /// If set, the lines between the squares are left out
+    #[arg(long = "svg-no-grid", action = ArgAction::SetFalse)]
+    pub show_grid: bool,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            stroke: "black".into(),
+            stroke_width: 1.5,
+            grid_color: "gray".into(),
+            background: None,
+            dead_color: "#333".into(),
+            font_family: "Arial".into(),
+            show_grid: true,
+        }
+    }
+}
+
+/// Values are written into the attributes as they are, so they can't contain anything that would end the attribute or the tag
+fn parse_svg_value(arg: &str) -> Result<String, String> {
+    match arg.chars().find(|c| matches!(c, '"' | '<' | '>' | '&')) {
+        Some(c) => Err(format!("'{c}' is not allowed in SVG attribute values")),
+        None => Ok(arg.to_string()),
+    }
+}
+
 #[derive(Parser, Clone, Debug)]
 pub struct InputArgs {
     /// If set, the program will use the Warnsdorff heuristic to calculate the knight's tour.
     /// Warning: This can take a long time for large boards.
-    #[arg(long, short = 'w', default_value_if("board_file", ArgPredicate::IsPresent, "true"))]
+    #[arg(long, short = 'w', default_value_ifs([
+        ("board_file", ArgPredicate::IsPresent, Some("true")),
+        ("wrap", ArgPredicate::Equals("horizontal".into()), Some("true")),
+        ("wrap", ArgPredicate::Equals("vertical".into()), Some("true")),
+        ("wrap", ArgPredicate::Equals("both".into()), Some("true")),
+    ]))]
     pub use_warnsdorff: bool,
 
     #[command(flatten)]
@@ -75,9 +417,31 @@ pub struct InputArgs {
 
     /// The size of the board in the form <WIDTH>[x<HEIGHT>]
     /// 
-    /// e.g. "12x9" for a 12 wide, 9 high board or "23" for a 23x23 board
+    /// e.g. "12x9" for a 12 wide, 9 high board or "23" for a 23x23 board. "X" and "×" work as well as "x"
     #[arg(long, short = 's', conflicts_with("board_file"), value_parser = parse_board_size)]
     pub board_size: Option<BoardSize>,
+
+    /// The order in which the divide and conquer solver solves the chunks of the board. A chunk is merged into the
+    /// tour as soon as the chunk it attaches to is, so the merges follow this order as far as possible.
+    /// Ignored when using Warnsdorff
+    #[arg(long, default_value = "default")]
+    pub solve_order: SolveOrder,
+}
+
+impl InputArgs {
+    /// Whether the board is a rectangle of --board-size, with at most the squares of --corner-radius and --dead-squares taken off,
+    /// without wrapping edges, and the tour is a knight's tour
+    pub fn is_rectangle(&self) -> bool {
+        self.warnsdorff.as_ref().is_none_or(|w| w.board_file.is_none() && w.wrap == Wrap::None && w.piece.is_knight() && w.knights == 1)
+    }
+
+    /// The squares --corner-radius and --dead-squares take off a board of the given size
+    pub fn dead_squares(&self, size: BoardSize) -> HashSet<BoardPos> {
+        let Some(ref warnsdorff) = self.warnsdorff else { return HashSet::new() };
+        let mut dead = warnsdorff.corner_radius.map(|radius| radius.dead_squares(size)).unwrap_or_default();
+        dead.extend(warnsdorff.dead_squares.iter().flat_map(|list| list.0.iter().copied()));
+        dead
+    }
 }
 
 #[derive(Parser, Clone, Debug)]
@@ -118,10 +482,32 @@ pub struct Warnsdorff {
     )]
     pub image_mode: Option<ImageMode>,
 
+    /// Swaps accessible and inaccessible squares of a board image after applying --image-mode
     #[arg(long, short = 'I', requires = "board_file_format")]
     pub invert_image_mode: bool,
 
-    /// If set, the program will only consider squares with an alpha value greater than this threshold as accessible
+    /// Removes the rows and columns along the edges of the board file that have no accessible squares.
+    /// Positions such as --starting-pos refer to the trimmed board
+    #[arg(long, requires = "board_file")]
+    pub trim_board: bool,
+
+    /// Surrounds the board read from --board-file with this many rings of inaccessible squares, after --trim-board.
+    /// Positions such as --starting-pos refer to the padded board
+    #[arg(long, value_name = "RINGS", requires = "board_file")]
+    pub pad_board: Option<Idx>,
+
+    /// Resizes the board image to this size before deciding which squares are accessible, instead of using one square per pixel.
+    /// --threshold applies to the resized pixels, so with --image-filter other than nearest, a square is accessible if the
+    /// pixels it covers are accessible on average
+    #[arg(long, value_name = "WxH", value_parser = parse_board_size, requires = "image_mode")]
+    pub image_board_size: Option<BoardSize>,
+
+    /// How pixels are combined when resizing the board image for --image-board-size. Averaging filters create pixels
+    /// that are neither black nor white, so --image-mode black-white only works with nearest
+    #[arg(long, default_value = "triangle", requires = "image_board_size")]
+    pub image_filter: ImageFilter,
+
+    /// The alpha (or luminance) value from which on a pixel is considered accessible. See --board-file-format for more information
     #[arg(
         short,
         long,
@@ -137,20 +523,118 @@ pub struct Warnsdorff {
     /// Individual values can be separated by either whitespace or commas. The order is top-left, top-right, bottom-right, bottom-left.
     /// Each corner value can either be a single number or a pair of numbers separated by a comma and enclosed in round brackets.
     /// In that case, the first number is the horizontal radius, the second the vertical radius.
+    ///
+    /// Without --use-warnsdorff, the tour is solved for the full rectangle first and then routed around the cut off corners.
+    /// If that fails, the board is solved with Warnsdorff instead.
     #[arg(
         long,
         short,
         value_parser = CornerRadius::parse,
-        requires_all = ["use_warnsdorff", "board_size"],
+        requires = "board_size",
         help = "The corner radius of the board. If set, the board will have rounded corners"
     )]
     pub corner_radius: Option<CornerRadius>,
 
+    /// Squares to take off the board, separated by commas or whitespace (e.g. "C3,D5,F1"). Together with --corner-radius,
+    /// the squares of both are taken off
+    #[arg(long, value_name = "SQUARES", value_parser = parse_dead_squares, requires = "board_size")]
+    pub dead_squares: Option<DeadSquares>,
+
     /// The starting position in the form <COLUMN>[-]<ROW> as on a normal chess board, starting in the upper left corner at A1 (or A-1).
     /// 
-    /// The 27th column is addressed as AA, then follows AB, AC, ..., 52 is AZ, 53 is BA and so on
-    #[arg(long, short = 'p', default_value = "A1", value_parser = parse_board_pos, requires = "warnsdorff_base")]
-    pub starting_pos: Option<BoardPos>,
+    /// The 27th column is addressed as AA, then follows AB, AC, ..., 52 is AZ, 53 is BA and so on.
+    ///
+    /// Squares can also be given as <COLUMN>,<ROW> in numbers counting from 1 (e.g. "28,12" is AB-12), or counting from 0
+    /// when prefixed with "0:" (e.g. "0:27,11"). This works wherever squares are listed, too.
+    ///
+    /// Alternatively, "random" starts on a random accessible square (see --random-seed) and "auto" starts on the
+    /// accessible square with the fewest onward moves, picking the topmost, then leftmost one if there are several.
+    ///
+    /// Tours start on A1 if this isn't given. Requires --use-warnsdorff, divide and conquer decides where its tours start
+    #[arg(long, short = 'p', value_parser = parse_start_pos, requires = "warnsdorff_base")]
+    pub starting_pos: Option<StartPos>,
+
+    /// Squares the tour has to start with, in the order they are visited: either a file or an inline list,
+    /// separated by commas or whitespace (e.g. "A1,C2,E1"). The search continues from the last of them and never
+    /// takes any of them back. Replaces --starting-pos
+    #[arg(long, value_parser = parse_prefix_moves, requires = "warnsdorff_base", conflicts_with = "starting_pos")]
+    pub prefix_moves: Option<PrefixMoves>,
+
+    /// Continues the search from a file written by --failure-dump (or any file listing squares as --prefix-moves reads them)
+    /// instead of starting over. The squares are replayed as if Warnsdorff had moved there itself, so unlike --prefix-moves,
+    /// they are taken back if the search gets stuck after them. The board has to be the one the file was written for.
+    /// Replaces --starting-pos
+    #[arg(long, value_name = "FILE", requires = "warnsdorff_base", conflicts_with_all(["starting_pos", "prefix_moves"]))]
+    pub resume: Option<PathBuf>,
+
+    /// The square the tour has to end on, in the same form as --starting-pos. Warnsdorff's rule only looks ahead from where
+    /// the knight is, so a search that gets stuck or exceeds --backtrack-limit is tried once more from the ending position
+    /// to the starting position, and the tour it finds is reversed. Only a fixed starting position is tried the other way round
+    #[arg(long, value_name = "SQUARE", value_parser = parse_board_pos, requires = "warnsdorff_base", conflicts_with_all(["prefix_moves", "resume", "snap_start"]))]
+    pub ending_pos: Option<BoardPos>,
+
+    /// The seed for --starting-pos random, or for --solve-order random with divide and conquer. Without it, the seed is taken
+    /// from the current time.
+    ///
+    /// Requires --use-warnsdorff or --solve-order random: divide and conquer otherwise builds the same tour whatever the seed,
+    /// use --shuffle to vary its tours
+    #[arg(long)]
+    pub random_seed: Option<u64>,
+
+    /// Connects opposite edges of the board, so that moves leaving the board on one side reappear on the other side.
+    ///
+    /// Implies --use-warnsdorff, which finds tours on wrapped boards quickly even when they are large
+    #[arg(long, default_value = "none")]
+    pub wrap: Wrap,
+
+    /// If the starting position is outside the board or on an inaccessible square, start on the closest accessible
+    /// square instead of failing. Ties are broken by picking the topmost, then leftmost square
+    #[arg(long, requires = "warnsdorff_base")]
+    pub snap_start: bool,
+
+    /// The piece that tours the board: knight, camel (which jumps 1 and 3 squares), giraffe (1 and 4) or any other leaper
+    /// given as the two distances it jumps, e.g. "2,3". Pieces other than the knight require --use-warnsdorff
+    #[arg(long, default_value = "knight", value_parser = parse_piece)]
+    pub piece: Leaper,
+
+    /// Covers the board with this many knights (or other pieces, see --piece) instead of one. Their paths don't share any squares
+    /// and together visit every accessible square. The knights start spread out over the board and take turns moving.
+    /// Text output prefixes each move number with the number of the knight making it (e.g. "2:17"), SVG output draws each path in its own color
+    #[arg(
+        long,
+        default_value = "1",
+        value_parser = value_parser!(u64).range(1..=255).map(|knights| knights as usize),
+        requires = "warnsdorff_base",
+        conflicts_with_all(["starting_pos", "prefix_moves", "resume", "ending_pos", "shuffle", "prefer_closed", "canonical_direction", "verify", "fail_on_open", "fail_on_closed"])
+    )]
+    pub knights: usize,
+
+    /// How many moves ahead to look when ordering the moves. 1 picks the square with the fewest onward moves,
+    /// higher values count the moves from there on as well, which can avoid backtracking on irregular boards
+    #[arg(long, default_value = "1", value_parser = value_parser!(u8).range(1..=4), requires = "warnsdorff_base")]
+    pub lookahead: u8,
+
+    /// How to choose between moves that look equally good
+    #[arg(long, default_value = "none", requires = "warnsdorff_base")]
+    pub tie_break: TieBreak,
+
+    /// The kind of tour Warnsdorff searches for. Besides any tour (basic), these are the tours divide and conquer builds its sectors from:
+    /// closed starts and ends on A1 (leaving A1 out on boards with an odd number of squares), stretched-h runs from A1 to A2
+    /// and stretched-v from A1 to B1, both keeping the moves the next sectors are merged through.
+    /// The structured tours are searched on the plain rectangle from their fixed start, so they can't be combined with the options
+    /// that shape the board, pick the start or change the piece
+    #[arg(long, default_value = "basic", requires = "warnsdorff_base")]
+    pub mode: SearchMode,
+
+    /// Boards with at most this many accessible squares are searched exhaustively once Warnsdorff gets stuck on them,
+    /// which proves that there is no tour from the starting position if none is found. 0 turns this off
+    #[arg(long, value_name = "SQUARES", default_value = "40", requires = "warnsdorff_base")]
+    pub exhaustive_limit: usize,
+
+    /// Gives up once Warnsdorff has taken back more than this many moves, writing --failure-dump if it is set so that the search
+    /// can be picked up again with --resume. Boards small enough for --exhaustive-limit are handed over to the exhaustive search instead
+    #[arg(long, value_name = "BACKTRACKS", requires = "warnsdorff_base")]
+    pub backtrack_limit: Option<usize>,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -158,6 +642,8 @@ pub enum OutputFormat {
     Auto,
     Text,
     Svg,
+    /// One character per square, showing the direction of the next move as an arrow
+    Arrows,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -172,8 +658,294 @@ pub enum ImageMode {
     Luminance,
 }
 
+/// How pixels are combined when resizing a board image
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ImageFilter {
+    /// Takes the closest pixel
+    Nearest,
+    /// Averages the pixels nearby, weighted by distance
+    #[default]
+    Triangle,
+    /// Cubic interpolation, keeping edges a little sharper
+    CatmullRom,
+    /// Gaussian blur
+    Gaussian,
+    /// Lanczos with a window of 3, the sharpest but slowest
+    Lanczos3,
+}
+
+/// Where the knight starts its tour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartPos {
+    Fixed(BoardPos),
+    Random,
+    Auto,
+}
+
+pub fn parse_start_pos(arg: &str) -> Result<StartPos, String> {
+    if arg.eq_ignore_ascii_case("random") {
+        Ok(StartPos::Random)
+    } else if arg.eq_ignore_ascii_case("auto") {
+        Ok(StartPos::Auto)
+    } else {
+        parse_board_pos(arg).map(StartPos::Fixed)
+    }
+}
+
+/// The squares a tour has to start with, in the order they are visited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixMoves(pub Vec<BoardPos>);
+
+/// Reads the squares from the file of the given name if there is one, and from the argument itself otherwise
+pub fn parse_prefix_moves(arg: &str) -> Result<PrefixMoves, String> {
+    let path = std::path::Path::new(arg);
+    let list = if path.is_file() {
+        std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?
+    } else {
+        arg.to_string()
+    };
+
+    let squares = parse_board_pos_list(&list)?;
+    if squares.is_empty() {
+        return Err("The prefix doesn't contain any squares".to_string());
+    }
+
+    Ok(PrefixMoves(squares))
+}
+
+/// The squares --dead-squares takes off the board, in the order they were listed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadSquares(pub Vec<BoardPos>);
+
+pub fn parse_dead_squares(arg: &str) -> Result<DeadSquares, String> {
+    let squares = parse_board_pos_list(arg)?;
+    if squares.is_empty() {
+        return Err("The list doesn't contain any squares".to_string());
+    }
+
+    Ok(DeadSquares(squares))
+}
+
+/// The kind of tour Warnsdorff searches for, see --mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SearchMode {
+    #[default]
+    Basic,
+    Closed,
+    StretchedH,
+    StretchedV,
+}
+
+/// What happens to an existing file where output is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OverwritePolicy {
+    /// Replace it
+    #[default]
+    Clobber,
+    /// Refuse to run
+    Error,
+    /// Rename it to <FILE>.bak-N with the first N not taken yet, then write the new one
+    Backup,
+    /// Keep it and don't solve the board, exiting successfully
+    Skip,
+}
+
+/// When the text board is colored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only when writing to a terminal and NO_COLOR isn't set
+    Auto,
+    /// Even when writing to a file or a pipe
+    Always,
+    #[default]
+    Never,
+}
+
+/// How Warnsdorff picks between moves with the same number of onward moves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TieBreak {
+    /// Keep the order in which the moves are generated
+    #[default]
+    None,
+    /// Prefer the move with the fewest onward moves one more move ahead (Pohl's rule)
+    Pohl,
+    /// Prefer the move farthest from the center of the board
+    Edge,
+}
+
+/// The order in which the divide and conquer solver works through the chunks of the board
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SolveOrder {
+    /// Row by row, starting in the top left corner
+    #[default]
+    Default,
+    /// Starting with the chunk in the center, then going around it in rings
+    Spiral,
+    /// By distance from the center of the board
+    CenterOut,
+    /// In a random order, seeded with --random-seed or from the current time
+    Random,
+}
+
+/// How elapsed times are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Precision {
+    /// In seconds to the millisecond (e.g. "83.456 seconds"), or to the microsecond below a millisecond
+    #[default]
+    Seconds,
+    /// In milliseconds to the microsecond (e.g. "83456.789 ms")
+    Millis,
+    /// In hours, minutes and seconds to a tenth of a second (e.g. "1m 23.4s"), or in milliseconds below a second and in
+    /// microseconds below a tenth of a millisecond
+    Human,
+}
+
+/// Which edges of the board the knight may jump across to reappear on the opposite side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Wrap {
+    /// A regular board
+    #[default]
+    None,
+    /// A cylinder, where the left and right edges are connected
+    Horizontal,
+    /// A cylinder, where the top and bottom edges are connected
+    Vertical,
+    /// A torus, where both pairs of opposite edges are connected
+    Both,
+}
+
+impl Wrap {
+    pub fn horizontal(self) -> bool {
+        matches!(self, Self::Horizontal | Self::Both)
+    }
+
+    pub fn vertical(self) -> bool {
+        matches!(self, Self::Vertical | Self::Both)
+    }
+
+    /// The same wrapping for a board with rows and columns swapped
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Horizontal => Self::Vertical,
+            Self::Vertical => Self::Horizontal,
+            other => other,
+        }
+    }
+}
+
+/// Which way round a solved tour is traversed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CanonicalDirection {
+    /// Keep the direction the solver produced
+    #[default]
+    AsSolved,
+    /// Start an open tour at whichever end comes first (by row, then by column).
+    /// Closed tours continue from their first square to the smaller of its two neighbors
+    StartTopleft,
+    /// End an open tour at whichever end comes first (by row, then by column).
+    /// Closed tours are treated the same as for start-topleft
+    EndTopleft,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum BoardFileType {
     Text,
     Image,
 }
+
+#[test]
+fn test_parse_start_pos() {
+    assert_eq!(parse_start_pos("B3"), Ok(StartPos::Fixed(BoardPos::new(1, 2))));
+    assert_eq!(parse_start_pos("random"), Ok(StartPos::Random));
+    assert_eq!(parse_start_pos("Auto"), Ok(StartPos::Auto));
+    assert!(parse_start_pos("A0").is_err());
+}
+
+#[test]
+fn test_dead_squares() {
+    assert_eq!(parse_dead_squares("C3,D5 F1"), Ok(DeadSquares(vec![BoardPos::new(2, 2), BoardPos::new(3, 4), BoardPos::new(5, 0)])));
+    assert_eq!(parse_dead_squares(" C3 ,, D5\n"), Ok(DeadSquares(vec![BoardPos::new(2, 2), BoardPos::new(3, 4)])));
+    assert!(parse_dead_squares("C3,??").is_err());
+    assert!(parse_dead_squares(" , ").is_err());
+
+    // the squares of --corner-radius are taken off as well
+    let input = Args::parse_from(["knights_tour", "-s", "8", "--dead-squares", "C3,A1", "--corner-radius", "4"]).input;
+    let dead = input.dead_squares(BoardSize::new(8, 8));
+    assert_eq!(dead.len(), 5);
+    assert!(dead.contains(&BoardPos::new(2, 2)) && dead.contains(&BoardPos::new(7, 7)));
+}
+
+#[test]
+fn test_wrap_implies_warnsdorff() {
+    let parse = |extra: &[&str]| Args::parse_from(["knights_tour", "-s", "8"].iter().chain(extra));
+    assert!(parse(&["--wrap", "both"]).input.use_warnsdorff);
+    assert!(parse(&["--wrap", "vertical", "-p", "B2"]).input.use_warnsdorff);
+    assert!(!parse(&[]).input.use_warnsdorff);
+    assert!(!parse(&["--wrap", "none"]).input.use_warnsdorff);
+}
+
+#[test]
+fn test_starting_pos_requires_warnsdorff() {
+    let check = |extra: &[&str]| {
+        let matches = Args::built_command().try_get_matches_from(["knights_tour", "-s", "8"].iter().chain(extra))?;
+        check_starting_pos(&matches)?;
+        Ok::<_, clap::Error>(Warnsdorff::from_arg_matches(&matches).unwrap())
+    };
+
+    for accepted in [&["-w", "-p", "B2"][..], &["--wrap", "both", "-p", "random"], &["-w", "--wrap", "none", "-p", "B2"], &["-w"], &[]] {
+        assert!(check(accepted).is_ok(), "{accepted:?}");
+    }
+    // without it, Warnsdorff starts on A1 where it reads the arguments
+    assert_eq!(check(&["-w"]).unwrap().starting_pos, None);
+    assert_eq!(Args::parse_from(["knights_tour", "-w"]).input.warnsdorff.map(|w| (w.starting_pos, w.exhaustive_limit)), Some((None, 40)));
+
+    let rejected = |extra: &[&str]| check(extra).unwrap_err().kind();
+    assert_eq!(rejected(&["-p", "B2"]), ErrorKind::MissingRequiredArgument);
+    assert_eq!(rejected(&["-p", "A1"]), ErrorKind::MissingRequiredArgument);
+    assert_eq!(rejected(&["--wrap", "none", "-p", "B2"]), ErrorKind::ArgumentConflict);
+    assert_eq!(rejected(&["--wrap", "none", "-p", "auto"]), ErrorKind::ArgumentConflict);
+}
+
+#[test]
+fn test_board_size_errors() {
+    let parse = |size: &str| Args::built_command().try_get_matches_from(["knights_tour", "-s", size]);
+    assert_eq!(parse("8 \u{d7} 6").unwrap().get_one::<BoardSize>("board_size"), Some(&BoardSize::new(8, 6)));
+
+    let error = parse("8x0").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::ValueValidation);
+    assert!(error.to_string().contains("The height of 8x0 is 0, but has to be at least 1"), "{error}");
+    assert!(parse("").unwrap_err().to_string().contains("The board size is empty"));
+}
+
+#[test]
+fn test_svg_style() {
+    let parse = |extra: &[&str]| Args::parse_from(["knights_tour", "-s", "8"].iter().chain(extra)).svg_style;
+    assert_eq!(parse(&[]), SvgStyle::default());
+
+    let style = parse(&["--svg-stroke-color", "#eee", "--svg-bg", "black", "--svg-no-grid", "--svg-stroke-width", "0.5"]);
+    assert_eq!((style.stroke.as_str(), style.background.as_deref(), style.show_grid, style.stroke_width), ("#eee", Some("black"), false, 0.5));
+
+    assert!(parse_svg_value("\"/><script").is_err());
+    assert_eq!(parse_svg_value("rgb(1, 2, 3)"), Ok("rgb(1, 2, 3)".to_string()));
+}
+
+#[test]
+fn test_deprecations() {
+    let matches = |extra: &[&str]| Args::built_command().get_matches_from(["knights_tour", "-s", "8"].iter().chain(extra));
+    let examples: [&[&str]; 2] = [
+        &["--wrap", "none", "--snap-start", "--random-seed", "5"],
+        &["--wrap", "none", "--lookahead", "2"],
+    ];
+    assert_eq!(examples.len(), DEPRECATIONS.len());
+    for (example, deprecation) in examples.iter().zip(DEPRECATIONS) {
+        let found: Vec<_> = deprecations(&matches(example)).map(|deprecation| deprecation.old).collect();
+        assert_eq!(found, [deprecation.old], "{example:?}");
+        // the old form still parses, and still ends up with divide and conquer
+        assert!(!Args::parse_from(["knights_tour", "-s", "8"].iter().chain(*example)).input.use_warnsdorff);
+    }
+
+    // the current forms don't warn
+    assert_eq!(deprecations(&matches(&["-w", "--wrap", "none", "-p", "B2", "--lookahead", "2"])).count(), 0);
+    assert_eq!(deprecations(&matches(&["--wrap", "none"])).count(), 0);
+    assert_eq!(deprecations(&matches(&["--wrap", "both", "-p", "B2"])).count(), 0);
+}
@@ -0,0 +1,160 @@
+use std::io::{Result, Write};
+
+use crate::{board::matrix2d::Matrix2D, board_pos::BoardPos, leaper::Leaper, tour::Tour};
+
+/// Explains the arrows: single arrows are steep moves (e.g. two rows, one column for the knight),
+/// double arrows are shallow moves (one row, two columns). Pieces moving straight get plain arrows
+fn legend(leaper: Leaper) -> String {
+    let amount = |n, unit: &str| match n {
+        1 => format!("one {unit}"),
+        2..=4 => format!("{} {unit}s", ["two", "three", "four"][n as usize - 2]),
+        _ => format!("{n} {unit}s"),
+    };
+
+    let (short, long) = leaper.distances();
+    let moves = match short {
+        0 => format!("↑→↓← {}", amount(long, "square")),
+        _ if short == long => format!("⇗⇘⇙⇖ {} and {}", amount(short, "row"), amount(short, "column")),
+        _ => format!(
+            "↗↘↙↖ {} and {}, ⇗⇘⇙⇖ {} and {}",
+            amount(long, "row"), amount(short, "column"), amount(short, "row"), amount(long, "column"),
+        ),
+    };
+
+    format!("S start, E end, {moves}")
+}
+
+/// Writes the tour as a grid of one character per square, each pointing in the direction of the next move
+pub fn render_arrows(writer: &mut impl Write, tour: &Tour) -> Result<()> {
+    let size = tour.size();
+    let mut grid = Matrix2D::new(size.width(), size.height(), || ' ');
+    for pos in tour.iter() {
+        let next = tour.next(pos);
+        *grid.at_mut(pos) = next.and_then(|next| tour.move_offset(pos, next)).map(glyph).unwrap_or('?');
+    }
+
+    // closed tours end where they started, so there is nothing to mark as the end.
+    // With several knights, every path gets its own start and end
+    for path in tour.paths() {
+        if !tour.is_closed() {
+            *grid.at_mut(path[path.len() - 1]) = 'E';
+        }
+
+        *grid.at_mut(path[0]) = 'S';
+    }
+
+    writeln!(writer, "{}", legend(tour.leaper()))?;
+    for row in 0..size.height() {
+        let line: String = (0..size.width()).map(|col| *grid.at(BoardPos::new(col, row))).collect();
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
+
+fn glyph((col, row): (i64, i64)) -> char {
+    let steep = row.abs() > col.abs();
+    match (col.signum(), row.signum(), steep) {
+        (0, -1, _) => '↑',
+        (1, 0, _) => '→',
+        (0, 1, _) => '↓',
+        (-1, 0, _) => '←',
+        (1, -1, true) => '↗',
+        (1, -1, false) => '⇗',
+        (1, 1, false) => '⇘',
+        (1, 1, true) => '↘',
+        (-1, 1, true) => '↙',
+        (-1, 1, false) => '⇙',
+        (-1, -1, false) => '⇖',
+        (-1, -1, true) => '↖',
+        _ => '?',
+    }
+}
+
+#[cfg(test)]
+fn offset(glyph: char, leaper: Leaper) -> Option<(i64, i64)> {
+    leaper.moves().find(|offset| self::glyph(*offset) == glyph)
+}
+
+#[cfg(test)]
+fn render_to_string(graph: &crate::move_graph::MoveGraph) -> String {
+    let mut buf = Vec::new();
+    render_arrows(&mut buf, &Tour::from_graph(graph).unwrap()).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[cfg(test)]
+use crate::move_graph::fixtures::{self, RING, SIX_BY_SIX};
+
+#[test]
+fn test_arrows_6x6() {
+    let graph = fixtures::open(6, 6, &SIX_BY_SIX);
+    let expected = [
+        &legend(Leaper::KNIGHT),
+        "S⇘⇙⇙↘↙",
+        "↘↙⇗⇗⇙⇖",
+        "↗↗↙⇗⇙↙",
+        "↘↙E↗↙⇖",
+        "↗↖⇘⇘⇙⇖",
+        "⇗⇗⇖⇗↗↖",
+        "",
+    ].join("\n");
+
+    assert_eq!(render_to_string(&graph), expected);
+}
+
+#[test]
+fn test_arrows_masked() {
+    // the center square of a 3x3 board can't be reached, so it's left blank
+    let graph = fixtures::open(3, 3, &RING);
+    let expected = [&legend(Leaper::KNIGHT), "S↘↙", "⇗ ⇙", "↗E⇖", ""].join("\n");
+
+    assert_eq!(render_to_string(&graph), expected);
+}
+
+#[test]
+fn test_arrows_follow_tour() {
+    let check = |width, height, tour: &[(u32, u32)]| {
+        let graph = fixtures::open(width, height, tour);
+        let rendered = render_to_string(&graph);
+        let grid: Vec<Vec<char>> = rendered.lines().skip(1).map(|line| line.chars().collect()).collect();
+        let at = |(col, row): (u32, u32)| grid[row as usize].get(col as usize).copied().unwrap_or(' ');
+
+        let mut pos = tour[0];
+        let mut followed = vec![pos];
+        assert_eq!(at(pos), 'S');
+
+        // the start square's direction isn't visible, but the second square is always the first move
+        pos = tour[1];
+        while at(pos) != 'E' {
+            followed.push(pos);
+            let (col, row) = offset(at(pos), Leaper::KNIGHT).unwrap_or_else(|| panic!("not an arrow at {pos:?}: {:?}", at(pos)));
+            pos = ((pos.0 as i64 + col) as u32, (pos.1 as i64 + row) as u32);
+        }
+
+        followed.push(pos);
+        assert_eq!(followed, tour);
+    };
+
+    check(6, 6, &SIX_BY_SIX);
+    check(3, 3, &RING);
+
+    let mut reversed = SIX_BY_SIX;
+    reversed.reverse();
+    check(6, 6, &reversed);
+}
+
+#[test]
+fn test_legend() {
+    assert_eq!(legend(Leaper::KNIGHT), "S start, E end, ↗↘↙↖ two rows and one column, ⇗⇘⇙⇖ one row and two columns");
+    assert_eq!(legend(Leaper::GIRAFFE), "S start, E end, ↗↘↙↖ four rows and one column, ⇗⇘⇙⇖ one row and four columns");
+    assert_eq!(legend(Leaper::new(0, 1).unwrap()), "S start, E end, ↑→↓← one square");
+    assert_eq!(legend(Leaper::new(2, 2).unwrap()), "S start, E end, ⇗⇘⇙⇖ two rows and two columns");
+
+    // every move of a piece gets its own arrow
+    for leaper in [Leaper::KNIGHT, Leaper::CAMEL, Leaper::new(2, 5).unwrap(), Leaper::new(0, 2).unwrap(), Leaper::new(3, 3).unwrap()] {
+        let glyphs: std::collections::HashSet<_> = leaper.moves().map(glyph).collect();
+        assert_eq!(glyphs.len(), leaper.moves().count(), "{leaper}");
+        assert!(!glyphs.contains(&'?'), "{leaper}");
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{board_pos::BoardPos, board_size::BoardSize};
+
+/// A set of board positions backed by one bit per square, addressed row by row
+pub struct BitSet {
+    bits: Vec<u64>,
+    width: usize,
+}
+
+impl BitSet {
+    pub fn new(size: BoardSize) -> Self {
+        let len = size.width() as usize * size.height() as usize;
+        Self { bits: vec![0; len.div_ceil(64)], width: size.width() as usize }
+    }
+
+    fn index(&self, pos: BoardPos) -> (usize, u64) {
+        let index = pos.row() as usize * self.width + pos.col() as usize;
+        (index / 64, 1 << (index % 64))
+    }
+
+    pub fn contains(&self, pos: BoardPos) -> bool {
+        let (word, mask) = self.index(pos);
+        self.bits[word] & mask != 0
+    }
+
+    /// Adds the position to the set, returning whether it was newly inserted
+    pub fn insert(&mut self, pos: BoardPos) -> bool {
+        let (word, mask) = self.index(pos);
+        let is_new = self.bits[word] & mask == 0;
+        self.bits[word] |= mask;
+        is_new
+    }
+}
+
+#[test]
+fn test_bitset() {
+    let mut set = BitSet::new(BoardSize::new(9, 8));
+    assert!(!set.contains(BoardPos::new(8, 7)));
+    assert!(set.insert(BoardPos::new(8, 7)));
+    assert!(!set.insert(BoardPos::new(8, 7)));
+    assert!(set.contains(BoardPos::new(8, 7)));
+    assert!(!set.contains(BoardPos::new(7, 8)));
+    assert!(set.insert(BoardPos::new(0, 0)));
+    assert!(!set.contains(BoardPos::new(1, 0)));
+}
@@ -1,4 +1,4 @@
-use std::convert::TryFrom;
+use std::{collections::HashSet, convert::TryFrom};
 
 use crate::{aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath}, board_size::BoardSize, board_pos::BoardPos};
 
@@ -56,10 +56,18 @@ impl CornerRadius {
             is_in_corner && square(point.0 - center.0) + square((point.1 - center.1) * e_w / e_h) > square(e_w)
         };
 
-        !is_in_corner(self.top_left.into(), pos, 0)
-        && !is_in_corner(self.top_right.into(), pos, 1)
-        && !is_in_corner(self.bottom_right.into(), pos, 2)
-        && !is_in_corner(self.bottom_left.into(), pos, 3)
+        !is_in_corner(self.top_left, pos, 0)
+        && !is_in_corner(self.top_right, pos, 1)
+        && !is_in_corner(self.bottom_right, pos, 2)
+        && !is_in_corner(self.bottom_left, pos, 3)
+    }
+
+    /// The squares of a board of the given size that are cut off by the rounded corners
+    pub fn dead_squares(&self, size: BoardSize) -> HashSet<BoardPos> {
+        (0..size.height())
+            .flat_map(|row| (0..size.width()).map(move |col| BoardPos::new(col, row)))
+            .filter(|pos| !self.is_in_range(*pos, size))
+            .collect()
     }
 
     #[cfg(test)]
@@ -83,7 +91,7 @@ impl CornerRadius {
     }
 
     pub fn parse(input: &str) -> Result<Self, String> {
-        Ok(Self::try_from(input)?)
+        Self::try_from(input)
     }
 }
 
@@ -182,7 +190,7 @@ impl TryFrom<&str> for CornerRadius {
         match state {
             ParseState::Number => corners.push(buf.into()),
             ParseState::Start|ParseState::After => {},
-            _ => return Err(format!("Unexpected end of input").into()),
+            _ => return Err("Unexpected end of input".into()),
         }
 
         match corners.len() {
@@ -195,8 +203,9 @@ impl TryFrom<&str> for CornerRadius {
 
 #[test]
 fn test_corner_radius_parsing() {
-    let test_ok = |input, expected: ((Idx, Idx), (Idx, Idx), (Idx, Idx), (Idx, Idx))| {
-        let result = CornerRadius::try_from(input).expect(&format!("Failed to parse {input}"));
+    type Corners = ((Idx, Idx), (Idx, Idx), (Idx, Idx), (Idx, Idx));
+    let test_ok = |input, expected: Corners| {
+        let result = CornerRadius::try_from(input).unwrap_or_else(|e| panic!("Failed to parse {input}: {e}"));
         println!("{input} -> {result:?} == {expected:?}");
         assert_eq!(result.top_left().vertical(), expected.0.0, "Top left v");
         assert_eq!(result.top_left().horizontal(), expected.0.1, "Top left h");
@@ -1,4 +1,4 @@
-use std::{fmt::Display, vec};
+use std::fmt::Display;
 
 use crate::{
     aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath},
@@ -6,10 +6,11 @@ use crate::{
     board_pos::BoardPos
 };
 
-#[derive(Debug)]
+/// A fixed size two-dimensional matrix, stored column by column in a single allocation
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Matrix2D<T>
-where T: 'static + Clone {
-    data: Box<[&'static mut [T]]>,
+where T: Clone {
+    data: Box<[T]>,
     w: Idx,
     h: Idx,
 }
@@ -18,38 +19,29 @@ impl<T> Matrix2D<T>
 where T: Clone
 {
     pub fn new(w: Idx, h: Idx, f: impl Fn() -> T) -> Self {
-        let base_vec = vec![f(); w as usize * h as usize];
-        let data = Self::split_buffer(w, h, base_vec);
+        let data = vec![f(); w as usize * h as usize].into_boxed_slice();
         Matrix2D { data, w, h, }
     }
 
-    pub fn map<R>(self, mut f: impl FnMut(&T) -> R) -> Matrix2D<R>
+    pub fn map<R>(self, f: impl FnMut(&T) -> R) -> Matrix2D<R>
     where R: Clone
     {
-        let mut base_vec = Vec::with_capacity(self.w as usize * self.h as usize);
-        for row in self.data.iter() {
-            for node in row.iter() {
-                base_vec.push(f(node));
-            }
-        }
-
-        let data = Self::split_buffer(self.w, self.h, base_vec);
+        let data = self.data.iter().map(f).collect();
         Matrix2D { data, w: self.w, h: self.h }
     }
 
-    fn split_buffer<X>(_width: Idx, height: Idx, base_vec: Vec<X>) -> Box<[&'static mut[X]]>
-    where X: Clone + 'static
-    {
-        let parts: Vec<_> = base_vec.leak().chunks_mut(height as usize).collect();
-        parts.into_boxed_slice()
+    fn index(&self, pos: BoardPos) -> usize {
+        assert!(self.is_in_range(pos), "Position out of bounds: {pos} ({pos:?}) in {}", self.size());
+        pos.col() as usize * self.h as usize + pos.row() as usize
     }
 
     pub fn at(&self, pos: BoardPos) -> &T {
-        &self.data[pos.col() as usize][pos.row() as usize]
+        &self.data[self.index(pos)]
     }
 
     pub fn at_mut(&mut self, pos: BoardPos) -> &mut T {
-        &mut self.data[pos.col() as usize][pos.row() as usize]
+        let index = self.index(pos);
+        &mut self.data[index]
     }
 
     pub fn is_in_range(&self, pos: BoardPos) -> bool {
@@ -60,35 +52,27 @@ where T: Clone
         BoardSize::new(self.w, self.h)
     }
 
-    pub fn iter(&self) -> Matrix2DIterator<T> {
-        Matrix2DIterator { matrix: self, col: 0, row: 0, start: BoardPos::new(0, 0), size: self.size() }
+    pub fn iter(&self) -> Matrix2DIterator<'_, T> {
+        self.iter_section(BoardPos::new(0, 0), self.size())
     }
 
     pub fn iter_section<'a>(&'a self, start: BoardPos, size: BoardSize) -> Matrix2DIterator<'a, T> {
-        Matrix2DIterator { matrix: self, col: start.col(), row: start.row(), start, size }
-    }
-}
-
-impl<T> Clone for Matrix2D<T>
-where T: 'static + Clone {
-    fn clone(&self) -> Self {
-        let mut base_vec = Vec::with_capacity(self.w as usize * self.h as usize);
-        for row in self.data.iter() {
-            for node in row.iter() {
-                base_vec.push(node.clone());
-            }
-        }
-        
-        let data = Self::split_buffer(self.w, self.h, base_vec);
-        Matrix2D { data, w: self.w, h: self.h }
+        let last = BoardPos::new((start.col() + size.width()).saturating_sub(1), (start.row() + size.height()).saturating_sub(1));
+        let remaining = size.width() as usize * size.height() as usize;
+        Matrix2DIterator { matrix: self, col: start.col(), row: start.row(), back_col: last.col(), back_row: last.row(), remaining, start, size }
     }
 }
 
+/// Iterates over (a section of) the matrix row by row, left to right, or the other way around from the back
 pub struct Matrix2DIterator<'a, T>
-where T: 'static + Clone {
+where T: Clone {
     matrix: &'a Matrix2D<T>,
     col: Idx,
     row: Idx,
+    // the last square not yet returned from the back
+    back_col: Idx,
+    back_row: Idx,
+    remaining: usize,
     start: BoardPos,
     size: BoardSize,
 }
@@ -110,23 +94,53 @@ where T: Clone {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.row == self.size.height() + self.start.row() {
+        if self.remaining == 0 {
             return None;
         }
 
         let pos = BoardPos::new(self.col, self.row);
         let val = self.matrix.at(pos);
         self.col += 1;
+        self.remaining -= 1;
 
         // this is technically branchless, because turning a bool into 1 or 0 is trivial with opimizations turned on
-        // and we do this because multiplication and addition is much faster than a branch prediction miss
-        self.col *= if self.col == self.size.width() + self.start.col() { 0 } else { 1 };
-        self.row += if self.col == 0 { 1 } else { 0 };
+        // and we do this because multiplication and addition is much faster than a branch prediction miss.
+        // at the end of a row, go back to the first column of the section, not of the matrix
+        let wrapped = self.col == self.size.width() + self.start.col();
+        self.col -= if wrapped { self.size.width() } else { 0 };
+        self.row += wrapped as Idx;
 
         Some(val)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
+impl<'a, T> DoubleEndedIterator for Matrix2DIterator<'a, T>
+where T: Clone {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let val = self.matrix.at(BoardPos::new(self.back_col, self.back_row));
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            // at the start of a row, continue at the last column of the section in the row above
+            let wrapped = self.back_col == self.start.col();
+            self.back_col = if wrapped { self.start.col() + self.size.width() - 1 } else { self.back_col - 1 };
+            self.back_row -= wrapped as Idx;
+        }
+
+        Some(val)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Matrix2DIterator<'a, T>
+where T: Clone {}
+
 impl<'a, T> IntoIterator for &'a Matrix2D<T>
 where T: Clone {
     type Item = &'a T;
@@ -175,3 +189,61 @@ where T: Display + Copy {
         Ok(())
     }
 }
+
+#[test]
+fn test_non_square_addressing() {
+    let mut matrix = Matrix2D::new(3, 7, || 0);
+    for col in 0..3 {
+        for row in 0..7 {
+            *matrix.at_mut(BoardPos::new(col, row)) = col * 10 + row;
+        }
+    }
+
+    assert_eq!(*matrix.at(BoardPos::new(2, 6)), 26);
+    assert_eq!(*matrix.at(BoardPos::new(0, 6)), 6);
+    assert_eq!(*matrix.at(BoardPos::new(2, 0)), 20);
+
+    // iteration is row by row, left to right
+    let expected: Vec<_> = (0..7).flat_map(|row| (0..3).map(move |col| col * 10 + row)).collect();
+    assert_eq!(matrix.iter().copied().collect::<Vec<_>>(), expected);
+
+    let section: Vec<_> = matrix.iter_section(BoardPos::new(0, 5), BoardSize::new(2, 2)).copied().collect();
+    assert_eq!(section, vec![5, 15, 6, 16]);
+
+    let mapped = matrix.map(|val| val + 1);
+    assert_eq!(*mapped.at(BoardPos::new(2, 6)), 27);
+    assert_eq!(mapped.size(), BoardSize::new(3, 7));
+}
+
+#[test]
+fn test_iter_section_matches_double_loop() {
+    for (width, height) in [(10, 10), (3, 7), (7, 3), (1, 5), (5, 1), (6, 4)] {
+        let mut matrix = Matrix2D::new(width, height, || BoardPos::ZERO);
+        for col in 0..width {
+            for row in 0..height {
+                *matrix.at_mut(BoardPos::new(col, row)) = BoardPos::new(col, row);
+            }
+        }
+
+        // every section, including those touching the right and bottom edges and those without any squares
+        for (col, row) in (0..=width).flat_map(|col| (0..=height).map(move |row| (col, row))) {
+            for (section_width, section_height) in (0..=width - col).flat_map(|w| (0..=height - row).map(move |h| (w, h))) {
+                let (start, size) = (BoardPos::new(col, row), BoardSize::new(section_width, section_height));
+                let expected: Vec<_> = (row..row + section_height)
+                    .flat_map(|row| (col..col + section_width).map(move |col| BoardPos::new(col, row)))
+                    .collect();
+                let section: Vec<_> = matrix.iter_section(start, size).copied().collect();
+                assert_eq!(section, expected, "{size} section at {start} of {}", matrix.size());
+                let backwards: Vec<_> = matrix.iter_section(start, size).rev().copied().collect();
+                assert_eq!(backwards, expected.iter().rev().copied().collect::<Vec<_>>(), "{size} section at {start} of {}", matrix.size());
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_out_of_bounds() {
+    let matrix = Matrix2D::new(3, 7, || 0);
+    matrix.at(BoardPos::new(3, 0));
+}
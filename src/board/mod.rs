@@ -2,19 +2,21 @@ pub mod matrix2d;
 pub mod corner_radius;
 mod corner;
 
-use std::{collections::HashSet, fmt::Display, vec};
+use std::{collections::BTreeSet, fmt::Display, vec};
 use matrix2d::Matrix2D;
 
 use crate::{aliases::BoardIndex as Idx, board_pos::BoardPos};
 
+#[derive(Debug, PartialEq, Eq)]
 pub struct Board {
     data: Matrix2D<usize>,
-    dead_squares: HashSet<BoardPos>,
+    // ordered, so that the debug output lists them the same way every time
+    dead_squares: BTreeSet<BoardPos>,
 }
 
 impl Board {
     pub fn new(w: Idx, h: Idx, value: usize) -> Self {
-        Self { data: Matrix2D::new(w, h, ||value), dead_squares: HashSet::new() }
+        Self { data: Matrix2D::new(w, h, ||value), dead_squares: BTreeSet::new() }
     }
 
     pub fn at(&self, pos: BoardPos) -> &usize {
@@ -37,122 +39,100 @@ impl Board {
         }
     }
     
-    pub fn with_dead_squares(self, dead_squares: HashSet<BoardPos>) -> Board {
+    pub fn with_dead_squares(self, dead_squares: BTreeSet<BoardPos>) -> Board {
         Board { dead_squares, ..self }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Neighbor{
-    Left,
-    _Right,
-    Top,
-    Bottom,
-    TopLeft,
-    TopRight,
-    BottomLeft,
-    BottomRight,
-}
-
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let size = self.data.size();
         let max = size.area() as usize - self.dead_squares.len();
         let max_len = max.to_string().len();
-
-        let border = |f: &mut std::fmt::Formatter<'_>, row: Idx, is_after: bool| -> std::fmt::Result {
-            let corner = |pos, h: Neighbor, v: Neighbor, with_self: bool| {
-                let mut neighbors = vec![v, h];
-                match (h, v) {
-                    (Neighbor::Left, Neighbor::Top) => neighbors.push(Neighbor::TopLeft),
-                    (Neighbor::_Right, Neighbor::Top) => neighbors.push(Neighbor::TopRight),
-                    (Neighbor::Left, Neighbor::Bottom) => neighbors.push(Neighbor::BottomLeft),
-                    (Neighbor::_Right, Neighbor::Bottom) => neighbors.push(Neighbor::BottomRight),
-                    _ => (),
-                }
-
-                if (!with_self || self.is_alive(pos)) || self.has_alive_neighbor(pos, neighbors) { "+" } else { " " }
-            };
-
-            if is_after {
-                writeln!(f)?;
+        let width = size.width() as usize;
+        let (alive_edge, dead_edge) = ("-".repeat(max_len + 2), " ".repeat(max_len + 2));
+
+        // the border between two rows, with a corner wherever one of the four squares around it is alive.
+        // It ends in a corner if the last square of the row it belongs to is alive
+        let mut border_line = String::with_capacity(width * (max_len + 3) + 1);
+        let mut border = |f: &mut std::fmt::Formatter<'_>, above: &[bool], below: &[bool], own: &[bool]| -> std::fmt::Result {
+            border_line.clear();
+            for col in 0..width {
+                let alive = above[col] || below[col];
+                let left_alive = col > 0 && (above[col - 1] || below[col - 1]);
+                border_line.push(if alive || left_alive { '+' } else { ' ' });
+                border_line.push_str(if alive { &alive_edge } else { &dead_edge });
             }
 
-            for x in 0..size.width() {
-                let pos = BoardPos::new(x, row);
-                let vertical = if is_after { Neighbor::Bottom } else { Neighbor::Top };
-                let left_corner = corner(pos, Neighbor::Left, vertical, true);
-                let border = if self.is_alive(pos) || self.has_alive_neighbor(pos, vec![vertical]) { "-" } else { " " };
-
-                write!(f, "{}{}", left_corner, border.repeat(max_len + 2))?;
+            if own[width - 1] {
+                border_line.push('+');
             }
 
-            if self.is_alive(BoardPos::new(size.width() - 1, row)) {
-                write!(f, "+")?;
-            }
-
-            Ok(())
+            f.write_str(&border_line)
         };
 
-        border(f, 0, false)?;
+        // the squares of each row are looked up once, and only two rows are kept at a time.
+        // Each row is put together in a buffer, as going through the formatter for every cell is what makes large boards slow
+        let outside = vec![false; width];
+        let mut current = self.alive_row(0);
+        let mut line = String::with_capacity(width * (max_len + 3) + 1);
+        border(f, &outside, &current, &current)?;
         for row in 0..size.height() {
-            writeln!(f)?;
-
-            for col in 0..size.width() {
-                let pos = BoardPos::new(col, row);
-                if self.is_alive(pos) || self.has_alive_neighbor(pos, vec![Neighbor::Left]){
-                    write!(f, "| ")?;
+            line.clear();
+            for col in 0..width {
+                if current[col] || (col > 0 && current[col - 1]) {
+                    line.push_str("| ");
+                }
+                else {
+                    // keeps the columns aligned with the borders
+                    line.push_str("  ");
                 }
 
-                if self.is_alive(pos) {
-                    // format specifier syntax used:
-                    // {
-                    // 1  -> the second argument (because 0-based)
-                    // :  -> a format specifier follows
-                    //' ' -> the padding character (a space)
-                    // >  -> right-align the text (alternatives are < for left-align and ^ for center)
-                    // 0$ -> the maximum width of the text, passed as an argument
-                    // }
-                    write!(f, "{1: >0$} ", max_len, *self.at(pos))?;
+                if current[col] {
+                    push_right_aligned(&mut line, *self.at(BoardPos::new(col as Idx, row)), max_len);
                 }
                 else {
-                    write!(f, "{1: >0$} ", max_len, ' ')?;
+                    line.push_str(&dead_edge[1..]);
                 }
             }
-            if self.is_alive(BoardPos::new(size.width() - 1, row)) {
-                write!(f, "|")?;
+            if current[width - 1] {
+                line.push('|');
             }
-            border(f, row, true)?;
+
+            writeln!(f)?;
+            f.write_str(&line)?;
+
+            let next = if row + 1 < size.height() { self.alive_row(row + 1) } else { outside.clone() };
+            writeln!(f)?;
+            border(f, &current, &next, &current)?;
+            current = next;
         }
         Ok(())
     }
 }
 
-impl Board {
-    fn has_alive_neighbor(&self, pos: BoardPos, neighbors: Vec<Neighbor>) -> bool {
-        let size = self.data.size();
-        for neighbor in neighbors {
-            let translate = match neighbor {
-                Neighbor::Left => (-1, 0),
-                Neighbor::_Right => (1, 0),
-                Neighbor::Top => (0, -1),
-                Neighbor::Bottom => (0, 1),
-                Neighbor::TopLeft => (-1, -1),
-                Neighbor::TopRight => (1, -1),
-                Neighbor::BottomLeft => (-1, 1),
-                Neighbor::BottomRight => (1, 1),
-            };
-
-            let neighbor = pos.try_translate_on_board(translate.0, translate.1, size);
-
-            if let Some(neighbor) = neighbor {
-                if self.is_alive(neighbor) {
-                    return true;
-                }
-            }
+/// Appends the number right-aligned to the given width and followed by a space, the same as `{: >width$} `
+fn push_right_aligned(line: &mut String, number: usize, width: usize) {
+    let mut digits = [0u8; 20];
+    let mut len = 0;
+    let mut rest = number;
+    loop {
+        digits[len] = b'0' + (rest % 10) as u8;
+        len += 1;
+        rest /= 10;
+        if rest == 0 {
+            break;
         }
+    }
 
-        false
+    line.extend(std::iter::repeat_n(' ', width.saturating_sub(len)));
+    line.extend(digits[..len].iter().rev().map(|digit| *digit as char));
+    line.push(' ');
+}
+
+impl Board {
+    fn alive_row(&self, row: Idx) -> Vec<bool> {
+        (0..self.data.size().width()).map(|col| self.is_alive(BoardPos::new(col, row))).collect()
     }
 
     fn is_alive(&self, pos: BoardPos) -> bool {
@@ -1,5 +1,26 @@
-use std::{fmt::{Debug, Display}, ops::{Add, Sub}};
-use crate::{aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath}, board_size::BoardSize, move_graph::Direction};
+use std::{cmp::Ordering, fmt::{Debug, Display}, ops::{Add, Sub}};
+use crate::{aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath}, args::Wrap, board_size::BoardSize, leaper::Leaper, move_graph::Direction};
+
+/// All eight knight moves, clockwise starting from the top
+pub const KNIGHT_MOVES: [(IdxMath, IdxMath); 8] = [(1, -2), (2, -1), (2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2)];
+
+/// The squares a knight on `pos` can jump to on a board of the given size, in the order Warnsdorff tries them on a tie
+pub fn knight_moves(pos: BoardPos, size: BoardSize) -> impl Iterator<Item = BoardPos> {
+    leaper_moves(pos, size, Wrap::None, Leaper::KNIGHT)
+}
+
+/// Like [knight_moves], but only leaving out the squares with negative coordinates, for callers that check the board themselves
+pub fn knight_moves_unbounded(pos: BoardPos) -> impl Iterator<Item = BoardPos> {
+    knight_moves(pos, BoardSize::new(Idx::MAX, Idx::MAX))
+}
+
+/// The squares the piece on `pos` can jump to, in the order Warnsdorff tries them on a tie (see [Leaper::search_move]).
+/// Moves off a wrapped edge come back in on the other side
+pub fn leaper_moves(pos: BoardPos, size: BoardSize, wrap: Wrap, leaper: Leaper) -> impl Iterator<Item = BoardPos> {
+    (0..8)
+        .filter_map(move |idx| leaper.search_move(idx))
+        .filter_map(move |(col, row)| pos.try_translate_wrapping(col, row, size, wrap))
+}
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 pub struct BoardPos(Idx, Idx);
@@ -74,35 +95,60 @@ impl BoardPos {
     }
 
     pub fn is_knight_move(&self, other: BoardPos) -> bool {
-        let col_diff = (self.col() as IdxMath - other.col() as IdxMath).abs();
-        let row_diff = (self.row() as IdxMath - other.row() as IdxMath).abs();
+        self.is_leaper_move(other, Leaper::KNIGHT)
+    }
 
-        (col_diff == 1 && row_diff == 2) || (col_diff == 2 && row_diff == 1)
+    pub fn is_leaper_move(&self, other: BoardPos, leaper: Leaper) -> bool {
+        leaper.is_move(self.col() as IdxMath - other.col() as IdxMath, self.row() as IdxMath - other.row() as IdxMath)
     }
 
     pub fn translate(&self, col: IdxMath, row: IdxMath) -> Self {
-        self.try_translate(col, row).expect(&format!("Invalid translation attempt: {self:?} -> {col}, {row}"))
+        self.try_translate(col, row).unwrap_or_else(|| panic!("Invalid translation attempt: {self:?} -> {col}, {row}"))
     }
 
     pub fn try_translate(&self, col: IdxMath, row: IdxMath) -> Option<Self> {
         self.try_translate_on_board(col, row, BoardSize::new(Idx::MAX, Idx::MAX))
     }
 
+    /// The position moved by the given offset, if it is still on the board, i.e. `col < width` and `row < height`
     pub fn try_translate_on_board(&self, col: IdxMath, row: IdxMath, board_size: BoardSize) -> Option<Self> {
-        let (w, h) = (board_size.width() as IdxMath, board_size.height() as IdxMath);
-        let (self_col , self_row) = (self.col() as IdxMath, self.row() as IdxMath);
-        let in_range = |min: IdxMath, val: IdxMath, max: IdxMath|{
-            if val < 0 { return val.abs() <= min } else { return val <= max }
+        let axis = |val: Idx, delta: IdxMath, len: Idx| {
+            let res = (val as IdxMath).checked_add(delta)?;
+            (0..len as IdxMath).contains(&res).then_some(res as Idx)
         };
 
-        if !in_range(self_col, col, w) || !in_range(self_row, row, h) { return None; }
+        Some(Self(
+            axis(self.col(), col, board_size.width())?,
+            axis(self.row(), row, board_size.height())?,
+        ))
+    }
+
+    /// Like [BoardPos::try_translate_on_board], except that the wrapped axes continue on the opposite side of the board
+    pub fn try_translate_wrapping(&self, col: IdxMath, row: IdxMath, board_size: BoardSize, wrap: Wrap) -> Option<Self> {
+        let axis = |val: Idx, delta: IdxMath, len: Idx, wraps: bool| {
+            let res = val as IdxMath + delta;
+            if wraps && len > 0 {
+                Some(res.rem_euclid(len as IdxMath) as Idx)
+            } else if (0..len as IdxMath).contains(&res) {
+                Some(res as Idx)
+            } else {
+                None
+            }
+        };
 
         Some(Self(
-            if col >= 0 { self.col() + col as Idx } else { self.col() - col.abs() as Idx },
-            if row >= 0 { self.row() + row as Idx } else { self.row() - row.abs() as Idx },
+            axis(self.col(), col, board_size.width(), wrap.horizontal())?,
+            axis(self.row(), row, board_size.height(), wrap.vertical())?,
         ))
     }
 
+    /// The leaper's move leading from this position to the other one, taking wrapped edges into account.
+    /// On narrow boards a move may lead there both across the board and across an edge, the one across the board is preferred
+    pub fn move_offset_to(&self, other: BoardPos, board_size: BoardSize, wrap: Wrap, leaper: Leaper) -> Option<(IdxMath, IdxMath)> {
+        leaper.moves().find(|(col, row)| self.try_translate_on_board(*col, *row, board_size) == Some(other))
+            .or_else(|| leaper.moves().find(|(col, row)| self.try_translate_wrapping(*col, *row, board_size, wrap) == Some(other)))
+    }
+
     pub fn if_move(&self, pos: BoardPos) -> Option<BoardPos> {
         if self.is_knight_move(pos) { Some(*self) } else { None }
     }
@@ -110,23 +156,106 @@ impl BoardPos {
 
 impl Display for BoardPos {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let w = alphabetize(self.col() + 1);
-        let h = self.row() + 1;
+        f.pad(name(*self, &mut [0; NAME_LEN]))
+    }
+}
+
+/// The longest name of a square: seven letters for the column and ten digits for the row
+const NAME_LEN: usize = 17;
+
+/// The name of the square (e.g. "B3"), put together in the buffer from the back, so that writing it doesn't allocate.
+/// Output of large boards names millions of squares
+fn name(pos: BoardPos, buf: &mut [u8; NAME_LEN]) -> &str {
+    let mut start = NAME_LEN;
+    let mut row = pos.row() as u64 + 1;
+    while row > 0 {
+        start -= 1;
+        buf[start] = b'0' + (row % 10) as u8;
+        row /= 10;
+    }
+
+    let letters = column_letters(pos.col(), &mut buf[..start]);
+    start -= letters;
+    std::str::from_utf8(&buf[start..]).expect("square names are ASCII")
+}
+
+/// Puts the letters of the column (the same as alphabetize) at the end of the buffer, returning how many there are
+fn column_letters(col: Idx, buf: &mut [u8]) -> usize {
+    let mut end = buf.len();
+    let mut col = col as u64 + 1;
+    while col > 0 {
+        col -= 1;
+        end -= 1;
+        buf[end] = b'A' + (col % 26) as u8;
+        col /= 26;
+    }
+
+    buf.len() - end
+}
+
+/// The letters of a zero-based column (e.g. "AB" for 27), written without allocating and padded like any string
+pub struct ColumnName(pub Idx);
+
+impl Display for ColumnName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buf = [0; NAME_LEN];
+        let letters = column_letters(self.0, &mut buf);
+        f.pad(std::str::from_utf8(&buf[NAME_LEN - letters..]).expect("column names are ASCII"))
+    }
+}
+
+/// The names of the squares of a board, each put together the first time it is asked for and kept for the next time.
+/// For output naming the same squares again and again, e.g. the debug table, which names most squares twice: as the
+/// square before one square and as the square after another
+pub struct PosFormatter {
+    width: Idx,
+    names: Vec<Option<Box<str>>>,
+}
 
-        Display::fmt(&format!("{w}{h}"), f)
+impl PosFormatter {
+    pub fn new(size: BoardSize) -> Self {
+        Self { width: size.width(), names: vec![None; size.area() as usize] }
     }
+
+    /// The name of a square of the board
+    pub fn name(&mut self, pos: BoardPos) -> &str {
+        let idx = pos.row() as usize * self.width as usize + pos.col() as usize;
+        self.names[idx].get_or_insert_with(|| name(pos, &mut [0; NAME_LEN]).into())
+    }
+}
+
+/// Writes the name of the square, the same as its Display implementation without any padding
+pub fn write_pos(w: &mut impl std::fmt::Write, pos: BoardPos) -> std::fmt::Result {
+    w.write_str(name(pos, &mut [0; NAME_LEN]))
 }
 
-fn alphabetize(mut val: Idx) -> String {
+/// Row by row, then column by column, the order the board is written in. Sorting by it keeps anything listing squares
+/// from a [std::collections::HashSet] the same from run to run
+impl Ord for BoardPos {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.row(), self.col()).cmp(&(other.row(), other.col()))
+    }
+}
+
+impl PartialOrd for BoardPos {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The column letters for a 1-based column number: A to Z, then AA, AB and so on. Kept as the reference the names
+/// are checked against
+#[cfg(test)]
+pub fn alphabetize(mut val: Idx) -> String {
     let mut buf = Vec::new();
     while val > 0 {
         val -= 1;
         let rem = val % 26;
-        val = val / 26;
+        val /= 26;
         buf.push(C('A') + rem);
     }
 
-    if buf.len() == 0 { buf.push('A'); }
+    if buf.is_empty() { buf.push('A'); }
 
     buf.reverse();
     let mut str = String::with_capacity(buf.len());
@@ -137,6 +266,35 @@ fn alphabetize(mut val: Idx) -> String {
     str
 }
 
+#[test]
+fn test_names() {
+    let old_name = |pos: BoardPos| format!("{}{}", alphabetize(pos.col() + 1), pos.row() + 1);
+    let squares = (0..30).flat_map(|row| (0..30).map(move |col| BoardPos::new(col, row)));
+    let edges = [25, 26, 701, 702, 18277, 18278, Idx::MAX - 1].map(|idx| BoardPos::new(idx, idx));
+    for pos in squares.chain(edges) {
+        let mut written = String::new();
+        write_pos(&mut written, pos).unwrap();
+        assert_eq!(written, old_name(pos));
+        assert_eq!(pos.to_string(), old_name(pos));
+    }
+
+    // the longest name there is
+    assert_eq!(BoardPos::new(Idx::MAX, Idx::MAX).to_string().len(), NAME_LEN);
+
+    let mut names = PosFormatter::new(BoardSize::new(30, 30));
+    for pos in (0..30).flat_map(|row| (0..30).map(move |col| BoardPos::new(col, row))) {
+        assert_eq!(names.name(pos), old_name(pos));
+        // from the cache the second time
+        assert_eq!(names.name(pos), old_name(pos));
+    }
+
+    for col in [0, 25, 26, 701, 702, Idx::MAX - 1] {
+        assert_eq!(ColumnName(col).to_string(), alphabetize(col + 1));
+    }
+    assert_eq!(format!("[{:^4}]", ColumnName(27)), "[ AB ]");
+    assert_eq!(format!("[{:^5}|{:<4}|{:>3}]", BoardPos::new(1, 2), BoardPos::ZERO, BoardPos::new(26, 9)), "[ B3  |A1  |AA10]");
+}
+
 #[test]
 fn test_alphabetize() {
     assert_eq!("A", alphabetize(1));
@@ -146,12 +304,12 @@ fn test_alphabetize() {
     assert_eq!("BA", alphabetize(53));
     assert_eq!("ZZZ", alphabetize(18278));
 
-    assert_eq!(BoardPos::try_from("A-1").unwrap().col(), 1);
-    assert_eq!(BoardPos::try_from("Z-1").unwrap().col(), 26);
-    assert_eq!(BoardPos::try_from("AA-1").unwrap().col(), 27);
-    assert_eq!(BoardPos::try_from("AZ-1").unwrap().col(), 52);
-    assert_eq!(BoardPos::try_from("BA-1").unwrap().col(), 53);
-    assert_eq!(BoardPos::try_from("ZZZ-1").unwrap().col(), 18278);
+    assert_eq!(BoardPos::try_from("A-1").unwrap().col() + 1, 1);
+    assert_eq!(BoardPos::try_from("Z-1").unwrap().col() + 1, 26);
+    assert_eq!(BoardPos::try_from("AA-1").unwrap().col() + 1, 27);
+    assert_eq!(BoardPos::try_from("AZ-1").unwrap().col() + 1, 52);
+    assert_eq!(BoardPos::try_from("BA-1").unwrap().col() + 1, 53);
+    assert_eq!(BoardPos::try_from("ZZZ-1").unwrap().col() + 1, 18278);
 }
 
 struct C(char);
@@ -167,7 +325,7 @@ impl Sub<Idx> for C {
     type Output = C;
 
     fn sub(self, rhs: Idx) -> Self::Output {
-        C(char::from_u32(self.0 as u32 - rhs as u32).unwrap())
+        C(char::from_u32(self.0 as u32 - rhs).unwrap())
     }
 }
 
@@ -175,15 +333,18 @@ impl Add<Idx> for C {
     type Output = char;
     
     fn add(self, rhs: Idx) -> Self::Output {
-        char::from_u32(self.0 as u32 + rhs as u32).unwrap()
+        char::from_u32(self.0 as u32 + rhs).unwrap()
     }
 }
 
 impl TryFrom<&str> for BoardPos {
     type Error = String;
 
+    /// Reads either form of [parse_board_pos]
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        const ERR: &str = "Expected a string of the format <COLUMN>[-]<ROW>, whith columns being letters and rows being numeric.";
+        if value.contains(',') || value.starts_with(ZERO_BASED) {
+            return parse_numeric(value);
+        }
 
         let mut col = None;
         let mut row = None;
@@ -208,6 +369,160 @@ impl TryFrom<&str> for BoardPos {
     }
 }
 
+const ERR: &str = "Expected a square as <COLUMN>[-]<ROW> with the column in letters (e.g. \"AB-12\"), \
+    or as <COLUMN>,<ROW> in numbers (e.g. \"28,12\"). Both count from 1, unless the numbers are prefixed with \"0:\" (e.g. \"0:27,11\")";
+
+const ZERO_BASED: &str = "0:";
+
+/// The numeric form, "12,34" counting from 1 or "0:11,33" counting from 0. Both numbers are required, so that a bare number is never a square
+fn parse_numeric(value: &str) -> Result<BoardPos, String> {
+    let (zero_based, pair) = match value.strip_prefix(ZERO_BASED) {
+        Some(pair) => (true, pair),
+        None => (false, value),
+    };
+    let (col, row) = pair.split_once(',').ok_or(ERR.to_string())?;
+    let parse = |number: &str, name: &str| -> Result<Idx, String> {
+        if number.starts_with('-') {
+            return Err(format!("The {name} of {value} is negative"));
+        }
+
+        if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ERR.into());
+        }
+
+        let number = number.parse::<Idx>().map_err(|_| format!("The {name} of {value} is larger than {}", Idx::MAX))?;
+        match (zero_based, number) {
+            (true, number) => Ok(number),
+            (false, 0) => Err(format!("The {name} of {value} is 0, but counts from 1 (prefix it with \"0:\" to count from 0)")),
+            (false, number) => Ok(number - 1),
+        }
+    };
+
+    Ok(BoardPos(parse(col, "column")?, parse(row, "row")?))
+}
+
+/// Reads a square either as on a chess board (e.g. "AB-12", see [BoardPos::try_from]) or as numbers (e.g. "28,12" or "0:27,11")
 pub fn parse_board_pos(arg: &str) -> Result<BoardPos, String> {
     arg.try_into()
 }
+
+/// Reads squares separated by commas or whitespace. As a bare number isn't a square, a number is joined with the one following it
+/// into a square of the numeric form, so "A1,3,2,C1" lists the squares A1, C2 and C1
+pub fn parse_board_pos_list(list: &str) -> Result<Vec<BoardPos>, String> {
+    let mut parts = list.split(|c: char| c == ',' || c.is_whitespace()).filter(|part| !part.is_empty());
+    let mut squares = Vec::new();
+    while let Some(part) = parts.next() {
+        let number = part.strip_prefix(ZERO_BASED).unwrap_or(part);
+        let square = if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+            let row = parts.next().ok_or_else(|| format!("{part} is missing the row that follows the column"))?;
+            parse_numeric(&format!("{part},{row}"))?
+        } else {
+            parse_board_pos(part)?
+        };
+        squares.push(square);
+    }
+
+    Ok(squares)
+}
+
+#[test]
+fn test_knight_moves() {
+    let squares = |list: &str| list.split(' ').map(|square| parse_board_pos(square).unwrap()).collect::<Vec<_>>();
+    let size = BoardSize::new(8, 8);
+    // the order Warnsdorff's search depends on: long jumps right, then left, then short jumps right and left, down before up
+    assert_eq!(knight_moves(BoardPos::new(3, 3), size).collect::<Vec<_>>(), squares("F5 F3 B5 B3 E6 E2 C6 C2"));
+    assert_eq!(knight_moves(BoardPos::ZERO, size).collect::<Vec<_>>(), squares("C2 B3"));
+    assert_eq!(knight_moves(BoardPos::new(7, 7), size).collect::<Vec<_>>(), squares("F7 G6"));
+    assert_eq!(knight_moves(BoardPos::new(2, 6), BoardSize::new(3, 7)).collect::<Vec<_>>(), squares("A6 B5"));
+
+    assert_eq!(knight_moves_unbounded(BoardPos::new(7, 7)).collect::<Vec<_>>(), squares("J9 J7 F9 F7 I10 I6 G10 G6"));
+    assert_eq!(knight_moves_unbounded(BoardPos::ZERO).collect::<Vec<_>>(), squares("C2 B3"));
+
+    // wrapped edges and other pieces keep the same order
+    let cylinder: Vec<_> = leaper_moves(BoardPos::new(2, 6), BoardSize::new(3, 7), Wrap::Horizontal, Leaper::KNIGHT).collect();
+    assert_eq!(cylinder, squares("B6 A6 A5 B5"));
+    assert_eq!(leaper_moves(BoardPos::ZERO, size, Wrap::None, Leaper::CAMEL).collect::<Vec<_>>(), squares("D2 B4"));
+}
+
+#[test]
+fn test_ordering() {
+    let mut squares = vec![BoardPos::new(1, 1), BoardPos::new(0, 2), BoardPos::new(2, 0), BoardPos::new(0, 1)];
+    squares.sort();
+    assert_eq!(squares, [BoardPos::new(2, 0), BoardPos::new(0, 1), BoardPos::new(1, 1), BoardPos::new(0, 2)]);
+}
+
+#[test]
+fn test_parse_numeric() {
+    assert_eq!(parse_board_pos("12,34"), Ok(BoardPos::new(11, 33)));
+    assert_eq!(parse_board_pos("1,1"), Ok(BoardPos::ZERO));
+    assert_eq!(parse_board_pos("0:11,33"), Ok(BoardPos::new(11, 33)));
+    assert_eq!(parse_board_pos("0:0,0"), Ok(BoardPos::ZERO));
+    // both forms name the same squares
+    assert_eq!(parse_board_pos("28,12"), parse_board_pos("AB-12"));
+
+    let max = Idx::MAX.to_string();
+    assert_eq!(parse_board_pos(&format!("{max},{max}")), Ok(BoardPos::new(Idx::MAX - 1, Idx::MAX - 1)));
+    assert_eq!(parse_board_pos(&format!("0:{max},0")), Ok(BoardPos::new(Idx::MAX, 0)));
+    assert!(parse_board_pos(&format!("1,{}", Idx::MAX as u64 + 1)).unwrap_err().contains("larger than"));
+
+    assert!(parse_board_pos("-1,2").unwrap_err().contains("negative"));
+    assert!(parse_board_pos("0:3,-2").unwrap_err().contains("negative"));
+    assert!(parse_board_pos("0,5").unwrap_err().contains("counts from 1"));
+    for invalid in ["12", "12,", ",34", "0:12", "1,2,3", "1.5,2", "A,1", "0:", "1, 2"] {
+        assert_eq!(parse_board_pos(invalid), Err(ERR.to_string()), "{invalid}");
+    }
+    assert!(ERR.contains("AB-12") && ERR.contains("28,12"));
+}
+
+#[test]
+fn test_parse_board_pos_list() {
+    let squares = |list: &str| list.split(' ').map(|square| parse_board_pos(square).unwrap()).collect::<Vec<_>>();
+    assert_eq!(parse_board_pos_list("A1,3,2,C1"), Ok(squares("A1 C2 C1")));
+    assert_eq!(parse_board_pos_list("0:0,0 2,3\nE1"), Ok(squares("A1 B3 E1")));
+    assert_eq!(parse_board_pos_list("A1, C2 ,E1"), Ok(squares("A1 C2 E1")));
+    assert_eq!(parse_board_pos_list(""), Ok(Vec::new()));
+    assert!(parse_board_pos_list("A1,3").unwrap_err().contains("missing the row"));
+    assert!(parse_board_pos_list("A1,3,B2").is_err());
+}
+
+#[test]
+fn test_translate_wrapping() {
+    let size = BoardSize::new(5, 6);
+    let pos = BoardPos::new(4, 0);
+
+    assert_eq!(pos.try_translate_wrapping(2, 1, size, Wrap::None), None);
+    assert_eq!(pos.try_translate_wrapping(2, 1, size, Wrap::Horizontal), Some(BoardPos::new(1, 1)));
+    assert_eq!(pos.try_translate_wrapping(1, -2, size, Wrap::Horizontal), None);
+    assert_eq!(pos.try_translate_wrapping(-1, -2, size, Wrap::Vertical), Some(BoardPos::new(3, 4)));
+    assert_eq!(pos.try_translate_wrapping(1, -2, size, Wrap::Both), Some(BoardPos::new(0, 4)));
+
+    assert_eq!(pos.move_offset_to(BoardPos::new(1, 1), size, Wrap::None, Leaper::KNIGHT), None);
+    assert_eq!(pos.move_offset_to(BoardPos::new(1, 1), size, Wrap::Horizontal, Leaper::KNIGHT), Some((2, 1)));
+    assert_eq!(pos.move_offset_to(BoardPos::new(2, 1), size, Wrap::None, Leaper::KNIGHT), Some((-2, 1)));
+
+    // four columns across, two to the right across the edge lead to the same square as two to the left
+    let narrow = BoardSize::new(4, 5);
+    assert_eq!(BoardPos::new(3, 0).move_offset_to(BoardPos::new(1, 1), narrow, Wrap::Both, Leaper::KNIGHT), Some((-2, 1)));
+}
+
+#[test]
+fn test_translate_on_board() {
+    let size = BoardSize::new(4, 3);
+    let offsets = (-5..=5).flat_map(|col| (-5..=5).map(move |row| (col, row)));
+    for pos in [(0, 0), (3, 0), (0, 2), (3, 2), (1, 0), (3, 1), (2, 2), (0, 1)].map(BoardPos::from) {
+        for (col, row) in offsets.clone() {
+            let (res_col, res_row) = (pos.col() as IdxMath + col, pos.row() as IdxMath + row);
+            let expected = ((0..4).contains(&res_col) && (0..3).contains(&res_row)).then(|| BoardPos::new(res_col as Idx, res_row as Idx));
+            assert_eq!(pos.try_translate_on_board(col, row, size), expected, "{pos:?} by ({col}, {row})");
+        }
+    }
+
+    // the pseudo unbounded board of try_translate only stops at zero and the last index
+    assert_eq!(BoardPos::new(1, 2).try_translate(-1, -2), Some(BoardPos::ZERO));
+    assert_eq!(BoardPos::new(1, 2).try_translate(-2, 1), None);
+    assert_eq!(BoardPos::new(1, 2).try_translate(1, -3), None);
+    assert_eq!(BoardPos::ZERO.try_translate(Idx::MAX as IdxMath - 1, 0), Some(BoardPos::new(Idx::MAX - 1, 0)));
+    assert_eq!(BoardPos::ZERO.try_translate(Idx::MAX as IdxMath, 0), None);
+    assert_eq!(BoardPos::new(0, Idx::MAX - 1).try_translate(0, 1), None);
+    assert_eq!(BoardPos::new(5, 5).try_translate(IdxMath::MAX, IdxMath::MIN), None);
+}
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, num::IntErrorKind};
 
 use crate::{aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath}, board_pos::BoardPos};
 
@@ -91,39 +91,83 @@ impl From<BoardSize> for (Idx, Idx) {
     }
 }
 
+/// Accepts "<width>x<height>" or "<length>" for a square board. 'X' and '×' work as well as 'x', and whitespace around the
+/// numbers is ignored (e.g. "8 × 8")
 impl TryFrom<&str> for BoardSize {
     type Error = String;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let mut w = None;
-        let mut h = None;
-        for part in value.split('x') {
-            if w.is_none() {
-                w = match part.parse::<Idx>() {
-                    Ok(val) => Some(val),
-                    Err(e) => return Err(e.to_string()),
-                };
-            }
-            else if h.is_none() {
-                h = match part.parse::<Idx>() {
-                    Ok(val) => Some(val),
-                    Err(e) => return Err(e.to_string()),
-                };
-            }
-            else {
-                return Err("Expected string of the form <width>x<height> or <length>".into());
-            }
+        const FORM: &str = "expected <width>x<height> or <length>, e.g. 12x9 or 8";
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(format!("The board size is empty, {FORM}"));
         }
 
-        let w = w.unwrap();
-        if h.is_none() {
-            h = Some(w);
+        let parts: Vec<_> = value.split(['x', 'X', '\u{d7}']).collect();
+        match parts[..] {
+            [length] => {
+                let length = dimension(value, "length", length)?;
+                Ok(BoardSize { width: length, height: length })
+            },
+            [w, h] => Ok(BoardSize { width: dimension(value, "width", w)?, height: dimension(value, "height", h)? }),
+            _ => Err(format!("{value} has more than two dimensions, {FORM}")),
         }
-        let h = h.unwrap();
+    }
+}
+
+/// One side of the board size `value`, which has to be at least 1
+fn dimension(value: &str, name: &str, part: &str) -> Result<Idx, String> {
+    let part = part.trim();
+    if part.is_empty() {
+        return Err(format!("The {name} of {value} is missing"));
+    }
 
-        Ok(BoardSize { width: w, height: h })
+    match part.parse::<Idx>() {
+        Ok(0) => Err(format!("The {name} of {value} is 0, but has to be at least 1")),
+        Ok(length) => Ok(length),
+        Err(e) if matches!(e.kind(), IntErrorKind::PosOverflow) => Err(format!("The {name} of {value} is larger than {}", Idx::MAX)),
+        Err(_) => Err(format!("The {name} of {value} is not a whole number: {part}")),
     }
 }
 
 pub fn parse_board_size(arg: &str) -> Result<BoardSize, String> {
     arg.try_into()
 }
+
+#[test]
+fn test_parse_rejects_empty_boards() {
+    assert_eq!(BoardSize::try_from("12x9"), Ok(BoardSize::new(12, 9)));
+    assert_eq!(BoardSize::try_from("7"), Ok(BoardSize::new(7, 7)));
+    assert!(BoardSize::try_from("0x5").is_err());
+    assert!(BoardSize::try_from("5x0").is_err());
+    assert!(BoardSize::try_from("0").is_err());
+
+    assert_eq!(parse_board_size("0x5"), Err("The width of 0x5 is 0, but has to be at least 1".into()));
+    assert_eq!(parse_board_size("5x0"), Err("The height of 5x0 is 0, but has to be at least 1".into()));
+    assert_eq!(parse_board_size("0"), Err("The length of 0 is 0, but has to be at least 1".into()));
+}
+
+#[test]
+fn test_parse_separators() {
+    for size in [BoardSize::new(12, 9), BoardSize::new(1, 1), BoardSize::new(Idx::MAX, 3)] {
+        assert_eq!(parse_board_size(&size.to_string()), Ok(size));
+    }
+
+    for arg in ["8x6", "8X6", "8\u{d7}6", "8 x 6", " 8\u{d7} 6 ", "\t8X6\n"] {
+        assert_eq!(parse_board_size(arg), Ok(BoardSize::new(8, 6)), "{arg:?}");
+    }
+    assert_eq!(parse_board_size(" 7 "), Ok(BoardSize::new(7, 7)));
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |arg: &str| parse_board_size(arg).unwrap_err();
+    assert_eq!(error(""), "The board size is empty, expected <width>x<height> or <length>, e.g. 12x9 or 8");
+    assert_eq!(error("  "), error(""));
+    assert_eq!(error("8x"), "The height of 8x is missing");
+    assert_eq!(error("x8"), "The width of x8 is missing");
+    assert_eq!(error("8x8x8"), "8x8x8 has more than two dimensions, expected <width>x<height> or <length>, e.g. 12x9 or 8");
+    assert_eq!(error("8xa"), "The height of 8xa is not a whole number: a");
+    assert_eq!(error("8*8"), "The length of 8*8 is not a whole number: 8*8");
+    assert_eq!(error("-3x8"), "The width of -3x8 is not a whole number: -3");
+    assert_eq!(error(&format!("{}x8", Idx::MAX as u64 + 1)), format!("The width of {}x8 is larger than {}", Idx::MAX as u64 + 1, Idx::MAX));
+}
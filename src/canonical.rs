@@ -0,0 +1,102 @@
+use crate::{aliases::BoardIndex as Idx, args::CanonicalDirection, board_pos::BoardPos, move_graph::MoveGraph};
+
+/// Orders squares by row, then by column
+fn key(pos: BoardPos) -> (Idx, Idx) {
+    (pos.row(), pos.col())
+}
+
+/// Reverses the tour if it doesn't already run in the requested direction
+pub fn canonicalize(graph: &mut MoveGraph, direction: CanonicalDirection) {
+    if direction == CanonicalDirection::AsSolved {
+        return;
+    }
+
+    let Some(start) = graph.tour().start() else { return };
+    let end = graph.tour().last().unwrap_or(start);
+    let is_closed = start != end && graph.node(end).next() == Some(start);
+
+    let reverse = if is_closed {
+        // closed tours start at their first square either way, so only the neighbors can decide the direction
+        let (second, last) = (graph.node(start).next().unwrap(), end);
+        key(second) > key(last)
+    } else {
+        match direction {
+            CanonicalDirection::StartTopleft => key(end) < key(start),
+            CanonicalDirection::EndTopleft => key(start) < key(end),
+            CanonicalDirection::AsSolved => unreachable!(),
+        }
+    };
+
+    if reverse {
+        graph.reverse_in_place();
+    }
+}
+
+#[cfg(test)]
+use crate::{move_graph::fixtures::{self, RING, SIX_BY_SIX}, validation::{self, TourSummary}};
+
+#[cfg(test)]
+fn summary(graph: &MoveGraph) -> TourSummary {
+    validation::validate(graph).unwrap()
+}
+
+#[test]
+fn test_open_tours() {
+    let pos = |col, row| BoardPos::new(col, row);
+    let solved = fixtures::open(6, 6, &SIX_BY_SIX);
+    let reversed = {
+        let mut graph = solved.clone();
+        graph.reverse_in_place();
+        graph
+    };
+    assert_eq!((summary(&reversed).start, summary(&reversed).end), (pos(2, 3), pos(0, 0)));
+
+    for graph in [&solved, &reversed] {
+        let mut res = graph.clone();
+        canonicalize(&mut res, CanonicalDirection::AsSolved);
+        assert_eq!(&res, graph);
+
+        let mut res = graph.clone();
+        canonicalize(&mut res, CanonicalDirection::StartTopleft);
+        assert_eq!(res, solved);
+
+        let mut res = graph.clone();
+        canonicalize(&mut res, CanonicalDirection::EndTopleft);
+        assert_eq!(res, reversed);
+    }
+}
+
+#[test]
+fn test_marked_start_moves_along() {
+    // warnsdorff marks its starting square by pointing it to itself
+    let mut graph = fixtures::open(6, 6, &SIX_BY_SIX);
+    *graph.node_mut(BoardPos::ZERO).prev_mut() = Some(BoardPos::ZERO);
+
+    canonicalize(&mut graph, CanonicalDirection::EndTopleft);
+    let summary = summary(&graph);
+    assert_eq!((summary.start, summary.end, summary.squares), (BoardPos::new(2, 3), BoardPos::ZERO, 36));
+    assert_eq!(graph.node(BoardPos::new(2, 3)).prev(), Some(BoardPos::new(2, 3)));
+    assert_eq!(graph.node(BoardPos::ZERO).next(), None);
+}
+
+#[test]
+fn test_closed_tours() {
+    // the ring continues from A1 to C2 and returns from B3, which comes later
+    let solved = fixtures::closed(3, 3, &RING);
+    let mut reversed = solved.clone();
+    reversed.reverse_in_place();
+    assert_ne!(solved, reversed);
+
+    for direction in [CanonicalDirection::StartTopleft, CanonicalDirection::EndTopleft] {
+        for graph in [&solved, &reversed] {
+            let mut res = graph.clone();
+            canonicalize(&mut res, direction);
+            assert_eq!(res, solved, "{direction:?}");
+            assert!(summary(&res).closed);
+        }
+    }
+
+    let mut res = reversed.clone();
+    canonicalize(&mut res, CanonicalDirection::AsSolved);
+    assert_eq!(res, reversed);
+}
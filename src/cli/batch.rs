@@ -0,0 +1,143 @@
+use std::{io::{self, BufWriter, Write}, path::{Path, PathBuf}, time::Duration};
+
+use crate::{
+    args::{Args, InputArgs, OutputFormat},
+    board_size::{parse_board_size, BoardSize},
+    move_graph::MoveGraph,
+    overwrite::{self, Preflight},
+};
+
+/// Reads the board sizes from a batch file
+pub fn read_batch(path: &Path) -> Result<Vec<BoardSize>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    parse_batch(&content).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Parses one board size per line, skipping empty lines and '#' comments
+fn parse_batch(content: &str) -> Result<Vec<BoardSize>, String> {
+    content.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_no, line)| parse_board_size(line).map_err(|e| format!("line {line_no}: {e}")))
+        .collect()
+}
+
+/// The file a solved board is written to in the output directory
+fn output_path(dir: &Path, size: BoardSize, format: OutputFormat) -> PathBuf {
+    let ext = match format {
+        OutputFormat::Svg => "svg",
+        _ => "txt",
+    };
+
+    dir.join(format!("{size}.{ext}"))
+}
+
+/// How a batch went: the exit code of the first board that wasn't solved or failed a check (or 0 if all tours passed),
+/// the number of boards solved and the number left out because their files already existed
+pub struct Outcome {
+    pub exit_code: u8,
+    pub solved: usize,
+    pub skipped: usize,
+}
+
+/// Solves all sizes one after the other, either writing the boards to --output-dir or printing a summary table.
+/// With --overwrite-policy error, every file is checked before the first board is solved
+pub fn run<'a>(sizes: &[BoardSize], solve: impl Fn(InputArgs) -> Option<(Duration, MoveGraph<'a>)>, args: &Args) -> io::Result<Outcome> {
+    let format = match args.output_format {
+        OutputFormat::Auto => OutputFormat::Text,
+        other => other,
+    };
+
+    if let Some(ref dir) = args.output_dir {
+        for &size in sizes {
+            overwrite::preflight(&output_path(dir, size, format), args.overwrite_policy)
+                .map_err(|e| io::Error::new(io::ErrorKind::AlreadyExists, e))?;
+        }
+        std::fs::create_dir_all(dir)?;
+    } else {
+        println!("{:>11} | {:<6} | {:>12}", "Size", "Solved", "Elapsed (ms)");
+        println!("{:->11}-+-{:-<6}-+-{:->12}", "", "", "");
+    }
+
+    let mut exit_code = 0;
+    let mut solved = 0;
+    let mut skipped = 0;
+    for &size in sizes {
+        if let Some(ref dir) = args.output_dir {
+            let path = output_path(dir, size, format);
+            // only skip is left to decide here, error has been checked above
+            if overwrite::preflight(&path, args.overwrite_policy).is_ok_and(|preflight| preflight == Preflight::Skip) {
+                eprintln!("{size}: {} already exists, skipping", path.display());
+                skipped += 1;
+                continue;
+            }
+        }
+
+        let mut input = args.input.clone();
+        input.board_size = Some(size);
+
+        let mut board = solve(input);
+        if let Some((_, ref mut board)) = board {
+            if let Some(iterations) = args.shuffle {
+                super::shuffle_tour(board, iterations);
+            }
+            if args.prefer_closed {
+                super::close_tour(board);
+            }
+            crate::canonical::canonicalize(board, args.canonical_direction);
+        }
+        let timings = crate::phase_timer::take();
+        let result = board.and_then(|(elapsed, board)| match super::check_tour(&board, args.checks) {
+            Ok(tour) => Some((elapsed, tour)),
+            Err(code) => {
+                eprintln!("{size}: discarding the tour");
+                if exit_code == 0 {
+                    exit_code = code;
+                }
+                None
+            },
+        });
+
+        if result.is_none() && exit_code == 0 {
+            exit_code = super::exit_code::NO_TOUR;
+        }
+        solved += usize::from(result.is_some());
+        match args.output_dir {
+            Some(ref dir) => if let Some((elapsed, ref tour)) = result {
+                let mut writer = BufWriter::new(overwrite::create(&output_path(dir, size, format), args.overwrite_policy)?);
+                let style = super::OutputStyle { format, svg: &args.svg_style, color: crate::terminal::use_color(args.color, false), time_format: args.time_format };
+                super::write_board(&mut writer, &style, tour, elapsed, timings.as_ref(), None)?;
+                writer.flush()?;
+            },
+            None => match result {
+                Some((elapsed, _)) => println!("{:>11} | {:<6} | {:>12.3}", size.to_string(), "yes", elapsed.as_secs_f64() * 1000.0),
+                None => println!("{:>11} | {:<6} | {:>12}", size.to_string(), "no", "-"),
+            },
+        }
+
+        if let Some(timings) = timings {
+            eprintln!("Timings for {size}:");
+            eprintln!("{timings}");
+        }
+    }
+
+    Ok(Outcome { exit_code, solved, skipped })
+}
+
+#[test]
+fn test_parse_batch() {
+    let sizes = parse_batch("8\n\n# rectangular boards\n  12x9  \r\n5x6\n").unwrap();
+    assert_eq!(sizes.iter().map(|size| size.to_string()).collect::<Vec<_>>(), ["8x8", "12x9", "5x6"]);
+
+    assert!(parse_batch("8\nfoo\n").unwrap_err().starts_with("line 2: "));
+    assert_eq!(parse_batch("# nothing to do\n").unwrap().len(), 0);
+}
+
+#[test]
+fn test_output_path() {
+    let dir = Path::new("out");
+    assert_eq!(output_path(dir, BoardSize::new(12, 9), OutputFormat::Svg), dir.join("12x9.svg"));
+    assert_eq!(output_path(dir, BoardSize::new(12, 9), OutputFormat::Text), dir.join("12x9.txt"));
+    assert_eq!(output_path(dir, BoardSize::new(5, 6), OutputFormat::Arrows), dir.join("5x6.txt"));
+}
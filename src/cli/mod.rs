@@ -0,0 +1,425 @@
+mod batch;
+
+use crate::{
+    args, arrows, canonical, context, debug_output, degree_map, divide_and_conquer, dprintln, elapsed_seconds, feasibility, mask,
+    move_graph, overwrite, phase_timer, random, summary, svg, terminal, text, tour, warnsdorff,
+};
+use args::{Args, InputArgs, OutputFormat, OverwritePolicy, Precision, SvgStyle, TourChecks};
+use context::SolverContext;
+use degree_map::DegreeMap;
+use feasibility::Feasibility;
+use move_graph::MoveGraph;
+use overwrite::Preflight;
+use warnsdorff::CountedTours;
+use phase_timer::PhaseTimer;
+use summary::Summary;
+use tour::Tour;
+use std::{fs::File, io::{self, BufWriter, Write}, path::Path, process::ExitCode, time::{Duration, Instant}};
+
+/// The exit codes listed in the help, besides 0 for success and 2 for invalid arguments (which clap exits with)
+mod exit_code {
+    pub const INPUT_ERROR: u8 = 1;
+    pub const NO_TOUR: u8 = 3;
+    pub const INVALID_TOUR: u8 = 4;
+    // a tour was found, but it is open despite --fail-on-open or closed despite --fail-on-closed
+    pub const WRONG_TOUR_TYPE: u8 = 5;
+}
+
+/// Parses the arguments and runs the program as they ask, see the knights_tour binary
+pub fn main() -> ExitCode {
+    let args = Args::parse();
+    
+    #[cfg(feature = "tracing")]
+    debug_output::init_subscriber(args.verbose);
+    SolverContext::new()
+        .with_verbosity(args.verbose)
+        .with_debug_dedupe(args.debug_dedupe)
+        .with_failure_dump(args.failure_dump.clone())
+        .with_cache_dir(args.cache_dir.clone())
+        .with_heatmap(args.heatmap.is_some())
+        .with_best_effort(args.best_effort)
+        .run(|| {
+            let code = run(args);
+            debug_output::flush();
+            code
+        })
+}
+
+fn run(args: Args) -> ExitCode {
+    if args.timings {
+        phase_timer::enable();
+    }
+
+    let (solve, solver): (fn(_) -> _, _) = if args.input.use_warnsdorff {
+        // divide and conquer only knows about rounded corners and listed dead squares, board files need warnsdorff
+        (warnsdorff::solve, "warnsdorff")
+    } else {
+        (divide_and_conquer::solve, "dnc")
+    };
+    let print_summary = !args.no_summary;
+
+    if let Some(ref batch_file) = args.batch {
+        let sizes = match batch::read_batch(batch_file) {
+            Ok(sizes) => sizes,
+            Err(e) => {
+                eprintln!("{e}");
+                return exit(print_summary, Summary::new("error").with("reason", "batch-file"), exit_code::INPUT_ERROR);
+            },
+        };
+
+        let start = Instant::now();
+        let outcome = match batch::run(&sizes, solve, &args) {
+            Ok(res) => res,
+            Err(e) => {
+                eprintln!("Could not write batch results: {e}");
+                return exit(print_summary, Summary::new("error").with("reason", "output-file"), exit_code::INPUT_ERROR);
+            },
+        };
+        let summary = Summary::new(if outcome.solved + outcome.skipped == sizes.len() { "solved" } else { "failed" })
+            .with("solver", solver)
+            .with("boards", sizes.len())
+            .with("solved", outcome.solved)
+            .with("skipped", outcome.skipped)
+            .with("duration_ms", start.elapsed().as_millis())
+            .with_metrics(summary::take());
+        return exit(print_summary, summary, outcome.exit_code);
+    }
+
+    // checked before anything else is done, so that nothing is solved for a file that won't be written
+    if let Some(ref path) = args.output_file {
+        match overwrite::preflight(path, args.overwrite_policy) {
+            Ok(Preflight::Write) => {
+                if let Err(e) = overwrite::check_writable(path) {
+                    eprintln!("Could not create {}: {e}", path.display());
+                    return exit(print_summary, Summary::new("error").with("reason", "output-file"), exit_code::INPUT_ERROR);
+                }
+            },
+            Ok(Preflight::Skip) => {
+                eprintln!("{} already exists, leaving it as it is", path.display());
+                return exit(print_summary, Summary::new("skipped").with("reason", "output-exists"), 0);
+            },
+            Err(e) => {
+                eprintln!("{e}");
+                return exit(print_summary, Summary::new("error").with("reason", "output-exists"), exit_code::INPUT_ERROR);
+            },
+        }
+    }
+
+    if args.explain_partitions && !args.input.use_warnsdorff {
+        if let Some(size) = args.input.board_size {
+            eprint!("{}", divide_and_conquer::explain_partitions(size));
+        }
+    }
+
+    if let Some(ref path) = args.emit_mask {
+        let Some((dead_squares, size)) = warnsdorff::effective_mask(&args.input) else {
+            return exit(print_summary, Summary::new("error").with("reason", "input").with("solver", solver), exit_code::INPUT_ERROR);
+        };
+        if let Err(e) = mask::write_mask(path, &dead_squares, size) {
+            eprintln!("Could not write mask to {}: {e}", path.display());
+            return exit(print_summary, Summary::new("error").with("reason", "output-file"), exit_code::INPUT_ERROR);
+        }
+    }
+
+    let degree_map = if args.degree_map {
+        let Some(map) = DegreeMap::from_args(&args.input) else {
+            return exit(print_summary, Summary::new("error").with("reason", "input").with("solver", solver), exit_code::INPUT_ERROR);
+        };
+        Some(map)
+    } else {
+        None
+    };
+
+    if args.dry_run {
+        // the sectors divide and conquer would solve the board in, for boards it solves at all
+        let plan = args.input.board_size
+            .filter(|size| !args.input.use_warnsdorff && matches!(feasibility::tour_exists(*size, false), Feasibility::Possible))
+            .map(|size| (size, divide_and_conquer::plan(size)));
+        if (degree_map.is_some() || plan.is_some()) && (!args.quiet || args.output_file.is_some()) {
+            let Some(mut writer) = open_output(args.output_file.as_deref(), args.overwrite_policy) else {
+                return exit(print_summary, Summary::new("error").with("reason", "output-file"), exit_code::INPUT_ERROR);
+            };
+            let svg = matches!(resolve_output_format(args.output_file.as_deref(), args.output_format), OutputFormat::Svg);
+            let res = match (&degree_map, &plan) {
+                (Some(map), _) if svg => svg::render_degree_map(&mut writer, map, &args.svg_style),
+                (Some(map), _) => text::render_degree_map(&mut writer, map),
+                (None, Some((size, sectors))) if svg => svg::render_plan(&mut writer, *size, sectors, &args.svg_style),
+                (None, Some((_, sectors))) => text::render_plan(&mut writer, sectors),
+                (None, None) => unreachable!(),
+            }.and_then(|_| writer.flush());
+            if let Err(e) = res {
+                eprintln!("Could not write the {}: {e}", if degree_map.is_some() { "degree map" } else { "sectors" });
+                return exit(print_summary, Summary::new("error").with("reason", "output-file"), exit_code::INPUT_ERROR);
+            }
+        }
+
+        let summary = Summary::new("dry-run").with("solver", solver);
+        let summary = match plan {
+            Some((_, sectors)) => summary.with("sectors", sectors.len()),
+            None => summary,
+        };
+        return exit(print_summary, summary, 0);
+    }
+
+    if let Some(tours) = args.count_solutions {
+        return count_solutions(&args.input, tours, args.time_format, print_summary);
+    }
+
+    let quiet = args.quiet;
+    let checks = args.checks;
+    let output_options = (args.output_file, args.output_format);
+    let overwrite_policy = args.overwrite_policy;
+    let svg_style = args.svg_style;
+    let color = args.color;
+    let time_format = args.time_format;
+    let canonical_direction = args.canonical_direction;
+    let prefer_closed = args.prefer_closed;
+    let shuffle = args.shuffle;
+    let heatmap = args.heatmap.clone();
+    let feasibility = feasibility::of_input(&args.input);
+    let (elapsed, mut board) = if let Some(res) = solve(args.input) {
+        res
+    } else {
+        let metrics = summary::take();
+        if metrics.input_error {
+            return exit(print_summary, Summary::new("error").with("reason", "input").with("solver", solver), exit_code::INPUT_ERROR);
+        }
+
+        println!("No solution possible for this board configuration");
+        write_heatmap(heatmap.as_deref(), None, &svg_style);
+        let summary = match feasibility {
+            Some(Feasibility::Impossible(reason)) => Summary::new("infeasible").with("reason", reason.code),
+            _ if metrics.proven_no_tour => Summary::new("infeasible").with("reason", "exhaustive-search"),
+            _ => Summary::new("failed"),
+        };
+        return exit(print_summary, summary.with("solver", solver).with_metrics(metrics), exit_code::NO_TOUR);
+    };
+
+    if let Some(iterations) = shuffle {
+        shuffle_tour(&mut board, iterations);
+    }
+    if prefer_closed {
+        close_tour(&mut board);
+    }
+    canonical::canonicalize(&mut board, canonical_direction);
+
+    let tour = match check_tour(&board, checks) {
+        Ok(tour) => tour,
+        Err(code) => {
+            let reason = if code == exit_code::INVALID_TOUR { "invalid-tour" } else { "wrong-tour-type" };
+            return exit(print_summary, Summary::new("rejected").with("reason", reason).with("size", board.size()).with("solver", solver), code);
+        },
+    };
+
+    write_heatmap(heatmap.as_deref(), Some(&tour), &svg_style);
+
+    let metrics = summary::take();
+    // the solver already said that there is no tour, what follows is the longest path it found instead (--best-effort)
+    let tour = tour.with_complete(!metrics.partial);
+    let summary = Summary::new(if tour.is_complete() { "solved" } else { "partial" })
+        .with("size", tour.size())
+        .with("solver", solver)
+        .with("closed", tour.is_closed());
+    let summary = if tour.is_complete() { summary } else { summary.with("complete", false).with("squares", tour.len()) };
+    let summary = summary.with("duration_ms", elapsed.as_millis()).with_metrics(metrics);
+
+    let mut timings = phase_timer::take();
+    // quiet only keeps the board off stdout, a requested file is written regardless
+    if !quiet || output_options.0.is_some() {
+        let out_format = resolve_output_format(output_options.0.as_deref(), output_options.1);
+        let Some(mut writer) = open_output(output_options.0.as_deref(), overwrite_policy) else {
+            return exit(print_summary, Summary::new("error").with("reason", "output-file"), exit_code::INPUT_ERROR);
+        };
+
+        let style = OutputStyle { format: out_format, svg: &svg_style, color: terminal::use_color(color, output_options.0.is_none()), time_format };
+        let start = Instant::now();
+        let res = write_board(&mut writer, &style, &tour, elapsed, timings.as_ref(), degree_map.as_ref()).and_then(|_| writer.flush());
+        if let Err(e) = res {
+            eprintln!("Could not write the board: {e}");
+            return exit(print_summary, Summary::new("error").with("reason", "output-file"), exit_code::INPUT_ERROR);
+        }
+        if let Some(ref mut timings) = timings {
+            timings.record("render".into(), start.elapsed());
+        }
+    }
+
+    if quiet {
+        println!("{}", elapsed_text(elapsed, time_format));
+    }
+
+    if let Some(timings) = timings {
+        eprint!("{timings}");
+    }
+
+    exit(print_summary, summary, if tour.is_complete() { 0 } else { exit_code::NO_TOUR })
+}
+
+/// Counts the tours of the board for --count-solutions, printing the count and the time it took
+fn count_solutions(input: &InputArgs, tours: CountedTours, time_format: Precision, print_summary: bool) -> ExitCode {
+    let Some((dead_squares, size)) = warnsdorff::effective_mask(input) else {
+        return exit(print_summary, Summary::new("error").with("reason", "input").with("solver", "count"), exit_code::INPUT_ERROR);
+    };
+
+    let alive = usize::try_from(size.area()).unwrap_or(usize::MAX).saturating_sub(dead_squares.len());
+    if alive > warnsdorff::COUNT_LIMIT {
+        eprintln!("Counting the tours of {alive} squares would take too long, --count-solutions takes at most {}.", warnsdorff::COUNT_LIMIT);
+        return exit(print_summary, Summary::new("error").with("reason", "too-large").with("solver", "count"), exit_code::INPUT_ERROR);
+    }
+
+    let wrap = input.warnsdorff.as_ref().map(|w| w.wrap).unwrap_or_default();
+    let leaper = input.warnsdorff.as_ref().map(|w| w.piece).unwrap_or_default();
+    let start = Instant::now();
+    let count = warnsdorff::count_tours(size, &dead_squares, wrap, leaper, tours);
+    let elapsed = start.elapsed();
+
+    let (counted, kind) = match tours {
+        CountedTours::All => ("all", ""),
+        CountedTours::Closed => ("closed", "closed "),
+    };
+    println!("{count} {kind}tours");
+    println!("{}", elapsed_text(elapsed, time_format));
+
+    let summary = Summary::new("counted")
+        .with("size", size)
+        .with("solver", "count")
+        .with("tours", count)
+        .with("counted", counted)
+        .with("duration_ms", elapsed.as_millis())
+        .with_metrics(summary::take());
+    exit(print_summary, summary, 0)
+}
+
+/// Prints the summary line unless it was turned off and returns the given exit code
+fn exit(print_summary: bool, summary: Summary, code: u8) -> ExitCode {
+    if print_summary {
+        eprintln!("{summary}");
+    }
+
+    ExitCode::from(code)
+}
+
+/// Shuffles the tour with a seed taken from the current time, which is recorded for the summary
+fn shuffle_tour(board: &mut MoveGraph, iterations: usize) {
+    let seed = random::Random::seed_from_time();
+    dprintln!(1 => { seed } "Shuffle seed: {seed}");
+    summary::record(|metrics| metrics.shuffle_seed = Some(seed));
+    phase_timer::time("shuffle", || board.shuffle(iterations, seed));
+}
+
+/// Closes the tour if it is open and that can be done cheaply, reporting the outcome on stderr
+fn close_tour(board: &mut MoveGraph) {
+    let Some(start) = board.tour().start() else { return };
+    let end = board.tour().last().unwrap_or(start);
+    if board.node(end).next() == Some(start) {
+        eprintln!("The tour is closed");
+    } else if board.try_close() {
+        eprintln!("Closed the open tour from {start} to {end}");
+    } else {
+        eprintln!("The tour from {start} to {end} is open and could not be closed");
+    }
+}
+
+/// Takes the tour out of the graph the solver found it in and applies the requested checks to it,
+/// reporting any failure on stderr and returning the exit code for it
+fn check_tour(board: &MoveGraph, checks: TourChecks) -> Result<Tour, u8> {
+    let tour = Tour::from_graph(board).map_err(|defect| {
+        eprintln!("Tour verification failed: {defect}");
+        exit_code::INVALID_TOUR
+    })?;
+
+    if !(checks.verify || checks.fail_on_open || checks.fail_on_closed) {
+        return Ok(tour);
+    }
+
+    let kind = if tour.is_closed() { "closed" } else { "open" };
+    dprintln!(1 => "Verified {kind} tour of {} squares from {} to {}", tour.len(), tour.start(), tour.end());
+
+    if (tour.is_closed() && checks.fail_on_closed) || (!tour.is_closed() && checks.fail_on_open) {
+        eprintln!("The tour from {} to {} is {kind}", tour.start(), tour.end());
+        return Err(exit_code::WRONG_TOUR_TYPE);
+    }
+
+    Ok(tour)
+}
+
+/// Writes the --heatmap file, if there is one. Like the failure dump, it is only reported if it can't be written
+fn write_heatmap(path: Option<&Path>, tour: Option<&Tour>, style: &SvgStyle) {
+    let Some(path) = path else {
+        return;
+    };
+
+    let Some(retractions) = SolverContext::with_current(|context| context.take_heatmap()) else {
+        eprintln!("No heatmap written to {}, Warnsdorff didn't search the board.", path.display());
+        return;
+    };
+
+    let res = File::create(path).map(BufWriter::new).and_then(|mut writer| {
+        svg::render_heatmap(&mut writer, &retractions, tour, style)?;
+        writer.flush()
+    });
+    if let Err(e) = res {
+        eprintln!("Could not write the heatmap to {}: {e}", path.display());
+    }
+}
+
+/// Creates the output file as --overwrite-policy says, or hands out stdout if there is none. Failures are reported on stderr.
+/// Either is buffered, the SVG of a large board is written in millions of small pieces; flush it when done to see errors
+fn open_output(output_file: Option<&Path>, policy: OverwritePolicy) -> Option<BufWriter<Box<dyn Write>>> {
+    let writer: Box<dyn Write> = match output_file {
+        Some(file) => match overwrite::create(file, policy) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("Could not create {}: {e}", file.display());
+                return None;
+            },
+        },
+        None => Box::new(std::io::stdout()),
+    };
+    Some(BufWriter::new(writer))
+}
+
+fn resolve_output_format(output_file: Option<&Path>, format: OutputFormat) -> OutputFormat {
+    match format {
+        OutputFormat::Auto => match output_file.and_then(|file| file.extension()) {
+            Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
+            _ => OutputFormat::Text,
+        },
+        other => other,
+    }
+}
+
+fn elapsed_text(elapsed: Duration, time_format: Precision) -> String {
+    format!("💩 {} 💩", elapsed_seconds(elapsed, time_format))
+}
+
+/// How the board is written: the format, with --output-format auto resolved, the look of SVG output, whether text output is colored
+/// and how the elapsed time is written
+struct OutputStyle<'a> {
+    format: OutputFormat,
+    svg: &'a SvgStyle,
+    color: bool,
+    time_format: Precision,
+}
+
+fn write_board(writer: &mut impl Write, style: &OutputStyle, tour: &Tour, elapsed: Duration, timings: Option<&PhaseTimer>, degree_map: Option<&DegreeMap>) -> io::Result<()> {
+    if matches!(style.format, OutputFormat::Text | OutputFormat::Arrows) {
+        writeln!(writer, "{}", elapsed_text(elapsed, style.time_format))?;
+        if !tour.is_complete() {
+            writeln!(writer, "Partial tour: no full tour was found, this is the longest path of {} squares", tour.len())?;
+        }
+        writeln!(writer)?;
+        // the degree map gets a grid of its own, before the tour
+        if let Some(map) = degree_map {
+            text::render_degree_map(writer, map)?;
+            writeln!(writer)?;
+        }
+    }
+
+    match style.format {
+        OutputFormat::Text if style.color => text::render_text_colored(writer, tour),
+        OutputFormat::Text => text::render_text(writer, tour),
+        OutputFormat::Svg => svg::render_svg(writer, tour, elapsed, style.time_format, timings, style.svg, degree_map),
+        OutputFormat::Arrows => arrows::render_arrows(writer, tour),
+        OutputFormat::Auto => unreachable!(),
+    }
+}
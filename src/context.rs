@@ -0,0 +1,198 @@
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, sync::{atomic::{AtomicBool, AtomicU8, Ordering}, Arc, Mutex, OnceLock}};
+
+use crate::{board::matrix2d::Matrix2D, board_size::BoardSize, move_graph::{Direction, MoveGraph}, phase_timer::PhaseTimer, summary::Metrics};
+
+pub type StretchedCache = HashMap<(BoardSize, Direction), &'static MoveGraph<'static>>;
+
+/// Everything the solvers keep between boards: the stretched sector cache, the verbosity of the debug output,
+/// the metrics for the summary, the phase timings, where to dump failed searches, where to keep sectors between runs, the --heatmap counts
+/// and the longest path of a failed search for --best-effort. Clones share their state, contexts made with [SolverContext::new] never do,
+/// so several solves can run on different threads, either together in one context or isolated in their own
+#[derive(Debug, Clone, Default)]
+pub struct SolverContext(Arc<State>);
+
+#[derive(Debug, Default)]
+struct State {
+    stretched_cache: Mutex<StretchedCache>,
+    verbosity: AtomicU8,
+    debug_dedupe: AtomicBool,
+    metrics: Mutex<Metrics>,
+    // None while --timings is off, so that recording is a no-op
+    timings: Mutex<Option<PhaseTimer>>,
+    failure_dump: Mutex<Option<PathBuf>>,
+    cache_dir: Mutex<Option<PathBuf>>,
+    heatmap_enabled: AtomicBool,
+    heatmap: Mutex<Option<Matrix2D<u32>>>,
+    best_effort: AtomicBool,
+    longest_path: Mutex<Option<MoveGraph<'static>>>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<SolverContext>> = const { RefCell::new(None) };
+}
+
+// used by everything that runs outside of SolverContext::run
+static GLOBAL: OnceLock<SolverContext> = OnceLock::new();
+
+impl SolverContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_verbosity(self, verbosity: u8) -> Self {
+        self.0.verbosity.store(verbosity, Ordering::Relaxed);
+        self
+    }
+
+    /// Collapses runs of identical debug output, see [crate::debug_output::print]
+    pub fn with_debug_dedupe(self, dedupe: bool) -> Self {
+        self.0.debug_dedupe.store(dedupe, Ordering::Relaxed);
+        self
+    }
+
+    /// Writes the moves of the last search that failed to this file (--failure-dump)
+    pub fn with_failure_dump(self, path: Option<PathBuf>) -> Self {
+        *self.0.failure_dump.lock().unwrap() = path;
+        self
+    }
+
+    /// Keeps the stretched sectors in this directory as well, for later runs to find (--cache-dir)
+    pub fn with_cache_dir(self, dir: Option<PathBuf>) -> Self {
+        *self.0.cache_dir.lock().unwrap() = dir;
+        self
+    }
+
+    /// Counts how often Warnsdorff takes a move back on each square (--heatmap)
+    pub fn with_heatmap(self, enabled: bool) -> Self {
+        self.0.heatmap_enabled.store(enabled, Ordering::Relaxed);
+        self
+    }
+
+    /// Keeps the longest path of a search that finds no tour, to be written instead (--best-effort)
+    pub fn with_best_effort(self, enabled: bool) -> Self {
+        self.0.best_effort.store(enabled, Ordering::Relaxed);
+        self
+    }
+
+    /// Runs `f` with this as the current context of the calling thread, restoring the previous one afterwards
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        struct Restore(Option<SolverContext>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+            }
+        }
+
+        let _restore = Restore(CURRENT.with(|current| current.replace(Some(self.clone()))));
+        f()
+    }
+
+    /// Calls `f` with the context the calling thread runs in, or the process wide one outside of [SolverContext::run]
+    pub fn with_current<T>(f: impl FnOnce(&SolverContext) -> T) -> T {
+        CURRENT.with(|current| match *current.borrow() {
+            Some(ref context) => f(context),
+            None => f(GLOBAL.get_or_init(SolverContext::new)),
+        })
+    }
+
+    pub fn verbosity(&self) -> u8 {
+        self.0.verbosity.load(Ordering::Relaxed)
+    }
+
+    pub fn debug_dedupe(&self) -> bool {
+        self.0.debug_dedupe.load(Ordering::Relaxed)
+    }
+
+    pub fn failure_dump(&self) -> Option<PathBuf> {
+        self.0.failure_dump.lock().unwrap().clone()
+    }
+
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.0.cache_dir.lock().unwrap().clone()
+    }
+
+    pub fn heatmap_enabled(&self) -> bool {
+        self.0.heatmap_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Keeps the counts of a search, replacing those of any search before it
+    pub fn record_heatmap(&self, counts: Matrix2D<u32>) {
+        *self.0.heatmap.lock().unwrap() = Some(counts);
+    }
+
+    pub fn take_heatmap(&self) -> Option<Matrix2D<u32>> {
+        self.0.heatmap.lock().unwrap().take()
+    }
+
+    pub fn best_effort(&self) -> bool {
+        self.0.best_effort.load(Ordering::Relaxed)
+    }
+
+    /// Keeps the longest path of a failed search, replacing that of any search before it
+    pub fn record_longest_path(&self, path: MoveGraph<'static>) {
+        *self.0.longest_path.lock().unwrap() = Some(path);
+    }
+
+    pub fn take_longest_path(&self) -> Option<MoveGraph<'static>> {
+        self.0.longest_path.lock().unwrap().take()
+    }
+
+    pub fn stretched_cache(&self) -> &Mutex<StretchedCache> {
+        &self.0.stretched_cache
+    }
+
+    pub fn metrics(&self) -> &Mutex<Metrics> {
+        &self.0.metrics
+    }
+
+    pub fn timings(&self) -> &Mutex<Option<PhaseTimer>> {
+        &self.0.timings
+    }
+}
+
+#[test]
+fn test_contexts_are_isolated() {
+    let (first, second) = (SolverContext::new().with_verbosity(3), SolverContext::new());
+    let shared = first.clone();
+
+    first.run(|| crate::summary::record(|metrics| metrics.iterations = 5));
+    assert_eq!(shared.run(crate::summary::take).iterations, 5);
+    assert_eq!(second.run(crate::summary::take).iterations, 0);
+
+    assert!(shared.run(|| crate::debug_output::is_enabled(3)));
+    assert!(!second.run(|| crate::debug_output::is_enabled(1)));
+    // nesting restores the outer context
+    assert!(first.run(|| { second.run(|| ()); crate::debug_output::is_enabled(3) }));
+}
+
+#[test]
+fn test_concurrent_solves() {
+    use crate::{args::Args, validation::validate};
+
+    let shared = SolverContext::new();
+    let handles: Vec<_> = (0..8usize).map(|i| {
+        let context = if i % 2 == 0 { shared.clone() } else { SolverContext::new() };
+        std::thread::spawn(move || {
+            let size = (10 + 2 * i).to_string();
+            let args = Args::parse_from(["knights_tour", "-s", size.as_str()]).input;
+            let (_, graph) = context.run(|| crate::divide_and_conquer::solve(args)).unwrap();
+            let tour = validate(&graph).unwrap();
+            assert_eq!(tour.squares, (10 + 2 * i) * (10 + 2 * i));
+
+            let start = format!("{}1", (b'A' + i as u8) as char);
+            let args = Args::parse_from(["knights_tour", "-w", "-s", "8", "-p", start.as_str()]).input;
+            let (_, graph) = context.run(|| crate::warnsdorff::solve(args)).unwrap();
+            assert_eq!(validate(&graph).unwrap().squares, 64);
+
+            (i, context)
+        })
+    }).collect();
+
+    let contexts: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    for (i, context) in contexts {
+        assert!(!context.stretched_cache().lock().unwrap().is_empty(), "thread {i} cached nothing");
+    }
+
+    // a new context starts out without any of the sectors cached above
+    assert!(SolverContext::new().run(|| crate::warnsdorff::get_stretched_cached(BoardSize::new(6, 6), Direction::Horizontal)).is_none());
+}
@@ -1,61 +1,260 @@
-static mut DEBUG_ENABLED: u8 = 0;
-pub fn set(value: u8) {
-    unsafe { DEBUG_ENABLED = value; }
-}
+use std::{cell::{Cell, RefCell}, hash::{DefaultHasher, Hash, Hasher}, io::{self, Write}};
+
+use crate::context::SolverContext;
 
-pub fn disable() {
-    unsafe { DEBUG_ENABLED = 0; }
+thread_local! {
+    // per thread, so that holding back the output of one solve leaves the others running alongside it alone
+    static SUSPENDED: Cell<bool> = const { Cell::new(false) };
+    // per thread as well, so that solves running alongside each other don't swallow each other's lines
+    static DEDUPE: RefCell<Dedupe> = RefCell::new(Dedupe::default());
 }
 
+/// Whether the current [SolverContext] asks for debug output of the given verbosity
 pub fn is_enabled(value: u8) -> bool {
-    unsafe { DEBUG_ENABLED >= value }
+    !is_suspended() && SolverContext::with_current(|context| context.verbosity() >= value)
 }
 
+/// Whether debug output is held back by [suspended], which also keeps back the events of the tracing feature
+pub fn is_suspended() -> bool {
+    SUSPENDED.with(Cell::get)
+}
+
+/// Runs `f` without any debug output on the calling thread. Afterwards, the output is back to how it was before,
+/// even if `f` panics
 pub fn suspended<T>(f: impl FnOnce() -> T) -> T {
-    let old = unsafe{ DEBUG_ENABLED };
-    disable();
-    let res = f();
-    if old > 0 {
-        unsafe { DEBUG_ENABLED = old; };
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SUSPENDED.with(|suspended| suspended.set(self.0));
+        }
     }
 
-    res
+    let _restore = Restore(SUSPENDED.with(|suspended| suspended.replace(true)));
+    f()
 }
 
-#[macro_export]
-macro_rules! dprint {
-    (1 => $($arg:tt)*) => {
-        if $crate::debug_output::is_enabled(1) {
-            eprint!($($arg)*);
+/// Writes debug output of the given verbosity to stderr. Called by [dprint] and [dprintln] once they know the output is enabled.
+/// If the current [SolverContext] asks for it, runs of identical output are collapsed, see [Dedupe].
+/// With the tracing feature, the macros send events instead and the subscriber decides what is written
+#[cfg_attr(feature = "tracing", allow(dead_code))]
+pub fn print(level: u8, args: std::fmt::Arguments, newline: bool) {
+    if !SolverContext::with_current(SolverContext::debug_dedupe) {
+        if newline {
+            eprintln!("{args}");
+        } else {
+            eprint!("{args}");
         }
-    };
-    (2 => $($arg:tt)*) => {
-        if $crate::debug_output::is_enabled(2) {
-            eprint!($($arg)*);
+        return;
+    }
+
+    let mut payload = args.to_string();
+    if newline {
+        payload.push('\n');
+    }
+    // there is nowhere left to report a failure to write debug output to
+    let _ = DEDUPE.with(|dedupe| dedupe.borrow_mut().write(&mut io::stderr(), level, &payload));
+}
+
+/// Writes the counts of the repeats held back on the calling thread, for when there is no more output to come
+pub fn flush() {
+    if !SolverContext::with_current(SolverContext::debug_dedupe) {
+        return;
+    }
+
+    let _ = DEDUPE.with(|dedupe| dedupe.borrow_mut().flush(&mut io::stderr()));
+}
+
+/// Collapses consecutive identical output of each verbosity into its first occurrence, followed by "… repeated N times"
+/// once different output of that verbosity comes along. Only hashes are kept, so the previous output needn't be
+#[derive(Debug, Default)]
+struct Dedupe {
+    // the hash of the last output and how often it was repeated since, for verbosity 1 to 3
+    last: [Option<(u64, usize)>; 3],
+}
+
+impl Dedupe {
+    fn write(&mut self, writer: &mut impl Write, level: u8, payload: &str) -> io::Result<()> {
+        let mut hasher = DefaultHasher::new();
+        payload.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let last = &mut self.last[level.clamp(1, 3) as usize - 1];
+        match last {
+            Some((last, repeats)) if *last == hash => {
+                *repeats += 1;
+                return Ok(());
+            },
+            _ => Self::write_repeats(writer, last.replace((hash, 0)))?,
         }
-    };
-    (3 => $($arg:tt)*) => {
-        if $crate::debug_output::is_enabled(3) {
-            eprint!($($arg)*);
+
+        writer.write_all(payload.as_bytes())
+    }
+
+    fn flush(&mut self, writer: &mut impl Write) -> io::Result<()> {
+        for last in &mut self.last {
+            Self::write_repeats(writer, last.take())?;
         }
+
+        Ok(())
+    }
+
+    fn write_repeats(writer: &mut impl Write, last: Option<(u64, usize)>) -> io::Result<()> {
+        match last {
+            Some((_, 1)) => writeln!(writer, "\u{2026} repeated once"),
+            Some((_, repeats)) if repeats > 1 => writeln!(writer, "\u{2026} repeated {repeats} times"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Routes the debug output through a subscriber writing to stderr, showing as much as the -v count asks for
+#[cfg(feature = "tracing")]
+pub fn init_subscriber(verbosity: u8) {
+    use tracing_subscriber::filter::LevelFilter;
+
+    let level = match verbosity {
+        0 => LevelFilter::OFF,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
     };
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(level)
+        .without_time()
+        .with_level(false)
+        .with_target(false)
+        .init();
 }
 
+/// Prints debug output for the given verbosity (1 to 3) to stderr, e.g. `dprint!(2 => "{size}")`.
+/// With the tracing feature, this is an event at the info (1), debug (2) or trace (3) level instead,
+/// which can carry structured fields ahead of the message: `dprint!(3 => { pos = %pos, count } "...")`
+#[macro_export]
+macro_rules! dprint {
+    ($level:tt => $($arg:tt)*) => {
+        $crate::debug_event!(false, $level => $($arg)*)
+    };
+}
+
+/// Like [dprint], ending the line
 #[macro_export]
 macro_rules! dprintln {
-    (1 => $($arg:tt)*) => {
-        if $crate::debug_output::is_enabled(1) {
-            eprintln!($($arg)*);
-        }
+    ($level:tt => $($arg:tt)*) => {
+        $crate::debug_event!(true, $level => $($arg)*)
     };
-    (2 => $($arg:tt)*) => {
-        if $crate::debug_output::is_enabled(2) {
-            eprintln!($($arg)*);
-        }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! debug_event {
+    ($newline:literal, $level:tt => ) => {
+        $crate::debug_event!(@emit $newline, $level, [] "")
     };
-    (3 => $($arg:tt)*) => {
-        if $crate::debug_output::is_enabled(3) {
-            eprintln!($($arg)*);
-        }
+    ($newline:literal, $level:tt => { $($field:tt)* } $($arg:tt)+) => {
+        $crate::debug_event!(@emit $newline, $level, [$($field)*,] $($arg)+)
     };
+    ($newline:literal, $level:tt => $($arg:tt)+) => {
+        $crate::debug_event!(@emit $newline, $level, [] $($arg)+)
+    };
+    // the fields only go to tracing, each followed by a comma
+    (@emit $newline:literal, $level:tt, [$($field:tt)*] $($arg:tt)+) => {{
+        #[cfg(not(feature = "tracing"))]
+        {
+            if $crate::debug_output::is_enabled($level) {
+                $crate::debug_output::print($level, format_args!($($arg)+), $newline);
+            }
+        }
+        #[cfg(feature = "tracing")]
+        {
+            if !$crate::debug_output::is_suspended() {
+                ::tracing::event!($crate::debug_level!($level), $($field)* $($arg)+);
+            }
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! debug_level {
+    (1) => { ::tracing::Level::INFO };
+    (2) => { ::tracing::Level::DEBUG };
+    (3) => { ::tracing::Level::TRACE };
+}
+
+#[test]
+fn test_dedupe() {
+    let mut dedupe = Dedupe::default();
+    let mut out = Vec::new();
+    let lines = [(3, "a\n"), (3, "a\n"), (3, "a\n"), (1, "x\n"), (3, "a\n"), (3, "b\n"), (1, "x\n"), (3, "b\n"), (3, "a\n"), (1, "y\n"), (1, "y\n")];
+    for (level, line) in lines {
+        dedupe.write(&mut out, level, line).unwrap();
+    }
+    dedupe.flush(&mut out).unwrap();
+
+    // each level only compares with its own last line
+    let expected = "a\nx\n\u{2026} repeated 3 times\nb\n\u{2026} repeated once\na\n\u{2026} repeated once\ny\n\u{2026} repeated once\n";
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+    // nothing held back after a flush
+    let mut out = Vec::new();
+    dedupe.write(&mut out, 3, "a\n").unwrap();
+    dedupe.flush(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "a\n");
+}
+
+#[test]
+fn test_suspended_nesting() {
+    SolverContext::new().with_verbosity(3).run(|| {
+        assert!(is_enabled(3));
+        suspended(|| {
+            assert!(!is_enabled(1));
+            // the inner call leaves the output suspended for the rest of the outer one
+            suspended(|| assert!(is_suspended()));
+            assert!(is_suspended());
+        });
+        assert!(is_enabled(3));
+
+        let res = std::panic::catch_unwind(|| suspended(|| panic!("while suspended")));
+        assert!(res.is_err());
+        assert!(!is_suspended());
+        assert!(is_enabled(3));
+    });
+}
+
+#[cfg(all(test, feature = "tracing"))]
+#[derive(Clone, Default)]
+struct CapturedOutput(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(all(test, feature = "tracing"))]
+impl std::io::Write for CapturedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_events() {
+    let output = CapturedOutput::default();
+    let writer = output.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_max_level(tracing::Level::TRACE)
+        .without_time()
+        .with_target(false)
+        .finish();
+
+    let args = crate::args::Args::parse_from(["knights_tour", "-w", "-s", "6", "-p", "random", "--random-seed", "7"]).input;
+    tracing::subscriber::with_default(subscriber, || crate::warnsdorff::solve(args));
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let event = |level: &str, text: &str| output.lines().any(|line| line.trim_start().starts_with(level) && line.contains(text));
+    assert!(event("INFO", "Random seed: 7 seed=7"), "{output}");
+    assert!(event("DEBUG", "expected_move_count=35"), "{output}");
+    assert!(event("TRACE", "Move #1: count=1 pos="), "{output}");
 }
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use crate::{args::{InputArgs, Wrap}, bitset::BitSet, board::matrix2d::Matrix2D, board_pos::BoardPos, board_size::BoardSize, leaper::Leaper, warnsdorff::{self, Degrees}};
+
+/// The number of accessible squares one move of the piece away from each accessible square, before any of them is visited.
+/// Squares with few neighbors are the ones a tour has trouble getting into and out of again
+pub struct DegreeMap {
+    // None for inaccessible squares
+    degrees: Matrix2D<Option<u8>>,
+}
+
+impl DegreeMap {
+    pub fn new(size: BoardSize, dead_squares: &HashSet<BoardPos>, wrap: Wrap, leaper: Leaper) -> Self {
+        let mut dead = BitSet::new(size);
+        for pos in dead_squares {
+            dead.insert(*pos);
+        }
+
+        let counts = Degrees::new(size, wrap, leaper, |pos| dead.contains(pos));
+        let mut degrees = Matrix2D::new(size.width(), size.height(), || None);
+        for col in 0..size.width() {
+            for row in 0..size.height() {
+                let pos = BoardPos::new(col, row);
+                if !dead.contains(pos) {
+                    *degrees.at_mut(pos) = Some(counts.get(pos) as u8);
+                }
+            }
+        }
+
+        Self { degrees }
+    }
+
+    /// The map of the board the solver would be given for these arguments, or None if the board can't be read
+    pub fn from_args(args: &InputArgs) -> Option<Self> {
+        let (dead_squares, size) = warnsdorff::effective_mask(args)?;
+        let (wrap, leaper) = args.warnsdorff.as_ref().map_or((Wrap::None, Leaper::KNIGHT), |w| (w.wrap, w.piece));
+        Some(Self::new(size, &dead_squares, wrap, leaper))
+    }
+
+    pub fn size(&self) -> BoardSize {
+        self.degrees.size()
+    }
+
+    /// The degree of the square, or None if it is inaccessible
+    pub fn get(&self, pos: BoardPos) -> Option<u8> {
+        *self.degrees.at(pos)
+    }
+}
+
+#[test]
+fn test_empty_board() {
+    let map = DegreeMap::new(BoardSize::new(8, 8), &HashSet::new(), Wrap::None, Leaper::KNIGHT);
+    let at = |col, row| map.get(BoardPos::new(col, row));
+
+    // corners
+    assert_eq!([at(0, 0), at(7, 0), at(0, 7), at(7, 7)], [Some(2); 4]);
+    // next to the corners, and further along the edges
+    assert_eq!([at(1, 0), at(0, 1), at(6, 7)], [Some(3); 3]);
+    assert_eq!([at(2, 0), at(0, 4), at(7, 3)], [Some(4); 3]);
+    assert_eq!(at(1, 1), Some(4));
+    assert_eq!(at(1, 3), Some(6));
+    // the center
+    assert_eq!([at(2, 2), at(3, 3), at(4, 4), at(5, 2)], [Some(8); 4]);
+
+    let total: u32 = (0..8).flat_map(|col| (0..8).map(move |row| BoardPos::new(col, row))).map(|pos| map.get(pos).unwrap() as u32).sum();
+    // twice the number of knight moves on an 8x8 board
+    assert_eq!(total, 2 * 168);
+}
+
+#[test]
+fn test_masked_board() {
+    // C3 and D4 are dead, which leaves the squares that could jump onto them with fewer neighbors
+    let dead = HashSet::from([BoardPos::new(2, 2), BoardPos::new(3, 3)]);
+    let map = DegreeMap::new(BoardSize::new(8, 8), &dead, Wrap::None, Leaper::KNIGHT);
+    let at = |col, row| map.get(BoardPos::new(col, row));
+
+    assert_eq!(at(2, 2), None);
+    assert_eq!(at(3, 3), None);
+    // A1 only reaches B3 and C2 either way
+    assert_eq!(at(0, 0), Some(2));
+    // B1 loses C3, E2 loses C3 and D4
+    assert_eq!(at(1, 0), Some(2));
+    assert_eq!(at(4, 1), Some(4));
+    // B3 loses D4, E4 loses C3, and E5 neighbors neither of them
+    assert_eq!(at(1, 2), Some(5));
+    assert_eq!(at(4, 3), Some(7));
+    assert_eq!(at(4, 4), Some(8));
+    // far away from both
+    assert_eq!(at(7, 7), Some(2));
+}
+
+#[test]
+fn test_wrap_and_leaper() {
+    let map = DegreeMap::new(BoardSize::new(8, 8), &HashSet::new(), Wrap::Both, Leaper::KNIGHT);
+    assert_eq!(map.get(BoardPos::new(0, 0)), Some(8));
+
+    let map = DegreeMap::new(BoardSize::new(8, 8), &HashSet::new(), Wrap::None, Leaper::CAMEL);
+    assert_eq!(map.get(BoardPos::new(0, 0)), Some(2));
+    assert_eq!(map.get(BoardPos::new(3, 3)), Some(8));
+}
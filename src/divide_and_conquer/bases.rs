@@ -1,50 +1,227 @@
 use crate::{
-    board_pos::BoardPos, board_size::BoardSize, move_graph::{
+    aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath},
+    board_pos::BoardPos,
+    board_size::BoardSize,
+    move_graph::{
         Direction,
         MoveGraph,
-    }, warnsdorff::{
+    },
+    warnsdorff::{
         get_stretched_cached,
+        get_stretched_in_memory,
         insert_stretched_cache,
     }
 };
 
+/// The knight's moves the base tours are written in, from 'a' to 'h'
+const STEPS: [(IdxMath, IdxMath); 8] = [(1, -2), (2, -1), (2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2)];
+
+/// Paths from (0,0) to (0,1) through sectors merged horizontally, with the moves merges rely on at the top right and bottom left corners.
+/// Sectors merged vertically use the same paths flipped. 6x3, 6x4, 8x4 and 10x4 have no such path
+const STRETCHED: [(Idx, Idx, &str); 15] = [
+    (3, 8, "cedehcfadgaahechecehaaf"),
+    (3, 10, "cedgddfadgbehaahebhededagbhaf"),
+    (4, 3, "cfacfadgbeg"),
+    (4, 5, "cdfhacefaadegabedgh"),
+    (4, 6, "cfacfcfchfchebgdbhafchf"),
+    (4, 7, "cfacfcefacfhbechgabedegabhf"),
+    (4, 8, "cfacfcfecafchgbhbefcdfhcfabgbhf"),
+    (4, 9, "cfacfcfcefacfhbgbhbefcfdchfcagbgbhf"),
+    (4, 10, "cdgbgecfcdegabgedchhcefacfhbahafecfabhf"),
+    (6, 5, "cbdegfaacdfgbbgfecbhbedgfhadg"),
+    (6, 6, "cbdefgaacdehcfghacbefehabcffebbdghg"),
+    (6, 7, "cbdgbedegfahadacedfghadcdgfhacagdcegfcagh"),
+    (6, 9, "cbdgbedggacdedfghabbgfecbdefgbehaccehafdchhfdfahbgadg"),
+    (8, 3, "daccafchebgfgebcbgegbeg"),
+    (10, 3, "daccbgegbebcafchebgfgfcaffadg"),
+];
+
+/// Open tours of the top left sector of boards three squares across, which have no closed tours short enough to be a sector,
+/// starting at (0,0) and with the same corner moves. Only the narrower orientation is listed, the other one is the same tour flipped
+const STRIPS: [(Idx, Idx, &str); 5] = [
+    (3, 4, "dafdafchebh"),
+    (3, 7, "dafchebeechebgdagdah"),
+    (3, 8, "dafdafddfadgbehbehbhebh"),
+    (3, 9, "decehbgadeechebgdahahebehb"),
+    (3, 10, "dafdebeechebgdahhadfcfdahaheb"),
+];
+
+/// Closed tours of the top left sector with the same corner moves, starting at (0,0) or, if both sides are odd, at (1,0) leaving (0,0) out.
+/// Only the narrower orientation is listed, the other one is the same tour flipped
+const CLOSED: [(Idx, Idx, &str); 21] = [
+    (5, 5, "edbagfdchcfgabdgbedgfab"),
+    (5, 6, "cbedfgbbhfedbgebchagfcceghacf"),
+    (5, 7, "ebbegbdfceghccfgacdgfacfhacagfcch"),
+    (5, 8, "cbegbdedfgahacdgbgfdedbafdghbddgfahcchg"),
+    (5, 9, "ebbegbdfcedgfadgahccefhacdggdcahgdgacagfcch"),
+    (5, 10, "cbegbdeghbcffdedbafcfgahbcefebchhfdcahahgedbdfgbh"),
+    (6, 6, "cbdefgbgacbedfgbdahgfcacedgfhacfebh"),
+    (6, 7, "cbdgbedegfahadgbcedfgacbgbgfedcbhechgfdah"),
+    (6, 8, "cbdgbedefgahadgbcdfcegfahabceecfghbchfdaddgfhbh"),
+    (6, 9, "cbdgbedgagfdedcbhagbgfecbghededafcbagfcdgfabcfchgbgfa"),
+    (6, 10, "cbdgbedgagfdedebcafchafdfgahccdfgbbhggadaceegecehbbaffhaceh"),
+    (7, 7, "ebbcedfgfadgahbcbefgedbcafchahfdfgdcbgacfghcbge"),
+    (7, 8, "cbcedegfhahbcbedefghcfcbchafhbgfecccfgfabaadegfcaghecaf"),
+    (7, 9, "ebbcedefghahbcbedfcegfhbgacfdfcbchebgffabbahfdfgdcbgacfghcbge"),
+    (7, 10, "cbcededgbegfhcfadgahcfdcbgdbhafcfggahbcbeffhacbdeefaagfdbcfabgfhbcfeh"),
+    (8, 8, "cbcdedgbegfgahacbcfadedfgfhcfahabcbedefaafgbdfaggbcdfgbeceghcag"),
+    (8, 9, "cbcdedfgfhcfadgahabcbededgbeggdghahbdcbhffhedccebagehcfhcbghbcffebbgffa"),
+    (8, 10, "cbcdedegfgahcfdcbchaffdbafcfgfhcfahahbcbdehbgfgedbdfcbagggbcbeeefaagfdbcagfcehh"),
+    (9, 9, "ebbcbededgbegfghahbeecfdbcbahagfefdfchebchghccccfgabahffcdfhhafdcbgdcfgacbghcfg"),
+    (9, 10, "cbcbededfgfgahcfdchehcfahahbcbcededgfhccagdfgbgghabcbdedfgbhgecfcaghhedbccfgacbghcfebgfaf"),
+    (10, 10, "cbcbdgbedgagegbehfededbcbchahagfccedegfgfadgahbgadgbccfcddfhgdggdcahgdgbafccbgechcchgfdfccbghcfggag"),
+];
+
 pub fn get<'a>(direction: Direction, size: BoardSize) -> Option<&'a MoveGraph<'a>> {
-    match (direction, size.width(), size.height()) {
-        (Direction::Horizontal, 4, 10) | (Direction::Vertical, 10, 4) => Some(get_4_by_10(direction)),
-        _ => None
+    let horizontal = if direction == Direction::Horizontal { size } else { size.flip() };
+    let (_, _, moves) = STRETCHED.iter().find(|(width, height, _)| BoardSize::new(*width, *height) == horizontal)?;
+
+    // not from the --cache-dir, a file left there must not stand in for the table
+    if let Some(result) = get_stretched_in_memory(size, direction) {
+        return Some(result)
+    }
+
+    let result = decode(horizontal, BoardPos::ZERO, moves);
+
+    let flipped_result = result.flip();
+    insert_stretched_cache(horizontal, Direction::Horizontal, result);
+    insert_stretched_cache(horizontal.flip(), Direction::Vertical, flipped_result);
+
+    get_stretched_cached(size, direction)
+}
+
+/// A closed tour of the top left sector, if there is one for its size
+pub fn get_closed(size: BoardSize) -> Option<MoveGraph<'static>> {
+    let flip = size.width() > size.height();
+    let narrow = if flip { size.flip() } else { size };
+    let (_, _, moves) = CLOSED.iter().find(|(width, height, _)| BoardSize::new(*width, *height) == narrow)?;
+
+    let skip_corner = !size.width().is_multiple_of(2) && !size.height().is_multiple_of(2);
+    let start = if skip_corner { BoardPos::new(1, 0) } else { BoardPos::ZERO };
+    let mut result = decode(narrow, start, moves);
+    let end = result.tour().last().unwrap();
+    *result.node_mut(end).next_mut() = Some(start);
+    *result.node_mut(start).prev_mut() = Some(end);
+
+    Some(if flip { result.flip() } else { result })
+}
+
+/// An open tour of the top left sector of a board three squares across, if there is one for its size
+pub fn get_strip(size: BoardSize) -> Option<MoveGraph<'static>> {
+    let flip = size.width() > size.height();
+    let narrow = if flip { size.flip() } else { size };
+    let (_, _, moves) = STRIPS.iter().find(|(width, height, _)| BoardSize::new(*width, *height) == narrow)?;
+
+    let result = decode(narrow, BoardPos::ZERO, moves);
+    Some(if flip { result.flip() } else { result })
+}
+
+fn decode<'a>(size: BoardSize, start: BoardPos, moves: &str) -> MoveGraph<'a> {
+    let tour = moves.bytes().scan(start, |pos, step| {
+        let (col, row) = STEPS[(step - b'a') as usize];
+        *pos = pos.translate(col, row);
+        Some(*pos)
+    });
+
+    MoveGraph::from_tour(size.width(), size.height(), std::iter::once(start).chain(tour))
+}
+
+#[cfg(test)]
+use crate::validation;
+#[cfg(test)]
+use super::contract::{check_stretched, merge_corners, StretchedContract};
+
+#[test]
+fn test_stretched_bases() {
+    for (width, height, moves) in STRETCHED {
+        let size = BoardSize::new(width, height);
+        assert_eq!(moves.len(), size.area() as usize - 1, "{size}");
+
+        for (direction, size) in [(Direction::Horizontal, size), (Direction::Vertical, size.flip())] {
+            let graph = get(direction, size).unwrap();
+            check_stretched(graph, &StretchedContract::new(size, direction)).unwrap_or_else(|e| panic!("{size} {direction:?}: {e}"));
+            assert_eq!(validation::validate(graph).unwrap().squares, size.area() as usize, "{size} {direction:?}");
+
+            // the base is what the solver finds in the cache from now on
+            assert!(std::ptr::eq(get_stretched_cached(size, direction).unwrap(), graph));
+        }
     }
 }
 
-fn get_4_by_10<'a>(direction: Direction) -> &'a MoveGraph<'a> {
-    let size = if direction == Direction::Horizontal {
-        BoardSize::new(4, 10)
-    }else {
-        BoardSize::new(10, 4)
-    };
+#[test]
+fn test_stretched_bases_ignore_cache_dir() {
+    use crate::{board_pos::knight_moves, context::SolverContext, temp_dir::TempDir};
+
+    // every path from (0,0) to (0,1) through the sector, in the order the moves are tried
+    fn paths(size: BoardSize, path: &mut Vec<BoardPos>, found: &mut dyn FnMut(&[BoardPos]) -> bool) -> bool {
+        if path.len() == size.area() as usize {
+            return found(path);
+        }
+
+        for next in knight_moves(*path.last().unwrap(), size) {
+            if !path.contains(&next) {
+                path.push(next);
+                if paths(size, path, found) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
 
-    if let Some(result) = get_stretched_cached(size, direction) {
-        return result
+        false
     }
 
-    let mut result = MoveGraph::new(4, 10);
-    let mut prev = BoardPos::new(0, 0);
-    let sequence = vec![
-        (2, 1), (3, 3), (1, 2), (3, 1), (1, 0), (0, 2), (2, 3), (0, 4), (2, 5), (3, 7),
-        (2, 9), (0, 8), (1, 6), (3, 5), (1, 4), (0, 6), (1, 8), (3, 9), (2, 7), (1, 5),
-        (3, 6), (2, 8), (0, 9), (1, 7), (3, 8), (1, 9), (0, 7), (2, 6), (3, 4), (2, 2),
-        (3, 0), (1, 1), (0, 3), (2, 4), (0, 5), (1, 3), (3, 2), (2, 0), (0, 1),
-    ];
-
-    for next in sequence {
-        let next = BoardPos::new(next.0, next.1);
-        *result.node_mut(prev).next_mut() = Some(next);
-        *result.node_mut(next).prev_mut() = Some(prev);
-        prev = next;
+    let dir = TempDir::new("bases");
+    let (size, direction) = (BoardSize::new(4, 5), Direction::Horizontal);
+    let table: Vec<_> = SolverContext::new().run(|| get(direction, size).unwrap().tour().collect());
+
+    // a different sector keeping the same contract, left in the directory by an earlier run
+    let mut other = vec![BoardPos::ZERO];
+    let contract = StretchedContract::new(size, direction);
+    assert!(paths(size, &mut other, &mut |path| {
+        path != table && check_stretched(&MoveGraph::from_tour(size.width(), size.height(), path.to_vec()), &contract).is_ok()
+    }));
+    let with_dir = || SolverContext::new().with_cache_dir(Some(dir.to_path_buf()));
+    with_dir().run(|| insert_stretched_cache(size, direction, MoveGraph::from_tour(size.width(), size.height(), other.clone())));
+    let stale: Vec<_> = with_dir().run(|| get_stretched_cached(size, direction).unwrap().tour().collect());
+    assert_eq!(stale, other);
+
+    let found: Vec<_> = with_dir().run(|| get(direction, size).unwrap().tour().collect());
+    assert_eq!(found, table);
+}
+
+#[test]
+fn test_closed_bases() {
+    for (width, height, _) in CLOSED {
+        for size in [BoardSize::new(width, height), BoardSize::new(height, width)] {
+            let graph = get_closed(size).unwrap();
+            let tour = validation::validate(&graph).unwrap_or_else(|e| panic!("{size}: {e}"));
+            let skipped = (!width.is_multiple_of(2) && !height.is_multiple_of(2)) as usize;
+            assert_eq!((tour.squares, tour.closed), (size.area() as usize - skipped, true), "{size}");
+            for (a, b) in merge_corners(size) {
+                assert!(graph.node(a).next() == Some(b) || graph.node(b).next() == Some(a), "{size}: {a} and {b} are not linked");
+            }
+        }
     }
 
-    let flipped_result = result.flip();
-    insert_stretched_cache(BoardSize::new(4, 10), Direction::Horizontal, result);
-    insert_stretched_cache(BoardSize::new(10, 4), Direction::Vertical, flipped_result);
+    for width in 5..=10 {
+        for height in 5..=10 {
+            assert!(get_closed(BoardSize::new(width, height)).is_some(), "{width}x{height}");
+        }
+    }
+}
 
-    get_stretched_cached(size, direction).unwrap()
-}
\ No newline at end of file
+#[test]
+fn test_strip_bases() {
+    for (width, height, moves) in STRIPS {
+        assert_eq!(moves.len(), (width * height) as usize - 1, "{width}x{height}");
+        for size in [BoardSize::new(width, height), BoardSize::new(height, width)] {
+            let graph = get_strip(size).unwrap();
+            let tour = validation::validate(&graph).unwrap_or_else(|e| panic!("{size}: {e}"));
+            assert_eq!((tour.squares, tour.start), (size.area() as usize, BoardPos::ZERO), "{size}");
+            for (a, b) in merge_corners(size) {
+                assert!(graph.node(a).next() == Some(b) || graph.node(b).next() == Some(a), "{size}: {a} and {b} are not linked");
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::Display;
+
+use crate::{board_pos::BoardPos, board_size::BoardSize, move_graph::{Direction, MoveGraph}, validation};
+
+/// The ends a stretched sector's path must have so that [super::merge::merge] can link it to the sector before it:
+/// it starts at (0,0) and ends on the square next to it, below for horizontal merges and to the right for vertical ones.
+/// Like every other sector, it must also keep the moves later sectors are merged through (see [merge_corners])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StretchedContract {
+    pub start: BoardPos,
+    pub end: BoardPos,
+    size: BoardSize,
+}
+
+impl StretchedContract {
+    pub fn new(size: BoardSize, direction: Direction) -> Self {
+        let end = match direction {
+            Direction::Horizontal => BoardPos::new(0, 1),
+            Direction::Vertical => BoardPos::new(1, 0),
+        };
+
+        Self { start: BoardPos::ZERO, end, size }
+    }
+}
+
+/// The moves at the top right and bottom left corners of a sector, which the sectors to its right and below it are merged through
+pub fn merge_corners(size: BoardSize) -> [(BoardPos, BoardPos); 2] {
+    let (width, height) = (size.width(), size.height());
+    [
+        (BoardPos::new(width - 2, 0), BoardPos::new(width - 1, 2)),
+        (BoardPos::new(0, height - 2), BoardPos::new(2, height - 1)),
+    ]
+}
+
+/// How a graph breaks a [StretchedContract]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    WrongSize(BoardSize),
+    Invalid(validation::Defect),
+    Closed,
+    WrongEnds(BoardPos, BoardPos),
+    MissingCorner(BoardPos, BoardPos),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongSize(size) => write!(f, "the graph is {size}"),
+            Self::Invalid(defect) => write!(f, "the path is invalid: {defect}"),
+            Self::Closed => write!(f, "the path is closed"),
+            Self::WrongEnds(start, end) => write!(f, "the path runs from {start} to {end}"),
+            Self::MissingCorner(from, to) => write!(f, "{from} and {to} are not linked"),
+        }
+    }
+}
+
+/// Checks that the graph is a single path between the ends of the contract that keeps the merge corners.
+/// Squares without any links are dead, corner moves are only required between live squares
+pub fn check_stretched(graph: &MoveGraph, contract: &StretchedContract) -> Result<(), Violation> {
+    if graph.size() != contract.size {
+        return Err(Violation::WrongSize(graph.size()));
+    }
+
+    let tour = validation::validate(graph).map_err(Violation::Invalid)?;
+    if tour.closed {
+        return Err(Violation::Closed);
+    }
+    if (tour.start, tour.end) != (contract.start, contract.end) {
+        return Err(Violation::WrongEnds(tour.start, tour.end));
+    }
+
+    let live = |pos: BoardPos| graph.node(pos).next().is_some() || graph.node(pos).prev().is_some();
+    let linked = |a: BoardPos, b: BoardPos| graph.node(a).next() == Some(b) || graph.node(b).next() == Some(a);
+    for (from, to) in merge_corners(contract.size) {
+        if live(from) && live(to) && !linked(from, to) {
+            return Err(Violation::MissingCorner(from, to));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_check_stretched() {
+    use crate::move_graph::fixtures;
+
+    let contract = StretchedContract::new(BoardSize::new(3, 3), Direction::Horizontal);
+    assert_eq!(StretchedContract::new(BoardSize::new(3, 3), Direction::Vertical).end, BoardPos::new(1, 0));
+
+    // the first six squares of the ring lead from A1 to A2 through both merge corners, B3 and C1 are dead
+    let path = fixtures::open(3, 3, &fixtures::RING[..6]);
+    assert_eq!(check_stretched(&path, &contract), Ok(()));
+    assert_eq!(check_stretched(&path.flip(), &StretchedContract::new(BoardSize::new(3, 3), Direction::Vertical)), Ok(()));
+
+    let ring = fixtures::open(3, 3, &fixtures::RING);
+    assert_eq!(check_stretched(&ring, &StretchedContract::new(BoardSize::new(4, 3), Direction::Horizontal)), Err(Violation::WrongSize(BoardSize::new(3, 3))));
+    assert_eq!(check_stretched(&fixtures::closed(3, 3, &fixtures::RING), &contract), Err(Violation::Closed));
+    assert_eq!(check_stretched(&ring, &contract), Err(Violation::WrongEnds(BoardPos::ZERO, BoardPos::new(1, 2))));
+    assert_eq!(check_stretched(&MoveGraph::new(3, 3), &contract), Err(Violation::Invalid(validation::Defect::Empty)));
+}
+
+#[test]
+fn test_stretched_sectors_keep_the_contract() {
+    use std::collections::HashSet;
+    use crate::warnsdorff::{self, Mode, StructureMode};
+
+    // every stretched sector the partitions of these boards produce, whether taken from the bases or found by the solver.
+    // The top left sector is solved as a closed tour instead
+    for size in [BoardSize::new(20, 20), BoardSize::new(23, 17), BoardSize::new(12, 31)] {
+        for super::partitions::Sector { size: sector, direction, .. } in super::partitions::partition_size(size).into_iter().skip(1) {
+            let contract = StretchedContract::new(sector, direction);
+            if let Some(base) = super::bases::get(direction, sector) {
+                assert_eq!(check_stretched(base, &contract), Ok(()), "{sector} {direction:?} base");
+                continue;
+            }
+
+            let (graph, _) = warnsdorff::solve_internal(sector, Mode::Structured(StructureMode::Stretched(direction))).unwrap();
+            assert_eq!(check_stretched(&graph, &contract), Ok(()), "{sector} {direction:?}");
+        }
+    }
+
+    // a sector solved around a colour balanced pair of dead squares keeps the contract too
+    let dead = HashSet::from([BoardPos::new(3, 3), BoardPos::new(4, 3)]);
+    for direction in [Direction::Horizontal, Direction::Vertical] {
+        let sector = BoardSize::new(8, 6);
+        let (graph, _) = warnsdorff::solve_internal(sector, Mode::Constrained(StructureMode::Stretched(direction), dead.clone())).unwrap();
+        assert_eq!(check_stretched(&graph, &StretchedContract::new(sector, direction)), Ok(()), "{direction:?}");
+    }
+}
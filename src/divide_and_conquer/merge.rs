@@ -1,107 +1,190 @@
-use std::fmt::{Debug, Display};
+use std::fmt::Display;
 
-use crate::{board_pos::BoardPos, board_size::BoardSize, move_graph::{Direction, MoveGraph, Node}};
+use crate::{aliases::BoardIndexOverflow as IdxMath, board_pos::BoardPos, board_size::BoardSize, move_graph::{Direction, MoveGraph, NodeRef}};
 
-pub fn merge<'a, 'b>(board: &'b mut MoveGraph<'a>, pos: BoardPos, latter_size: BoardSize, direction: Direction) {
-    // for the start and end of the second graph, find the possible moves ending on the first graph
-    // among those moves, find any one where both target nodes are directly connected by a single move (this can be hardcoded for each direction)
-    // connect the target nodes to the corresponding nodes in the second graph
+use super::contract::StretchedContract;
 
-    let second_start = pos;
-    let second_end = pos + match direction {
-        Direction::Horizontal => BoardPos::new(0, 1),
-        Direction::Vertical => BoardPos::new(1, 0),
-    };
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeError {
+    /// Neither seam next to the sector is a move on the tour before it
+    NoSeam(BoardPos),
+    /// A link the merge would create is not a knight's move
+    IllegalMove(BoardPos, BoardPos),
+    /// A square at the seam isn't linked the way the merge expects
+    UnexpectedLinks { pos: BoardPos, prev: Option<BoardPos>, next: Option<BoardPos> },
+    /// The links around a square don't agree with each other after the merge
+    Inconsistent(BoardPos),
+}
 
-    let (first_end, first_start) = match direction {
-        Direction::Horizontal => (pos.translate(-2, 0), pos.translate(-1, 2)),
-        Direction::Vertical => (pos.translate(0, -2), pos.translate(2, -1)),
-    };
+impl Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let or_none = |pos: &Option<BoardPos>| pos.map_or("none".to_string(), |pos| pos.to_string());
+        match self {
+            Self::NoSeam(pos) => write!(f, "no seam next to {pos} is a move on the tour"),
+            Self::IllegalMove(from, to) => write!(f, "{from} to {to} is not a knight's move"),
+            Self::UnexpectedLinks { pos, prev, next } => write!(f, "{pos} is linked from {} to {}", or_none(prev), or_none(next)),
+            Self::Inconsistent(pos) => write!(f, "the links around {pos} don't match up"),
+        }
+    }
+}
 
-    if board.node(first_end).next() == Some(first_start) {
-        board.reverse_section(pos, latter_size);
+/// The pairs of squares of the first graph the ends of the second one can be linked to, relative to the second graph:
+/// the first square is linked to its end, the second to its start. The first pair is the merge corner the sectors keep
+/// (see [super::contract::merge_corners]), the second the mirrored one that is tried if the corner doesn't work out
+fn seams(direction: Direction) -> [[(IdxMath, IdxMath); 2]; 2] {
+    match direction {
+        Direction::Horizontal => [[(-2, 0), (-1, 2)], [(-1, 3), (-2, 1)]],
+        Direction::Vertical => [[(0, -2), (2, -1)], [(3, -1), (1, -2)]],
     }
-    
-    let update_node = |node: &mut Node, old_target, new_target| -> Result<(), ErrInfo>{
-        if (node.prev() == old_target) | (old_target.is_none() & (node.prev() == Some(node.pos()))) {
-            *node.prev_mut() = Some(new_target);
+}
+
+/// Links the path of the sector at `pos` into the tour before it, in place of a move of that tour next to the sector.
+/// Nothing is changed if no seam works out, except for a bug showing up in the check afterwards
+pub fn merge<'a, 'b>(board: &'b mut MoveGraph<'a>, pos: BoardPos, latter_size: BoardSize, direction: Direction) -> Result<(), MergeError> {
+    let contract = StretchedContract::new(latter_size, direction);
+    let second_start = pos + contract.start;
+    let second_end = pos + contract.end;
+
+    let mut first_error = None;
+    for seam in seams(direction) {
+        let [first_end, first_start] = seam.map(|(col, row)| pos.try_translate(col, row).filter(|&pos| board.size().fits(pos)));
+        let (Some(first_end), Some(first_start)) = (first_end, first_start) else {
+            first_error.get_or_insert(MergeError::NoSeam(pos));
+            continue;
+        };
+
+        match link(board, [first_start, first_end, second_start, second_end], pos, latter_size) {
+            Ok(()) => return Ok(()),
+            Err(e) => { first_error.get_or_insert(e); },
         }
-        else if (node.next() == old_target) | (old_target.is_none() & (node.next() == Some(node.pos()))) {
-            *node.next_mut() = Some(new_target);
+    }
+
+    Err(first_error.unwrap_or(MergeError::NoSeam(pos)))
+}
+
+/// Replaces the move between the first two squares with the path of the sector between the last two
+fn link(board: &mut MoveGraph, [first_start, first_end, second_start, second_end]: [BoardPos; 4], pos: BoardPos, latter_size: BoardSize) -> Result<(), MergeError> {
+    let linked = |a: BoardPos, b: BoardPos| board.node(a).next() == Some(b) && board.node(b).prev() == Some(a);
+    let reverse = linked(first_end, first_start);
+    if !reverse && !linked(first_start, first_end) {
+        return Err(MergeError::NoSeam(pos));
+    }
+
+    let updates = [(first_start, Some(first_end), second_start), (first_end, Some(first_start), second_end), (second_start, None, first_start), (second_end, None, first_end)];
+    for &(from, _, to) in &updates {
+        if !from.is_knight_move(to) {
+            return Err(MergeError::IllegalMove(from, to));
         }
-        else {
-            let node_pos = node.pos();
-            let node_prev = node.prev();
-            let node_next = node.next();
-            return Err(ErrInfo {
-                node_pos,
-                node_prev,
-                node_next,
-                old_target,
-                new_target,
-            });
+    }
+
+    // the path of the second graph has to run the same way as the tour it replaces the move of
+    if reverse {
+        board.reverse_section(pos, latter_size);
+    }
+
+    if let Some(&(node, _, _)) = updates.iter().find(|&&(node, old_target, _)| !links_to(board.node(node), old_target)) {
+        if reverse {
+            board.reverse_section(pos, latter_size);
         }
 
-        Ok(())
+        let node = board.node(node);
+        return Err(MergeError::UnexpectedLinks { pos: node.pos(), prev: node.prev(), next: node.next() });
+    }
+
+    // in the order the tour runs: the square before the seam, the path of the sector, the square after the seam
+    let (before, after, path_start, path_end) = if reverse {
+        (first_end, first_start, second_end, second_start)
+    } else {
+        (first_start, first_end, second_start, second_end)
     };
+    board.unlink(before);
+    board.link(before, path_start);
+    board.link(path_end, after);
 
-    macro_rules! chain {
-        ($res:ident = $expr:expr) => {
-            if $res.is_ok() {
-                $res = $expr;
-            }
-        };
+    for (node, _, _) in updates {
+        if !consistent(board, node) {
+            return Err(MergeError::Inconsistent(node));
+        }
     }
 
-    let mut res = Ok(());
-    chain!(res = update_node(board.node_mut(first_start), Some(first_end), second_start));
-    chain!(res = update_node(board.node_mut(first_end), Some(first_start), second_end));
-    chain!(res = update_node(board.node_mut(second_start), None, first_start));
-    chain!(res = update_node(board.node_mut(second_end), None, first_end));
-
-    if let Err(info) = res {
-        eprintln!("pos: {pos:?} latter_size: {latter_size:?}");
-        eprintln!("first_start: {first_start} ({first_start:?}), first_end: {first_end} ({first_end:?})");
-        eprintln!("second_start: {second_start} ({second_start:?}), second_end: {second_end} ({second_end:?})");
-        eprintln!("{board:?}");
-        let ErrInfo { node_pos, node_prev, node_next, old_target, new_target } = info;
-        panic!(
-            concat!(
-                "Invalid node: {0} ({0:?}) ",
-                "[ {1} ({1:?}) -> {2} ({2:?}) ] ",
-                "- {3:?} - {4:?} [{5:?}]"
-            ),
-            node_pos,
-            BPO(node_prev),
-            BPO(node_next),
-            old_target,
-            new_target,
-            direction
-        );
-    }
+    Ok(())
 }
 
-struct ErrInfo {
-    node_pos: BoardPos,
-    node_prev: Option<BoardPos>,
-    node_next: Option<BoardPos>,
-    old_target: Option<BoardPos>,
-    new_target: BoardPos,
+/// Whether a link of the node points at `old_target`. Without one, that's the open end of the path,
+/// which for its start may also be marked by linking back to itself
+fn links_to(node: NodeRef, old_target: Option<BoardPos>) -> bool {
+    let matches = |link: Option<BoardPos>| link == old_target || (old_target.is_none() && link == Some(node.pos()));
+    matches(node.prev()) || matches(node.next())
 }
 
-/// Display adapter for [Option]&lt;[BoardPos]&gt;
-struct BPO(Option<BoardPos>);
-impl Display for BPO {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.0 {
-            Some(pos) => Display::fmt(&pos, f),
-            None => write!(f, "None"),
-        }
-    }
+/// Whether the squares before and after the square link back to it
+fn consistent(board: &MoveGraph, pos: BoardPos) -> bool {
+    let node = board.node(pos);
+    let prev_ok = node.prev().is_none_or(|prev| prev == pos || board.node(prev).next() == Some(pos));
+    let next_ok = node.next().is_none_or(|next| next == pos || board.node(next).prev() == Some(pos));
+    prev_ok && next_ok
 }
 
-impl Debug for BPO {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(&self.0, f)
+/// Links the squares into a path starting on the first one
+#[cfg(test)]
+fn path(graph: &mut MoveGraph, squares: &[BoardPos]) {
+    *graph.node_mut(squares[0]).prev_mut() = Some(squares[0]);
+    for pair in squares.windows(2) {
+        *graph.node_mut(pair[0]).next_mut() = Some(pair[1]);
+        *graph.node_mut(pair[1]).prev_mut() = Some(pair[0]);
     }
 }
+
+#[test]
+fn test_merge() {
+    use crate::validation::validate;
+    use super::splice::ring;
+
+    let squares = |pos: BoardPos, squares: [(u32, u32); 4]| squares.map(|(col, row)| pos + BoardPos::new(col, row));
+    let horizontal = [(0, 0), (1, 2), (2, 0), (0, 1)];
+    let vertical = horizontal.map(|(col, row)| (row, col));
+
+    // through the merge corner of the ring
+    let mut graph = ring(6, 3, (0, 0));
+    let pos = BoardPos::new(3, 0);
+    path(&mut graph, &squares(pos, horizontal));
+    assert_eq!(merge(&mut graph, pos, BoardSize::new(3, 3), Direction::Horizontal), Ok(()));
+    let tour = validate(&graph).unwrap();
+    assert_eq!((tour.squares, tour.closed), (12, true));
+
+    let mut graph = ring(3, 6, (0, 0));
+    let pos = BoardPos::new(0, 3);
+    path(&mut graph, &squares(pos, vertical));
+    assert_eq!(merge(&mut graph, pos, BoardSize::new(3, 3), Direction::Vertical), Ok(()));
+    let tour = validate(&graph).unwrap();
+    assert_eq!((tour.squares, tour.closed), (12, true));
+
+    // the corner isn't part of the shifted ring, the mirrored seam is
+    let mut graph = ring(6, 4, (0, 1));
+    let pos = BoardPos::new(3, 0);
+    path(&mut graph, &squares(pos, horizontal));
+    assert_eq!(merge(&mut graph, pos, BoardSize::new(3, 4), Direction::Horizontal), Ok(()));
+    assert_eq!(validate(&graph).unwrap().squares, 12);
+
+    let mut graph = ring(4, 6, (1, 0));
+    let pos = BoardPos::new(0, 3);
+    path(&mut graph, &squares(pos, vertical));
+    assert_eq!(merge(&mut graph, pos, BoardSize::new(4, 3), Direction::Vertical), Ok(()));
+    assert_eq!(validate(&graph).unwrap().squares, 12);
+
+    // neither seam is on the ring, which is left as it was
+    let mut graph = ring(8, 5, (2, 2));
+    let pos = BoardPos::new(5, 0);
+    path(&mut graph, &squares(pos, horizontal));
+    let links = |graph: &MoveGraph| graph.nodes().map(|node| (node.prev(), node.next())).collect::<Vec<_>>();
+    let before = links(&graph);
+    assert_eq!(merge(&mut graph, pos, BoardSize::new(3, 5), Direction::Horizontal), Err(MergeError::NoSeam(pos)));
+    assert_eq!(links(&graph), before);
+
+    // the end of the path is linked already
+    let mut graph = ring(6, 3, (0, 0));
+    let pos = BoardPos::new(3, 0);
+    path(&mut graph, &squares(pos, horizontal));
+    *graph.node_mut(pos.translate(0, 1)).next_mut() = Some(pos.translate(2, 2));
+    assert!(matches!(merge(&mut graph, pos, BoardSize::new(3, 3), Direction::Horizontal), Err(MergeError::UnexpectedLinks { .. })));
+}
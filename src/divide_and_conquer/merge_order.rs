@@ -0,0 +1,183 @@
+use crate::{
+    aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath},
+    args::SolveOrder,
+    board_pos::BoardPos,
+    board_size::BoardSize,
+    move_graph::Direction,
+    random::Random,
+};
+
+use super::partitions::Sector;
+
+// sectors are at most 10 squares wide, so rings of that width go once around the center
+const RING_WIDTH: IdxMath = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Solve(usize),
+    Merge(usize),
+}
+
+/// The sector each sector is merged into, which holds the squares the merge links to.
+/// The first sector isn't merged into anything, all the others end up merged into it
+pub fn merge_targets(sectors: &[Sector]) -> Vec<Option<usize>> {
+    sectors.iter()
+        .map(|&Sector { pos, direction, .. }| {
+            if pos == BoardPos::ZERO {
+                return None;
+            }
+
+            // the end of the tour of the earlier sector, as linked to by the merge
+            let first_end = match direction {
+                Direction::Horizontal => pos.translate(-2, 0),
+                Direction::Vertical => pos.translate(0, -2),
+            };
+            sectors.iter().position(|sector| sector.contains(first_end))
+        })
+        .collect()
+}
+
+/// The order to solve the sectors in and when to merge each of them into its target.
+/// A sector is merged once both it and its target are solved and the target is merged itself (or is the first sector),
+/// as merging may reverse the tour of the sector, which only works while the sector is on its own
+pub fn schedule(sectors: &[Sector], targets: &[Option<usize>], size: BoardSize, order: SolveOrder, seed: u64) -> Vec<Step> {
+    let mut children = vec![Vec::new(); sectors.len()];
+    for (idx, target) in targets.iter().enumerate() {
+        if let Some(target) = *target {
+            children[target].push(idx);
+        }
+    }
+
+    let mut solved = vec![false; sectors.len()];
+    let mut merged = vec![false; sectors.len()];
+    let mut steps = Vec::with_capacity(2 * sectors.len());
+    for idx in solve_order(sectors, size, order, seed) {
+        steps.push(Step::Solve(idx));
+        solved[idx] = true;
+
+        let mut ready = match targets[idx] {
+            None => {
+                merged[idx] = true;
+                children[idx].iter().copied().filter(|child| solved[*child]).collect()
+            },
+            Some(target) if merged[target] => vec![idx],
+            Some(_) => continue,
+        };
+
+        // merging a sector may allow the sectors waiting for it to be merged as well
+        while let Some(idx) = ready.pop() {
+            steps.push(Step::Merge(idx));
+            merged[idx] = true;
+            ready.extend(children[idx].iter().rev().copied().filter(|child| solved[*child]));
+        }
+    }
+
+    steps
+}
+
+/// The order to solve the sectors in, regardless of when they can be merged
+fn solve_order(sectors: &[Sector], size: BoardSize, order: SolveOrder, seed: u64) -> Vec<usize> {
+    // twice the offset of the center of the sector from the center of the board, to stay in whole numbers
+    let offset = |&Sector { pos, size: sector_size, .. }: &Sector| {
+        let axis = |start: Idx, len: Idx, total: Idx| 2 * start as IdxMath + len as IdxMath - total as IdxMath;
+        (axis(pos.col(), sector_size.width(), size.width()), axis(pos.row(), sector_size.height(), size.height()))
+    };
+
+    let mut res: Vec<usize> = (0..sectors.len()).collect();
+    match order {
+        SolveOrder::Default => {},
+        SolveOrder::CenterOut => res.sort_by_key(|idx| {
+            let (col, row) = offset(&sectors[*idx]);
+            col * col + row * row
+        }),
+        SolveOrder::Spiral => {
+            let center = BoardPos::new(size.width() / 2, size.height() / 2);
+            let key = |idx: usize| {
+                let (col, row) = offset(&sectors[idx]);
+                let is_center = sectors[idx].contains(center);
+                let ring = if is_center { 0 } else { 1 + col.abs().max(row.abs()) / (2 * RING_WIDTH) };
+                (ring, (row as f64).atan2(col as f64))
+            };
+            res.sort_by(|a, b| key(*a).partial_cmp(&key(*b)).unwrap());
+        },
+        SolveOrder::Random => {
+            let mut random = Random::new(seed);
+            for idx in (1..res.len()).rev() {
+                res.swap(idx, random.below(idx + 1));
+            }
+        },
+    }
+
+    res
+}
+
+#[cfg(test)]
+use super::partitions::partition_size;
+
+/// Checks that every sector is solved once, and merged once after it and its target are solved and the target is merged
+#[cfg(test)]
+fn assert_valid(steps: &[Step], targets: &[Option<usize>], context: &str) {
+    let mut solved = vec![false; targets.len()];
+    let mut merged: Vec<bool> = targets.iter().map(|target| target.is_none()).collect();
+    for step in steps {
+        match *step {
+            Step::Solve(idx) => {
+                assert!(!solved[idx], "{context}: sector {idx} solved twice");
+                solved[idx] = true;
+            },
+            Step::Merge(idx) => {
+                let target = targets[idx].unwrap_or_else(|| panic!("{context}: the first sector {idx} is merged"));
+                assert!(solved[idx] && solved[target], "{context}: sector {idx} merged before it or its target was solved");
+                assert!(merged[target], "{context}: sector {idx} merged into the unmerged sector {target}");
+                assert!(!merged[idx], "{context}: sector {idx} merged twice");
+                merged[idx] = true;
+            },
+        }
+    }
+
+    assert!(solved.iter().chain(&merged).all(|done| *done), "{context}: not all sectors were solved and merged");
+}
+
+#[test]
+fn test_orders_respect_merge_targets() {
+    for width in (10..=60).step_by(5) {
+        for height in [10, 13, 24, 41] {
+            let size = BoardSize::new(width, height);
+            let sectors = partition_size(size);
+            let targets = merge_targets(&sectors);
+            assert_eq!(targets.iter().filter(|target| target.is_none()).count(), 1, "{size}");
+
+            for order in [SolveOrder::Default, SolveOrder::Spiral, SolveOrder::CenterOut, SolveOrder::Random] {
+                assert_valid(&schedule(&sectors, &targets, size, order, 42), &targets, &format!("{size} {order:?}"));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_default_order() {
+    // each sector is merged right after it is solved, the same as solving them all first and merging them in order
+    let size = BoardSize::new(20, 20);
+    let sectors = partition_size(size);
+    let steps = schedule(&sectors, &merge_targets(&sectors), size, SolveOrder::Default, 0);
+    let mut expected = vec![Step::Solve(0)];
+    for idx in 1..sectors.len() {
+        expected.extend([Step::Solve(idx), Step::Merge(idx)]);
+    }
+    assert_eq!(steps, expected);
+}
+
+#[test]
+fn test_spiral_starts_in_the_center() {
+    for size in [BoardSize::new(30, 30), BoardSize::new(50, 24), BoardSize::new(17, 41)] {
+        let sectors = partition_size(size);
+        let Step::Solve(first) = schedule(&sectors, &merge_targets(&sectors), size, SolveOrder::Spiral, 0)[0] else {
+            panic!("{size}: the first step merges");
+        };
+
+        let Sector { pos, size: sector_size, .. } = sectors[first];
+        let center = BoardPos::new(size.width() / 2, size.height() / 2);
+        assert!((pos.col()..pos.col() + sector_size.width()).contains(&center.col()), "{size}: {pos} {sector_size}");
+        assert!((pos.row()..pos.row() + sector_size.height()).contains(&center.row()), "{size}: {pos} {sector_size}");
+    }
+}
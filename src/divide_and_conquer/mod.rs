@@ -1,17 +1,29 @@
-use std::{mem::{replace, MaybeUninit}, time::{Duration, Instant}};
+use std::{collections::HashSet, fmt::Display, mem::MaybeUninit, time::{Duration, Instant}};
 
 use crate::{
     aliases::BoardIndex as Idx,
-    args::InputArgs,
+    args::{InputArgs, SolveOrder},
     board_pos::BoardPos,
     board_size::BoardSize,
+    dprintln,
+    feasibility::{self, Feasibility},
     move_graph::{Direction, MoveGraph},
+    phase_timer,
+    random::Random,
+    repair,
+    summary,
     warnsdorff::{self, Mode, StructureMode}
 };
 
+pub mod contract;
 mod merge;
+mod merge_order;
 mod partitions;
 mod bases;
+mod splice;
+
+use merge_order::Step;
+pub use partitions::{Sector, SolveQuadrantMode};
 
 pub fn solve<'a>(args: InputArgs) -> Option<(Duration, MoveGraph<'a>)> {
     // algorithm shamelessly stolen from https://www.sciencedirect.com/science/article/pii/S0166218X04003488
@@ -28,107 +40,272 @@ pub fn solve<'a>(args: InputArgs) -> Option<(Duration, MoveGraph<'a>)> {
     // step 2: generate a closed knight's tour for each chunk if possible, and noting start and finish otherwise
     // step 2.5 (optional, if I have time): generate each chunk in parallel
     // step 3: stitch the tours together
-    // step 4 (optional, if I have time): apply the obfuscation algorithm
+    // step 4: apply the obfuscation algorithm (done for any solver with --shuffle, see MoveGraph::shuffle)
     let size = args.board_size?;
-    let solve = if size.width() % 2 == 0 || size.height() % 2 == 0 /* can be a closed tour */ {
-        divide_and_conquer_impl
-    } else {
-        divide_and_conquer_open
-    };
+    match feasibility::tour_exists(size, false) {
+        Feasibility::Possible => {},
+        Feasibility::Trivial => {
+            // a single square is its own tour, marked as visited the same way warnsdorff marks its starting square
+            let mut graph = MoveGraph::new(1, 1);
+            *graph.node_mut(BoardPos::ZERO).prev_mut() = Some(BoardPos::ZERO);
+            return Some((Duration::ZERO, graph));
+        },
+        Feasibility::Impossible(reason) => {
+            println!("No knight's tour possible for a {size} board: {reason}.");
+            return None;
+        },
+    }
+
+    let dead = args.dead_squares(size);
+    if let Feasibility::Impossible(reason) = feasibility::tour_exists_around(size, &dead, false) {
+        println!("No knight's tour possible for a {size} board: {reason}.");
+        return None;
+    }
+
+    let closed = !skips_corner(size, &dead);
 
     let start = Instant::now();
 
-    let graph = solve(size)?;
+    let seed = args.warnsdorff.as_ref().and_then(|w| w.random_seed);
+    let (mut graph, leftover) = match divide_and_conquer_impl(size, args.solve_order, seed, &dead) {
+        Ok(res) => res,
+        Err(Abort::Unsolved) => return None,
+        Err(Abort::Merge(pos, e)) => {
+            eprintln!("Could not merge the sector at {pos} into the tour: {e}. Falling back to Warnsdorff.");
+            return solve_with_warnsdorff(args);
+        },
+    };
+
+    // the closed tour of an odd board skips (0,0), which the open tour starts on instead
+    if !closed {
+        if let Err(e) = splice::attach(&mut graph, BoardPos::ZERO, &leftover) {
+            eprintln!("Could not attach {} to the tour: {e}. Falling back to Warnsdorff.", BoardPos::ZERO);
+            return solve_with_warnsdorff(args);
+        }
+    }
+
+    // dead squares the sectors couldn't be solved around are cut out of the finished tour
+    if !leftover.is_empty() {
+        if let Err(e) = phase_timer::time("repair", || repair::excise_squares(&mut graph, &leftover)) {
+            eprintln!("Could not cut the dead squares out of the tour: {e}. Falling back to Warnsdorff.");
+            return solve_with_warnsdorff(args);
+        }
+    }
 
+    debug_assert_eq!(graph.check_consistency(), Ok(()));
     let duration = start.elapsed();
 
     Some((duration, graph))
 }
 
-#[derive(Debug, Clone, Copy)]
-enum SolveQuadrantMode {
-    Closed,
-    Stretched(Direction),
+/// The sectors the board is solved in, in the order they are listed in before --solve-order rearranges them,
+/// as shown by --dry-run
+pub fn plan(size: BoardSize) -> Vec<Sector> {
+    partitions::partition_size(size)
 }
 
-fn divide_and_conquer_open<'a>(size: BoardSize) -> Option<MoveGraph<'a>> {
-    // split the graph into parts
-    // solve each part (topmost leftmost as structured closed tour skipping (0,0))
-    // merge the parts together
-    let mut graph = divide_and_conquer_impl(size)?;
-    // insert move from (0,0) into the tour
-    let node = graph.node_mut(BoardPos::new(0, 0));
-    *node.next_mut() = Some(BoardPos::new(2, 1));
-
-    let next = graph.node_mut(BoardPos::new(2, 1));
-    let prev = replace(next.prev_mut(), Some(BoardPos::new(0, 0)));
-    if let Some(prev) = prev {
-        let prev = graph.node_mut(prev);
-        *prev.next_mut() = None;
+/// Why the board is cut into the sectors it is, as printed for --explain-partitions
+pub fn explain_partitions(size: BoardSize) -> impl Display {
+    partitions::explain_partition_size(size).1
+}
+
+fn solve_with_warnsdorff<'a>(mut args: InputArgs) -> Option<(Duration, MoveGraph<'a>)> {
+    args.use_warnsdorff = true;
+    if let Some(ref mut warnsdorff) = args.warnsdorff {
+        // the default starting position may well be cut off
+        warnsdorff.snap_start = true;
     }
 
-    Some(graph)
+    warnsdorff::solve(args)
+}
+
+/// Why [divide_and_conquer_impl] gave up on the board
+#[derive(Debug, Clone, Copy)]
+enum Abort {
+    /// A sector has no tour of the shape it needs
+    Unsolved,
+    /// The tour of the sector at the position couldn't be linked to the one before it
+    Merge(BoardPos, merge::MergeError),
 }
 
-fn divide_and_conquer_impl<'a>(size: BoardSize) -> Option<MoveGraph<'a>> {
+/// Solves the board sector by sector. Sectors with dead squares are solved around them where possible,
+/// the dead squares of all other sectors are returned to be cut out of the tour. The seed (--random-seed) is only
+/// used by [SolveOrder::Random], which takes it from the current time if there is none
+fn divide_and_conquer_impl<'a>(
+    size: BoardSize,
+    solve_order: SolveOrder,
+    seed: Option<u64>,
+    dead: &HashSet<BoardPos>,
+) -> Result<(MoveGraph<'a>, HashSet<BoardPos>), Abort> {
     let mut graph = MoveGraph::new(size.width(), size.height());
 
     // todo: parallelize
-    let partitions = partitions::partition_size(size);
+    let partitions = phase_timer::time("partition", || partitions::partition_size(size));
     // for sector in partitions.iter() {
-    //     eprintln!("{}: {} {:?}", sector.0, sector.1, sector.2);
+    //     eprintln!("{}: {} {:?}", sector.pos, sector.size, sector.direction);
     // }
 
     // panic!("end test");
 
-    for sector in partitions.iter() {
-        let mode = if sector.0 == BoardPos::ZERO {
-            SolveQuadrantMode::Closed
-        } else {
-            SolveQuadrantMode::Stretched(sector.2)
-        };
+    let targets = merge_order::merge_targets(&partitions);
+    let seed = match solve_order {
+        SolveOrder::Random => {
+            let seed = seed.unwrap_or_else(Random::seed_from_time);
+            dprintln!(1 => { seed } "Solve order seed: {seed}");
+            summary::record(|metrics| metrics.seed = Some(seed));
+            seed
+        },
+        _ => 0,
+    };
+
+    let mut leftover = HashSet::new();
 
-        divide_and_conquer_impl_board(&mut graph, sector.0, sector.1, mode)?;
+    for step in merge_order::schedule(&partitions, &targets, size, solve_order, seed) {
+        match step {
+            Step::Solve(idx) => {
+                let sector = partitions[idx];
+                let sector_dead: Vec<_> = dead.iter().filter(|pos| sector.contains(**pos)).copied().collect();
+                let required = required_squares(&partitions, &targets, idx, size, dead);
+                let local_dead = (!sector_dead.is_empty() && required.iter().all(|pos| !dead.contains(pos)))
+                    .then(|| sector_dead.iter().map(|pos| *pos - sector.pos).collect());
+
+                let constrained = phase_timer::time(format!("sector {}", sector.size), || divide_and_conquer_impl_board(&mut graph, sector.pos, sector.size, sector.mode, local_dead))
+                    .ok_or(Abort::Unsolved)?;
+                if !constrained {
+                    // the corner skipped by the closed tour of an odd sector is not part of the tour to begin with
+                    leftover.extend(sector_dead.into_iter().filter(|pos| graph.node(*pos).next().is_some() || graph.node(*pos).prev().is_some()));
+                }
+            },
+            Step::Merge(idx) => {
+                let sector = partitions[idx];
+                phase_timer::time("merge", || merge::merge(&mut graph, sector.pos, sector.size, sector.direction))
+                    .map_err(|e| Abort::Merge(sector.pos, e))?;
+            },
+        }
     }
 
-    for sector in partitions.iter() {
-        let direction = match (sector.0.col(), sector.0.row()) {
-            (0, 0) => continue,
-            (_, _) => sector.2,
-        };
+    Ok((graph, leftover))
+}
+
+/// The squares a sector's tour has to visit to be merged with the rest of the board:
+/// the ends of the path it is merged through, the squares its children are merged into,
+/// and the square the open tour of an odd board is attached to
+fn required_squares(partitions: &[Sector], targets: &[Option<usize>], idx: usize, size: BoardSize, dead: &HashSet<BoardPos>) -> Vec<BoardPos> {
+    let Sector { pos, direction, .. } = partitions[idx];
+    let mut res = Vec::new();
+    if pos == BoardPos::ZERO {
+        if skips_corner(size, dead) {
+            res.push(BoardPos::new(2, 1));
+        }
+    } else {
+        res.push(pos);
+        res.push(pos + match direction {
+            Direction::Horizontal => BoardPos::new(0, 1),
+            Direction::Vertical => BoardPos::new(1, 0),
+        });
+    }
 
-        merge::merge(&mut graph, sector.0, sector.1, direction);
+    for (child, _) in targets.iter().enumerate().filter(|(_, target)| **target == Some(idx)) {
+        let Sector { pos, direction, .. } = partitions[child];
+        match direction {
+            Direction::Horizontal => res.extend([pos.translate(-2, 0), pos.translate(-1, 2)]),
+            Direction::Vertical => res.extend([pos.translate(0, -2), pos.translate(2, -1)]),
+        }
     }
 
-    Some(graph)
+    res
+}
+
+/// Whether the closed tour of the top left sector leaves out (0,0), for the open tour of an odd board to start on.
+/// Without (0,0) an odd board can be closed the same way as an even one, and boards three squares across
+/// are covered by an open tour to begin with
+fn skips_corner(size: BoardSize, dead: &HashSet<BoardPos>) -> bool {
+    !size.width().is_multiple_of(2) && !size.height().is_multiple_of(2) && !dead.contains(&BoardPos::ZERO) && size.width().min(size.height()) > 3
 }
 
-fn divide_and_conquer_impl_board<'a, 'b>(move_graph: &'b mut MoveGraph<'a>, offset: BoardPos, size: BoardSize, mode: SolveQuadrantMode) -> Option<()> {
+/// Solves a sector and returns whether it was solved around the given dead squares.
+/// Sectors that can't be are solved as if the dead squares were alive
+fn divide_and_conquer_impl_board<'a, 'b>(move_graph: &'b mut MoveGraph<'a>, offset: BoardPos, size: BoardSize, mode: SolveQuadrantMode, dead: Option<HashSet<BoardPos>>) -> Option<bool> {
     assert!(size.width() <= 10 && size.height() <= 10, "size: {}, should be subdivided", size);
 
     let solver_mode = match mode {
         SolveQuadrantMode::Closed => {
             let [min_dimension, max_dimension] = minmax(size.width(), size.height());
             match (min_dimension, max_dimension) {
+                (3, 4|7..=10) if dead.is_none() => {
+                    move_graph.insert_section(&bases::get_strip(size)?, offset);
+                    return Some(false);
+                },
                 (3, 4|7|8)|(4, _) => Mode::Freeform,
-                (n, m) if (n >= 4) & (m > 4) => Mode::Structured(StructureMode::Closed((n % 2 != 0) & (m % 2 != 0))),
+                (n, m) if (n >= 4) & (m > 4) => {
+                    if let (None, Some(base)) = (&dead, bases::get_closed(size)) {
+                        move_graph.insert_section(&base, offset);
+                        return Some(false);
+                    }
+
+                    Mode::Structured(StructureMode::Closed((n % 2 != 0) & (m % 2 != 0)))
+                },
                 _ => return None,
             }
         },
         SolveQuadrantMode::Stretched(direction) => {
-            if let Some(base) = bases::get(direction, size) {
+            if let (None, Some(base)) = (&dead, bases::get(direction, size)) {
+                debug_check_stretched(base, direction);
                 move_graph.insert_section(base, offset);
-                return Some(());
+                return Some(false);
             }
 
             Mode::Structured(StructureMode::Stretched(direction))
         },
     };
 
-    let (graph, _) = warnsdorff::solve_internal(size.into(), solver_mode)?;
+    if let (Mode::Structured(structure), Some(dead)) = (&solver_mode, dead) {
+        let skipped = matches!(structure, StructureMode::Closed(true)).then_some(BoardPos::ZERO);
+        let failure = if balanced(size, &dead, skipped) {
+            match warnsdorff::solve_internal(size, Mode::Constrained(*structure, dead)) {
+                Ok((graph, _)) => {
+                    if let SolveQuadrantMode::Stretched(direction) = mode {
+                        debug_check_stretched(&graph, direction);
+                    }
+                    move_graph.insert_section(&graph, offset);
+                    return Some(true);
+                },
+                Err(failure) => failure.to_string(),
+            }
+        } else {
+            "the dead squares aren't balanced between the colors".to_string()
+        };
+
+        dprintln!(1 => "Could not solve the {size} sector at {offset} around its dead squares: {failure}.");
+    }
+
+    let (graph, _) = warnsdorff::solve_internal(size, solver_mode)
+        .map_err(|failure| dprintln!(1 => "Could not solve the {size} sector at {offset}: {failure}."))
+        .ok()?;
+    if let SolveQuadrantMode::Stretched(direction) = mode {
+        debug_check_stretched(&graph, direction);
+    }
 
     move_graph.insert_section(&graph, offset);
-    return Some(());
+    Some(false)
+}
+
+/// Merging relies on stretched sectors keeping their contract, which debug builds check for every sector solved
+fn debug_check_stretched(graph: &MoveGraph, direction: Direction) {
+    if cfg!(debug_assertions) {
+        if let Err(violation) = contract::check_stretched(graph, &contract::StretchedContract::new(graph.size(), direction)) {
+            panic!("The {} {direction:?} sector breaks its contract: {violation}\n{graph:?}", graph.size());
+        }
+    }
+}
+
+/// Whether a sector has as many light as dark live squares, which both closed tours and paths between neighboring squares need
+fn balanced(size: BoardSize, dead: &HashSet<BoardPos>, skipped: Option<BoardPos>) -> bool {
+    let light = |pos: &BoardPos| (pos.col() + pos.row()).is_multiple_of(2);
+    let (dead_light, dead_dark) = dead.iter().chain(skipped.iter().filter(|pos| !dead.contains(pos)))
+        .fold((0, 0), |(l, d), pos| if light(pos) { (l + 1, d) } else { (l, d + 1) });
+    let area = size.area() as u64;
+    (area.div_ceil(2) - dead_light) == (area / 2 - dead_dark)
 }
 
 /// Order two values in ascending order
@@ -143,3 +320,63 @@ fn minmax(a: Idx, b: Idx) -> [Idx; 2] {
     res[(a <= b) as usize] = MaybeUninit::new(b);
     unsafe { [res[0].assume_init(), res[1].assume_init()] }
 }
+
+#[test]
+fn test_balanced() {
+    let size = BoardSize::new(6, 5);
+    assert!(balanced(size, &HashSet::new(), None));
+    assert!(balanced(size, &HashSet::from([BoardPos::new(2, 2), BoardPos::new(3, 2)]), None));
+    assert!(!balanced(size, &HashSet::from([BoardPos::new(2, 2), BoardPos::new(4, 2)]), None));
+
+    // the skipped corner of an odd sector is light, the same as one of the dead squares
+    let size = BoardSize::new(5, 5);
+    assert!(balanced(size, &HashSet::new(), Some(BoardPos::ZERO)));
+    assert!(balanced(size, &HashSet::from([BoardPos::ZERO]), Some(BoardPos::ZERO)));
+    assert!(balanced(size, &HashSet::from([BoardPos::new(1, 1), BoardPos::new(1, 2)]), Some(BoardPos::ZERO)));
+}
+
+#[test]
+fn test_dead_squares_in_sectors() {
+    // the rounded corners are balanced, so each corner sector is solved around them and the tour stays closed
+    let args = crate::args::Args::parse_from(["knights_tour", "-s", "20", "--corner-radius", "3"]).input;
+    let dead = args.warnsdorff.as_ref().and_then(|w| w.corner_radius).unwrap().dead_squares(BoardSize::new(20, 20));
+    let (graph, leftover) = divide_and_conquer_impl(BoardSize::new(20, 20), SolveOrder::Default, None, &dead).unwrap();
+    assert!(leftover.is_empty());
+
+    let tour = crate::validation::validate(&graph).unwrap();
+    assert!(tour.closed);
+    assert_eq!(tour.squares, 400 - dead.len());
+
+    // whatever can't be solved around is cut out afterwards
+    for size in ["12", "21x20", "30x27"] {
+        let args = crate::args::Args::parse_from(["knights_tour", "-s", size, "--corner-radius", "5"]).input;
+        let board_size = args.board_size.unwrap();
+        let dead = args.warnsdorff.as_ref().and_then(|w| w.corner_radius).unwrap().dead_squares(board_size);
+        let (_, graph) = solve(args).unwrap();
+        let tour = crate::validation::validate(&graph).unwrap();
+        assert_eq!(tour.squares, board_size.area() as usize - dead.len(), "{size}");
+    }
+}
+
+#[test]
+fn test_strips() {
+    // boards three squares across are cut into a row of sectors with an open tour at the start
+    for length in 3..=60 {
+        for size in [BoardSize::new(3, length), BoardSize::new(length, 3)] {
+            if feasibility::tour_exists(size, false) != Feasibility::Possible {
+                continue;
+            }
+
+            let (graph, leftover) = divide_and_conquer_impl(size, SolveOrder::Default, None, &HashSet::new()).unwrap_or_else(|_| panic!("{size}"));
+            assert!(leftover.is_empty());
+            let tour = crate::validation::validate(&graph).unwrap_or_else(|e| panic!("{size}: {e}"));
+            assert_eq!(tour.squares, size.area() as usize, "{size}");
+        }
+    }
+
+    for size in ["3x37", "37x3"] {
+        let args = crate::args::Args::parse_from(["knights_tour", "-s", size]).input;
+        let (_, graph) = solve(args).unwrap();
+        assert_eq!(crate::validation::validate(&graph).unwrap().squares, 111, "{size}");
+    }
+}
@@ -1,40 +1,116 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, fmt::Display};
 
 use crate::{aliases::BoardIndex as Idx, board_pos::BoardPos, board_size::BoardSize, move_graph::Direction};
 
 use super::minmax;
 
-fn segment_length(length: Idx, other: Idx) -> Vec<(Idx, Idx)> {
-    if other <= 10 {
-        if length <= 10 {
-            return vec![(0, length)];
+/// Why the board was cut into the sectors it was, as an indented narrative with one line per decision
+#[derive(Debug, Default)]
+pub struct Explanation(Vec<String>);
+
+impl Explanation {
+    fn note(&mut self, depth: usize, line: String) {
+        self.0.push(format!("{}{line}", "  ".repeat(depth)));
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|line| writeln!(f, "{line}"))
+    }
+}
+
+/// How a sector's tour is found: the first sector holds the closed tour all the others are merged into,
+/// every other one a path between the squares it is merged through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveQuadrantMode {
+    Closed,
+    Stretched(Direction),
+}
+
+impl Display for SolveQuadrantMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Closed => write!(f, "closed"),
+            Self::Stretched(Direction::Horizontal) => write!(f, "stretched-h"),
+            Self::Stretched(Direction::Vertical) => write!(f, "stretched-v"),
         }
+    }
+}
 
-        if other == 3 {
-            let len = length as usize;
-            let remainder = len - 10 % 4;
-            let parts = (len - 10 / 4) + if remainder == 0 { 0 } else { 1 };
-            let mut res = vec![(0, 0); parts + 1];
-            res[0] = (0, length - parts as Idx * 4);
-            for i in 1..=parts {
-                let prev = res[i - 1];
-                res[i] = (prev.0 + prev.1, 4);
-            }
+/// A part of the board that is solved on its own before it is merged with the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sector {
+    pub pos: BoardPos,
+    pub size: BoardSize,
+    /// The direction the sector is merged into the sector before it in
+    pub direction: Direction,
+    pub mode: SolveQuadrantMode,
+}
+
+impl Sector {
+    fn new((pos, size, direction): (BoardPos, BoardSize, Direction)) -> Self {
+        let mode = if pos == BoardPos::ZERO {
+            SolveQuadrantMode::Closed
+        } else {
+            SolveQuadrantMode::Stretched(direction)
+        };
 
-            return res;
+        Self { pos, size, direction, mode }
+    }
+
+    pub fn contains(&self, square: BoardPos) -> bool {
+        (self.pos.col()..self.pos.col() + self.size.width()).contains(&square.col())
+            && (self.pos.row()..self.pos.row() + self.size.height()).contains(&square.row())
+    }
+}
+
+// only formats the line if the decisions are being recorded, so partitioning doesn't get slower otherwise
+macro_rules! explain {
+    ($explanation:ident, $depth:expr, $($arg:tt)*) => {
+        if let Some(explanation) = $explanation.as_deref_mut() {
+            explanation.note($depth, format!($($arg)*));
         }
+    };
+}
+
+fn segment_length(length: Idx, other: Idx, mut explanation: Option<&mut Explanation>) -> Vec<(Idx, Idx)> {
+    if length == 3 {
+        explain!(explanation, 2, "3 is too short to be cut at all");
+        return vec![(0, length)];
+    }
+
+    // sectors up to 10 squares across are solved whole, cutting the short side of a long board
+    // only leaves sectors too narrow to have the tours they need
+    if length <= 10 {
+        explain!(explanation, 2, "{length} is short enough for a single segment");
+        return vec![(0, length)];
+    }
+
+    if other == 3 {
+        let res = strip_segments(length);
+        let rest: Vec<_> = res[1..].iter().map(|(_, len)| len.to_string()).collect();
+        explain!(explanation, 2, "the other side is 3: {length} is cut into a segment of {} and segments of {}", res[0].1, rest.join(", "));
+        return res;
     }
 
     let capacity = (length as usize / 6) + 1;
     let mut segments = Vec::with_capacity(capacity);
-    let mut queue = VecDeque::from(vec![(0, length)]);
+    let mut queue = VecDeque::from(vec![(0, length, 2)]);
     queue.reserve(capacity);
 
-    while let Some((offset, length)) = queue.pop_front() {
+    while let Some((offset, length, depth)) = queue.pop_front() {
         let (first, second) = split_length(length);
+        let again = match (first > 10, second > 10) {
+            (true, true) => ", both longer than 10 and split again".to_string(),
+            (true, false) => format!(", {first} is longer than 10 and split again"),
+            (false, true) => format!(", {second} is longer than 10 and split again"),
+            (false, false) => String::new(),
+        };
+        explain!(explanation, depth, "{length} at {offset} is split into {first} and {second} (the second part is kept even){again}");
         let mut consume = |rel_offset, val| {
             if val > 10 {
-                queue.push_back((offset + rel_offset, val));
+                queue.push_back((offset + rel_offset, val, depth + 1));
             } else {
                 segments.push((offset + rel_offset, val));
             }
@@ -47,6 +123,31 @@ fn segment_length(length: Idx, other: Idx) -> Vec<(Idx, Idx)> {
     segments
 }
 
+/// Cuts the long side of a board three squares across into segments with tours that keep the moves merges rely on:
+/// a first segment of 7 to 10 squares (see [super::bases::get_strip]) and stretched ones of 4, 8 or 10 squares.
+/// No other stretched lengths up to 10 have such a path, so what is left after the first segment must be even and neither 2 nor 6
+fn strip_segments(length: Idx) -> Vec<(Idx, Idx)> {
+    let fits = |rest: Idx| rest.is_multiple_of(2) && rest >= 4 && rest != 6;
+    let first = [10, 9, 8, 7].into_iter().find(|first| fits(length - first)).unwrap();
+
+    let rest = length - first;
+    let (tens, remainder) = (rest / 10, rest % 10);
+    let mut lengths = match remainder {
+        2 => vec![10; tens as usize - 1].into_iter().chain([8, 4]).collect(),
+        6 => vec![10; tens as usize - 1].into_iter().chain([8, 8]).collect(),
+        _ => vec![10; tens as usize].into_iter().chain((remainder > 0).then_some(remainder)).collect::<Vec<_>>(),
+    };
+    lengths.insert(0, first);
+
+    lengths.into_iter()
+        .scan(0, |offset, length| {
+            let segment = (*offset, length);
+            *offset += length;
+            Some(segment)
+        })
+        .collect()
+}
+
 pub fn split_length(length: Idx) -> (Idx, Idx) {
     // split the length into two parts, the first part is half of the length, rounded up, minus one if the remainder would be odd
     // (to make sure the second part is always even, because then we are guaranteed able to make a stretched tour for the second part)
@@ -54,42 +155,73 @@ pub fn split_length(length: Idx) -> (Idx, Idx) {
     (half, length - half)
 }
 
-pub fn partition_size(size: BoardSize) -> Vec<(BoardPos, BoardSize, Direction)> {
+pub fn partition_size(size: BoardSize) -> Vec<Sector> {
+    partition_size_impl(size, None)
+}
+
+/// Partitions the board like [partition_size], recording why it was cut the way it was
+pub fn explain_partition_size(size: BoardSize) -> (Vec<Sector>, Explanation) {
+    let mut explanation = Explanation::default();
+    let sectors = partition_size_impl(size, Some(&mut explanation));
+    (sectors, explanation)
+}
+
+fn partition_size_impl(size: BoardSize, mut explanation: Option<&mut Explanation>) -> Vec<Sector> {
     let (width, height) = (size.width(), size.height());
-    let horizontal = segment_length(width, height);
+    explain!(explanation, 0, "{size} board");
+    explain!(explanation, 1, "columns (width {width}):");
+    let horizontal = segment_length(width, height, explanation.as_deref_mut());
     let vertical = if width == height {
          // optimization: if the board is square, the vertical partitions are the same as the horizontal partitions,
          // no need to calculate them again
+        explain!(explanation, 1, "rows: the same as the columns, as the board is square");
         horizontal.clone()
     } else {
-        segment_length(height, width)
+        explain!(explanation, 1, "rows (height {height}):");
+        segment_length(height, width, explanation.as_deref_mut())
     };
 
-    sectors_from_partitions(horizontal, vertical)
+    let sectors = sectors_from_partitions(horizontal, vertical, explanation.as_deref_mut());
+    explain!(explanation, 1, "{} sectors in total", sectors.len());
+    sectors.into_iter().map(Sector::new).collect()
 }
 
-fn sectors_from_partitions(horizontal: Vec<(Idx, Idx)>, vertical: Vec<(Idx, Idx)>) -> Vec<(BoardPos, BoardSize, Direction)> {
+fn sectors_from_partitions(horizontal: Vec<(Idx, Idx)>, vertical: Vec<(Idx, Idx)>, mut explanation: Option<&mut Explanation>) -> Vec<(BoardPos, BoardSize, Direction)> {
+    explain!(explanation, 1, "sectors ({} columns by {} rows):", horizontal.len(), vertical.len());
     let mut sectors = Vec::with_capacity(horizontal.len() * vertical.len() * 2);
     for (y, height) in vertical {
         for (x, width) in horizontal.iter().copied() {
-            partition_sector_further(&mut sectors, BoardPos::new(x, y), BoardSize::new(width, height));
+            partition_sector_further(&mut sectors, BoardPos::new(x, y), BoardSize::new(width, height), explanation.as_deref_mut());
         }
     }
 
     sectors
 }
 
-fn partition_sector_further(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, pos: BoardPos, size: BoardSize) {
+fn partition_sector_further(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, pos: BoardPos, size: BoardSize, explanation: Option<&mut Explanation>) {
     let closed = pos == BoardPos::ZERO;
 
     if closed {
-        partition_closed_sector(sectors, pos, size);
+        partition_closed_sector(sectors, pos, size, explanation);
     } else {
-        partition_open_sector(sectors, pos, size);
+        partition_open_sector(sectors, pos, size, explanation);
+    }
+}
+
+/// Describes the sectors a sector was cut into, e.g. "5x6 and 5x4"
+fn describe_parts(parts: &[(BoardPos, BoardSize, Direction)]) -> String {
+    let sizes: Vec<_> = parts.iter().map(|(_, size, _)| size.to_string()).collect();
+    match sizes.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+        _ => sizes.join(""),
     }
 }
 
-fn partition_closed_sector(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, pos: BoardPos, size: BoardSize) {
+fn direction_name(direction: Direction) -> &'static str {
+    if direction.is_horizontal() { "horizontally" } else { "vertically" }
+}
+
+fn partition_closed_sector(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, pos: BoardPos, size: BoardSize, mut explanation: Option<&mut Explanation>) {
     type Sectors<'a> = &'a mut Vec<(BoardPos, BoardSize, Direction)>;
 
     // redefine vec! macro to push elements to a mutable reference instead of allocating a new vector
@@ -116,15 +248,22 @@ fn partition_closed_sector(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>,
         ];
     };
 
+    let first = sectors.len();
     match (short_side, long_side) {
         (5, 10) => make_sector(sectors, 5, 6, 4),
         (5, 9) => make_sector(sectors, 5, 5, 4),
         (7, 9) => make_sector(sectors, 7, 5, 4),
         (_, _) => vec![sectors => (pos, size, pos.merge_direction())],
     };
+
+    if sectors.len() - first > 1 {
+        explain!(explanation, 2, "{size} at {pos} (closed): cut into {} by the closed table entry ({short_side}, {long_side})", describe_parts(&sectors[first..]));
+    } else {
+        explain!(explanation, 2, "{size} at {pos} (closed): kept whole");
+    }
 }
 
-fn partition_open_sector(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, pos: BoardPos, size: BoardSize) {
+fn partition_open_sector(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, pos: BoardPos, size: BoardSize, mut explanation: Option<&mut Explanation>) {
     type Sectors<'a> = &'a mut Vec<(BoardPos, BoardSize, Direction)>;
 
     // redefine vec! macro to push elements to a mutable reference instead of allocating a new vector
@@ -166,6 +305,7 @@ fn partition_open_sector(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, po
     // (e.g. 10x8 with horizontal merge direction)
     // so we further partition the sectors to make them more manageable
     // partitions stolen from https://csie.ntnu.edu.tw/~linss/knighttours/bases.html
+    let first = sectors.len();
     match (non_merge_axis, merge_axis) {
         (5, 8) => make_sector(sectors, 5, 4, 4),
         (5, 10) => make_sector(sectors, 5, 6, 4),
@@ -183,4 +323,50 @@ fn partition_open_sector(sectors: &mut Vec<(BoardPos, BoardSize, Direction)>, po
         (10, 10) => make_sector_2(sectors, 10, 4, 3, 3),
         (_, _) => vec![sectors => (pos, size, pos.merge_direction())],
     };
+
+    let direction = direction_name(merge_direction);
+    if sectors.len() - first > 1 {
+        explain!(explanation, 2, "{size} at {pos} (merged {direction}): cut into {} by the open table entry ({non_merge_axis}, {merge_axis})", describe_parts(&sectors[first..]));
+    } else {
+        explain!(explanation, 2, "{size} at {pos} (merged {direction}): kept whole");
+    }
+}
+
+#[test]
+fn test_explanation_does_not_change_partitions() {
+    for size in [BoardSize::new(50, 50), BoardSize::new(37, 41), BoardSize::new(30, 8), BoardSize::new(9, 10)] {
+        let (sectors, explanation) = explain_partition_size(size);
+        assert_eq!(sectors, partition_size(size));
+        assert!(explanation.to_string().ends_with(&format!("  {} sectors in total\n", sectors.len())), "{explanation}");
+    }
+}
+
+#[test]
+fn test_strip_segments() {
+    use super::bases;
+
+    for length in 11..=60 {
+        let segments = segment_length(length, 3, None);
+        assert_eq!(segments.iter().map(|(_, len)| len).sum::<Idx>(), length, "{length}: {segments:?}");
+        assert!(segments.windows(2).all(|pair| pair[0].0 + pair[0].1 == pair[1].0), "{length}: {segments:?}");
+        assert!(segments.iter().all(|(_, len)| *len <= 10), "{length}: {segments:?}");
+
+        // the first segment is solved as the top left sector, the others are stretched between the squares they are merged through
+        assert!(bases::get_strip(BoardSize::new(segments[0].1, 3)).is_some(), "{length}: {segments:?}");
+        for (_, len) in &segments[1..] {
+            assert!(bases::get(Direction::Horizontal, BoardSize::new(*len, 3)).is_some(), "{length}: {segments:?}");
+        }
+
+        // the short side isn't cut
+        assert_eq!(segment_length(3, length, None), [(0, 3)]);
+    }
+}
+
+#[test]
+fn test_short_side_is_kept_whole() {
+    for long in 11..=30 {
+        for short in 4..=10 {
+            assert_eq!(segment_length(short, long, None), [(0, short)], "{short} next to {long}");
+        }
+    }
 }
@@ -0,0 +1,99 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{board_pos::{BoardPos, KNIGHT_MOVES}, move_graph::MoveGraph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpliceError {
+    /// The square to attach is already linked to the tour
+    Linked(BoardPos),
+    /// None of the square's neighbors is on the closed tour with a link that can be taken over
+    NoNeighbor(BoardPos),
+}
+
+impl Display for SpliceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Linked(pos) => write!(f, "{pos} is already part of the tour"),
+            Self::NoNeighbor(pos) => write!(f, "no square a move away from {pos} is on the closed tour"),
+        }
+    }
+}
+
+/// Turns a closed tour that skips `square` into an open tour starting there: the first neighbor on the tour whose link
+/// from the square before it can be taken over becomes the second square of the tour, and the square before it the last one.
+/// Neighbors in `avoid` (e.g. dead squares yet to be cut out) are left alone. Returns the neighbor the square was attached to
+pub fn attach(graph: &mut MoveGraph, square: BoardPos, avoid: &HashSet<BoardPos>) -> Result<BoardPos, SpliceError> {
+    if graph.node(square).next().is_some() || graph.node(square).prev().is_some() {
+        return Err(SpliceError::Linked(square));
+    }
+
+    let size = graph.size();
+    let neighbor = KNIGHT_MOVES.iter()
+        .filter_map(|&(col, row)| square.try_translate(col, row))
+        .filter(|&pos| size.fits(pos) && !avoid.contains(&pos) && graph.is_move(square, pos))
+        .find(|&pos| on_cycle(graph, pos))
+        .ok_or(SpliceError::NoNeighbor(square))?;
+
+    // the square before the neighbor becomes the end of the tour
+    let prev = graph.node(neighbor).prev().unwrap();
+    graph.unlink(prev);
+    graph.link(square, neighbor);
+
+    debug_assert_eq!(graph.check_consistency(), Ok(()));
+    Ok(neighbor)
+}
+
+/// Whether the square is linked both ways to a square before and after it, so the link from before can be taken over
+fn on_cycle(graph: &MoveGraph, pos: BoardPos) -> bool {
+    let node = graph.node(pos);
+    let (Some(prev), Some(next)) = (node.prev(), node.next()) else {
+        return false;
+    };
+
+    // a square marked as the start of a tour links back to itself
+    prev != pos && graph.node(prev).next() == Some(pos) && graph.node(next).prev() == Some(pos)
+}
+
+/// A 3x3 ring of squares without its center, shifted to start at the given square
+#[cfg(test)]
+pub(super) fn ring(width: u32, height: u32, offset: (i64, i64)) -> MoveGraph<'static> {
+    let cycle = [(0, 0), (1, 2), (2, 0), (0, 1), (2, 2), (1, 0), (0, 2), (2, 1)].map(|(col, row)| BoardPos::new(col, row).translate(offset.0, offset.1));
+    let mut graph = MoveGraph::new(width, height);
+    for (idx, &pos) in cycle.iter().enumerate() {
+        let next = cycle[(idx + 1) % cycle.len()];
+        *graph.node_mut(pos).next_mut() = Some(next);
+        *graph.node_mut(next).prev_mut() = Some(pos);
+    }
+
+    graph
+}
+
+#[test]
+fn test_attach() {
+    use crate::validation::validate;
+
+    // (2,1) is on the ring, (1,2) is its center
+    let mut graph = ring(3, 4, (0, 1));
+    assert_eq!(attach(&mut graph, BoardPos::ZERO, &HashSet::new()), Ok(BoardPos::new(2, 1)));
+    let tour = validate(&graph).unwrap();
+    assert_eq!((tour.squares, tour.start, tour.end, tour.closed), (9, BoardPos::ZERO, BoardPos::new(1, 3), false));
+
+    // (2,1) is the center this time, so the other neighbor has to do
+    let mut graph = ring(4, 3, (1, 0));
+    assert_eq!(attach(&mut graph, BoardPos::ZERO, &HashSet::new()), Ok(BoardPos::new(1, 2)));
+    let tour = validate(&graph).unwrap();
+    assert_eq!((tour.squares, tour.start, tour.end), (9, BoardPos::ZERO, BoardPos::new(2, 0)));
+
+    // neighbors that are about to be cut out don't count
+    let mut graph = ring(3, 4, (0, 1));
+    assert_eq!(attach(&mut graph, BoardPos::ZERO, &HashSet::from([BoardPos::new(2, 1)])), Err(SpliceError::NoNeighbor(BoardPos::ZERO)));
+    assert_eq!(validate(&graph).unwrap().squares, 8);
+
+    // neither neighbor is on the ring
+    let mut graph = ring(5, 5, (2, 2));
+    assert_eq!(attach(&mut graph, BoardPos::ZERO, &HashSet::new()), Err(SpliceError::NoNeighbor(BoardPos::ZERO)));
+
+    // the square is on the ring already
+    let mut graph = ring(3, 3, (0, 0));
+    assert_eq!(attach(&mut graph, BoardPos::ZERO, &HashSet::new()), Err(SpliceError::Linked(BoardPos::ZERO)));
+}
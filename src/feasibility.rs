@@ -0,0 +1,215 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{aliases::BoardIndex as Idx, args::InputArgs, board_pos::BoardPos, board_size::BoardSize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feasibility {
+    /// A tour exists and has to be searched for
+    Possible,
+    /// The board consists of a single square, which is its own tour
+    Trivial,
+    /// No tour exists for this board size, for the given reason
+    Impossible(Reason),
+}
+
+/// Why no tour exists, as a short code for scripts and a sentence for people
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reason {
+    pub code: &'static str,
+    pub text: &'static str,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+const fn impossible(code: &'static str, text: &'static str) -> Feasibility {
+    Feasibility::Impossible(Reason { code, text })
+}
+
+impl Feasibility {
+    #[cfg(test)]
+    pub fn is_possible(self) -> bool {
+        !matches!(self, Self::Impossible(_))
+    }
+}
+
+impl Display for Feasibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Possible => write!(f, "a tour exists"),
+            Self::Trivial => write!(f, "the board consists of a single square"),
+            Self::Impossible(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+/// Determines whether a knight's tour exists on a rectangular board without dead squares.
+///
+/// Closed tours follow Schwenk's theorem: an m x n board with m <= n has a closed tour unless
+/// m and n are both odd, m is 1, 2 or 4, or m is 3 and n is 4, 6 or 8.
+/// Open tours exist on every m x n board with m <= n except when m is 1 or 2, m is 3 and n is 3, 5 or 6,
+/// or m and n are both 4.
+pub fn tour_exists(size: BoardSize, closed: bool) -> Feasibility {
+    let [m, n] = ordered(size.width(), size.height());
+
+    if m == 0 {
+        return impossible("no-squares", "the board has no squares");
+    }
+
+    if closed {
+        match (m, n) {
+            (m, n) if m % 2 == 1 && n % 2 == 1 => impossible("parity", "closed tours require an even number of squares"),
+            (1|2|4, _) => impossible("too-narrow", "closed tours are impossible on boards 1, 2 or 4 squares wide"),
+            (3, 4|6|8) => impossible("small-board", "3x4, 3x6 and 3x8 boards have no closed tour"),
+            _ => Feasibility::Possible,
+        }
+    } else {
+        match (m, n) {
+            (1, 1) => Feasibility::Trivial,
+            (1|2, _) => impossible("too-narrow", "boards 1 or 2 squares wide have no tour"),
+            (3, 3|5|6) => impossible("small-board", "3x3, 3x5 and 3x6 boards have no tour"),
+            (4, 4) => impossible("small-board", "4x4 boards have no tour"),
+            _ => Feasibility::Possible,
+        }
+    }
+}
+
+/// Determines whether a knight's tour can exist on a rectangular board with the given squares taken off. Without any, that's [tour_exists].
+///
+/// Otherwise only the squares left are counted: the knight alternates between light and dark squares, so an open tour needs as many light
+/// squares as dark ones give or take one, and a closed tour exactly as many. Boards passing that may still have no tour
+pub fn tour_exists_around(size: BoardSize, dead: &HashSet<BoardPos>, closed: bool) -> Feasibility {
+    if dead.is_empty() {
+        return tour_exists(size, closed);
+    }
+
+    let (mut light, mut dark) = (0usize, 0usize);
+    for pos in (0..size.height()).flat_map(|row| (0..size.width()).map(move |col| BoardPos::new(col, row))) {
+        match ((pos.col() + pos.row()) % 2 == 0, dead.contains(&pos)) {
+            (_, true) => {},
+            (true, false) => light += 1,
+            (false, false) => dark += 1,
+        }
+    }
+
+    match light + dark {
+        0 => impossible("no-squares", "the board has no squares left"),
+        1 => Feasibility::Trivial,
+        live if closed && live % 2 == 1 => impossible("parity", "closed tours require an even number of squares"),
+        _ if closed && light != dark => impossible("color-imbalance", "closed tours require as many light squares as dark ones"),
+        _ if light.abs_diff(dark) > 1 => impossible("color-imbalance", "the light and dark squares left differ by more than one"),
+        _ => Feasibility::Possible,
+    }
+}
+
+/// Whether a knight's tour can exist on the board the arguments describe, as far as that is known without searching.
+/// Only rectangles of --board-size, with the squares of --corner-radius and --dead-squares taken off, are judged
+pub fn of_input(args: &InputArgs) -> Option<Feasibility> {
+    let size = args.board_size.filter(|_| args.is_rectangle())?;
+    Some(tour_exists_around(size, &args.dead_squares(size), false))
+}
+
+fn ordered(a: Idx, b: Idx) -> [Idx; 2] {
+    if a <= b { [a, b] } else { [b, a] }
+}
+
+/// Exhaustively checks for open or closed tours on tiny boards to cross-check [tour_exists]
+#[cfg(test)]
+fn brute_force(size: BoardSize, closed: bool) -> bool {
+    use crate::{board::matrix2d::Matrix2D, board_pos::BoardPos};
+
+    fn visit(visited: &mut Matrix2D<bool>, pos: BoardPos, start: BoardPos, remaining: usize, closed: bool) -> bool {
+        let size = visited.size();
+        if remaining == 0 {
+            return !closed || pos.is_knight_move(start);
+        }
+
+        let moves = [(1, -2), (2, -1), (2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2)];
+        for (col, row) in moves {
+            let Some(next) = pos.try_translate(col, row).filter(|p| size.fits(*p)) else { continue };
+            if *visited.at(next) {
+                continue;
+            }
+
+            *visited.at_mut(next) = true;
+            let found = visit(visited, next, start, remaining - 1, closed);
+            *visited.at_mut(next) = false;
+            if found {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    let mut visited = Matrix2D::new(size.width(), size.height(), || false);
+    let area = size.area() as usize;
+    (0..size.width()).flat_map(|col| (0..size.height()).map(move |row| BoardPos::new(col, row)))
+        .any(|start| {
+            *visited.at_mut(start) = true;
+            let found = visit(&mut visited, start, start, area - 1, closed);
+            *visited.at_mut(start) = false;
+            found
+        })
+}
+
+#[test]
+fn test_classic_cases() {
+    let open = |w, h| tour_exists(BoardSize::new(w, h), false);
+    let closed = |w, h| tour_exists(BoardSize::new(w, h), true);
+
+    assert_eq!(open(1, 1), Feasibility::Trivial);
+    assert!(!open(0, 5).is_possible());
+    assert!(!open(1, 8).is_possible());
+    assert!(!open(8, 2).is_possible());
+    assert!(!open(3, 3).is_possible());
+    assert!(open(3, 4).is_possible());
+    assert!(open(4, 3).is_possible());
+    assert!(!open(4, 4).is_possible());
+    assert!(open(5, 5).is_possible());
+    assert!(open(8, 8).is_possible());
+
+    assert!(!closed(1, 1).is_possible());
+    assert!(!closed(3, 4).is_possible());
+    assert!(!closed(4, 10).is_possible());
+    assert!(!closed(5, 5).is_possible());
+    assert!(closed(3, 10).is_possible());
+    assert!(closed(6, 6).is_possible());
+    assert!(closed(8, 8).is_possible());
+}
+
+#[test]
+fn test_agrees_with_brute_force() {
+    for w in 1..=4 {
+        for h in w..=5 {
+            let size = BoardSize::new(w, h);
+            assert_eq!(tour_exists(size, false).is_possible(), brute_force(size, false), "open {size}");
+            assert_eq!(tour_exists(size, true).is_possible(), brute_force(size, true), "closed {size}");
+        }
+    }
+}
+
+#[test]
+fn test_dead_squares() {
+    let size = BoardSize::new(8, 8);
+    let around = |squares: &[(Idx, Idx)], closed| tour_exists_around(size, &squares.iter().map(|&pos| BoardPos::from(pos)).collect(), closed);
+
+    // one of each color keeps the board balanced, two of the same color don't
+    assert_eq!(around(&[(2, 2), (3, 4)], false), Feasibility::Possible);
+    assert_eq!(around(&[(2, 2), (3, 4)], true), Feasibility::Possible);
+    assert!(matches!(around(&[(0, 0), (2, 0)], false), Feasibility::Impossible(Reason { code: "color-imbalance", .. })));
+
+    // a single square off leaves an odd number, which an open tour can still cover
+    assert_eq!(around(&[(2, 2)], false), Feasibility::Possible);
+    assert!(matches!(around(&[(2, 2)], true), Feasibility::Impossible(Reason { code: "parity", .. })));
+
+    // without dead squares, the rules for rectangles apply
+    assert_eq!(around(&[], false), tour_exists(size, false));
+    assert_eq!(tour_exists_around(BoardSize::new(3, 3), &HashSet::new(), false), tour_exists(BoardSize::new(3, 3), false));
+
+    let everything: HashSet<_> = (0..2).flat_map(|row| (0..2).map(move |col| BoardPos::new(col, row))).collect();
+    assert!(matches!(tour_exists_around(BoardSize::new(2, 2), &everything, false), Feasibility::Impossible(Reason { code: "no-squares", .. })));
+}
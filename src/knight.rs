@@ -1,13 +1,25 @@
-use crate::{aliases::BoardIndexOverflow as IdxMath, board_pos::BoardPos, debug_output, dprintln};
-
+use crate::{
+    aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath},
+    args::{TieBreak, Wrap},
+    board_pos::{self, BoardPos},
+    board_size::BoardSize,
+    debug_output,
+    dprintln,
+    leaper::Leaper,
+};
+
+/// The piece on the board, which moves like a knight unless given a different leaper
 #[derive(Clone, Copy)]
 pub struct Knight {
     position: BoardPos,
+    board_size: BoardSize,
+    wrap: Wrap,
+    leaper: Leaper,
 }
 
 impl Knight {
-    pub fn new(position: BoardPos) -> Self {
-        Knight { position }
+    pub fn new(position: BoardPos, board_size: BoardSize, wrap: Wrap, leaper: Leaper) -> Self {
+        Knight { position, board_size, wrap, leaper }
     }
 
     pub fn position(&self) -> BoardPos {
@@ -19,25 +31,45 @@ impl Knight {
     }
 
     pub fn clone_to(&self, new_pos: BoardPos) -> Self {
-        Knight { position: new_pos }
+        Knight { position: new_pos, ..*self }
     }
 
-    pub fn get_possible_moves(&self, reachable: &impl Fn(BoardPos, BoardPos) -> bool) -> Vec<BoardPos> {
+    /// The moves allowed by `reachable`, ordered by the number of ways to continue `moves_ahead` moves from each target
+    /// square (`onward_moves`), with dead ends (squares with no move onwards) last. Moves with the same count are ordered by `tie_break`
+    pub fn get_possible_moves(
+        &self,
+        reachable: &impl Fn(BoardPos, BoardPos) -> bool,
+        onward_moves: impl Fn(BoardPos, u8) -> usize,
+        moves_ahead: u8,
+        tie_break: TieBreak,
+    ) -> Vec<BoardPos> {
         let mut possible_moves: Vec<BoardPos> = self.get_possible_moves_impl(reachable).collect();
 
-        const MOVES_AHEAD: u8 = 1;
-        possible_moves.sort_by_cached_key(|pos| match self.clone_to(*pos).possible_moves_count(&reachable, MOVES_AHEAD){
-            n if n < MOVES_AHEAD as usize => usize::MAX,
-            n => n
+        // only squares without a move onwards are dead ends, a low count further ahead just makes a square come first
+        possible_moves.sort_by_cached_key(|pos| {
+            let dead_end = onward_moves(*pos, 1) == 0;
+            (dead_end, onward_moves(*pos, moves_ahead), self.tie_break_key(*pos, &onward_moves, moves_ahead, tie_break))
         });
 
         possible_moves
     }
 
-    fn get_possible_moves_impl<'a, F>(&'a self, reachable: &'a F) -> PossibleMovesIterator<'a, F>
-    where F : Fn(BoardPos, BoardPos) -> bool
-    {
-        PossibleMovesIterator { knight: *self, reachable, offset: 0 }
+    /// Lower keys win ties
+    fn tie_break_key(&self, pos: BoardPos, onward_moves: impl Fn(BoardPos, u8) -> usize, moves_ahead: u8, tie_break: TieBreak) -> IdxMath {
+        match tie_break {
+            TieBreak::None => 0,
+            TieBreak::Pohl => onward_moves(pos, moves_ahead + 1) as IdxMath,
+            TieBreak::Edge => {
+                // doubled, so that the center of boards with an even side length is a whole number
+                let from_center = |idx: Idx, len: Idx| 2 * idx as IdxMath - (len as IdxMath - 1);
+                let (col, row) = (from_center(pos.col(), self.board_size.width()), from_center(pos.row(), self.board_size.height()));
+                -(col * col + row * row)
+            },
+        }
+    }
+
+    fn get_possible_moves_impl<'a>(&'a self, reachable: &'a impl Fn(BoardPos, BoardPos) -> bool) -> impl Iterator<Item = BoardPos> + 'a {
+        board_pos::leaper_moves(self.position, self.board_size, self.wrap, self.leaper).filter(move |&pos| reachable(self.position, pos))
     }
 
     pub fn possible_moves_count(&self, reachable: &impl Fn(BoardPos, BoardPos) -> bool, moves_ahead: u8) -> usize {
@@ -56,51 +88,47 @@ impl Knight {
             )
         };
 
-        dprintln!(3 => "{} -> {} moves", self.position, move_count);
+        dprintln!(3 => { pos = %self.position, moves = move_count } "{} -> {} moves", self.position, move_count);
         move_count
     }
 }
 
-struct PossibleMovesIterator<'a, F>
-where F: Fn(BoardPos, BoardPos) -> bool
-{
-    knight: Knight,
-    reachable: &'a F,
-    offset: i8,
-}
+#[test]
+fn test_tie_break() {
+    // from C2 on an empty 8x8 board, A1, A3, B4, D4, E1 and E3 are all reachable
+    let size = BoardSize::new(8, 8);
+    let knight = Knight::new(BoardPos::new(2, 1), size, Wrap::None, Leaper::KNIGHT);
+    let reachable = |_, to: BoardPos| size.fits(to);
+    let all_equal = |_, _| 4;
+    let moves = |tie_break| knight.get_possible_moves(&reachable, all_equal, 1, tie_break);
 
-impl <'a, F> Iterator for PossibleMovesIterator<'a, F>
-where F: Fn(BoardPos, BoardPos) -> bool
-{
-    type Item = BoardPos;
+    // the order of the offsets decides without a tie break
+    assert_eq!(moves(TieBreak::None)[0], BoardPos::new(4, 2));
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.offset >= 8 {
-            return None;
-        }
+    // A1 is the farthest from the center
+    assert_eq!(moves(TieBreak::Edge)[0], BoardPos::new(0, 0));
 
-        // generate sequence of
-        //  2,  1
-        //  2, -1
-        // -2,  1
-        // -2, -1
-        //  1,  2
-        //  1, -2
-        // -1,  2
-        // -1, -2
-        let offset = self.offset as IdxMath;
-        let h_neg = 1 - (2 * ((offset / 2) % 2));
-        let h_offset = (2 - offset / 4) * h_neg;
-        let v_neg = 1 - (2 * (offset % 2));
-        let v_offset = (1 + offset / 4) * v_neg;
-
-        self.offset += 1;
-        if let Some(pos) = self.knight.position.try_translate(h_offset, v_offset) {
-            if (self.reachable)(self.knight.position, pos) {
-                return Some(pos);
-            }
-        }
+    // Pohl's rule looks one move further, where B4 has the fewest options
+    let onward = |pos: BoardPos, moves_ahead| if moves_ahead == 2 && pos == BoardPos::new(1, 3) { 1 } else { 4 };
+    assert_eq!(knight.get_possible_moves(&reachable, onward, 1, TieBreak::Pohl)[0], BoardPos::new(1, 3));
+}
 
-        self.next()
-    }
-}
\ No newline at end of file
+#[test]
+fn test_lookahead_dead_ends() {
+    // from C2 on an empty 8x8 board, E3 is tried first when all else is equal
+    let size = BoardSize::new(8, 8);
+    let knight = Knight::new(BoardPos::new(2, 1), size, Wrap::None, Leaper::KNIGHT);
+    let reachable = |_, to: BoardPos| size.fits(to);
+
+    // E3 has no move onwards, A1 has a single one, which leads to a single square two moves ahead
+    let (dead_end, narrow) = (BoardPos::new(4, 2), BoardPos::new(0, 0));
+    let onward = |pos: BoardPos, moves_ahead| match pos {
+        _ if pos == dead_end => 0,
+        _ if pos == narrow => 1,
+        _ => 4usize.pow(moves_ahead as u32),
+    };
+
+    let moves = knight.get_possible_moves(&reachable, onward, 2, TieBreak::None);
+    assert_eq!(moves[0], narrow);
+    assert_eq!(*moves.last().unwrap(), dead_end);
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+
+use crate::aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath};
+
+/// A piece that jumps `a` squares along one axis and `b` along the other, in any direction. The knight is the (1,2)-leaper
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Leaper {
+    a: Idx,
+    b: Idx,
+}
+
+impl Default for Leaper {
+    fn default() -> Self {
+        Self::KNIGHT
+    }
+}
+
+impl Leaper {
+    pub const KNIGHT: Self = Self { a: 1, b: 2 };
+    pub const CAMEL: Self = Self { a: 1, b: 3 };
+    pub const GIRAFFE: Self = Self { a: 1, b: 4 };
+
+    /// The leaper jumping the given distances, in either order. There is none that doesn't move at all
+    pub fn new(a: Idx, b: Idx) -> Option<Self> {
+        (a > 0 || b > 0).then(|| Self { a: a.min(b), b: a.max(b) })
+    }
+
+    pub fn is_knight(self) -> bool {
+        self == Self::KNIGHT
+    }
+
+    /// The distinct moves, clockwise starting from the top (the same order as [crate::board_pos::KNIGHT_MOVES] for the knight)
+    pub fn moves(self) -> impl Iterator<Item = (IdxMath, IdxMath)> + Clone {
+        let (a, b) = (self.a as IdxMath, self.b as IdxMath);
+        let all = [(a, -b), (b, -a), (b, a), (a, b), (-a, b), (-b, a), (-b, -a), (-a, -b)];
+        // leapers moving straight or diagonally only have every other of the eight moves
+        all.into_iter().step_by(if self.has_four_moves() { 2 } else { 1 })
+    }
+
+    /// The move the Warnsdorff search tries in the given place (0 to 7) on a tie, or None if it is the same as an earlier one
+    pub fn search_move(self, idx: usize) -> Option<(IdxMath, IdxMath)> {
+        // 2, 1 / 2, -1 / -2, 1 / -2, -1 / 1, 2 / 1, -2 / -1, 2 / -1, -2 for the knight
+        let raw = |idx: usize| {
+            let (long, short) = (self.b as IdxMath, self.a as IdxMath);
+            let (col, row) = if idx < 4 { (long, short) } else { (short, long) };
+            let col_sign = if (idx / 2).is_multiple_of(2) { 1 } else { -1 };
+            let row_sign = if idx.is_multiple_of(2) { 1 } else { -1 };
+            (col * col_sign, row * row_sign)
+        };
+
+        let offset = raw(idx);
+        if self.has_four_moves() && (0..idx).any(|earlier| raw(earlier) == offset) {
+            return None;
+        }
+
+        Some(offset)
+    }
+
+    /// Whether the offset is one of the moves
+    pub fn is_move(self, col: IdxMath, row: IdxMath) -> bool {
+        let (col, row) = (col.unsigned_abs(), row.unsigned_abs());
+        let (a, b) = (self.a as u64, self.b as u64);
+        (col, row) == (a, b) || (col, row) == (b, a)
+    }
+
+    /// The number of columns and rows of the shorter and the longer part of the jump
+    pub fn distances(self) -> (Idx, Idx) {
+        (self.a, self.b)
+    }
+
+    fn has_four_moves(self) -> bool {
+        self.a == 0 || self.a == self.b
+    }
+}
+
+impl Display for Leaper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::KNIGHT => write!(f, "knight"),
+            Self::CAMEL => write!(f, "camel"),
+            Self::GIRAFFE => write!(f, "giraffe"),
+            Self { a, b } => write!(f, "({a},{b})-leaper"),
+        }
+    }
+}
+
+/// Parses a piece by name (knight, camel, giraffe) or as the two distances of a leaper separated by a comma (e.g. "2,3")
+pub fn parse_piece(arg: &str) -> Result<Leaper, String> {
+    let named = [Leaper::KNIGHT, Leaper::CAMEL, Leaper::GIRAFFE];
+    if let Some(leaper) = named.into_iter().find(|leaper| arg.eq_ignore_ascii_case(&leaper.to_string())) {
+        return Ok(leaper);
+    }
+
+    let parts: Vec<_> = arg.trim_start_matches('(').trim_end_matches(')').split(',').map(str::trim).collect();
+    let [a, b] = parts[..] else {
+        return Err(format!("expected knight, camel, giraffe or two distances like 2,3, found {arg}"));
+    };
+
+    let distance = |part: &str| part.parse::<Idx>().map_err(|e| format!("invalid distance {part}: {e}"));
+    Leaper::new(distance(a)?, distance(b)?).ok_or_else(|| "a piece needs to move at least one square".to_string())
+}
+
+#[test]
+fn test_moves() {
+    assert_eq!(Leaper::KNIGHT.moves().collect::<Vec<_>>(), crate::board_pos::KNIGHT_MOVES);
+    assert_eq!(Leaper::CAMEL.moves().count(), 8);
+    assert!(Leaper::CAMEL.moves().all(|(col, row)| Leaper::CAMEL.is_move(col, row)));
+    assert_eq!(Leaper::new(0, 1).unwrap().moves().collect::<Vec<_>>(), [(0, -1), (1, 0), (0, 1), (-1, 0)]);
+    assert_eq!(Leaper::new(2, 2).unwrap().moves().collect::<Vec<_>>(), [(2, -2), (2, 2), (-2, 2), (-2, -2)]);
+    assert!(!Leaper::KNIGHT.is_move(1, 3));
+}
+
+#[test]
+fn test_search_moves() {
+    let search = |leaper: Leaper| (0..8).filter_map(|idx| leaper.search_move(idx)).collect::<Vec<_>>();
+    assert_eq!(search(Leaper::KNIGHT), [(2, 1), (2, -1), (-2, 1), (-2, -1), (1, 2), (1, -2), (-1, 2), (-1, -2)]);
+    assert_eq!(search(Leaper::GIRAFFE)[..2], [(4, 1), (4, -1)]);
+    assert_eq!(search(Leaper::new(0, 3).unwrap()), [(3, 0), (-3, 0), (0, 3), (0, -3)]);
+    assert_eq!(search(Leaper::new(1, 1).unwrap()), [(1, 1), (1, -1), (-1, 1), (-1, -1)]);
+}
+
+#[test]
+fn test_parse_piece() {
+    assert_eq!(parse_piece("knight"), Ok(Leaper::KNIGHT));
+    assert_eq!(parse_piece("Camel"), Ok(Leaper::CAMEL));
+    assert_eq!(parse_piece("giraffe"), Ok(Leaper::GIRAFFE));
+    assert_eq!(parse_piece("2,1"), Ok(Leaper::KNIGHT));
+    assert_eq!(parse_piece("(2, 3)"), Ok(Leaper::new(2, 3).unwrap()));
+    assert!(parse_piece("0,0").is_err());
+    assert!(parse_piece("zebra").is_err());
+    assert!(parse_piece("1,2,3").is_err());
+    assert_eq!(Leaper::new(3, 2).unwrap().to_string(), "(2,3)-leaper");
+}
@@ -0,0 +1,116 @@
+//! The knights_tour program. Besides [main], which the binary runs, only what the integration tests need is exported:
+//! the solvers, the [Tour] they find and what it takes to call them, and the moves of the knight
+
+mod args;
+mod board_pos;
+mod board_size;
+mod board;
+mod knight;
+mod leaper;
+mod warnsdorff;
+mod divide_and_conquer;
+mod debug_output;
+mod move_graph;
+mod svg;
+mod arrows;
+mod text;
+mod mask;
+mod feasibility;
+mod bitset;
+mod validation;
+mod phase_timer;
+mod canonical;
+mod random;
+mod repair;
+mod summary;
+mod context;
+mod degree_map;
+mod overwrite;
+mod tour;
+mod terminal;
+mod cli;
+#[cfg(test)]
+mod temp_dir;
+
+pub use args::{Args, InputArgs, Precision, SvgStyle};
+pub use board_pos::{knight_moves, knight_moves_unbounded, BoardPos};
+pub use board_size::BoardSize;
+pub use cli::main;
+pub use divide_and_conquer::solve as solve_divide_and_conquer;
+pub use feasibility::{tour_exists, Feasibility};
+pub use random::Random;
+pub use svg::render_svg;
+pub use tour::Tour;
+pub use warnsdorff::solve as solve_warnsdorff;
+
+pub mod aliases {
+    // aliases for the board index type
+    // Note that the Overflow type must be signed, otherwise it WILL overflow. It should also be larger than the Index type as to prevent overflows with very large boards.
+    pub type BoardIndex = u32;
+    pub type BoardIndexOverflow = i64;
+
+    // Ensure that we don't accidentally define invalid index types
+    const _: () = assert!(std::mem::size_of::<BoardIndex>() <= std::mem::size_of::<BoardIndexOverflow>());
+    const _: () = assert!(std::mem::size_of::<BoardIndex>() <= std::mem::size_of::<usize>());
+    const _: () = assert!(BoardIndex::MIN == 0);
+    const _: () = assert!(BoardIndexOverflow::MIN < 0);
+}
+
+/// The elapsed time without decoration, for output that should look presentable
+pub(crate) fn elapsed_seconds(elapsed: std::time::Duration, precision: args::Precision) -> String {
+    format!("Elapsed time: {}", format_duration(elapsed, precision))
+}
+
+/// A duration as --time-format asks for it. Digits that don't fit are cut off rather than rounded, so that e.g. 59.99 seconds
+/// don't turn into a minute
+pub(crate) fn format_duration(d: std::time::Duration, precision: args::Precision) -> String {
+    match precision {
+        // below a millisecond, there would be nothing but zeroes
+        args::Precision::Seconds if d.as_millis() == 0 => format!("0.{:06} seconds", d.subsec_micros()),
+        args::Precision::Seconds => format!("{}.{:03} seconds", d.as_secs(), d.subsec_millis()),
+        args::Precision::Millis => format!("{}.{:03} ms", d.as_millis(), d.subsec_micros() % 1000),
+        args::Precision::Human if d.as_micros() < 100 => format!("{}µs", d.as_micros()),
+        args::Precision::Human if d.as_secs() == 0 => format!("{}.{}ms", d.subsec_millis(), d.subsec_micros() % 1000 / 100),
+        args::Precision::Human => {
+            let (secs, tenths) = (d.as_secs(), d.subsec_millis() / 100);
+            match (secs / 3600, secs / 60 % 60, secs % 60) {
+                (0, 0, s) => format!("{s}.{tenths}s"),
+                (0, m, s) => format!("{m}m {s}.{tenths}s"),
+                (h, m, s) => format!("{h}h {m}m {s}.{tenths}s"),
+            }
+        },
+    }
+}
+
+#[test]
+fn test_format_duration() {
+    use std::time::Duration;
+    use args::Precision::*;
+
+    let just_below_a_second = Duration::from_micros(999_900);
+    assert_eq!(format_duration(just_below_a_second, Seconds), "0.999 seconds");
+    assert_eq!(format_duration(just_below_a_second, Millis), "999.900 ms");
+    assert_eq!(format_duration(just_below_a_second, Human), "999.9ms");
+
+    let minute = Duration::from_secs(60);
+    assert_eq!(format_duration(minute, Seconds), "60.000 seconds");
+    assert_eq!(format_duration(minute, Millis), "60000.000 ms");
+    assert_eq!(format_duration(minute, Human), "1m 0.0s");
+    assert_eq!(format_duration(minute - Duration::from_micros(1), Human), "59.9s");
+
+    assert_eq!(format_duration(Duration::from_micros(42), Seconds), "0.000042 seconds");
+    assert_eq!(format_duration(Duration::from_micros(42), Human), "42µs");
+    assert_eq!(format_duration(Duration::from_micros(100), Human), "0.1ms");
+}
+
+#[test]
+fn test_human_duration() {
+    use std::time::Duration;
+
+    let human = |millis| format_duration(Duration::from_millis(millis), args::Precision::Human);
+    assert_eq!(human(0), "0µs");
+    assert_eq!(human(1_000), "1.0s");
+    assert_eq!(human(83_456), "1m 23.4s");
+    assert_eq!(human(3_600_000), "1h 0m 0.0s");
+    assert_eq!(human(90_061_900), "25h 1m 1.9s");
+}
@@ -0,0 +1,42 @@
+use std::{collections::HashSet, error::Error, fs::File, io::{BufWriter, Write}, path::Path};
+
+use image::{ImageFormat, Rgba, RgbaImage};
+
+use crate::{board_pos::BoardPos, board_size::BoardSize};
+
+/// Writes which squares of the board are accessible, in a form --board-file reads back.
+/// Image files (by extension) get a black pixel per accessible square and a white one per inaccessible square,
+/// everything else gets a text grid with '#' for accessible squares and spaces for inaccessible ones
+pub fn write_mask(path: &Path, dead_squares: &HashSet<BoardPos>, size: BoardSize) -> Result<(), Box<dyn Error>> {
+    let is_image = path.extension().is_some_and(|ext| !ext.eq_ignore_ascii_case("txt") && ImageFormat::from_extension(ext).is_some());
+    if is_image {
+        let image = RgbaImage::from_fn(size.width(), size.height(), |x, y| {
+            if dead_squares.contains(&BoardPos::new(x, y)) { Rgba([255, 255, 255, 255]) } else { Rgba([0, 0, 0, 255]) }
+        });
+        image.save(path)?;
+        return Ok(());
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_text_mask(&mut writer, dead_squares, size)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// The trailing spaces are kept, so that a row ending in inaccessible squares doesn't make the board narrower when read back
+pub fn write_text_mask(writer: &mut impl Write, dead_squares: &HashSet<BoardPos>, size: BoardSize) -> std::io::Result<()> {
+    for row in 0..size.height() {
+        let line: String = (0..size.width()).map(|col| if dead_squares.contains(&BoardPos::new(col, row)) { ' ' } else { '#' }).collect();
+        writeln!(writer, "{line}")?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_write_text_mask() {
+    let dead = HashSet::from([BoardPos::new(0, 0), BoardPos::new(2, 1), BoardPos::new(2, 0)]);
+    let mut buf = Vec::new();
+    write_text_mask(&mut buf, &dead, BoardSize::new(3, 2)).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), " # \n## \n");
+}
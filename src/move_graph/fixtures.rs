@@ -0,0 +1,49 @@
+//! Known tours for use in tests
+
+use crate::{aliases::BoardIndex as Idx, board_pos::BoardPos};
+
+use super::MoveGraph;
+
+/// An open tour of a 6x6 board, starting in the top left corner
+pub const SIX_BY_SIX: [(Idx, Idx); 36] = [
+    (0, 0), (2, 1), (4, 0), (5, 2), (4, 4), (2, 5), (0, 4), (1, 2), (2, 0), (0, 1), (1, 3), (0, 5),
+    (2, 4), (4, 5), (5, 3), (3, 2), (5, 1), (3, 0), (1, 1), (0, 3), (1, 5), (3, 4), (5, 5), (4, 3),
+    (3, 5), (5, 4), (3, 3), (4, 1), (2, 2), (1, 4), (0, 2), (1, 0), (3, 1), (5, 0), (4, 2), (2, 3),
+];
+
+/// The eight outer squares of a 3x3 board. The last square is a knight's move away from the first one,
+/// so this can be used as both an open and a closed tour. The center square is never visited.
+pub const RING: [(Idx, Idx); 8] = [(0, 0), (2, 1), (0, 2), (1, 0), (2, 2), (0, 1), (2, 0), (1, 2)];
+
+/// A closed tour of a 5x6 cylinder (wrapping horizontally). Eight of its moves cross the seam between the left and the right edge,
+/// so it is no tour at all on a plain board.
+pub const CYLINDER: [(Idx, Idx); 30] = [
+    (0, 0), (2, 1), (4, 0), (1, 1), (3, 0), (4, 2), (0, 4), (2, 5), (3, 3), (4, 5), (1, 4), (3, 5), (2, 3), (1, 5), (4, 4),
+    (0, 2), (1, 0), (3, 1), (4, 3), (0, 5), (1, 3), (3, 4), (2, 2), (0, 1), (2, 0), (4, 1), (0, 3), (3, 2), (2, 4), (1, 2),
+];
+
+pub fn open(width: Idx, height: Idx, tour: &[(Idx, Idx)]) -> MoveGraph<'static> {
+    MoveGraph::from_tour(width, height, tour.iter().copied().map(BoardPos::from))
+}
+
+pub fn closed(width: Idx, height: Idx, tour: &[(Idx, Idx)]) -> MoveGraph<'static> {
+    let mut graph = open(width, height, tour);
+    let (first, last) = (BoardPos::from(tour[0]), BoardPos::from(tour[tour.len() - 1]));
+    *graph.node_mut(last).next_mut() = Some(first);
+    *graph.node_mut(first).prev_mut() = Some(last);
+    graph
+}
+
+/// Several open paths on one board, e.g. the paths of knights covering the board together
+pub fn paths(width: Idx, height: Idx, paths: &[&[(Idx, Idx)]]) -> MoveGraph<'static> {
+    let mut graph = MoveGraph::new(width, height);
+    for path in paths {
+        for pair in path.windows(2) {
+            let (from, to) = (BoardPos::from(pair[0]), BoardPos::from(pair[1]));
+            *graph.node_mut(from).next_mut() = Some(to);
+            *graph.node_mut(to).prev_mut() = Some(from);
+        }
+    }
+
+    graph
+}
@@ -0,0 +1,145 @@
+//! Applies random sequences of graph operations to random partial tours and checks that the graph stays consistent.
+//! The crate has no library target for an external fuzzer to link against, so this runs as a regular test over many seeds
+
+use crate::{
+    aliases::BoardIndex as Idx,
+    args::Wrap,
+    board_pos::{BoardPos, KNIGHT_MOVES},
+    board_size::BoardSize,
+    random::Random,
+};
+
+use super::{fixtures, Direction, MoveGraph};
+
+const SEEDS: u64 = 200;
+const OPERATIONS: usize = 30;
+// graphs stop growing through combine and insert_section beyond this
+const MAX_SIDE: Idx = 24;
+
+/// Every node sits in its own slot, every link is a knight's move and is matched by a link back.
+/// A square linking back to itself marks the start of a tour
+fn assert_consistent(graph: &MoveGraph, context: &str) {
+    let positions = (0..graph.height()).flat_map(|row| (0..graph.width()).map(move |col| BoardPos::new(col, row)));
+    for pos in positions {
+        let node = graph.node(pos);
+        assert_eq!(node.pos(), pos, "{context}: node in the wrong slot\n{graph:?}");
+
+        if let Some(next) = node.next() {
+            assert!(graph.size().fits(next), "{context}: {pos} -> {next} leaves the board");
+            assert!(graph.is_move(pos, next), "{context}: {pos} -> {next} is not a knight's move\n{graph:?}");
+            assert_eq!(graph.node(next).prev(), Some(pos), "{context}: {pos} -> {next} is not linked back\n{graph:?}");
+        }
+
+        if let Some(prev) = node.prev().filter(|prev| *prev != pos) {
+            assert!(graph.size().fits(prev), "{context}: {prev} -> {pos} leaves the board");
+            assert_eq!(graph.node(prev).next(), Some(pos), "{context}: {prev} -> {pos} is not linked forward\n{graph:?}");
+        }
+    }
+
+    let mut slots: Vec<BoardPos> = graph.nodes().map(|node| node.pos()).collect();
+    slots.sort_by_key(|pos| (pos.row(), pos.col()));
+    slots.dedup();
+    assert_eq!(slots.len(), graph.size().area() as usize, "{context}: nodes() misses or repeats squares");
+    assert_eq!(graph.check_consistency(), Ok(()), "{context}\n{graph:?}");
+}
+
+/// A random walk from a random square, stopping when stuck or after a random number of moves
+fn random_walk(size: BoardSize, random: &mut Random) -> MoveGraph<'static> {
+    let mut visited = vec![false; size.area() as usize];
+    let slot = |pos: BoardPos| (pos.row() * size.width() + pos.col()) as usize;
+    let mut pos = BoardPos::new(random.below(size.width() as usize) as Idx, random.below(size.height() as usize) as Idx);
+    let mut walk = vec![pos];
+    visited[slot(pos)] = true;
+    for _ in 0..random.below(size.area() as usize + 1) {
+        let moves: Vec<BoardPos> = KNIGHT_MOVES.iter()
+            .filter_map(|(col, row)| pos.try_translate_wrapping(*col, *row, size, Wrap::None))
+            .filter(|next| !visited[slot(*next)])
+            .collect();
+        let Some(&next) = moves.get(random.below(moves.len().max(1))) else { break };
+        visited[slot(next)] = true;
+        walk.push(next);
+        pos = next;
+    }
+
+    MoveGraph::from_tour(size.width(), size.height(), walk)
+}
+
+fn random_size(random: &mut Random) -> BoardSize {
+    BoardSize::new(1 + random.below(8) as Idx, 1 + random.below(8) as Idx)
+}
+
+/// The fixtures first, random walks after them
+fn base_graph(seed: u64, random: &mut Random) -> MoveGraph<'static> {
+    match seed {
+        0 => fixtures::open(6, 6, &fixtures::SIX_BY_SIX),
+        1 => fixtures::open(3, 3, &fixtures::RING),
+        2 => fixtures::closed(3, 3, &fixtures::RING),
+        _ => random_walk(random_size(random), random),
+    }
+}
+
+#[test]
+fn test_operations_keep_graph_consistent() {
+    for seed in 0..SEEDS {
+        let mut random = Random::new(seed);
+        let mut graph = base_graph(seed, &mut random);
+        let mut history = vec![format!("{} start", graph.size())];
+        assert_consistent(&graph, &history.join(", "));
+
+        for _ in 0..OPERATIONS {
+            let operation = match random.below(9) {
+                0 => {
+                    graph = graph.reverse();
+                    "reverse".to_string()
+                },
+                1 => {
+                    graph = graph.flip();
+                    "flip".to_string()
+                },
+                2 => {
+                    graph.reverse_in_place();
+                    "reverse_in_place".to_string()
+                },
+                3 if graph.width() + 8 <= MAX_SIDE => {
+                    let other = random_walk(BoardSize::new(1 + random.below(8) as Idx, graph.height()), &mut random);
+                    let width = other.width();
+                    graph = graph.combine(other, Direction::Horizontal);
+                    format!("combine horizontally with width {width}")
+                },
+                4 if graph.height() + 8 <= MAX_SIDE => {
+                    let other = random_walk(BoardSize::new(graph.width(), 1 + random.below(8) as Idx), &mut random);
+                    let height = other.height();
+                    graph = graph.combine(other, Direction::Vertical);
+                    format!("combine vertically with height {height}")
+                },
+                5 if graph.width().max(graph.height()) + 4 <= MAX_SIDE => {
+                    // the section's links stay among its own squares, so it can be reversed on its own afterwards
+                    let offset = BoardPos::new(random.below(5) as Idx, random.below(5) as Idx);
+                    let mut larger = MoveGraph::new(graph.width() + offset.col(), graph.height() + offset.row());
+                    larger.insert_section(&graph, offset);
+                    let size = graph.size();
+                    graph = larger;
+                    assert_consistent(&graph, &format!("{}, insert_section at {offset}", history.join(", ")));
+                    graph.reverse_section(offset, size);
+                    format!("insert_section and reverse_section at {offset}")
+                },
+                6 => {
+                    let iterations = random.below(50);
+                    graph.shuffle(iterations, seed);
+                    format!("shuffle {iterations}")
+                },
+                7 => format!("try_close: {}", graph.try_close()),
+                _ => {
+                    // views have to look the same as the graph they are taken of
+                    assert_eq!(MoveGraph::ref_to(&graph), graph);
+                    assert_eq!(MoveGraph::ref_to(&graph).reverse(), graph.clone().reverse());
+                    assert_eq!(graph.section(BoardPos::ZERO, graph.size()), graph);
+                    "views".to_string()
+                },
+            };
+
+            history.push(operation);
+            assert_consistent(&graph, &format!("seed {seed}: {}", history.join(", ")));
+        }
+    }
+}
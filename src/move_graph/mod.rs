@@ -1,7 +1,7 @@
-use std::{fmt::Debug, ops::Not};
+use std::{fmt::Debug, ops::{Not, Range}};
 
 use crate::{
-    aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath}, board::{matrix2d::Matrix2D, Board}, board_pos::BoardPos, board_size::BoardSize, dprintln
+    aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath}, args::Wrap, board::{matrix2d::Matrix2D, Board}, board_pos::{BoardPos, ColumnName, PosFormatter}, board_size::BoardSize, dprintln, leaper::Leaper
 };
 
 mod node;
@@ -9,10 +9,17 @@ mod node_ref;
 mod move_graph_data;
 mod nodes_iterator;
 mod print_move;
+mod shuffle;
+mod tour_iterator;
+#[cfg(test)]
+pub mod fixtures;
+#[cfg(test)]
+mod invariants;
 pub use node::Node;
 pub use node_ref::NodeRef;
 use move_graph_data::MoveGraphData;
 pub use nodes_iterator::NodesIterator;
+pub use tour_iterator::TourIterator;
 
 use crate::print_move;
 
@@ -20,36 +27,14 @@ use crate::print_move;
 pub struct MoveGraph<'a> {
     width: Idx,
     height: Idx,
+    wrap: Wrap,
+    leaper: Leaper,
     nodes: MoveGraphData<'a>,
 }
 
 impl<'a> Debug for MoveGraph<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let max_len = BoardPos::new(self.width -1, self.height -1).to_string().len();
-        let empty = " ".repeat(max_len);
-        for y in 0..self.height {
-            for x in 0..self.width {
-                write!(f, "| ")?;
-                let pos = BoardPos::new(x, y);
-                let node = self.node(pos);
-                if let Some(prev) = node.prev() {
-                    write!(f, "{: ^max_len$}", prev)?;
-                }
-                else {
-                    write!(f, "{}", empty)?;
-                }
-
-                if let Some(next) = node.next() {
-                    write!(f, " -> {: ^max_len$} ", next)?;
-                }
-                else {
-                    write!(f, "    {} ", empty)?;
-                }
-            }
-            writeln!(f, "|")?;
-        }
-
-        Ok(())
+        self.write_table(f, 0..self.width, 0..self.height)
     }
 }
 
@@ -94,32 +79,112 @@ impl Not for Direction {
     }
 }
 
+/// Two graphs are equal if they have the same size, wrapping and piece and every square links to the same squares,
+/// no matter whether either of them owns its nodes or is a (reversed) view into another graph
+impl PartialEq for MoveGraph<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        if (self.width, self.height, self.wrap, self.leaper) != (other.width, other.height, other.wrap, other.leaper) {
+            return false;
+        }
+
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| BoardPos::new(col, row)))
+            .all(|pos| {
+                let (node, other) = (self.node(pos), other.node(pos));
+                node.prev() == other.prev() && node.next() == other.next()
+            })
+    }
+}
+
+impl Eq for MoveGraph<'_> {}
+
 impl<'a> MoveGraph<'a> {
     pub fn new(width: Idx, height: Idx) -> Self {
         let mut res = Self::new_empty(width, height);
 
         for y in 0..height {
             for x in 0..width {
-                let mut edges = Vec::with_capacity(8);
-                for (dx, dy) in (-2..=2 as IdxMath).flat_map(|y|((-2..=2 as IdxMath).map(move |x|(x, y)))) {
-                    if dx.abs() + dy.abs() == 3 && !matches!((dx, dy), (0,_)|(_,0)) {
-                        let nx = x as IdxMath + dx;
-                        let ny = y as IdxMath + dy;
-                        if nx >= 0 && nx < width as IdxMath && ny >= 0 && ny < height as IdxMath {
-                            edges.push(BoardPos::new(nx as Idx, ny as Idx));
-                        }
-                    }
-                }
+                *res.nodes.at_mut(BoardPos::new(x, y)) = Node::new(BoardPos::new(x, y));
+            }
+        }
 
-                *res.nodes.at_mut(BoardPos::new(x, y)) = Node::new(BoardPos::new(x, y), edges);
+        res
+    }
+
+    /// Builds a graph of the given size from the squares of a tour, in the order they are visited
+    pub fn from_tour(width: Idx, height: Idx, tour: impl IntoIterator<Item = BoardPos>) -> Self {
+        let mut res = Self::new(width, height);
+        let mut prev: Option<BoardPos> = None;
+        for next in tour {
+            if let Some(prev) = prev {
+                *res.node_mut(prev).next_mut() = Some(next);
             }
+
+            *res.node_mut(next).prev_mut() = prev;
+            prev = Some(next);
         }
 
         res
     }
 
     pub fn ref_to(&'a self) -> Self {
-        Self { width: self.width, height: self.height, nodes: MoveGraphData::Ref(self) }
+        Self { width: self.width, height: self.height, wrap: self.wrap, leaper: self.leaper, nodes: MoveGraphData::Ref(self) }
+    }
+
+    /// A view of the part of the graph of the given size at `start`. Its squares count from its own top left corner,
+    /// links to squares outside of it are left out
+    #[cfg(test)]
+    pub fn section(&'a self, start: BoardPos, size: BoardSize) -> Self {
+        Self { width: size.width(), height: size.height(), wrap: self.wrap, leaper: self.leaper, nodes: MoveGraphData::Section(self, start, size) }
+    }
+
+    /// The links of the squares up to `radius` squares away from `center` in either direction, in the same table as the Debug output
+    pub fn dump_around(&self, center: BoardPos, radius: Idx) -> String {
+        let cols = center.col().saturating_sub(radius)..center.col().saturating_add(radius + 1).min(self.width);
+        let rows = center.row().saturating_sub(radius)..center.row().saturating_add(radius + 1).min(self.height);
+        let mut res = String::new();
+        self.write_table(&mut res, cols, rows).unwrap();
+        res
+    }
+
+    /// Writes prev -> next for each square of the given columns and rows, with the column letters above and the row numbers to the left
+    fn write_table(&self, f: &mut impl std::fmt::Write, cols: Range<Idx>, rows: Range<Idx>) -> std::fmt::Result {
+        let max_len = BoardPos::new(self.width -1, self.height -1).to_string().len();
+        let empty = " ".repeat(max_len);
+        let cell_len = 2 * max_len + 5;
+        let gutter = self.height.to_string().len();
+        let mut names = PosFormatter::new(self.size());
+
+        write!(f, "{: >gutter$} ", "")?;
+        for x in cols.clone() {
+            write!(f, "| {: ^cell_len$}", ColumnName(x))?;
+        }
+        writeln!(f, "|")?;
+
+        for y in rows {
+            write!(f, "{: >gutter$} ", y + 1)?;
+            for x in cols.clone() {
+                write!(f, "| ")?;
+                let pos = BoardPos::new(x, y);
+                let node = self.node(pos);
+                if let Some(prev) = node.prev() {
+                    write!(f, "{: ^max_len$}", names.name(prev))?;
+                }
+                else {
+                    write!(f, "{}", empty)?;
+                }
+
+                if let Some(next) = node.next() {
+                    write!(f, " -> {: ^max_len$} ", names.name(next))?;
+                }
+                else {
+                    write!(f, "    {} ", empty)?;
+                }
+            }
+            writeln!(f, "|")?;
+        }
+
+        Ok(())
     }
 
     pub fn width(&self) -> Idx {
@@ -130,11 +195,61 @@ impl<'a> MoveGraph<'a> {
         self.height
     }
 
+    pub fn size(&self) -> BoardSize {
+        BoardSize::new(self.width, self.height)
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    pub fn with_wrap(self, wrap: Wrap) -> Self {
+        Self { wrap, ..self }
+    }
+
+    /// The piece moving through the graph, the knight unless set otherwise
+    pub fn leaper(&self) -> Leaper {
+        self.leaper
+    }
+
+    pub fn with_leaper(self, leaper: Leaper) -> Self {
+        Self { leaper, ..self }
+    }
+
+    /// The move of the piece leading from one square to the other, taking wrapped edges into account
+    pub fn move_offset(&self, from: BoardPos, to: BoardPos) -> Option<(IdxMath, IdxMath)> {
+        from.move_offset_to(to, self.size(), self.wrap, self.leaper)
+    }
+
+    pub fn is_move(&self, from: BoardPos, to: BoardPos) -> bool {
+        self.move_offset(from, to).is_some()
+    }
+
     pub fn nodes(&'a self) -> NodesIterator<'a> {
         self.nodes.into_iter()
     }
 
-    pub fn node(&self, pos: BoardPos) -> NodeRef {
+    /// Iterates over the squares in the order the knight visits them
+    pub fn tour(&'a self) -> TourIterator<'a> {
+        TourIterator::new(self)
+    }
+
+    /// Iterates over the separate paths through the graph (e.g. one per knight covering the board together), ordered row by row
+    /// by the square each path starts on. A graph holding a single tour, open or closed, has a single path that is the same as [Self::tour]
+    pub fn paths(&'a self) -> Vec<TourIterator<'a>> {
+        // the starting square of a warnsdorff path is marked by pointing to itself, which is all a path of one square has
+        let starts: Vec<_> = self.nodes()
+            .filter(|node| node.prev().is_none_or(|prev| prev == node.pos()) && (node.next().is_some() || node.prev().is_some()))
+            .map(|node| node.pos())
+            .collect();
+        if starts.is_empty() {
+            return self.tour().start().map(|_| self.tour()).into_iter().collect();
+        }
+
+        starts.into_iter().map(|start| TourIterator::starting_at(self, start)).collect()
+    }
+
+    pub fn node(&self, pos: BoardPos) -> NodeRef<'_> {
         self.nodes.at(pos)
     }
 
@@ -142,7 +257,39 @@ impl<'a> MoveGraph<'a> {
         self.nodes.at_mut(pos)
     }
 
-    pub fn to_board(self) -> Board {
+    /// Makes `to` the square after `from`, setting the links of both squares. Whatever they were linked to before has to be
+    /// unlinked first, or it is left pointing at them
+    pub fn link(&mut self, from: BoardPos, to: BoardPos) {
+        debug_assert!(self.is_move(from, to), "{from} to {to} is not a move of the {}", self.leaper);
+        *self.node_mut(from).next_mut() = Some(to);
+        *self.node_mut(to).prev_mut() = Some(from);
+    }
+
+    /// Removes the link from `from` to the square after it on both squares, returning that square
+    pub fn unlink(&mut self, from: BoardPos) -> Option<BoardPos> {
+        let to = self.node_mut(from).next_mut().take()?;
+        debug_assert_eq!(self.node(to).prev(), Some(from), "{to} isn't linked back to {from}");
+        *self.node_mut(to).prev_mut() = None;
+        Some(to)
+    }
+
+    /// Whether every link is matched by one back, returning the first square (row by row) where one isn't.
+    /// A square linking to itself marks the start of a tour, or its end in a reversed view of one
+    pub fn check_consistency(&self) -> Result<(), BoardPos> {
+        let positions = (0..self.height).flat_map(|row| (0..self.width).map(move |col| BoardPos::new(col, row)));
+        for pos in positions {
+            let node = self.node(pos);
+            let next_ok = node.next().is_none_or(|next| next == pos || self.node(next).prev() == Some(pos));
+            let prev_ok = node.prev().is_none_or(|prev| prev == pos || self.node(prev).next() == Some(pos));
+            if !next_ok || !prev_ok {
+                return Err(pos);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn to_board(&self) -> Board {
         let dead_squares = self.nodes.into_iter().filter_map(|node| {
             let pos = node.pos();
             if node.next().is_none() && node.prev().is_none() {
@@ -154,41 +301,13 @@ impl<'a> MoveGraph<'a> {
         }).collect();
 
         let mut board = Board::new(self.width, self.height, 0).with_dead_squares(dead_squares);
-        let pos = BoardPos::new(0, 0);
-        let mut node = self.node(pos);
-
-        // find first node in the chain (or self.nodes[0].next in case of a cycle)
-        while let Some(prev_pos) = node.prev() {
-            if prev_pos == pos {
-                dprintln!(2 => "Cycle detected at {pos}!");
-                break;
-            }
-
-            dprintln!(3 => "Going back one {} -> {}!", pos, prev_pos);
-            node = self.node(prev_pos);
-        }
-
-        let pos = if let Some(prev_node) = node.prev() {
-            node = self.node(prev_node);
-            pos
-        }
-        else {
-            node.pos()
-        };
-
-        print_move!(1 => node.prev(), node.pos(), node.next());
-        *board.at_mut(pos) = 1;
-
-        let mut i = 2;
-        while let Some(pos) = node.next() {
-            if *board.at_mut(pos) != 0 {
-                break;
-            }
 
+        let mut i = 0;
+        for pos in self.tour() {
+            i += 1;
             *board.at_mut(pos) = i;
-            node = self.node(pos);
+            let node = self.node(pos);
             print_move!(i => node.prev(), node.pos(), node.next());
-            i += 1;
         }
 
         dprintln!(3 => "i = {}", i);
@@ -197,8 +316,8 @@ impl<'a> MoveGraph<'a> {
     }
 
     fn new_empty(width: Idx, height: Idx) -> Self {
-        let mk_node = || Node::new(BoardPos::new(0, 0), Vec::new());
-        Self { width, height, nodes: MoveGraphData::Direct(Matrix2D::new(width, height, mk_node)) }
+        let mk_node = || Node::new(BoardPos::new(0, 0));
+        Self { width, height, wrap: Wrap::None, leaper: Leaper::KNIGHT, nodes: MoveGraphData::Direct(Matrix2D::new(width, height, mk_node)) }
     }
 
     fn ensure_dimension(&self, other: &Self, dim: impl Fn(&Self) -> Idx, name: &str) {
@@ -241,6 +360,8 @@ impl<'a> MoveGraph<'a> {
         Self {
             width: self.width,
             height: self.height,
+            wrap: self.wrap,
+            leaper: self.leaper,
             nodes: match self.nodes {
                 MoveGraphData::Direct(matrix) => MoveGraphData::Direct(matrix.map(|node| node.reverse())),
                 MoveGraphData::Ref(data) => MoveGraphData::ReverseRef(data),
@@ -251,11 +372,11 @@ impl<'a> MoveGraph<'a> {
         }
     }
     
-    fn section_node(&self, start: BoardPos, size: BoardSize, pos: BoardPos) -> NodeRef {
+    fn section_node(&self, start: BoardPos, size: BoardSize, pos: BoardPos) -> NodeRef<'_> {
         if !size.fits(pos) {
             panic!("Position out of bounds: {} > {}", pos, size);
         }
-        self.nodes.at(pos + start)
+        self.nodes.at(pos + start).in_section(start, size)
     }
     
     pub fn insert_section(&mut self, graph: &MoveGraph, offset: BoardPos) {
@@ -277,8 +398,69 @@ impl<'a> MoveGraph<'a> {
         }
     }
 
+    /// Reverses the whole tour. Unlike [MoveGraph::reverse], this moves the marker of a starting square
+    /// that points to itself (as left by warnsdorff) over to the new starting square.
+    pub fn reverse_in_place(&mut self) {
+        let positions = (0..self.height).flat_map(|row| (0..self.width).map(move |col| BoardPos::new(col, row)));
+        let mut marked_start = None;
+        for pos in positions.clone() {
+            let node = self.nodes.at_mut(pos);
+            node.reverse_in_place();
+            // the old marker now claims that the old starting square is followed by itself
+            if node.next() == Some(pos) {
+                *node.next_mut() = None;
+                marked_start = Some(pos);
+            }
+        }
+
+        if let Some(marked_start) = marked_start {
+            let new_start = positions
+                .map(|pos| self.node(pos))
+                .find(|node| node.prev().is_none() && node.next().is_some())
+                .map_or(marked_start, |node| node.pos());
+            *self.node_mut(new_start).prev_mut() = Some(new_start);
+        }
+    }
+
+    /// Turns an open tour into a closed one if possible, either by linking its ends if they are a knight's move apart,
+    /// or by reversing the end of the tour from a square whose successor is a knight's move from the start,
+    /// if that square is itself a knight's move from the end. Returns whether the tour is closed afterwards
+    pub fn try_close(&mut self) -> bool {
+        let tour: Vec<BoardPos> = self.tour().collect();
+        let (Some(&start), Some(&end)) = (tour.first(), tour.last()) else { return false };
+        if tour.len() < 3 {
+            return false;
+        }
+
+        if self.node(end).next() == Some(start) {
+            return true;
+        }
+
+        let pivot = if self.is_move(end, start) {
+            tour.len() - 1
+        } else {
+            // start .. tour[i] -> end .. tour[i + 1] -> start
+            match (0..tour.len() - 1).find(|&i| self.is_move(tour[i], end) && self.is_move(tour[i + 1], start)) {
+                Some(i) => {
+                    for &pos in &tour[i + 1..] {
+                        self.node_mut(pos).reverse_in_place();
+                    }
+
+                    *self.node_mut(tour[i]).next_mut() = Some(end);
+                    *self.node_mut(end).prev_mut() = Some(tour[i]);
+                    i + 1
+                },
+                None => return false,
+            }
+        };
+
+        *self.node_mut(tour[pivot]).next_mut() = Some(start);
+        *self.node_mut(start).prev_mut() = Some(tour[pivot]);
+        true
+    }
+
     pub fn flip(&self) -> Self {
-        let mut res = Self::new(self.height, self.width);
+        let mut res = Self::new(self.height, self.width).with_wrap(self.wrap.flip()).with_leaper(self.leaper);
         for node in self.nodes() {
             let res_node = res.node_mut(node.pos().flip());
             *res_node.next_mut() = node.next().map(|p|p.flip());
@@ -288,3 +470,229 @@ impl<'a> MoveGraph<'a> {
         res
     }
 }
+
+#[test]
+fn test_equality_ignores_representation() {
+    use fixtures::SIX_BY_SIX;
+
+    let direct = fixtures::open(6, 6, &SIX_BY_SIX);
+    let copy = fixtures::open(6, 6, &SIX_BY_SIX);
+    assert_eq!(direct, copy);
+    assert_eq!(direct, MoveGraph::ref_to(&direct));
+    assert_eq!(direct, direct.section(BoardPos::ZERO, BoardSize::new(6, 6)));
+
+    // reversing twice, whether by view or by value, leads back to the original
+    let reversed = direct.clone().reverse();
+    assert_ne!(direct, reversed);
+    assert_eq!(direct, MoveGraph::ref_to(&reversed).reverse());
+    assert_eq!(reversed, MoveGraph::ref_to(&direct).reverse());
+    assert_eq!(reversed, direct.section(BoardPos::ZERO, BoardSize::new(6, 6)).reverse());
+
+    // a section is equal to a graph holding the links within it, translated to its own coordinates
+    let offset = BoardPos::new(2, 1);
+    let section = direct.section(offset, BoardSize::new(3, 3));
+    let mut expected = MoveGraph::new(3, 3);
+    let local = |pos: Option<BoardPos>| pos
+        .filter(|pos| (2..5).contains(&pos.col()) && (1..4).contains(&pos.row()))
+        .map(|pos| pos - offset);
+    for col in 0..3 {
+        for row in 0..3 {
+            let pos = BoardPos::new(col, row);
+            let node = direct.node(pos + offset);
+            *expected.node_mut(pos).next_mut() = local(node.next());
+            *expected.node_mut(pos).prev_mut() = local(node.prev());
+        }
+    }
+    assert_eq!(section, expected);
+}
+
+#[test]
+fn test_equality_detects_differences() {
+    use fixtures::{RING, SIX_BY_SIX};
+
+    let direct = fixtures::open(6, 6, &SIX_BY_SIX);
+    let mut changed = direct.clone();
+    *changed.node_mut(BoardPos::new(3, 3)).next_mut() = None;
+    assert_ne!(direct, changed);
+    assert_ne!(MoveGraph::ref_to(&direct), MoveGraph::ref_to(&changed));
+
+    // same links, different shape
+    assert_ne!(fixtures::open(3, 3, &RING), fixtures::open(4, 3, &RING));
+    assert_ne!(fixtures::closed(3, 3, &RING), fixtures::open(3, 3, &RING));
+    assert_ne!(fixtures::closed(3, 3, &RING), fixtures::closed(3, 3, &RING).with_wrap(Wrap::Both));
+    assert_ne!(MoveGraph::new(2, 3), MoveGraph::new(3, 2));
+    assert_eq!(MoveGraph::new(2, 3), MoveGraph::new(3, 2).flip());
+}
+
+#[test]
+fn test_link_and_unlink() {
+    use fixtures::RING;
+
+    let mut graph = fixtures::open(3, 3, &RING[..4]);
+    let (last, next) = (BoardPos::from(RING[3]), BoardPos::from(RING[4]));
+    graph.link(last, next);
+    assert_eq!(graph, fixtures::open(3, 3, &RING[..5]));
+    assert_eq!(graph.check_consistency(), Ok(()));
+
+    assert_eq!(graph.unlink(last), Some(next));
+    assert_eq!(graph.unlink(last), None);
+    assert_eq!(graph, fixtures::open(3, 3, &RING[..4]));
+    assert_eq!(graph.check_consistency(), Ok(()));
+
+    // a square linking to itself marks the start, which a reversed view turns into the end
+    let mut marked = fixtures::open(3, 3, &RING);
+    *marked.node_mut(BoardPos::ZERO).prev_mut() = Some(BoardPos::ZERO);
+    assert_eq!(marked.check_consistency(), Ok(()));
+    assert_eq!(marked.reverse().check_consistency(), Ok(()));
+    assert_eq!(fixtures::closed(3, 3, &RING).check_consistency(), Ok(()));
+
+    // a link set on one side only
+    let mut graph = fixtures::open(3, 3, &RING[..4]);
+    *graph.node_mut(next).prev_mut() = Some(last);
+    assert_eq!(graph.check_consistency(), Err(next));
+    *graph.node_mut(next).prev_mut() = None;
+    *graph.node_mut(BoardPos::from(RING[1])).next_mut() = None;
+    assert_eq!(graph.check_consistency(), Err(BoardPos::from(RING[2])));
+}
+
+#[test]
+#[should_panic(expected = "is not a move of the knight")]
+#[cfg(debug_assertions)]
+fn test_link_checks_the_move() {
+    MoveGraph::new(3, 3).link(BoardPos::ZERO, BoardPos::new(1, 1));
+}
+
+#[test]
+fn test_board_equality() {
+    let board = fixtures::open(6, 6, &fixtures::SIX_BY_SIX).to_board();
+    assert_eq!(board, fixtures::open(6, 6, &fixtures::SIX_BY_SIX).to_board());
+    assert_ne!(board, fixtures::open(6, 6, &fixtures::SIX_BY_SIX).reverse().to_board());
+
+    let ring = fixtures::open(3, 3, &fixtures::RING).to_board();
+    // the unvisited center square is dead
+    assert_ne!(ring, fixtures::open(3, 3, &fixtures::RING).to_board().with_dead_squares(Default::default()));
+    assert_ne!(ring, fixtures::open(3, 4, &fixtures::RING).to_board());
+}
+
+#[test]
+fn test_debug_table() {
+    let mut graph = MoveGraph::new(5, 4);
+    for (from, to) in [((0, 0), (2, 1)), ((2, 1), (4, 0)), ((4, 0), (3, 2))] {
+        *graph.node_mut(BoardPos::new(from.0, from.1)).next_mut() = Some(BoardPos::new(to.0, to.1));
+        *graph.node_mut(BoardPos::new(to.0, to.1)).prev_mut() = Some(BoardPos::new(from.0, from.1));
+    }
+
+    assert_eq!(format!("{graph:?}"), concat!(
+        "  |     A    |     B    |     C    |     D    |     E    |\n",
+        "1 |    -> C2 |          |          |          | C2 -> D3 |\n",
+        "2 |          |          | A1 -> E1 |          |          |\n",
+        "3 |          |          |          | E1       |          |\n",
+        "4 |          |          |          |          |          |\n",
+    ));
+
+    // the section is cut off at the bottom edge, but keeps the width of the cells of the whole board
+    assert_eq!(graph.dump_around(BoardPos::new(3, 2), 1), concat!(
+        "  |     C    |     D    |     E    |\n",
+        "2 | A1 -> E1 |          |          |\n",
+        "3 |          | E1       |          |\n",
+        "4 |          |          |          |\n",
+    ));
+}
+
+#[test]
+fn test_try_close() {
+    use crate::validation;
+    use fixtures::{RING, SIX_BY_SIX};
+
+    // the ends are a knight's move apart
+    let mut graph = fixtures::open(3, 3, &RING);
+    assert!(graph.try_close());
+    assert_eq!(graph, fixtures::closed(3, 3, &RING));
+    assert!(graph.try_close());
+    assert_eq!(graph, fixtures::closed(3, 3, &RING));
+
+    // A3 is a knight's move from the end and followed by B3, which is a knight's move from the start
+    let mut graph = fixtures::open(6, 6, &SIX_BY_SIX);
+    assert!(graph.try_close());
+    let summary = validation::validate(&graph).unwrap();
+    assert!(summary.closed);
+    assert_eq!(summary.squares, 36);
+    assert_eq!(graph.node(BoardPos::new(0, 4)).next(), Some(BoardPos::new(2, 3)));
+    assert_eq!(graph.node(BoardPos::new(1, 2)).next(), Some(BoardPos::new(0, 0)));
+
+    // neither works, the tour is left alone
+    let open = fixtures::open(3, 3, &RING[..5]);
+    let mut graph = open.clone();
+    assert!(!graph.try_close());
+    assert_eq!(graph, open);
+}
+
+#[test]
+fn test_paths() {
+    use fixtures::{RING, SIX_BY_SIX};
+
+    let collect = |graph: &MoveGraph| graph.paths().into_iter().map(|path| path.collect::<Vec<_>>()).collect::<Vec<_>>();
+    let squares = |tour: &[(Idx, Idx)]| tour.iter().copied().map(BoardPos::from).collect::<Vec<_>>();
+
+    assert_eq!(collect(&fixtures::open(6, 6, &SIX_BY_SIX)), [squares(&SIX_BY_SIX)]);
+    assert_eq!(collect(&fixtures::closed(3, 3, &RING)), [squares(&RING)]);
+    assert!(collect(&MoveGraph::new(3, 3)).is_empty());
+
+    // ordered by their first square, a single square marked as the start is a path of its own
+    let mut graph = fixtures::paths(3, 3, &[&RING[4..], &RING[..4]]);
+    *graph.node_mut(BoardPos::new(1, 1)).prev_mut() = Some(BoardPos::new(1, 1));
+    assert_eq!(collect(&graph), [squares(&RING[..4]), squares(&[(1, 1)]), squares(&RING[4..])]);
+}
+
+#[test]
+fn test_section_nodes() {
+    let parent = MoveGraph::new(10, 10);
+    let positions = |graph: &MoveGraph| graph.nodes().map(|node| node.pos()).collect::<Vec<_>>();
+    // sections count from their own top left corner
+    let expected = |size: BoardSize| (0..size.height())
+        .flat_map(|row| (0..size.width()).map(move |col| BoardPos::new(col, row)))
+        .collect::<Vec<_>>();
+
+    // sections at the right and bottom edges, and sections of sections
+    for (start, size) in [(BoardPos::new(3, 2), BoardSize::new(6, 4)), (BoardPos::new(4, 6), BoardSize::new(6, 4)), (BoardPos::new(9, 0), BoardSize::new(1, 10))] {
+        let section = parent.section(start, size);
+        assert_eq!(positions(&section), expected(size), "{size} at {start}");
+        assert_eq!(positions(&section.section(BoardPos::ZERO, size)), expected(size), "{size} at {start}");
+        assert_eq!(positions(&section.section(BoardPos::new(0, 1), BoardSize::new(1, 2))), expected(BoardSize::new(1, 2)));
+    }
+}
+
+#[test]
+fn test_section_coordinates() {
+    let args = crate::args::InputArgs { use_warnsdorff: false, warnsdorff: None, board_size: Some(BoardSize::new(10, 10)), solve_order: Default::default() };
+    let (_, graph) = crate::divide_and_conquer::solve(args).unwrap();
+    let (start, size) = (BoardPos::new(3, 2), BoardSize::new(5, 6));
+    let inside = |pos: BoardPos| (3..8).contains(&pos.col()) && (2..8).contains(&pos.row());
+    for section in [graph.section(start, size), graph.section(BoardPos::new(1, 1), BoardSize::new(8, 8)).section(BoardPos::new(2, 1), size)] {
+        // the nodes are where they are looked up, by the iterators as by [MoveGraph::node]
+        for (idx, node) in section.nodes().enumerate() {
+            let pos = BoardPos::new(idx as Idx % size.width(), idx as Idx / size.width());
+            assert_eq!(node.pos(), pos);
+            assert_eq!(section.node(pos).pos(), pos);
+
+            // the links of the whole board, translated, as long as they stay within the section
+            let outer = graph.node(pos + start);
+            assert_eq!(node.next(), outer.next().filter(|&next| inside(next)).map(|next| next - start), "{pos}");
+            assert_eq!(node.prev(), outer.prev().filter(|&prev| inside(prev)).map(|prev| prev - start), "{pos}");
+            assert_eq!(section.node(pos).next(), node.next());
+        }
+
+        // every link within the section is a move within it, and is linked back
+        for node in section.nodes() {
+            if let Some(next) = node.next() {
+                assert!(section.is_move(node.pos(), next), "{} -> {next}", node.pos());
+                assert_eq!(section.node(next).prev(), Some(node.pos()));
+            }
+        }
+
+        let reversed = section.clone().reverse();
+        for node in section.nodes() {
+            assert_eq!(reversed.node(node.pos()).next(), node.prev());
+        }
+    }
+}
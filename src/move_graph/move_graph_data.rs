@@ -20,9 +20,9 @@ impl<'a> MoveGraphData<'a> {
         }
     }
 
-    pub fn at(&self, pos: BoardPos) -> NodeRef {
+    pub fn at(&self, pos: BoardPos) -> NodeRef<'_> {
         match self {
-            Self::Direct(matrix) => NodeRef::Direct(matrix.at(pos)),
+            Self::Direct(matrix) => NodeRef::direct(matrix.at(pos)),
             Self::Ref(graph) => graph.node(pos),
             Self::ReverseRef(graph) => graph.node(pos).reverse(),
             Self::Section(graph, start, size) => graph.section_node(*start, *size, pos),
@@ -34,26 +34,24 @@ impl<'a> MoveGraphData<'a> {
         match self {
             Self::Direct(matrix) => matrix.iter_section(start, size).into(),
             Self::Ref(graph) => graph.nodes.iter_section(start, size),
-            Self::ReverseRef(graph) => graph.nodes.iter_section(start, size).reverse(),
+            Self::ReverseRef(graph) => graph.nodes.iter_section(start, size).reversed_links(),
             Self::Section(graph, rel_to, section_size) => {
-                if section_size > &size {
-                    graph.nodes.iter_section(start + *rel_to, size)
-                } else {
-                    panic!("Section size is smaller than requested size");
-                }
-
+                assert!(contains(*section_size, start, size), "{size} at {start} doesn't fit into the {section_size} section");
+                graph.nodes.iter_section(start + *rel_to, size).in_section(*rel_to, *section_size)
             },
-            Self::ReverseSection(graph, rel_to, section_size) =>{
-                if section_size > &size {
-                    graph.nodes.iter_section(start + *rel_to, size).reverse()
-                } else {
-                    panic!("Section size is smaller than requested size");
-                }
+            Self::ReverseSection(graph, rel_to, section_size) => {
+                assert!(contains(*section_size, start, size), "{size} at {start} doesn't fit into the {section_size} section");
+                graph.nodes.iter_section(start + *rel_to, size).in_section(*rel_to, *section_size).reversed_links()
             },
         }
     }
 }
 
+/// Whether the part of the given size at `start` lies within `outer`
+fn contains(outer: BoardSize, start: BoardPos, size: BoardSize) -> bool {
+    start.col() + size.width() <= outer.width() && start.row() + size.height() <= outer.height()
+}
+
 impl<'a> IntoIterator for &'a MoveGraphData<'a> {
     type Item = NodeRef<'a>;
     type IntoIter = NodesIterator<'a>;
@@ -62,9 +60,9 @@ impl<'a> IntoIterator for &'a MoveGraphData<'a> {
         match self {
             MoveGraphData::Direct(matrix) => matrix.into_iter().into(),
             MoveGraphData::Ref(graph) => graph.nodes.into_iter(),
-            MoveGraphData::ReverseRef(graph) => graph.nodes.into_iter().reverse(),
-            MoveGraphData::Section(graph, start, size) => graph.nodes.iter_section(*start, *size),
-            MoveGraphData::ReverseSection(graph, start, size) => graph.nodes.iter_section(*start, *size).reverse(),
+            MoveGraphData::ReverseRef(graph) => graph.nodes.into_iter().reversed_links(),
+            MoveGraphData::Section(graph, start, size) => graph.nodes.iter_section(*start, *size).in_section(*start, *size),
+            MoveGraphData::ReverseSection(graph, start, size) => graph.nodes.iter_section(*start, *size).in_section(*start, *size).reversed_links(),
         }
     }
 }
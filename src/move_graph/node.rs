@@ -1,10 +1,8 @@
-use crate::board_pos::BoardPos;
+use crate::{args::Wrap, board_pos::{self, BoardPos}, board_size::BoardSize, leaper::Leaper};
 
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Node {
     pos: BoardPos,
-    edges: Vec<BoardPos>,
     next: Option<BoardPos>,
     prev: Option<BoardPos>,
 }
@@ -30,14 +28,15 @@ impl Node {
         &mut self.prev
     }
 
-    pub fn edges(&self) -> &[BoardPos] {
-        &self.edges
+    /// The squares reachable from this node with a single move of the piece on a board of the given size.
+    /// Computed on demand so that nodes don't need to carry any heap allocations around.
+    pub fn edges_on(&self, size: BoardSize, wrap: Wrap, leaper: Leaper) -> impl Iterator<Item = BoardPos> {
+        board_pos::leaper_moves(self.pos, size, wrap, leaper)
     }
 
     pub fn reverse(&self) -> Self {
         Self {
             pos: self.pos,
-            edges: self.edges.clone(),
             next: self.prev,
             prev: self.next,
         }
@@ -47,10 +46,9 @@ impl Node {
         std::mem::swap(&mut self.next, &mut self.prev);
     }
 
-    pub fn new(pos: BoardPos, edges: Vec<BoardPos>) -> Self {
+    pub fn new(pos: BoardPos) -> Self {
         Node {
             pos,
-            edges,
             next: None,
             prev: None,
         }
@@ -59,9 +57,40 @@ impl Node {
     pub fn clone_with_offset(&self, offset: BoardPos) -> Self {
         Node {
             pos: self.pos + offset,
-            edges: self.edges.iter().map(|pos| *pos + offset).collect(),
             next: self.next.map(|pos| pos + offset),
             prev: self.prev.map(|pos| pos + offset),
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_edges_on() {
+    let size = BoardSize::new(8, 8);
+    let edges = |col, row| Node::new(BoardPos::new(col, row)).edges_on(size, Wrap::None, Leaper::KNIGHT).collect::<Vec<_>>();
+
+    assert_eq!(edges(0, 0), vec![BoardPos::new(2, 1), BoardPos::new(1, 2)]);
+    assert_eq!(edges(7, 7), vec![BoardPos::new(5, 6), BoardPos::new(6, 5)]);
+    assert_eq!(edges(3, 3).len(), 8);
+    assert!(edges(3, 3).iter().all(|pos| pos.is_knight_move(BoardPos::new(3, 3))));
+
+    let camel: Vec<_> = Node::new(BoardPos::ZERO).edges_on(size, Wrap::None, Leaper::CAMEL).collect();
+    assert_eq!(camel, [BoardPos::new(3, 1), BoardPos::new(1, 3)]);
+
+    // non-square boards must respect both dimensions
+    let size = BoardSize::new(3, 7);
+    let edges: Vec<_> = Node::new(BoardPos::new(2, 6)).edges_on(size, Wrap::None, Leaper::KNIGHT).collect();
+    assert_eq!(edges, vec![BoardPos::new(0, 5), BoardPos::new(1, 4)]);
+
+    // on a cylinder, moves off the right edge come back in on the left edge
+    let edges: Vec<_> = Node::new(BoardPos::new(2, 6)).edges_on(size, Wrap::Horizontal, Leaper::KNIGHT).collect();
+    assert_eq!(edges, vec![BoardPos::new(1, 5), BoardPos::new(0, 5), BoardPos::new(0, 4), BoardPos::new(1, 4)]);
+}
+
+#[test]
+fn test_node_has_no_heap_data() {
+    // nodes are stored by value in the move graph, so being Copy guarantees that allocating a graph
+    // doesn't incur a per-node heap allocation on top of the matrix itself
+    fn assert_copy<T: Copy>() {}
+    assert_copy::<Node>();
+    assert!(std::mem::size_of::<Node>() <= 4 * std::mem::size_of::<BoardPos>());
+}
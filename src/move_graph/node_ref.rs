@@ -1,46 +1,64 @@
-use crate::board_pos::BoardPos;
+use crate::{board_pos::BoardPos, board_size::BoardSize};
 
 use super::Node;
 
 
-pub enum NodeRef<'a> {
-    Direct(&'a Node),
-    Reverse(&'a Node),
+/// A node as a view of the graph holding it sees it: with swapped links if the view is reversed, and in the coordinates
+/// of the section if the view is one. Links to squares outside of the section are left out
+#[derive(Clone, Copy)]
+pub struct NodeRef<'a> {
+    node: &'a Node,
+    is_reversed: bool,
+    // the top left corner of the section in the coordinates of the graph holding the node, and the size of the section
+    section: Option<(BoardPos, BoardSize)>,
 }
 
 impl<'a> NodeRef<'a> {
+    pub fn direct(node: &'a Node) -> Self {
+        Self { node, is_reversed: false, section: None }
+    }
+
     pub fn pos(&self) -> BoardPos {
-        match self {
-            Self::Direct(node) => node.pos(),
-            Self::Reverse(node) => node.pos(),
+        match self.section {
+            Some((start, _)) => self.node.pos() - start,
+            None => self.node.pos(),
         }
     }
 
     pub fn next(&self) -> Option<BoardPos> {
-        match self {
-            Self::Direct(node) => node.next(),
-            Self::Reverse(node) => node.prev(),
-        }
+        let next = if self.is_reversed { self.node.prev() } else { self.node.next() };
+        next.and_then(|pos| self.local(pos))
     }
 
     pub fn prev(&self) -> Option<BoardPos> {
-        match self {
-            Self::Direct(node) => node.prev(),
-            Self::Reverse(node) => node.next(),
-        }
+        let prev = if self.is_reversed { self.node.next() } else { self.node.prev() };
+        prev.and_then(|pos| self.local(pos))
     }
 
     pub fn reverse(self) -> Self {
-        match self {
-            Self::Direct(node) => Self::Reverse(node),
-            Self::Reverse(node) => Self::Direct(node),
-        }
+        Self { is_reversed: !self.is_reversed, ..self }
+    }
+
+    /// The node as seen through a section of the given size at `start` of the view it was taken from
+    pub fn in_section(self, start: BoardPos, size: BoardSize) -> Self {
+        let outer = self.section.map_or(BoardPos::ZERO, |(outer, _)| outer);
+        Self { section: Some((outer + start, size)), ..self }
     }
 
     pub fn clone_with_offset(&self, offset: BoardPos) -> Node {
-        match self {
-            Self::Direct(node) => node.clone_with_offset(offset),
-            Self::Reverse(node) => node.clone_with_offset(offset).reverse(),
-        }
+        let mut node = Node::new(self.pos() + offset);
+        *node.next_mut() = self.next().map(|pos| pos + offset);
+        *node.prev_mut() = self.prev().map(|pos| pos + offset);
+        node
+    }
+
+    /// The square in the coordinates of the section, if it lies within it
+    fn local(&self, pos: BoardPos) -> Option<BoardPos> {
+        let Some((start, size)) = self.section else {
+            return Some(pos);
+        };
+
+        let local = BoardPos::new(pos.col().checked_sub(start.col())?, pos.row().checked_sub(start.row())?);
+        size.fits(local).then_some(local)
     }
-}
\ No newline at end of file
+}
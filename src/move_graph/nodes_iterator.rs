@@ -1,22 +1,40 @@
-use crate::board::matrix2d::Matrix2DIterator;
+use crate::{board::matrix2d::Matrix2DIterator, board_pos::BoardPos, board_size::BoardSize};
 
 use super::{Node, NodeRef};
 
 
+/// Iterates over the nodes of a graph row by row, left to right. Use `rev()` to go the other way around
 pub struct NodesIterator<'a> {
     iter: Matrix2DIterator<'a, Node>,
     is_reversed: bool,
+    section: Option<(BoardPos, BoardSize)>,
 }
 
 impl<'a> NodesIterator<'a> {
-    pub fn reverse(self) -> Self {
-        Self { iter: self.iter, is_reversed: !self.is_reversed }
+    /// Swaps the links of the nodes, as in a graph whose tour runs the other way. The squares are visited in the same order
+    pub fn reversed_links(self) -> Self {
+        Self { is_reversed: !self.is_reversed, ..self }
+    }
+
+    /// Translates the nodes into the coordinates of the section of the given size at `start`, see [NodeRef::in_section]
+    pub fn in_section(self, start: BoardPos, size: BoardSize) -> Self {
+        let outer = self.section.map_or(BoardPos::ZERO, |(outer, _)| outer);
+        Self { section: Some((outer + start, size)), ..self }
+    }
+
+    fn node_ref(&self, node: &'a Node) -> NodeRef<'a> {
+        let node = NodeRef::direct(node);
+        let node = if self.is_reversed { node.reverse() } else { node };
+        match self.section {
+            Some((start, size)) => node.in_section(start, size),
+            None => node,
+        }
     }
 }
 
 impl<'a> From<Matrix2DIterator<'a, Node>> for NodesIterator<'a> {
     fn from(iter: Matrix2DIterator<'a, Node>) -> Self {
-        Self { iter, is_reversed: false }
+        Self { iter, is_reversed: false, section: None }
     }
 }
 
@@ -25,11 +43,54 @@ impl<'a> Iterator for NodesIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let result = self.iter.next()?;
-        if self.is_reversed {
-            Some(NodeRef::Reverse(result))
-        }
-        else {
-            Some(NodeRef::Direct(result))
-        }
+        Some(self.node_ref(result))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for NodesIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.iter.next_back()?;
+        Some(self.node_ref(result))
+    }
+}
+
+impl<'a> ExactSizeIterator for NodesIterator<'a> {}
+#[test]
+fn test_order_in_every_mode() {
+    use crate::{board_pos::BoardPos, board_size::BoardSize, move_graph::MoveGraph};
+
+    // A1 -> C1 -> B2 -> A2 is no tour, but the links only need to be there to be swapped
+    let graph = MoveGraph::from_tour(3, 2, [BoardPos::new(0, 0), BoardPos::new(2, 0), BoardPos::new(1, 1), BoardPos::new(0, 1)]);
+    let reversed = MoveGraph::ref_to(&graph).reverse();
+    let section = graph.section(BoardPos::new(1, 0), BoardSize::new(2, 2));
+    let reversed_section = graph.section(BoardPos::new(1, 0), BoardSize::new(2, 2)).reverse();
+    let pos = |col, row| BoardPos::new(col, row);
+    let positions = |iter: &mut dyn Iterator<Item = NodeRef>| iter.map(|node| node.pos()).collect::<Vec<_>>();
+
+    let all = [pos(0, 0), pos(1, 0), pos(2, 0), pos(0, 1), pos(1, 1), pos(2, 1)];
+    // sections count from their own top left corner
+    let right = [pos(0, 0), pos(1, 0), pos(0, 1), pos(1, 1)];
+    for (graph, expected) in [(&graph, &all[..]), (&MoveGraph::ref_to(&graph), &all), (&reversed, &all), (&section, &right), (&reversed_section, &right)] {
+        assert_eq!(positions(&mut graph.nodes()), expected);
+        assert_eq!(positions(&mut graph.nodes().rev()), expected.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(graph.nodes().len(), expected.len());
     }
-}
\ No newline at end of file
+
+    // reversing swaps the links, whichever end the squares are taken from
+    let next = |graph: &MoveGraph| graph.nodes().map(|node| node.next()).collect::<Vec<_>>();
+    assert_eq!(next(&graph), [Some(pos(2, 0)), None, Some(pos(1, 1)), None, Some(pos(0, 1)), None]);
+    assert_eq!(next(&reversed), [None, None, Some(pos(0, 0)), Some(pos(1, 1)), Some(pos(2, 0)), None]);
+    assert_eq!(reversed.nodes().next_back().unwrap().next(), None);
+    // links leaving the section are left out
+    assert_eq!(next(&section), [None, Some(pos(0, 1)), None, None]);
+    assert_eq!(next(&reversed_section), [None, None, Some(pos(1, 0)), None]);
+
+    // meeting in the middle
+    let mut nodes = graph.nodes();
+    assert_eq!((nodes.next().unwrap().pos(), nodes.next_back().unwrap().pos()), (pos(0, 0), pos(2, 1)));
+    assert_eq!(positions(&mut nodes), [pos(1, 0), pos(2, 0), pos(0, 1), pos(1, 1)]);
+}
@@ -0,0 +1,99 @@
+use crate::{board::matrix2d::Matrix2D, board_pos::BoardPos, random::Random};
+
+use super::MoveGraph;
+
+impl MoveGraph<'_> {
+    /// Makes the tour less regular by trying the given number of times to swap two of its moves for two others:
+    /// if a square is a move of the piece away from another one and so are their successors, the tour can jump between them
+    /// and walk the part in between backwards. The tour keeps its ends, and stays closed if it was closed
+    pub fn shuffle(&mut self, iterations: usize, seed: u64) {
+        let mut order: Vec<BoardPos> = self.tour().collect();
+        let len = order.len();
+        if len < 4 {
+            return;
+        }
+
+        let closed = self.node(order[len - 1]).next() == Some(order[0]);
+        let mut index = Matrix2D::new(self.width, self.height, || usize::MAX);
+        for (idx, pos) in order.iter().enumerate() {
+            *index.at_mut(*pos) = idx;
+        }
+
+        let (size, wrap, leaper) = (self.size(), self.wrap, self.leaper);
+        let mut random = Random::new(seed);
+        let mut candidates = Vec::with_capacity(8);
+        for _ in 0..iterations {
+            let first = random.below(len - 1);
+            candidates.clear();
+            for (col, row) in leaper.moves() {
+                let Some(other) = order[first].try_translate_wrapping(col, row, size, wrap) else { continue };
+                let second = *index.at(other);
+                if second == usize::MAX {
+                    continue;
+                }
+
+                // the moves leaving lo and hi are replaced by lo -> hi and lo + 1 -> hi + 1
+                let (lo, hi) = if first < second { (first, second) } else { (second, first) };
+                if hi <= lo + 1 || (hi + 1 == len && !closed) {
+                    continue;
+                }
+
+                if self.is_move(order[lo + 1], order[(hi + 1) % len]) {
+                    candidates.push((lo, hi));
+                }
+            }
+
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let (lo, hi) = candidates[random.below(candidates.len())];
+            order[lo + 1..=hi].reverse();
+            for (pos, idx) in order[lo + 1..=hi].iter().zip(lo + 1..) {
+                *index.at_mut(*pos) = idx;
+            }
+        }
+
+        // the first square keeps its link back, which marks it as the start of open tours
+        for idx in 0..len - 1 {
+            *self.node_mut(order[idx]).next_mut() = Some(order[idx + 1]);
+            *self.node_mut(order[idx + 1]).prev_mut() = Some(order[idx]);
+        }
+        if closed {
+            *self.node_mut(order[len - 1]).next_mut() = Some(order[0]);
+            *self.node_mut(order[0]).prev_mut() = Some(order[len - 1]);
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::{board_size::BoardSize, validation};
+
+#[test]
+fn test_shuffle_keeps_tour_valid() {
+    let solve = |size: BoardSize| {
+        let args = crate::args::InputArgs { use_warnsdorff: false, warnsdorff: None, board_size: Some(size), solve_order: Default::default() };
+        crate::divide_and_conquer::solve(args).unwrap().1
+    };
+
+    for size in [BoardSize::new(20, 20), BoardSize::new(21, 21)] {
+        let solved = solve(size);
+        let before = validation::validate(&solved).unwrap();
+
+        let mut shuffled = solved.clone();
+        shuffled.shuffle(10_000, 42);
+        let after = validation::validate(&shuffled).unwrap();
+        assert_eq!((after.squares, after.closed), (before.squares, before.closed), "{size}");
+        if !before.closed {
+            assert_eq!((after.start, after.end), (before.start, before.end), "{size}");
+        }
+
+        let moved = solved.tour().zip(shuffled.tour()).filter(|(a, b)| a != b).count();
+        assert!(moved > before.squares / 2, "{size}: only {moved} squares moved");
+
+        // the same seed gives the same tour
+        let mut again = solved.clone();
+        again.shuffle(10_000, 42);
+        assert_eq!(again, shuffled);
+    }
+}
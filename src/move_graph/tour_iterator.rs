@@ -0,0 +1,64 @@
+use crate::board_pos::BoardPos;
+
+use super::MoveGraph;
+
+/// Iterates over the squares of a tour in the order they are visited, starting at the first square of the tour.
+/// For closed tours, iteration starts at the first visited square in row-major order and stops before returning to it.
+pub struct TourIterator<'a> {
+    graph: &'a MoveGraph<'a>,
+    start: Option<BoardPos>,
+    current: Option<BoardPos>,
+    remaining: usize,
+}
+
+impl<'a> TourIterator<'a> {
+    pub fn new(graph: &'a MoveGraph<'a>) -> Self {
+        let remaining = graph.width() as usize * graph.height() as usize;
+        let start = Self::find_start(graph, remaining);
+        Self { graph, start, current: start, remaining }
+    }
+
+    /// Iterates over the path through the graph that starts at the given square
+    pub fn starting_at(graph: &'a MoveGraph<'a>, start: BoardPos) -> Self {
+        let remaining = graph.width() as usize * graph.height() as usize;
+        Self { graph, start: Some(start), current: Some(start), remaining }
+    }
+
+    /// The first square of the tour, if any square has been visited at all
+    pub fn start(&self) -> Option<BoardPos> {
+        self.start
+    }
+
+    fn find_start(graph: &MoveGraph, max_steps: usize) -> Option<BoardPos> {
+        let first = graph.nodes().find(|node| node.next().is_some() || node.prev().is_some())?.pos();
+
+        // walk backwards until we either find the beginning of the chain or end up where we started (closed tour).
+        // the starting square of a warnsdorff tour is marked by pointing to itself
+        let mut pos = first;
+        for _ in 0..max_steps {
+            match graph.node(pos).prev() {
+                Some(prev) if prev == first => return Some(first),
+                Some(prev) if prev != pos => pos = prev,
+                _ => return Some(pos),
+            }
+        }
+
+        Some(pos)
+    }
+}
+
+impl<'a> Iterator for TourIterator<'a> {
+    type Item = BoardPos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let current = self.current?;
+        self.remaining -= 1;
+        self.current = self.graph.node(current).next().filter(|next| Some(*next) != self.start && *next != current);
+
+        Some(current)
+    }
+}
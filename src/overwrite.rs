@@ -0,0 +1,101 @@
+use std::{fs::{File, OpenOptions}, io, path::{Path, PathBuf}};
+
+use crate::args::OverwritePolicy;
+
+/// Whether the board for an output file is worth solving, decided before solving it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Preflight {
+    Write,
+    /// The file exists and --overwrite-policy skip leaves it alone
+    Skip,
+}
+
+/// Checks the output file against the policy before anything is solved for it, so that no time is spent
+/// on a board that can't be written
+pub fn preflight(path: &Path, policy: OverwritePolicy) -> Result<Preflight, String> {
+    if !path.exists() {
+        return Ok(Preflight::Write);
+    }
+
+    match policy {
+        OverwritePolicy::Clobber | OverwritePolicy::Backup => Ok(Preflight::Write),
+        OverwritePolicy::Error => Err(format!("{} already exists, use --overwrite-policy to replace or keep it", path.display())),
+        OverwritePolicy::Skip => Ok(Preflight::Skip),
+    }
+}
+
+/// Makes sure the output file can be created before anything is solved for it, by creating it and removing it again.
+/// An existing file is opened without truncating it, so that it is left as it is if solving fails
+pub fn check_writable(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::IsADirectory, "it is a directory"));
+    }
+
+    if path.exists() {
+        OpenOptions::new().append(true).open(path).map(drop)
+    } else {
+        File::create_new(path)?;
+        std::fs::remove_file(path)
+    }
+}
+
+/// Creates the output file according to the policy. A file that appeared since the [preflight] is only
+/// replaced where the policy allows it
+pub fn create(path: &Path, policy: OverwritePolicy) -> io::Result<File> {
+    match policy {
+        OverwritePolicy::Clobber => File::create(path),
+        OverwritePolicy::Error | OverwritePolicy::Skip => File::create_new(path),
+        OverwritePolicy::Backup => {
+            if path.exists() {
+                std::fs::rename(path, backup_path(path))?;
+            }
+            File::create(path)
+        },
+    }
+}
+
+/// The first of "<file>.bak-1", "<file>.bak-2" and so on that doesn't exist yet
+fn backup_path(path: &Path) -> PathBuf {
+    (1..)
+        .map(|n| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(format!(".bak-{n}"));
+            PathBuf::from(name)
+        })
+        .find(|backup| !backup.exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+use crate::temp_dir::TempDir;
+
+#[test]
+fn test_policies() {
+    let dir = TempDir::new("overwrite");
+    let path = dir.join("tour.txt");
+
+    // nothing to protect yet
+    for policy in [OverwritePolicy::Clobber, OverwritePolicy::Error, OverwritePolicy::Backup, OverwritePolicy::Skip] {
+        assert_eq!(preflight(&path, policy), Ok(Preflight::Write));
+    }
+
+    check_writable(&path).unwrap();
+    assert!(!path.exists());
+    assert!(check_writable(&dir.join("missing").join("tour.txt")).is_err());
+    assert!(check_writable(&dir).is_err());
+
+    std::fs::write(&path, "old").unwrap();
+    check_writable(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+    assert_eq!(preflight(&path, OverwritePolicy::Clobber), Ok(Preflight::Write));
+    assert_eq!(preflight(&path, OverwritePolicy::Skip), Ok(Preflight::Skip));
+    assert!(preflight(&path, OverwritePolicy::Error).unwrap_err().contains("already exists"));
+    assert!(create(&path, OverwritePolicy::Error).is_err());
+
+    // backups count up, keeping every earlier one
+    create(&path, OverwritePolicy::Backup).unwrap();
+    std::fs::write(&path, "newer").unwrap();
+    create(&path, OverwritePolicy::Backup).unwrap();
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+    assert_eq!((read("tour.txt.bak-1").as_str(), read("tour.txt.bak-2").as_str(), read("tour.txt").as_str()), ("old", "newer", ""));
+}
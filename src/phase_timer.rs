@@ -0,0 +1,101 @@
+use std::{fmt::Display, time::{Duration, Instant}};
+
+use crate::context::SolverContext;
+
+/// Starts collecting timings in the current [SolverContext], discarding anything collected so far
+pub fn enable() {
+    SolverContext::with_current(|context| *context.timings().lock().unwrap() = Some(PhaseTimer::default()));
+}
+
+/// Hands out the timings collected since the last call and starts over, if timings are enabled
+pub fn take() -> Option<PhaseTimer> {
+    SolverContext::with_current(|context| context.timings().lock().unwrap().as_mut().map(std::mem::take))
+}
+
+pub fn record(phase: impl Into<String>, duration: Duration) {
+    SolverContext::with_current(|context| {
+        if let Some(ref mut timer) = *context.timings().lock().unwrap() {
+            timer.record(phase.into(), duration);
+        }
+    });
+}
+
+/// Runs `f`, recording how long it took under the given phase
+pub fn time<T>(phase: impl Into<String>, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let res = f();
+    record(phase, start.elapsed());
+    res
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseStats {
+    pub count: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+/// Aggregated durations per phase, in the order the phases were first recorded
+#[derive(Debug, Default, Clone)]
+pub struct PhaseTimer {
+    phases: Vec<(String, PhaseStats)>,
+}
+
+impl PhaseTimer {
+    pub fn record(&mut self, phase: String, duration: Duration) {
+        let idx = match self.phases.iter().position(|(name, _)| *name == phase) {
+            Some(idx) => idx,
+            None => {
+                self.phases.push((phase, PhaseStats::default()));
+                self.phases.len() - 1
+            },
+        };
+
+        let stats = &mut self.phases[idx].1;
+        stats.count += 1;
+        stats.total += duration;
+        stats.max = stats.max.max(duration);
+    }
+
+    pub fn phases(&self) -> impl Iterator<Item = (&str, PhaseStats)> {
+        self.phases.iter().map(|(name, stats)| (name.as_str(), *stats))
+    }
+}
+
+impl Display for PhaseTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name_width = self.phases().map(|(name, _)| name.chars().count()).max().unwrap_or(0).max(5);
+        let ms = |duration: Duration| format!("{:.3}", duration.as_secs_f64() * 1000.0);
+
+        // no dashes in the rule, as the table is also embedded in SVG comments
+        writeln!(f, "{:<name_width$} | {:>6} | {:>12} | {:>12}", "Phase", "Count", "Total (ms)", "Max (ms)")?;
+        writeln!(f, "{:=<name_width$}=+={:=>6}=+={:=>12}=+={:=>12}", "", "", "", "")?;
+        for (name, stats) in self.phases() {
+            writeln!(f, "{name:<name_width$} | {:>6} | {:>12} | {:>12}", stats.count, ms(stats.total), ms(stats.max))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_aggregation() {
+    let mut timer = PhaseTimer::default();
+    let ms = Duration::from_millis;
+    timer.record("partition".into(), ms(1));
+    timer.record("sector 10x8".into(), ms(5));
+    timer.record("sector 6x6".into(), ms(2));
+    timer.record("sector 10x8".into(), ms(7));
+
+    let phases: Vec<_> = timer.phases().collect();
+    assert_eq!(phases, [
+        ("partition", PhaseStats { count: 1, total: ms(1), max: ms(1) }),
+        ("sector 10x8", PhaseStats { count: 2, total: ms(12), max: ms(7) }),
+        ("sector 6x6", PhaseStats { count: 1, total: ms(2), max: ms(2) }),
+    ]);
+
+    let table = timer.to_string();
+    assert_eq!(table.lines().count(), 5);
+    assert!(table.lines().nth(3).unwrap().starts_with("sector 10x8 |      2 |       12.000 |        7.000"));
+    assert!(!table.contains("--"));
+}
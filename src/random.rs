@@ -0,0 +1,45 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A small, seedable pseudo random number generator (splitmix64). Not suitable for anything security related.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Seeds from the current time
+    pub fn seed_from_time() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in 0..bound
+    pub fn below(&mut self, bound: usize) -> usize {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+#[test]
+fn test_random() {
+    let mut a = Random::new(42);
+    let mut b = Random::new(42);
+    let first: Vec<_> = (0..8).map(|_| a.next_u64()).collect();
+    assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+    assert_ne!(first, (0..8).map(|_| Random::new(43).next_u64()).collect::<Vec<_>>());
+
+    let mut counts = [0; 5];
+    for _ in 0..5000 {
+        counts[a.below(5)] += 1;
+    }
+    assert!(counts.iter().all(|&count| (800..1200).contains(&count)), "{counts:?}");
+}
@@ -0,0 +1,749 @@
+use std::{collections::{HashMap, HashSet}, fmt::Display};
+
+use crate::{aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath}, args::Wrap, board_pos::{BoardPos, KNIGHT_MOVES}, board_size::BoardSize, dprintln, move_graph::MoveGraph, random::Random};
+
+// the window around a hole starts this many squares wide and grows up to the maximum if no route is found
+const MIN_MARGIN: Idx = 2;
+const MAX_MARGIN: Idx = 4;
+// search steps per attempt before giving up on a window
+const STEP_BUDGET: usize = 2_000;
+// larger holes are taken apart right away, as the search gets slow for the large windows around them
+const MAX_HOLE: usize = 12;
+// how often moving an end of the tour onto a square is tried before giving up
+const WALK_ATTEMPTS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairError {
+    /// The graph doesn't contain a tour
+    NoTour,
+    /// No route around the hole containing this square was found
+    Stuck(BoardPos),
+}
+
+impl Display for RepairError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoTour => write!(f, "there is no tour to repair"),
+            Self::Stuck(pos) => write!(f, "could not route the tour around the hole at {pos}"),
+        }
+    }
+}
+
+/// Removes the given squares from a tour covering the whole board and re-routes the tour around the holes they leave behind.
+/// Only squares close to a hole are re-routed, the rest of the tour is kept in large pieces.
+/// A closed tour stays closed if possible, otherwise it is opened up.
+pub fn excise_squares(graph: &mut MoveGraph, dead: &HashSet<BoardPos>) -> Result<(), RepairError> {
+    let size = graph.size();
+    let dead: HashSet<_> = dead.iter().copied().filter(|pos| size.fits(*pos)).collect();
+    // nothing to route around, the tour stays as it is
+    if dead.is_empty() {
+        return graph.tour().start().map(|_| ()).ok_or(RepairError::NoTour);
+    }
+
+    let old_order: Vec<_> = graph.tour().collect();
+    let (Some(&first), Some(&last)) = (old_order.first(), old_order.last()) else {
+        return Err(RepairError::NoTour);
+    };
+
+    let closed = old_order.len() > 1 && graph.node(last).next() == Some(first);
+    let mut tour = Tour::new(old_order.clone(), closed, size);
+
+    // holes that don't upset the balance of light and dark squares can usually be routed around without changing the ends of the tour.
+    // If that doesn't work, the hole is taken apart into pairs of neighboring squares, which are always balanced
+    let mut pending = Vec::new();
+    for hole in holes(&dead) {
+        if let Some(res) = (hole.len() <= MAX_HOLE).then(|| tour.reroute(&hole)).flatten() {
+            tour = res;
+            continue;
+        }
+
+        for piece in dominoes(&hole) {
+            match (piece.len() == 2).then(|| tour.reroute(&piece)).flatten() {
+                Some(res) => tour = res,
+                None => pending.extend(piece),
+            }
+        }
+    }
+
+    // the remaining squares are cut off an end of the tour one at a time. A closed tour is opened up at the first of them,
+    // then an end of the same color is moved onto each of the others. As a tour can't have more than one square more of one
+    // color than of the other, light and dark squares have to take turns
+    let mut balance: IdxMath = tour.order.iter().copied().map(color).sum();
+    let mut random = Random::new(0);
+    while let Some(&first) = pending.first() {
+        let ends: Vec<_> = tour.order.first().into_iter().chain(tour.order.last()).filter(|_| !tour.closed).collect();
+        let distance = |pos: BoardPos| ends.iter().map(|end| end.col().abs_diff(pos.col()).max(end.row().abs_diff(pos.row()))).min();
+        let mut candidates: Vec<_> = (0..pending.len()).filter(|idx| (balance - color(pending[*idx])).abs() <= 1).collect();
+        candidates.sort_by_key(|idx| distance(pending[*idx]));
+
+        // if the end can't be moved onto one square, another one may work and make room for the first one
+        let idx = candidates.into_iter()
+            .find(|idx| {
+                dprintln!(2 => "Cutting {} off the tour", pending[*idx]);
+                tour.cut_off(pending[*idx], &mut random)
+            })
+            .ok_or(RepairError::Stuck(first))?;
+        let pos = pending.remove(idx);
+        balance -= color(pos);
+    }
+
+    relink(graph, &old_order, &tour);
+
+    Ok(())
+}
+
+/// Groups the squares into holes of squares touching each other (including diagonally), each sorted row by row
+fn holes(dead: &HashSet<BoardPos>) -> Vec<Vec<BoardPos>> {
+    let mut sorted: Vec<_> = dead.iter().copied().collect();
+    sorted.sort_by_key(|pos| (pos.row(), pos.col()));
+
+    let mut seen = HashSet::new();
+    let mut res = Vec::new();
+    for pos in sorted {
+        if !seen.insert(pos) {
+            continue;
+        }
+
+        let mut hole = vec![pos];
+        let mut idx = 0;
+        while let Some(&current) = hole.get(idx) {
+            idx += 1;
+            for (col, row) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                if let Some(neighbor) = current.try_translate(col, row).filter(|pos| dead.contains(pos)) {
+                    if seen.insert(neighbor) {
+                        hole.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        hole.sort_by_key(|pos| (pos.row(), pos.col()));
+        res.push(hole);
+    }
+
+    res
+}
+
+/// Takes a hole apart into pairs of squares next to each other, and single squares where that isn't possible
+fn dominoes(hole: &[BoardPos]) -> Vec<Vec<BoardPos>> {
+    let squares: HashSet<_> = hole.iter().copied().collect();
+    let mut taken = HashSet::new();
+    let mut res = Vec::new();
+    for &pos in hole {
+        if !taken.insert(pos) {
+            continue;
+        }
+
+        let partner = [(1, 0), (0, 1), (-1, 0), (0, -1)].into_iter()
+            .filter_map(|(col, row)| pos.try_translate(col, row))
+            .find(|other| squares.contains(other) && !taken.contains(other));
+        match partner {
+            Some(other) => {
+                taken.insert(other);
+                res.push(vec![pos, other]);
+            },
+            None => res.push(vec![pos]),
+        }
+    }
+
+    res
+}
+
+/// The squares within `margin` squares of the hole (in any direction), except for the hole itself.
+/// This includes squares of other holes that are still part of the tour, which will be removed later on
+fn window(hole: &HashSet<BoardPos>, size: BoardSize, margin: Idx) -> HashSet<BoardPos> {
+    let margin = margin as IdxMath;
+    let mut res = HashSet::new();
+    for pos in hole {
+        for col in -margin..=margin {
+            for row in -margin..=margin {
+                if let Some(neighbor) = pos.try_translate_wrapping(col, row, size, Wrap::None).filter(|pos| !hole.contains(pos)) {
+                    res.insert(neighbor);
+                }
+            }
+        }
+    }
+
+    res
+}
+
+fn neighbors(pos: BoardPos) -> impl Iterator<Item = BoardPos> {
+    KNIGHT_MOVES.into_iter().filter_map(move |(col, row)| pos.try_translate(col, row))
+}
+
+/// +1 for light squares, -1 for dark squares. Every move changes the color, so a tour alternates between them
+fn color(pos: BoardPos) -> IdxMath {
+    if (pos.col() + pos.row()).is_multiple_of(2) { 1 } else { -1 }
+}
+
+struct Tour {
+    order: Vec<BoardPos>,
+    closed: bool,
+    size: BoardSize,
+    // the position of each square in the order, by row and column
+    index: Vec<usize>,
+}
+
+impl Tour {
+    fn new(order: Vec<BoardPos>, closed: bool, size: BoardSize) -> Self {
+        let mut tour = Self { order, closed, size, index: vec![usize::MAX; size.area() as usize] };
+        tour.reindex(0);
+        tour
+    }
+
+    fn key(&self, pos: BoardPos) -> usize {
+        pos.row() as usize * self.size.width() as usize + pos.col() as usize
+    }
+
+    /// Updates the positions of the squares from the given index on
+    fn reindex(&mut self, from: usize) {
+        for idx in from..self.order.len() {
+            let key = self.key(self.order[idx]);
+            self.index[key] = idx;
+        }
+    }
+
+    fn position(&self, pos: BoardPos) -> Option<usize> {
+        let idx = *self.index.get(self.key(pos))?;
+        (self.order.get(idx) == Some(&pos)).then_some(idx)
+    }
+
+    /// Finds a new tour without the squares of the hole, changing only the links of squares near it.
+    /// A closed tour stays closed and an open tour keeps its ends
+    fn reroute(&self, hole: &[BoardPos]) -> Option<Tour> {
+        let hole_set: HashSet<_> = hole.iter().copied().collect();
+        let balance = self.order.iter().copied().map(color).sum::<IdxMath>() - hole.iter().copied().map(color).sum::<IdxMath>();
+        if balance.abs() > 1 {
+            return None;
+        }
+
+        (MIN_MARGIN..=MAX_MARGIN).find_map(|margin| {
+            let problem = Problem::new(self, &hole_set, window(&hole_set, self.size, margin));
+            problem.solve(problem.kept_ends(self.closed), balance)
+        })
+    }
+
+    /// Removes the square from the tour, opening up a closed tour there. For an open tour, an end of the same color
+    /// is moved onto the square first
+    fn cut_off(&mut self, pos: BoardPos, random: &mut Random) -> bool {
+        if self.closed {
+            let Some(idx) = self.position(pos) else { return false };
+            self.order.rotate_left(idx);
+            self.order.remove(0);
+            self.closed = false;
+            self.reindex(0);
+            return true;
+        }
+
+        // only an end of the same color can be moved onto the square, trying the nearer one first if both are
+        let (Some(&first), Some(&last)) = (self.order.first(), self.order.last()) else { return false };
+        let distance = |end: BoardPos| end.col().abs_diff(pos.col()).max(end.row().abs_diff(pos.row()));
+        if distance(first) < distance(last) {
+            self.order.reverse();
+            self.reindex(0);
+        }
+
+        for _ in 0..2 {
+            if color(*self.order.last().unwrap()) == color(pos) && self.move_end_to(pos, random) {
+                self.order.pop();
+                return true;
+            }
+            self.order.reverse();
+            self.reindex(0);
+        }
+
+        false
+    }
+
+    /// Moves the end of an open tour onto the square by repeatedly linking it to a square a knight's move away
+    /// and reversing the part of the tour after that square, which makes the square that came after it the new end.
+    /// The end keeps its color along the way. Gives up after a while if it can't get there
+    fn move_end_to(&mut self, target: BoardPos, random: &mut Random) -> bool {
+        let distance = |pos: BoardPos| {
+            let (col, row) = (pos.col().abs_diff(target.col()) as u64, pos.row().abs_diff(target.row()) as u64);
+            col * col + row * row
+        };
+
+        let Some(&start) = self.order.last() else { return false };
+        let limit = 4 * start.col().abs_diff(target.col()).max(start.row().abs_diff(target.row())) as usize + 200;
+        for attempt in 0..WALK_ATTEMPTS {
+            // the greedy walk can get caught going in circles, so later attempts take more and more random steps
+            let mut visited = HashSet::new();
+            for _ in 0..limit {
+                let end = *self.order.last().unwrap();
+                if end == target {
+                    return true;
+                }
+                visited.insert(end);
+
+                // the squares that could become the new end, preferring ones that weren't the end before and are closer to the target
+                let len = self.order.len();
+                let mut options: Vec<_> = neighbors(end)
+                    .filter(|pos| self.size.fits(*pos))
+                    .filter_map(|pos| self.position(pos))
+                    .filter(|idx| *idx < len.saturating_sub(2))
+                    .map(|idx| (visited.contains(&self.order[idx + 1]), distance(self.order[idx + 1]), random.next_u64(), idx))
+                    .collect();
+                if options.is_empty() {
+                    break;
+                }
+                options.sort();
+
+                let pick = if random.below(WALK_ATTEMPTS) < attempt { random.below(options.len()) } else { 0 };
+                let idx = options[pick].3;
+                self.order[idx + 1..].reverse();
+                self.reindex(idx + 1);
+            }
+        }
+
+        dprintln!(2 => "Could not move the end of the tour onto {target}");
+        false
+    }
+}
+
+/// How the rerouted tour has to start and end
+#[derive(Debug, Clone, Copy)]
+struct Ends {
+    closed: bool,
+    // the segments the tour has to start and end with, traversed in their original direction
+    start: Option<usize>,
+    end: Option<usize>,
+}
+
+/// The tour split into the squares near the hole, which can be rearranged freely, and the segments of the tour
+/// between them, which are kept as they are but may be connected in any order and direction
+struct Problem<'a> {
+    tour: &'a Tour,
+    free: HashSet<BoardPos>,
+    // the index of the first square and the length of each segment, which may wrap around the end of a closed tour
+    segments: Vec<(usize, usize)>,
+    // segment index by the squares at either end of it
+    endpoints: HashMap<BoardPos, usize>,
+    // the square that followed each free square or segment end in the original tour
+    original_next: HashMap<BoardPos, BoardPos>,
+    // whether an open tour started and ended in a segment
+    starts_outside: bool,
+    ends_outside: bool,
+}
+
+impl<'a> Problem<'a> {
+    fn new(tour: &'a Tour, hole: &HashSet<BoardPos>, window: HashSet<BoardPos>) -> Self {
+        let order = &tour.order;
+        let len = order.len();
+        let next = |idx: usize| (tour.closed || idx + 1 < len).then(|| order[(idx + 1) % len]);
+
+        let free: HashSet<_> = window.into_iter().filter(|pos| tour.position(*pos).is_some()).collect();
+        let mut inside: Vec<_> = free.iter().chain(hole).filter_map(|pos| tour.position(*pos)).collect();
+        inside.sort_unstable();
+
+        // the gaps between the squares inside the window. For a closed tour, the part after the last one
+        // continues with the part before the first one
+        let mut segments = Vec::new();
+        match (inside.first(), inside.last()) {
+            (Some(&first), Some(&last)) => {
+                if tour.closed {
+                    segments.push((last + 1, len - 1 - last + first));
+                } else {
+                    segments.push((0, first));
+                }
+                segments.extend(inside.windows(2).map(|pair| (pair[0] + 1, pair[1] - pair[0] - 1)));
+                if !tour.closed {
+                    segments.push((last + 1, len - 1 - last));
+                }
+            },
+            _ => segments.push((0, len)),
+        }
+        segments.retain(|(_, count)| *count > 0);
+
+        let mut original_next = HashMap::new();
+        for pos in &free {
+            if let Some(next) = next(tour.position(*pos).unwrap()) {
+                original_next.insert(*pos, next);
+            }
+        }
+
+        let mut endpoints = HashMap::new();
+        for (idx, &(start, count)) in segments.iter().enumerate() {
+            let last = (start + count - 1) % len;
+            endpoints.insert(order[start % len], idx);
+            endpoints.insert(order[last], idx);
+            if let Some(next) = next(last) {
+                original_next.insert(order[last], next);
+            }
+        }
+
+        let starts_outside = !tour.closed && inside.first() != Some(&0);
+        let ends_outside = !tour.closed && !inside.is_empty() && inside.last() != Some(&(len - 1));
+
+        Self { tour, free, segments, endpoints, original_next, starts_outside, ends_outside }
+    }
+
+    fn first(&self, segment: usize) -> BoardPos {
+        self.tour.order[self.segments[segment].0 % self.tour.order.len()]
+    }
+
+    fn last(&self, segment: usize) -> BoardPos {
+        let (start, count) = self.segments[segment];
+        self.tour.order[(start + count - 1) % self.tour.order.len()]
+    }
+
+    fn squares(&self, segment: usize) -> impl DoubleEndedIterator<Item = BoardPos> + '_ {
+        let (start, count) = self.segments[segment];
+        (start..start + count).map(|idx| self.tour.order[idx % self.tour.order.len()])
+    }
+
+    /// A tour that is closed again or keeps the ends of the original tour that are outside the window
+    fn kept_ends(&self, closed: bool) -> Ends {
+        if closed {
+            Ends { closed, start: (!self.segments.is_empty()).then_some(0), end: None }
+        } else {
+            Ends { closed, start: self.starts_outside.then_some(0), end: self.ends_outside.then(|| self.segments.len() - 1) }
+        }
+    }
+
+    fn solve(&self, ends: Ends, balance: IdxMath) -> Option<Tour> {
+        // the ends of an open tour are of different colors if there are as many light as dark squares,
+        // otherwise both are of the more common color
+        let start = ends.start.map(|idx| self.first(idx));
+        let end = ends.end.map(|idx| self.last(idx));
+        let feasible = match (ends.closed, balance, start, end) {
+            (true, balance, _, _) => balance == 0,
+            (false, 0, Some(start), Some(end)) => color(start) != color(end),
+            (false, balance, start, end) => balance == 0 || start.into_iter().chain(end).all(|pos| color(pos) == balance),
+        };
+        if !feasible {
+            return None;
+        }
+
+        let mut search = Search {
+            problem: self,
+            ends,
+            free: self.free.clone(),
+            used: vec![false; self.segments.len()],
+            unused: self.segments.len(),
+            path: Vec::new(),
+            start: None,
+            steps: 0,
+        };
+
+        let found = match ends.start {
+            Some(idx) => search.enter(idx, self.first(idx)),
+            // a closed tour can start anywhere, so it might as well start on the first free square
+            None if ends.closed => {
+                let pos = self.free.iter().min_by_key(|pos| (pos.row(), pos.col())).copied()?;
+                search.visit(pos)
+            },
+            None => search.search(None),
+        };
+
+        if !found {
+            dprintln!(2 => "No route found after {} steps", search.steps);
+            return None;
+        }
+
+        let mut order = Vec::new();
+        for step in search.path {
+            match step {
+                Step::Square(pos) => order.push(pos),
+                Step::Segment(idx, false) => order.extend(self.squares(idx)),
+                Step::Segment(idx, true) => order.extend(self.squares(idx).rev()),
+            }
+        }
+
+        Some(Tour::new(order, ends.closed, self.tour.size))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Square(BoardPos),
+    // a segment and whether it is traversed backwards
+    Segment(usize, bool),
+}
+
+/// Depth first search for a tour through all free squares and segments, preferring the original route, then the
+/// squares with the fewest onward moves
+struct Search<'a> {
+    problem: &'a Problem<'a>,
+    ends: Ends,
+    free: HashSet<BoardPos>,
+    used: Vec<bool>,
+    unused: usize,
+    path: Vec<Step>,
+    // the first square of the tour, which the last one has to connect to for a closed tour
+    start: Option<BoardPos>,
+    steps: usize,
+}
+
+impl Search<'_> {
+    fn unused_segment(&self, pos: BoardPos) -> Option<usize> {
+        self.problem.endpoints.get(&pos).copied().filter(|idx| !self.used[*idx])
+    }
+
+    fn is_open(&self, pos: BoardPos) -> bool {
+        self.free.contains(&pos) || self.unused_segment(pos).is_some()
+    }
+
+    /// The square the tour continues from after entering at `pos`
+    fn exit(&self, pos: BoardPos) -> BoardPos {
+        match self.unused_segment(pos) {
+            Some(idx) if self.problem.first(idx) == pos => self.problem.last(idx),
+            Some(idx) => self.problem.first(idx),
+            None => pos,
+        }
+    }
+
+    fn onward_moves(&self, pos: BoardPos) -> usize {
+        neighbors(self.exit(pos)).filter(|next| *next != pos && self.is_open(*next)).count()
+    }
+
+    /// Whether the tour can continue with `pos`
+    fn is_candidate(&self, pos: BoardPos) -> bool {
+        if self.free.contains(&pos) {
+            return true;
+        }
+
+        match self.unused_segment(pos) {
+            // the final segment can only be entered at its original start once everything else is done
+            Some(idx) if Some(idx) == self.ends.end => self.problem.first(idx) == pos && self.free.is_empty() && self.unused == 1,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn is_done(&self, current: BoardPos) -> bool {
+        self.free.is_empty() && self.unused == 0 && (!self.ends.closed || self.start.is_some_and(|start| start.is_knight_move(current)))
+    }
+
+    /// Whether moving on from `left` to `current` cuts off free squares that can't all be the end of the tour
+    fn strands_squares(&self, left: BoardPos, current: BoardPos) -> bool {
+        let links = |pos: BoardPos| neighbors(pos)
+            .filter(|next| self.is_open(*next) || *next == current || self.ends.closed && Some(*next) == self.start)
+            .count();
+
+        let loose_ends = if self.ends.closed || self.ends.end.is_some() { 0 } else { 1 };
+        let mut stranded = 0;
+        for pos in neighbors(left).filter(|pos| self.free.contains(pos)) {
+            match links(pos) {
+                0 => return true,
+                1 => stranded += 1,
+                _ => {},
+            }
+        }
+
+        stranded > loose_ends
+    }
+
+    fn enter(&mut self, idx: usize, pos: BoardPos) -> bool {
+        let reversed = self.problem.first(idx) != pos;
+        let exit = self.exit(pos);
+        self.used[idx] = true;
+        self.unused -= 1;
+        self.path.push(Step::Segment(idx, reversed));
+        self.start.get_or_insert(pos);
+
+        if self.search(Some(exit)) {
+            return true;
+        }
+
+        self.path.pop();
+        self.used[idx] = false;
+        self.unused += 1;
+        if self.path.is_empty() {
+            self.start = None;
+        }
+
+        false
+    }
+
+    fn visit(&mut self, pos: BoardPos) -> bool {
+        self.free.remove(&pos);
+        self.path.push(Step::Square(pos));
+        self.start.get_or_insert(pos);
+
+        if self.search(Some(pos)) {
+            return true;
+        }
+
+        self.path.pop();
+        self.free.insert(pos);
+        if self.path.is_empty() {
+            self.start = None;
+        }
+
+        false
+    }
+
+    /// Whether taking `pos` next from `current` leaves a square behind that can't be reached anymore
+    fn is_dead_end(&mut self, current: BoardPos, pos: BoardPos) -> bool {
+        let exit = self.exit(pos);
+        let segment = self.unused_segment(pos);
+        match segment {
+            Some(idx) => self.used[idx] = true,
+            None => { self.free.remove(&pos); },
+        }
+
+        let res = self.strands_squares(current, exit);
+
+        match segment {
+            Some(idx) => self.used[idx] = false,
+            None => { self.free.insert(pos); },
+        }
+
+        res
+    }
+
+    fn search(&mut self, current: Option<BoardPos>) -> bool {
+        self.steps += 1;
+        if self.steps > STEP_BUDGET {
+            return false;
+        }
+
+        if current.is_some_and(|current| self.is_done(current)) {
+            return true;
+        }
+
+        let mut candidates: Vec<BoardPos> = match current {
+            Some(current) => neighbors(current).filter(|pos| self.is_candidate(*pos)).collect(),
+            None => self.free.iter().chain(self.problem.endpoints.keys()).copied().filter(|pos| self.is_candidate(*pos)).collect(),
+        };
+
+        let original_next = current.and_then(|current| self.problem.original_next.get(&current).copied());
+        candidates.sort_by_key(|pos| (Some(*pos) != original_next, self.onward_moves(*pos), pos.row(), pos.col()));
+
+        for pos in candidates {
+            if current.is_some_and(|current| self.is_dead_end(current, pos)) {
+                continue;
+            }
+
+            let found = match self.unused_segment(pos) {
+                Some(idx) => self.enter(idx, pos),
+                None => self.visit(pos),
+            };
+
+            if found {
+                return true;
+            }
+
+            if self.steps > STEP_BUDGET {
+                return false;
+            }
+        }
+
+        false
+    }
+}
+
+/// Replaces the tour in the graph with the rerouted one
+fn relink(graph: &mut MoveGraph, old_order: &[BoardPos], tour: &Tour) {
+    // warnsdorff marks its starting square by pointing it to itself
+    let marked_start = !tour.closed && graph.node(old_order[0]).prev() == Some(old_order[0]);
+
+    for pos in old_order {
+        let node = graph.node_mut(*pos);
+        *node.next_mut() = None;
+        *node.prev_mut() = None;
+    }
+
+    for pair in tour.order.windows(2) {
+        *graph.node_mut(pair[0]).next_mut() = Some(pair[1]);
+        *graph.node_mut(pair[1]).prev_mut() = Some(pair[0]);
+    }
+
+    let (Some(&first), Some(&last)) = (tour.order.first(), tour.order.last()) else { return };
+    if tour.closed {
+        *graph.node_mut(last).next_mut() = Some(first);
+        *graph.node_mut(first).prev_mut() = Some(last);
+    } else if marked_start {
+        *graph.node_mut(first).prev_mut() = Some(first);
+    }
+}
+
+#[cfg(test)]
+use crate::{board::corner_radius::CornerRadius, move_graph::fixtures::{self, SIX_BY_SIX}, validation::validate};
+
+#[cfg(test)]
+fn squares(list: &[(Idx, Idx)]) -> Vec<BoardPos> {
+    list.iter().map(|&(col, row)| BoardPos::new(col, row)).collect()
+}
+
+#[cfg(test)]
+fn solved(size: &str) -> MoveGraph<'static> {
+    let size = crate::board_size::parse_board_size(size).unwrap();
+    let args = crate::args::InputArgs { use_warnsdorff: false, warnsdorff: None, board_size: Some(size), solve_order: Default::default() };
+    crate::divide_and_conquer::solve(args).unwrap().1
+}
+
+#[test]
+fn test_holes() {
+    let dead = squares(&[(5, 5), (0, 0), (1, 1), (3, 0), (0, 2)]).into_iter().collect();
+    assert_eq!(holes(&dead), [squares(&[(0, 0), (1, 1), (0, 2)]), squares(&[(3, 0)]), squares(&[(5, 5)])]);
+}
+
+#[test]
+fn test_dominoes() {
+    // an L of three squares leaves one over
+    let hole = squares(&[(0, 0), (1, 0), (0, 1)]);
+    assert_eq!(dominoes(&hole), [squares(&[(0, 0), (1, 0)]), squares(&[(0, 1)])]);
+
+    let hole = squares(&[(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    assert_eq!(dominoes(&hole), [squares(&[(0, 0), (1, 0)]), squares(&[(2, 0), (2, 1)]), squares(&[(0, 1), (1, 1)])]);
+}
+
+#[test]
+fn test_excise_keeps_closed_tour() {
+    let mut graph = solved("10");
+    let dead = squares(&[(4, 4), (5, 4)]).into_iter().collect();
+    assert_eq!(excise_squares(&mut graph, &dead), Ok(()));
+
+    let summary = validate(&graph).unwrap();
+    assert_eq!((summary.squares, summary.closed), (98, true));
+}
+
+#[test]
+fn test_excise_opens_tour() {
+    // a single square upsets the balance of light and dark squares, so the tour can't stay closed
+    let mut graph = solved("10");
+    let dead = squares(&[(3, 6)]).into_iter().collect();
+    assert_eq!(excise_squares(&mut graph, &dead), Ok(()));
+
+    let summary = validate(&graph).unwrap();
+    assert_eq!((summary.squares, summary.closed), (99, false));
+}
+
+#[test]
+fn test_excise_from_open_tour() {
+    let mut graph = fixtures::open(6, 6, &SIX_BY_SIX);
+    let dead = squares(&[(0, 0), (5, 5), (2, 3)]).into_iter().collect();
+    assert_eq!(excise_squares(&mut graph, &dead), Ok(()));
+    assert_eq!(validate(&graph).unwrap().squares, 33);
+    assert!(graph.tour().all(|pos| !dead.contains(&pos)));
+}
+
+#[test]
+fn test_excise_nothing() {
+    let mut graph = fixtures::open(6, 6, &SIX_BY_SIX);
+    let before = graph.clone();
+    assert_eq!(excise_squares(&mut graph, &HashSet::new()), Ok(()));
+    assert_eq!(graph.tour().collect::<Vec<_>>(), before.tour().collect::<Vec<_>>());
+
+    // squares off the board don't change anything either
+    let dead = squares(&[(6, 0), (0, 9)]).into_iter().collect();
+    assert_eq!(excise_squares(&mut graph, &dead), Ok(()));
+    assert_eq!(validate(&graph).unwrap().squares, 36);
+
+    assert_eq!(excise_squares(&mut MoveGraph::new(6, 6), &HashSet::new()), Err(RepairError::NoTour));
+}
+
+#[test]
+fn test_excise_rounded_corners() {
+    for (size, radius) in [("20", "5"), ("30x24", "2 5 7 3"), ("40", "10")] {
+        let mut graph = solved(size);
+        let dead = CornerRadius::parse(radius).unwrap().dead_squares(graph.size());
+        assert_eq!(excise_squares(&mut graph, &dead), Ok(()), "{size} {radius}");
+
+        let summary = validate(&graph).unwrap();
+        assert_eq!(summary.squares, graph.size().area() as usize - dead.len(), "{size} {radius}");
+        assert!(graph.tour().all(|pos| !dead.contains(&pos)), "{size} {radius}");
+    }
+}
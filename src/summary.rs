@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use crate::context::SolverContext;
+
+/// Counters collected by the solvers, summed up over all the boards solved for one run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// Steps taken by Warnsdorff, including the ones that were undone again
+    pub iterations: u64,
+    /// Moves undone by Warnsdorff
+    pub backtracks: u64,
+    /// The seed used to pick a random starting position
+    pub seed: Option<u64>,
+    /// The seed used by --shuffle
+    pub shuffle_seed: Option<u64>,
+    /// Set when a solver gave up because of its input (e.g. an unreadable board file) rather than the board itself
+    pub input_error: bool,
+    /// Set when the exhaustive search tried every path without finding a tour
+    pub proven_no_tour: bool,
+    /// Set when the solver handed out the longest path it found instead of a tour (--best-effort)
+    pub partial: bool,
+}
+
+/// Updates the metrics of the current [SolverContext], which the solvers fill in as they go
+pub fn record(f: impl FnOnce(&mut Metrics)) {
+    SolverContext::with_current(|context| f(&mut context.metrics().lock().unwrap()));
+}
+
+/// Hands out the metrics collected so far in the current [SolverContext] and starts over
+pub fn take() -> Metrics {
+    SolverContext::with_current(|context| std::mem::take(&mut *context.metrics().lock().unwrap()))
+}
+
+/// The final line printed to stderr, made of space separated key=value pairs.
+/// Keys keep their meaning once added, so scripts can rely on them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Summary {
+    pub fn new(result: &str) -> Self {
+        Self::empty().with("result", result)
+    }
+
+    /// A line without a result, for describing something other than a run
+    pub fn empty() -> Self {
+        Summary { fields: Vec::new() }
+    }
+
+    /// Adds a field. Whitespace in the value is replaced, as it would split the value in two
+    pub fn with(mut self, key: &'static str, value: impl Display) -> Self {
+        let value = value.to_string().replace(char::is_whitespace, "_");
+        self.fields.push((key, value));
+        self
+    }
+
+    pub fn with_metrics(self, metrics: Metrics) -> Self {
+        let res = self.with("iterations", metrics.iterations).with("backtracks", metrics.backtracks);
+        let res = match metrics.seed {
+            Some(seed) => res.with("seed", seed),
+            None => res,
+        };
+        match metrics.shuffle_seed {
+            Some(seed) => res.with("shuffle_seed", seed),
+            None => res,
+        }
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, (key, value)) in self.fields.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{key}={value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_summary_line() {
+    let summary = Summary::new("solved").with("size", "12x9").with("closed", true).with("duration_ms", 412);
+    assert_eq!(summary.to_string(), "result=solved size=12x9 closed=true duration_ms=412");
+
+    let summary = Summary::new("failed").with("reason", "no tour\tfound");
+    assert_eq!(summary.to_string(), "result=failed reason=no_tour_found");
+
+    let metrics = Metrics { iterations: 10, backtracks: 2, seed: Some(42), shuffle_seed: None, input_error: false, proven_no_tour: false, partial: false };
+    assert_eq!(Summary::new("solved").with_metrics(metrics).to_string(), "result=solved iterations=10 backtracks=2 seed=42");
+    let metrics = Metrics { shuffle_seed: Some(7), ..Metrics::default() };
+    assert_eq!(Summary::new("solved").with_metrics(metrics).to_string(), "result=solved iterations=0 backtracks=0 shuffle_seed=7");
+    assert_eq!(Summary::new("solved").with_metrics(Metrics::default()).to_string(), "result=solved iterations=0 backtracks=0");
+}
@@ -1,68 +1,627 @@
-use std::{io::{Result, Write}, time::Duration};
+use std::{borrow::Cow, fmt::Display, hash::{DefaultHasher, Hash, Hasher}, io::{Result, Write}, ops::Range, time::Duration};
 
-use crate::move_graph::{MoveGraph, NodesIterator};
+use crate::{
+    aliases::{BoardIndex as Idx, BoardIndexOverflow as IdxMath},
+    args::{Precision, SvgStyle},
+    board::matrix2d::Matrix2D,
+    board_pos::{write_pos, BoardPos},
+    board_size::BoardSize,
+    degree_map::DegreeMap,
+    divide_and_conquer::{Sector, SolveQuadrantMode},
+    phase_timer::PhaseTimer,
+    summary::Summary,
+    tour::Tour,
+};
+use clap::ValueEnum;
 use svg_macro::svg;
 
-pub fn render_svg(writer: &mut impl Write, move_graph: &MoveGraph, duration: Duration) -> Result<()> {
-    const MARGIN: usize = 10;
-    const TITLE_BAR: usize = 20;
-    const END_BORDER: usize = 1;
-    let width = move_graph.width() as usize * 10 + END_BORDER;
+const MARGIN: usize = 10;
+const TITLE_BAR: usize = 20;
+const END_BORDER: usize = 1;
+
+/// Writes the tour, drawing the degree map beneath it if one is given and filling the squares it can't visit
+pub fn render_svg(writer: &mut impl Write, tour: &Tour, duration: Duration, time_format: Precision, timings: Option<&PhaseTimer>, style: &SvgStyle, degree_map: Option<&DegreeMap>) -> Result<()> {
+    let width = tour.size().width() as usize * 10 + END_BORDER;
     let mut file_width = (width + 2 * MARGIN).max(250);
-    let height = move_graph.height() as usize * 10 + END_BORDER;
+    let height = tour.size().height() as usize * 10 + END_BORDER;
     let file_height = height + MARGIN + TITLE_BAR;
-    let moves_iter = ConnectionsIter::new(move_graph, TITLE_BAR, MARGIN);
+    let moves_iter = ConnectionsIter::new(tour, TITLE_BAR, MARGIN, style);
+    let timings = timings.map(|timings| format!("<!--\n{timings}-->")).unwrap_or_default();
+    let background: Vec<_> = style.background.iter().map(|color| format!("<rect width=\"100%\" height=\"100%\" fill=\"{color}\" />")).collect();
+    let degrees = degree_map.map(degree_cells).unwrap_or_default();
+    let dead = dead_cells(tour, &style.dead_color);
+    let (stroke, grid_color, font_family) = (&style.stroke, &style.grid_color, &style.font_family);
+
+    // several images on one page mustn't share ids, so they are prefixed with a hash of the tour
+    let id = id_prefix(tour);
+    let (title_id, desc_id, grid_id) = (format!("{id}-title"), format!("{id}-desc"), format!("{id}-grid"));
+    let (title, desc) = describe(tour, duration);
 
-    let dur = (duration.as_secs(), duration.subsec_millis());
-    let duration = if dur == (0,0){
+    // longer titles than the usual "Elapsed time: 1.234 seconds" (e.g. with microseconds) need more room
+    let duration = crate::elapsed_seconds(duration, time_format);
+    if duration.chars().count() > "Elapsed time: 1.234 seconds".len() {
         file_width = file_width.max(300);
-        format!("💩 Elapsed time: {}.{:06} seconds 💩", dur.0, duration.subsec_micros())
-    } else {
-        format!("💩 Elapsed time: {}.{:03} seconds 💩", dur.0, dur.1)
-    };
+    }
 
     svg! { writer =>
-        <svg xmlns="http://www.w3.org/2000/svg" width=#file_width height=#file_height>
+        <svg xmlns="http://www.w3.org/2000/svg" width=#file_width height=#file_height role="img" aria-labelledby=#(format!("{title_id} {desc_id}"))>
+            <title id=#title_id>#title</title>
+            <desc id=#desc_id>#desc</desc>
+            #raw(timings)
+            #(#background)*
             <defs>
-                <pattern id="grid" width="10" height="10" patternUnits="userSpaceOnUse">
+                <pattern id=#grid_id width="10" height="10" patternUnits="userSpaceOnUse">
                     // grid pattern (1px left and top line on a 10*10 square)
-                    <path d="M 10 0 L 0 0 0 10" fill="none" stroke="gray" stroke-width="1" />
+                    <path d="M 10 0 L 0 0 0 10" fill="none" stroke=#grid_color stroke-width="1" />
+                </pattern>
+            </defs>
+            <text x=#MARGIN y=#MARGIN font-size="15" dominant-baseline="middle" font-family=#font_family fill=#stroke>#duration</text>
+            #(#degrees)*
+            #(#dead)*
+            <rect x=#MARGIN y=#TITLE_BAR #width #height fill=#(if style.show_grid { format!("url(#{grid_id})") } else { "none".into() }) />
+            <g role="img" aria-label=#title>
+                #(#moves_iter)*
+            </g>
+        </svg>
+    };
+
+    Ok(())
+}
+
+/// Writes the degree map on its own, laid out the same as the tour would be
+pub fn render_degree_map(writer: &mut impl Write, map: &DegreeMap, style: &SvgStyle) -> Result<()> {
+    let size = map.size();
+    let width = size.width() as usize * 10 + END_BORDER;
+    let height = size.height() as usize * 10 + END_BORDER;
+    let title = format!("Degree map of a {}\u{d7}{} board", size.width(), size.height());
+    let degrees = degree_cells(map);
+    let background: Vec<_> = style.background.iter().map(|color| format!("<rect width=\"100%\" height=\"100%\" fill=\"{color}\" />")).collect();
+    let (stroke, grid_color, font_family) = (&style.stroke, &style.grid_color, &style.font_family);
+
+    svg! { writer =>
+        <svg xmlns="http://www.w3.org/2000/svg" width=#((width + 2 * MARGIN).max(250)) height=#(height + MARGIN + TITLE_BAR) role="img" aria-label=#title>
+            <title>#title</title>
+            #(#background)*
+            <defs>
+                <pattern id="degree-grid" width="10" height="10" patternUnits="userSpaceOnUse">
+                    <path d="M 10 0 L 0 0 0 10" fill="none" stroke=#grid_color stroke-width="1" />
+                </pattern>
+            </defs>
+            <text x=#MARGIN y=#MARGIN font-size="15" dominant-baseline="middle" font-family=#font_family fill=#stroke>#title</text>
+            #(#degrees)*
+            <rect x=#MARGIN y=#TITLE_BAR #width #height fill=#(if style.show_grid { "url(#degree-grid)" } else { "none" }) />
+        </svg>
+    };
+
+    Ok(())
+}
+
+/// A square filled with the color of its degree for every accessible square, from red for no moves through yellow to green for eight
+fn degree_cells(map: &DegreeMap) -> Vec<String> {
+    let size = map.size();
+    (0..size.height()).flat_map(|row| (0..size.width()).map(move |col| BoardPos::new(col, row))).filter_map(|pos| {
+        let degree = map.get(pos)?;
+        let (x, y) = (pos.col() as usize * 10 + MARGIN, pos.row() as usize * 10 + TITLE_BAR);
+        Some(format!("<rect x=\"{x}\" y=\"{y}\" width=\"10\" height=\"10\" fill=\"hsl({}, 80%, 75%)\" data-degree=\"{degree}\" />", degree.min(8) as u32 * 15))
+    }).collect()
+}
+
+/// A square filled with the given color for every square of the board the tour doesn't visit
+fn dead_cells(tour: &Tour, color: &str) -> Vec<String> {
+    tour.dead_squares().map(|pos| {
+        let (x, y) = (pos.col() as usize * 10 + MARGIN, pos.row() as usize * 10 + TITLE_BAR);
+        format!("<rect x=\"{x}\" y=\"{y}\" width=\"10\" height=\"10\" fill=\"{color}\" data-dead=\"{pos}\" />")
+    }).collect()
+}
+
+/// Writes how often Warnsdorff took a move back on each square (--heatmap), laid out the same as the tour would be and
+/// with the tour drawn over it if one was found
+pub fn render_heatmap(writer: &mut impl Write, retractions: &Matrix2D<u32>, tour: Option<&Tour>, style: &SvgStyle) -> Result<()> {
+    let size = retractions.size();
+    let width = size.width() as usize * 10 + END_BORDER;
+    let height = size.height() as usize * 10 + END_BORDER;
+    let total: u64 = retractions.iter().map(|&count| count as u64).sum();
+    let title = format!("{total} moves taken back on a {}\u{d7}{} board", size.width(), size.height());
+    let cells = heatmap_cells(retractions);
+    let moves: Vec<_> = tour.map(|tour| ConnectionsIter::new(tour, TITLE_BAR, MARGIN, style).collect()).unwrap_or_default();
+    let background: Vec<_> = style.background.iter().map(|color| format!("<rect width=\"100%\" height=\"100%\" fill=\"{color}\" />")).collect();
+    let (stroke, grid_color, font_family) = (&style.stroke, &style.grid_color, &style.font_family);
+
+    svg! { writer =>
+        <svg xmlns="http://www.w3.org/2000/svg" width=#((width + 2 * MARGIN).max(250)) height=#(height + MARGIN + TITLE_BAR) role="img" aria-label=#title>
+            <title>#title</title>
+            #(#background)*
+            <defs>
+                <pattern id="heatmap-grid" width="10" height="10" patternUnits="userSpaceOnUse">
+                    <path d="M 10 0 L 0 0 0 10" fill="none" stroke=#grid_color stroke-width="1" />
+                </pattern>
+            </defs>
+            <text x=#MARGIN y=#MARGIN font-size="15" dominant-baseline="middle" font-family=#font_family fill=#stroke>#title</text>
+            #(#cells)*
+            <rect x=#MARGIN y=#TITLE_BAR #width #height fill=#(if style.show_grid { "url(#heatmap-grid)" } else { "none" }) />
+            #(#moves)*
+        </svg>
+    };
+
+    Ok(())
+}
+
+/// A square for every square a move was taken back on, from yellow for one through to red for the most of any square.
+/// The counts of a few squares easily dwarf those of all others, so the scale is logarithmic
+fn heatmap_cells(retractions: &Matrix2D<u32>) -> Vec<String> {
+    let size = retractions.size();
+    let max = (retractions.iter().copied().max().unwrap_or(0) as f64).ln_1p();
+    (0..size.height()).flat_map(|row| (0..size.width()).map(move |col| BoardPos::new(col, row))).filter_map(|pos| {
+        let count = *retractions.at(pos);
+        if count == 0 {
+            return None;
+        }
+
+        let hue = 60.0 * (1.0 - (count as f64).ln_1p() / max);
+        let (x, y) = (pos.col() as usize * 10 + MARGIN, pos.row() as usize * 10 + TITLE_BAR);
+        Some(format!("<rect x=\"{x}\" y=\"{y}\" width=\"10\" height=\"10\" fill=\"hsl({}, 90%, 60%)\" data-retractions=\"{count}\" />", hue.round()))
+    }).collect()
+}
+
+/// Draws the sectors the board is solved in over its grid, numbered like the table --dry-run prints otherwise.
+/// The closed sector is blue, sectors merged horizontally are green and vertically merged ones orange
+pub fn render_plan(writer: &mut impl Write, size: BoardSize, sectors: &[Sector], style: &SvgStyle) -> Result<()> {
+    let width = size.width() as usize * 10 + END_BORDER;
+    let height = size.height() as usize * 10 + END_BORDER;
+    let title = format!("{} sectors of a {}\u{d7}{} board", sectors.len(), size.width(), size.height());
+    let (fills, outlines) = sector_cells(sectors, &style.stroke);
+    let background: Vec<_> = style.background.iter().map(|color| format!("<rect width=\"100%\" height=\"100%\" fill=\"{color}\" />")).collect();
+    let (stroke, grid_color, font_family) = (&style.stroke, &style.grid_color, &style.font_family);
+
+    svg! { writer =>
+        <svg xmlns="http://www.w3.org/2000/svg" width=#((width + 2 * MARGIN).max(250)) height=#(height + MARGIN + TITLE_BAR) role="img" aria-label=#title>
+            <title>#title</title>
+            #(#background)*
+            <defs>
+                <pattern id="plan-grid" width="10" height="10" patternUnits="userSpaceOnUse">
+                    <path d="M 10 0 L 0 0 0 10" fill="none" stroke=#grid_color stroke-width="1" />
                 </pattern>
             </defs>
-            <text x=#MARGIN y=#MARGIN font-size="15" dominant-baseline="middle" font-family="Arial" fill="black">#duration</text>
-            <rect x=#MARGIN y=#TITLE_BAR #width #height fill="url(#grid)" />
-            #(#moves_iter)*
+            <text x=#MARGIN y=#MARGIN font-size="15" dominant-baseline="middle" font-family=#font_family fill=#stroke>#title</text>
+            #(#fills)*
+            <rect x=#MARGIN y=#TITLE_BAR #width #height fill=#(if style.show_grid { "url(#plan-grid)" } else { "none" }) />
+            <g font-family=#font_family font-size="10" text-anchor="middle" dominant-baseline="middle" fill=#stroke>
+                #(#outlines)*
+            </g>
         </svg>
     };
 
     Ok(())
 }
 
+/// The colored rectangle beneath the grid and the outline and number above it for every sector
+fn sector_cells(sectors: &[Sector], stroke: &str) -> (Vec<String>, Vec<String>) {
+    sectors.iter().enumerate().map(|(idx, sector)| {
+        let (x, y) = (sector.pos.col() as usize * 10 + MARGIN, sector.pos.row() as usize * 10 + TITLE_BAR);
+        let (width, height) = (sector.size.width() as usize * 10, sector.size.height() as usize * 10);
+        let hue = match sector.mode {
+            SolveQuadrantMode::Closed => 210,
+            SolveQuadrantMode::Stretched(direction) if direction.is_horizontal() => 120,
+            SolveQuadrantMode::Stretched(_) => 30,
+        };
+        let fill = format!("<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" fill=\"hsl({hue}, 70%, 80%)\" data-sector=\"{idx}\" data-mode=\"{}\" />", sector.mode);
+        let outline = format!(
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" fill=\"none\" stroke=\"{stroke}\" stroke-width=\"2\" /><text x=\"{}\" y=\"{}\">{idx}</text>",
+            x + width / 2,
+            y + height / 2,
+        );
+        (fill, outline)
+    }).unzip()
+}
+
+/// A short sentence for the title, e.g. "Knight's tour on a 12×9 board, 108 moves, closed",
+/// and the parameters of the tour as key=value pairs like those of the summary line for the description
+fn describe(tour: &Tour, duration: Duration) -> (String, String) {
+    let (paths, moves, closed) = (tour.paths().len(), tour.moves(), tour.is_closed());
+    let (board, piece) = (format!("{}\u{d7}{}", tour.size().width(), tour.size().height()), tour.leaper());
+
+    let title = if paths > 1 {
+        format!("Cover of a {board} board by {paths} {piece}s, {moves} moves")
+    } else if !tour.is_complete() {
+        format!("Partial {piece}'s tour on a {board} board, {moves} moves, no full tour found")
+    } else {
+        let piece = piece.to_string();
+        let (first, rest) = piece.split_at(1);
+        format!("{}{rest}'s tour on a {board} board, {moves} moves, {}", first.to_uppercase(), if closed { "closed" } else { "open" })
+    };
+
+    let wrap = tour.wrap().to_possible_value().map_or_else(String::new, |value| value.get_name().to_string());
+    let desc = Summary::empty()
+        .with("size", tour.size())
+        .with("piece", piece)
+        .with("wrap", wrap)
+        .with("paths", paths)
+        .with("moves", moves)
+        .with("closed", closed)
+        .with("complete", tour.is_complete())
+        .with("duration_ms", duration.as_millis());
+
+    (title, desc.to_string())
+}
+
+/// Replaces the characters that would end a text or a double quoted attribute value early with entities.
+/// The svg! macro calls this for every value it interpolates
+pub fn xml_escape(value: &str) -> Cow<'_, str> {
+    if !value.contains(['&', '<', '>', '"']) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut res = String::with_capacity(value.len() + 16);
+    for c in value.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            c => res.push(c),
+        }
+    }
+
+    Cow::Owned(res)
+}
+
+/// The same tour always gets the same prefix, different tours most likely different ones
+fn id_prefix(tour: &Tour) -> String {
+    let mut hasher = DefaultHasher::new();
+    tour.size().hash(&mut hasher);
+    for row in 0..tour.size().height() {
+        for col in 0..tour.size().width() {
+            tour.next(BoardPos::new(col, row)).hash(&mut hasher);
+        }
+    }
+
+    format!("kt-{:08x}", hasher.finish() as u32)
+}
+
+/// The stroke colors of the paths when several knights cover the board, the first one is replaced by --svg-stroke-color
+const PATH_COLORS: [&str; 8] = ["black", "crimson", "royalblue", "forestgreen", "darkorange", "purple", "teal", "saddlebrown"];
+
 struct ConnectionsIter<'a> {
-    iter: NodesIterator<'a>,
+    tour: &'a Tour,
+    // the squares of the board row by row, by their index
+    iter: Range<usize>,
     v_offset: usize,
     h_offset: usize,
+    // the second half of a move crossing a seam, which is drawn as a ghost segment
+    ghost: Option<Segment<'a>>,
+    style: &'a SvgStyle,
 }
 
 impl<'a> ConnectionsIter<'a> {
-    fn new(graph: &'a MoveGraph<'a>, v_offset: usize, h_offset: usize) -> Self {
-        ConnectionsIter{ iter: graph.nodes(), v_offset, h_offset }
+    fn new(tour: &'a Tour, v_offset: usize, h_offset: usize, style: &'a SvgStyle) -> Self {
+        let squares = tour.size().width() as usize * tour.size().height() as usize;
+        ConnectionsIter{ tour, iter: 0..squares, v_offset, h_offset, ghost: None, style }
+    }
+
+    fn line(&self, from: (IdxMath, IdxMath), to: (IdxMath, IdxMath), name: (BoardPos, BoardPos), dashed: bool, stroke: &'a str) -> Segment<'a> {
+        let point = |(col, row): (IdxMath, IdxMath)| (col * 10 + 5 + self.h_offset as IdxMath, row * 10 + 5 + self.v_offset as IdxMath);
+        Segment { from: point(from), to: point(to), name, dashed, stroke, width: self.style.stroke_width }
+    }
+
+    fn stroke(&self, pos: BoardPos) -> &'a str {
+        let path = self.tour.visit(pos).map_or(0, |(path, _)| path);
+        match path % PATH_COLORS.len() {
+            0 => &self.style.stroke,
+            path => PATH_COLORS[path],
+        }
     }
 }
 
 impl<'a> Iterator for ConnectionsIter<'a> {
-    type Item = String;
+    type Item = Segment<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let node = self.iter.next()?;
-        if let Some(next) = node.next() {
-            let pos = (node.pos().col() as usize * 10 + 5 + self.h_offset, node.pos().row() as usize * 10 + 5 + self.v_offset);
-            let next = (next.col() as usize * 10 + 5 + self.h_offset, next.row() as usize * 10 + 5 + self.v_offset);
-            let res = format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\" stroke-width=\"1.5\" />", pos.0, pos.1, next.0, next.1);
-            Some(res)
+        if let Some(ghost) = self.ghost.take() {
+            return Some(ghost);
         }
-        else{
-            self.next()
+
+        let idx = self.iter.next()?;
+        let width = self.tour.size().width() as usize;
+        let square = BoardPos::new((idx % width) as Idx, (idx / width) as Idx);
+        let Some(next) = self.tour.next(square) else {
+            return self.next();
+        };
+
+        let name = (square, next);
+        let stroke = self.stroke(square);
+        let coords = |pos: BoardPos| (pos.col() as IdxMath, pos.row() as IdxMath);
+        let offset = self.tour.move_offset(square, next);
+        let (pos, next) = (coords(square), coords(next));
+        let Some(offset) = offset.filter(|offset| (pos.0 + offset.0, pos.1 + offset.1) != next) else {
+            return Some(self.line(pos, next, name, false, stroke));
+        };
+
+        // the move crosses a seam: draw it leaving the board on one side and coming back in on the other side
+        self.ghost = Some(self.line((next.0 - offset.0, next.1 - offset.1), next, name, true, stroke));
+        Some(self.line(pos, (pos.0 + offset.0, pos.1 + offset.1), name, true, stroke))
+    }
+}
+
+/// The line drawn for a move, or for one half of a move crossing a seam. There is one for every square of the tour,
+/// so they are written straight into the output rather than formatted into strings first
+struct Segment<'a> {
+    from: (IdxMath, IdxMath),
+    to: (IdxMath, IdxMath),
+    // the squares of the move
+    name: (BoardPos, BoardPos),
+    dashed: bool,
+    stroke: &'a str,
+    width: f32,
+}
+
+impl Display for Segment<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ((x1, y1), (x2, y2), (from, to)) = (self.from, self.to, self.name);
+        write!(f, "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"{}\" stroke-width=\"{}\"", self.stroke, self.width)?;
+        if self.dashed {
+            write!(f, " stroke-dasharray=\"2\"")?;
         }
+        f.write_str(" data-move=\"")?;
+        write_pos(f, from)?;
+        f.write_str("-")?;
+        write_pos(f, to)?;
+        f.write_str("\" />")
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+use crate::{args::Wrap, move_graph::{fixtures::{self, CYLINDER, RING, SIX_BY_SIX}, MoveGraph}};
+
+#[cfg(test)]
+fn render(graph: &MoveGraph, duration: Duration) -> String {
+    let mut buf = Vec::new();
+    render_svg(&mut buf, &Tour::from_graph(graph).unwrap(), duration, Precision::Seconds, None, &SvgStyle::default(), None).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_dimensions() {
+    // 10px per square plus the closing grid line, a 10px margin around the board and a 20px title bar instead of the top margin.
+    // the document is at least 250px wide so the title fits
+    let small = render(&fixtures::closed(3, 3, &RING), Duration::from_millis(1500));
+    assert!(small.contains("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"250\" height=\"61\" role=\"img\""), "{small}");
+    assert!(small.contains("<rect x=\"10\" y=\"20\" width=\"31\" height=\"31\" fill=\"url(#kt-"), "{small}");
+
+    let large = render(&fixtures::open(30, 20, &RING), Duration::from_millis(1500));
+    assert!(large.contains("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"321\" height=\"231\" role=\"img\""), "{large}");
+    assert!(large.contains("<rect x=\"10\" y=\"20\" width=\"301\" height=\"201\" fill=\"url(#kt-"), "{large}");
+
+    // the longer title for durations below a millisecond needs at least 300px
+    let fast = render(&fixtures::closed(3, 3, &RING), Duration::from_micros(42));
+    assert!(fast.contains("width=\"300\" height=\"61\" "), "{fast}");
+}
+
+#[test]
+fn test_title() {
+    let svg = render(&fixtures::closed(3, 3, &RING), Duration::from_millis(1500));
+    assert!(svg.contains("<text x=\"10\" y=\"10\" "), "{svg}");
+    assert_eq!(svg.matches("Elapsed time: 1.500 seconds").count(), 1);
+
+    // no floating point formatting involved, so no rounding or locale surprises
+    let svg = render(&fixtures::closed(3, 3, &RING), Duration::from_micros(999));
+    assert_eq!(svg.matches("Elapsed time: 0.000999 seconds").count(), 1);
+    let svg = render(&fixtures::closed(3, 3, &RING), Duration::from_nanos(61_999_999_999));
+    assert_eq!(svg.matches("Elapsed time: 61.999 seconds").count(), 1);
+
+    let mut buf = Vec::new();
+    render_svg(&mut buf, &Tour::from_graph(&fixtures::closed(3, 3, &RING)).unwrap(), Duration::from_millis(83_456), Precision::Human, None, &SvgStyle::default(), None).unwrap();
+    let svg = String::from_utf8(buf).unwrap();
+    assert_eq!(svg.matches("Elapsed time: 1m 23.4s").count(), 1, "{svg}");
+}
+
+#[test]
+fn test_one_line_per_move() {
+    let open = render(&fixtures::open(6, 6, &SIX_BY_SIX), Duration::from_millis(1));
+    assert_eq!(open.matches("<line ").count(), 35);
+
+    let closed = render(&fixtures::closed(3, 3, &RING), Duration::from_millis(1));
+    assert_eq!(closed.matches("<line ").count(), 8);
+    assert!(closed.contains("<line x1=\"15\" y1=\"25\" x2=\"35\" y2=\"35\" "), "{closed}");
+}
+
+#[test]
+fn test_seam_segments() {
+    let count = |graph: &MoveGraph, needle: &str| render(graph, Duration::ZERO).matches(needle).count();
+
+    // every move of a plain tour is a single segment
+    let plain = fixtures::open(6, 6, &SIX_BY_SIX);
+    assert_eq!(count(&plain, "<line"), 35);
+    assert_eq!(count(&plain, "data-move=\"A1-C2\""), 1);
+
+    // E1 -> B2 leaves the board on the right and comes back in on the left
+    let cylinder = fixtures::closed(5, 6, &CYLINDER).with_wrap(Wrap::Horizontal);
+    assert_eq!(count(&cylinder, "data-move=\"E1-B2\""), 2);
+    assert_eq!(count(&cylinder, "data-move=\"A1-C2\""), 1);
+    assert_eq!(count(&cylinder, "<line"), 30 + 8);
+}
+
+#[test]
+fn test_path_colors() {
+    let svg = render(&fixtures::paths(3, 3, &[&RING[..5], &RING[5..]]), Duration::ZERO);
+    assert_eq!(svg.matches("stroke=\"black\" stroke-width").count(), 4, "{svg}");
+    assert_eq!(svg.matches("stroke=\"crimson\" stroke-width").count(), 2, "{svg}");
+    assert!(svg.contains("stroke=\"crimson\" stroke-width=\"1.5\" data-move=\"A2-C1\""), "{svg}");
+
+    // a single tour stays black
+    let svg = render(&fixtures::open(6, 6, &SIX_BY_SIX), Duration::ZERO);
+    assert_eq!(svg.matches("stroke=\"black\" stroke-width").count(), 35);
+}
+
+#[test]
+fn test_style() {
+    let style = SvgStyle {
+        stroke: "white".into(),
+        stroke_width: 2.0,
+        grid_color: "#444".into(),
+        background: Some("rgb(30,30,30)".into()),
+        dead_color: "black".into(),
+        font_family: "Georgia".into(),
+        show_grid: false,
+    };
+    let mut buf = Vec::new();
+    render_svg(&mut buf, &Tour::from_graph(&fixtures::open(6, 6, &SIX_BY_SIX)).unwrap(), Duration::from_millis(1500), Precision::Seconds, None, &style, None).unwrap();
+    let svg = String::from_utf8(buf).unwrap();
+
+    assert!(svg.contains("<rect width=\"100%\" height=\"100%\" fill=\"rgb(30,30,30)\" />"), "{svg}");
+    assert!(svg.contains("stroke=\"#444\""), "{svg}");
+    assert!(svg.contains("font-family=\"Georgia\" fill=\"white\">"), "{svg}");
+    assert!(svg.contains("fill=\"none\"/>"), "{svg}");
+    assert_eq!(svg.matches("stroke=\"white\" stroke-width=\"2\"").count(), 35, "{svg}");
+    assert!(!svg.contains('\u{1F4A9}'), "{svg}");
+
+    // the defaults look like they always did, minus the emoji
+    let svg = render(&fixtures::open(6, 6, &SIX_BY_SIX), Duration::from_millis(1500));
+    assert!(!svg.contains("100%"), "{svg}");
+    assert!(svg.contains("font-family=\"Arial\" fill=\"black\">"), "{svg}");
+    assert!(svg.contains("fill=\"url(#kt-"), "{svg}");
+}
+
+#[test]
+fn test_accessibility() {
+    let has_line = |svg: &str, line: &str| svg.lines().any(|l| l.trim() == line);
+
+    let svg = render(&fixtures::closed(3, 3, &RING), Duration::from_millis(12));
+    let id_prefix = |graph: &MoveGraph| id_prefix(&Tour::from_graph(graph).unwrap());
+    let id = id_prefix(&fixtures::closed(3, 3, &RING));
+    assert!(svg.contains(&format!("role=\"img\" aria-labelledby=\"{id}-title {id}-desc\">")), "{svg}");
+    assert!(has_line(&svg, "Knight's tour on a 3\u{d7}3 board, 8 moves, closed"), "{svg}");
+    assert!(has_line(&svg, "size=3x3 piece=knight wrap=none paths=1 moves=8 closed=true complete=true duration_ms=12"), "{svg}");
+    assert!(svg.contains("<g role=\"img\" aria-label=\"Knight's tour on a 3\u{d7}3 board, 8 moves, closed\">"), "{svg}");
+    // the title comes first, as screen readers expect
+    assert!(svg.lines().nth(1).unwrap().contains(&format!("<title id=\"{id}-title\">")), "{svg}");
+
+    let svg = render(&fixtures::open(6, 6, &SIX_BY_SIX), Duration::ZERO);
+    assert!(has_line(&svg, "Knight's tour on a 6\u{d7}6 board, 35 moves, open"), "{svg}");
+    assert!(svg.contains("closed=false"), "{svg}");
+
+    // the longest path found instead of a tour says so
+    let mut buf = Vec::new();
+    let partial = Tour::from_graph(&fixtures::open(3, 3, &RING[..5])).unwrap().with_complete(false);
+    render_svg(&mut buf, &partial, Duration::ZERO, Precision::Seconds, None, &SvgStyle::default(), None).unwrap();
+    let partial = String::from_utf8(buf).unwrap();
+    assert!(has_line(&partial, "Partial knight's tour on a 3\u{d7}3 board, 4 moves, no full tour found"), "{partial}");
+    assert!(partial.contains("closed=false complete=false"), "{partial}");
+
+    let svg = render(&fixtures::paths(3, 3, &[&RING[..5], &RING[5..]]), Duration::ZERO);
+    assert!(has_line(&svg, "Cover of a 3\u{d7}3 board by 2 knights, 6 moves"), "{svg}");
+
+    // ids differ between tours, so that several of them can be put on one page
+    let ids: Vec<_> = svg.match_indices(" id=\"").map(|(idx, _)| svg[idx..].split('"').nth(1).unwrap()).collect();
+    assert_eq!(ids.len(), 3);
+    assert!(ids.iter().all(|id| id.starts_with(&id_prefix(&fixtures::paths(3, 3, &[&RING[..5], &RING[5..]])))));
+    assert_ne!(id, id_prefix(&fixtures::paths(3, 3, &[&RING[..5], &RING[5..]])));
+}
+
+#[test]
+fn test_dead_squares() {
+    // the ring leaves out the center, which is filled in beneath the grid and the moves
+    let svg = render(&fixtures::closed(3, 3, &RING), Duration::ZERO);
+    assert_eq!(svg.matches("data-dead=").count(), 1, "{svg}");
+    assert!(svg.contains("<rect x=\"20\" y=\"30\" width=\"10\" height=\"10\" fill=\"#333\" data-dead=\"B2\" />"), "{svg}");
+    assert!(svg.find("data-dead").unwrap() < svg.find("fill=\"url(#kt-").unwrap(), "{svg}");
+
+    let svg = render(&fixtures::open(6, 6, &SIX_BY_SIX), Duration::ZERO);
+    assert!(!svg.contains("data-dead"), "{svg}");
+}
+
+#[test]
+fn test_degree_map() {
+    let tour = Tour::from_graph(&fixtures::closed(3, 3, &RING)).unwrap();
+    let map = DegreeMap::new(tour.size(), &std::collections::HashSet::from([BoardPos::new(1, 1)]), Wrap::None, crate::leaper::Leaper::KNIGHT);
+    let mut buf = Vec::new();
+    render_svg(&mut buf, &tour, Duration::ZERO, Precision::Seconds, None, &SvgStyle::default(), Some(&map)).unwrap();
+    let svg = String::from_utf8(buf).unwrap();
+
+    // one square per accessible square, beneath the grid and the moves
+    assert_eq!(svg.matches("data-degree=\"2\"").count(), 8, "{svg}");
+    assert!(svg.contains("<rect x=\"10\" y=\"20\" width=\"10\" height=\"10\" fill=\"hsl(30, 80%, 75%)\" data-degree=\"2\" />"), "{svg}");
+    assert!(svg.contains("<rect x=\"20\" y=\"30\" width=\"10\" height=\"10\" fill=\"#333\" data-dead=\"B2\" />"), "{svg}");
+    assert!(svg.rfind("data-degree").unwrap() < svg.find("fill=\"url(#kt-").unwrap(), "{svg}");
+    assert!(svg.rfind("data-degree").unwrap() < svg.find("<line").unwrap(), "{svg}");
+
+    let mut buf = Vec::new();
+    render_degree_map(&mut buf, &map, &SvgStyle::default()).unwrap();
+    let svg = String::from_utf8(buf).unwrap();
+    assert_eq!(svg.matches("data-degree").count(), 8, "{svg}");
+    assert!(svg.lines().any(|line| line.trim() == "Degree map of a 3\u{d7}3 board"), "{svg}");
+    assert!(!svg.contains("<line"), "{svg}");
+}
+
+#[test]
+fn test_heatmap() {
+    let tour = Tour::from_graph(&fixtures::closed(3, 3, &RING)).unwrap();
+    let mut retractions = Matrix2D::new(3, 3, || 0);
+    *retractions.at_mut(BoardPos::new(2, 0)) = 1;
+    *retractions.at_mut(BoardPos::new(0, 2)) = 9;
+    *retractions.at_mut(BoardPos::new(1, 2)) = 99;
+
+    let mut buf = Vec::new();
+    render_heatmap(&mut buf, &retractions, Some(&tour), &SvgStyle::default()).unwrap();
+    let svg = String::from_utf8(buf).unwrap();
+    roxmltree::Document::parse(&svg).unwrap();
+
+    // squares nothing was taken back on stay blank, the others go from yellow to red on a log scale
+    assert_eq!(svg.matches("data-retractions=").count(), 3, "{svg}");
+    assert!(svg.contains("<rect x=\"30\" y=\"20\" width=\"10\" height=\"10\" fill=\"hsl(51, 90%, 60%)\" data-retractions=\"1\" />"), "{svg}");
+    assert!(svg.contains("fill=\"hsl(30, 90%, 60%)\" data-retractions=\"9\""), "{svg}");
+    assert!(svg.contains("fill=\"hsl(0, 90%, 60%)\" data-retractions=\"99\""), "{svg}");
+    assert!(svg.lines().any(|line| line.trim() == "109 moves taken back on a 3\u{d7}3 board"), "{svg}");
+    assert_eq!(svg.matches("<line").count(), 8, "{svg}");
+    assert!(svg.rfind("data-retractions").unwrap() < svg.find("<line").unwrap(), "{svg}");
+
+    let mut buf = Vec::new();
+    render_heatmap(&mut buf, &Matrix2D::new(3, 3, || 0), None, &SvgStyle::default()).unwrap();
+    let svg = String::from_utf8(buf).unwrap();
+    assert!(!svg.contains("data-retractions") && !svg.contains("<line"), "{svg}");
+}
+
+#[test]
+fn test_plan() {
+    let size = BoardSize::new(12, 12);
+    let mut buf = Vec::new();
+    render_plan(&mut buf, size, &crate::divide_and_conquer::plan(size), &SvgStyle::default()).unwrap();
+    let svg = String::from_utf8(buf).unwrap();
+    roxmltree::Document::parse(&svg).unwrap();
+
+    assert_eq!(svg.matches("data-sector=").count(), 4, "{svg}");
+    assert!(svg.contains("<rect x=\"10\" y=\"20\" width=\"60\" height=\"60\" fill=\"hsl(210, 70%, 80%)\" data-sector=\"0\" data-mode=\"closed\" />"), "{svg}");
+    assert!(svg.contains("data-sector=\"3\" data-mode=\"stretched-v\""), "{svg}");
+    assert!(svg.lines().any(|line| line.trim() == "4 sectors of a 12\u{d7}12 board"), "{svg}");
+    assert!(svg.rfind("data-sector").unwrap() < svg.find("url(#plan-grid)").unwrap(), "{svg}");
+}
+
+#[test]
+fn test_xml_escape() {
+    assert!(matches!(xml_escape("Knight's tour"), Cow::Borrowed("Knight's tour")));
+    assert_eq!(xml_escape("a < b && \"c\" > d"), "a &lt; b &amp;&amp; &quot;c&quot; &gt; d");
+}
+
+#[test]
+fn test_output_is_xml() {
+    let svg = render(&fixtures::closed(3, 3, &RING), Duration::from_millis(1500));
+    roxmltree::Document::parse(&svg).unwrap();
+
+    // values that aren't valid XML on their own are escaped, the markup around them isn't
+    let (writer, duration, attr) = (&mut Vec::new(), "<&> seconds", "\"/><script");
+    let res: Result<()> = (|| {
+        svg! { writer =>
+            <svg xmlns="http://www.w3.org/2000/svg" data-attr=#attr>
+                <text>#duration</text>
+                <text>#(format!("{duration}!"))</text>
+            </svg>
+        };
+        Ok(())
+    })();
+    res.unwrap();
+
+    let svg = String::from_utf8(writer.to_vec()).unwrap();
+    let doc = roxmltree::Document::parse(&svg).unwrap();
+    assert_eq!(doc.root_element().attribute("data-attr"), Some(attr));
+    let texts: Vec<_> = doc.descendants().filter(|node| node.has_tag_name("text")).map(|node| node.text().unwrap().trim()).collect();
+    assert_eq!(texts, ["<&> seconds", "<&> seconds!"]);
+}
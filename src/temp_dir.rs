@@ -0,0 +1,35 @@
+//! Directories for the tests that read and write files
+
+use std::{ops::Deref, path::{Path, PathBuf}};
+
+/// A directory of the test's own, removed with everything in it when dropped, even if the test fails
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates the directory, named after the test and the process so that parallel runs don't share it
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("knights_tour_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,29 @@
+use std::{ffi::OsString, io::IsTerminal};
+
+use crate::args::ColorChoice;
+
+/// Whether the board is colored as --color says. Without a file to write to, the board goes to stdout,
+/// which is colored for "auto" only if it is a terminal and NO_COLOR is unset or empty
+pub fn use_color(choice: ColorChoice, to_stdout: bool) -> bool {
+    let terminal = to_stdout && std::io::stdout().is_terminal();
+    decide(choice, terminal, std::env::var_os("NO_COLOR"))
+}
+
+fn decide(choice: ColorChoice, terminal: bool, no_color: Option<OsString>) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => terminal && no_color.is_none_or(|value| value.is_empty()),
+    }
+}
+
+#[test]
+fn test_decide() {
+    assert!(decide(ColorChoice::Auto, true, None));
+    assert!(decide(ColorChoice::Auto, true, Some("".into())));
+    assert!(!decide(ColorChoice::Auto, true, Some("1".into())));
+    assert!(!decide(ColorChoice::Auto, false, None));
+
+    assert!(decide(ColorChoice::Always, false, Some("1".into())));
+    assert!(!decide(ColorChoice::Never, true, None));
+}
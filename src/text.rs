@@ -0,0 +1,303 @@
+use std::io::{Result, Write};
+
+use crate::{
+    aliases::BoardIndex as Idx,
+    board_pos::BoardPos,
+    degree_map::DegreeMap,
+    divide_and_conquer::{Sector, SolveQuadrantMode},
+    tour::Tour,
+};
+
+/// Writes the tour as a numbered grid, the same as the text of [crate::board::Board], one row at a time.
+/// No [crate::board::Board] is built, so this also works for boards too large for one.
+/// If several knights cover the board together, each move number is prefixed with the number of the knight (e.g. "2:17")
+pub fn render_text(writer: &mut impl Write, tour: &Tour) -> Result<()> {
+    render(writer, tour, false)
+}
+
+/// Writes the same grid as [render_text], with the move numbers colored from blue for the first moves of a path to red for its last ones
+/// using the 256 colors of ANSI terminals. Dead squares stay blank
+pub fn render_text_colored(writer: &mut impl Write, tour: &Tour) -> Result<()> {
+    render(writer, tour, true)
+}
+
+/// The six steps from blue to red through purple of the color cube of 256 color terminals (blue decreasing as red increases)
+const GRADIENT: [u8; 6] = [21, 56, 91, 126, 161, 196];
+
+/// The color of the square with the given index into a path of the given length
+fn gradient(idx: usize, len: usize) -> u8 {
+    GRADIENT[idx * GRADIENT.len() / len.max(1)]
+}
+
+fn render(writer: &mut impl Write, tour: &Tour, color: bool) -> Result<()> {
+    use std::fmt::Write as _;
+
+    let (width, height) = (tour.size().width() as usize, tour.size().height());
+    // the knight is only shown when there is more than one
+    let several = tour.paths().len() > 1;
+    let max_len = if several {
+        tour.paths().iter().enumerate().map(|(knight, path)| digits(knight + 1) + 1 + digits(path.len())).max().unwrap_or(1)
+    } else {
+        digits(tour.len())
+    };
+    let (alive_edge, dead_edge) = ("-".repeat(max_len + 2), " ".repeat(max_len + 2));
+
+    // the line above (or below) a row, with a corner wherever one of the four squares around it is alive.
+    // It ends in a corner if the last square of the row it belongs to is alive
+    let mut border_line = String::with_capacity(width * (max_len + 3) + 1);
+    let mut border = |writer: &mut dyn Write, above: &[bool], below: &[bool], own: &[bool]| -> Result<()> {
+        border_line.clear();
+        for col in 0..width {
+            let alive = above[col] || below[col];
+            let left_alive = col > 0 && (above[col - 1] || below[col - 1]);
+            border_line.push(if alive || left_alive { '+' } else { ' ' });
+            border_line.push_str(if alive { &alive_edge } else { &dead_edge });
+        }
+
+        if own[width - 1] {
+            border_line.push('+');
+        }
+
+        writer.write_all(border_line.as_bytes())
+    };
+
+    // which squares of a row are alive is looked up once, and only two rows are kept at a time.
+    // Each row is put together in a buffer, and so is every label, as allocating for every cell is what makes large boards slow
+    let alive_row = |alive: &mut [bool], row: Idx| {
+        for (col, alive) in alive.iter_mut().enumerate() {
+            *alive = row < height && !tour.is_dead(BoardPos::new(col as Idx, row));
+        }
+    };
+    let outside = vec![false; width];
+    let (mut current, mut next) = (vec![false; width], vec![false; width]);
+    alive_row(&mut current, 0);
+    let mut line = String::with_capacity(width * (max_len + 3) + 1);
+    let mut label = String::with_capacity(max_len);
+    border(writer, &outside, &current, &current)?;
+    for row in 0..height {
+        line.clear();
+        for col in 0..width {
+            line.push_str(if current[col] || (col > 0 && current[col - 1]) { "| " } else { "  " });
+            let Some((path, idx)) = tour.visit(BoardPos::new(col as Idx, row)) else {
+                line.push_str(&dead_edge[1..]);
+                continue;
+            };
+
+            label.clear();
+            if several {
+                write!(label, "{}:{}", path + 1, idx + 1).unwrap();
+            } else {
+                write!(label, "{}", idx + 1).unwrap();
+            }
+
+            // the padding stays outside of the color, so that only the number is colored
+            line.extend(std::iter::repeat_n(' ', max_len - label.len()));
+            if color {
+                write!(line, "\x1b[38;5;{}m{label}\x1b[0m ", gradient(idx, tour.paths()[path].len())).unwrap();
+            } else {
+                line.push_str(&label);
+                line.push(' ');
+            }
+        }
+
+        if current[width - 1] {
+            line.push('|');
+        }
+
+        writer.write_all(b"\n")?;
+        writer.write_all(line.as_bytes())?;
+        alive_row(&mut next, row + 1);
+        writer.write_all(b"\n")?;
+        border(writer, &current, &next, &current)?;
+        std::mem::swap(&mut current, &mut next);
+    }
+
+    writeln!(writer)
+}
+
+/// How many digits the number is written with
+fn digits(number: usize) -> usize {
+    number.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// Writes the degree of every accessible square as a digit and a space for every inaccessible one, one row per line
+/// like the masks written by --emit-mask
+pub fn render_degree_map(writer: &mut impl Write, map: &DegreeMap) -> Result<()> {
+    let size = map.size();
+    for row in 0..size.height() {
+        let line: String = (0..size.width())
+            .map(|col| map.get(BoardPos::new(col, row)).map_or(' ', |degree| char::from_digit(degree as u32, 10).unwrap_or('+')))
+            .collect();
+        writeln!(writer, "{line}")?;
+    }
+
+    Ok(())
+}
+
+/// Writes the sectors the board is solved in as a table with a line per sector: its number, the square it starts on,
+/// its size, how its tour is found and the direction it is merged into the sector before it in
+pub fn render_plan(writer: &mut impl Write, sectors: &[Sector]) -> Result<()> {
+    let header = ["sector", "offset", "size", "mode", "merge"].map(String::from);
+    let rows: Vec<[String; 5]> = sectors.iter().enumerate().map(|(idx, sector)| {
+        let merge = match sector.mode {
+            SolveQuadrantMode::Closed => "-",
+            SolveQuadrantMode::Stretched(direction) if direction.is_horizontal() => "horizontal",
+            SolveQuadrantMode::Stretched(_) => "vertical",
+        };
+        [idx.to_string(), sector.pos.to_string(), sector.size.to_string(), sector.mode.to_string(), merge.to_string()]
+    }).collect();
+
+    let mut widths = [0; 5];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<_> = row.iter().zip(widths).map(|(cell, width)| format!("{cell:<width$}")).collect();
+        writeln!(writer, "{}", cells.join("  ").trim_end())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+use crate::move_graph::fixtures::{self, RING, SIX_BY_SIX};
+
+#[cfg(test)]
+fn render_to_string(graph: &crate::move_graph::MoveGraph) -> String {
+    let mut buf = Vec::new();
+    render_text(&mut buf, &Tour::from_graph(graph).unwrap()).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_text_matches_board() {
+    let graphs = [
+        fixtures::open(6, 6, &SIX_BY_SIX),
+        fixtures::open(3, 3, &RING),
+        fixtures::closed(3, 3, &RING),
+        crate::move_graph::MoveGraph::from_tour(4, 3, [BoardPos::new(1, 2), BoardPos::new(3, 1)]),
+    ];
+
+    for graph in &graphs {
+        assert_eq!(render_to_string(graph), format!("{}\n", Tour::from_graph(graph).unwrap().to_board()), "{graph:?}");
+    }
+}
+
+#[test]
+fn test_text_masked() {
+    // dead squares in the last row have no border below them
+    let graph = crate::move_graph::MoveGraph::from_tour(3, 2, [BoardPos::new(0, 0), BoardPos::new(2, 1)]);
+    let expected = [
+        "+---+       ",
+        "| 1 |       ",
+        "+---+   +---",
+        "        | 2 |",
+        "        +---+",
+        "",
+    ].join("\n");
+
+    assert_eq!(render_to_string(&graph), expected);
+}
+
+#[test]
+fn test_text_large_masked_board() {
+    use crate::{board::corner_radius::CornerRadius, board_size::BoardSize, leaper::Leaper};
+
+    let size = BoardSize::new(1200, 900);
+    let dead = CornerRadius::parse("100").unwrap().dead_squares(size);
+    let dead_in_first_row = dead.iter().filter(|pos| pos.row() == 0).count() / 2;
+
+    // a knight for every row, each moving along it one square at a time
+    let rows: Vec<Vec<_>> = (0..900)
+        .map(|row| (0..1200).filter(|&col| !dead.contains(&BoardPos::new(col, row))).map(|col| (col, row)).collect())
+        .collect();
+    let rows: Vec<&[_]> = rows.iter().map(Vec::as_slice).collect();
+    let graph = fixtures::paths(1200, 900, &rows).with_leaper(Leaper::new(0, 1).unwrap());
+    let mut buf = Vec::new();
+    render_text(&mut buf, &Tour::from_graph(&graph).unwrap()).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2 * 900 + 1);
+
+    // a row away from the corners is complete
+    let max_len = "900:1200".len();
+    let full_row: String = (1..=1200).map(|idx| format!("| {:>max_len$} ", format!("451:{idx}"))).collect();
+    let full_border = format!("{}+", format!("+{}", "-".repeat(max_len + 2)).repeat(1200));
+    assert_eq!(lines[2 * 450 + 1], format!("{full_row}|"));
+    assert_eq!(lines[2 * 450], full_border);
+
+    // the first row starts and ends with the dead squares of the rounded corners
+    let cell = max_len + 3;
+    let first_row = lines[1];
+    assert_eq!(first_row.len(), 1200 * cell);
+    assert!(first_row[..dead_in_first_row * cell].trim().is_empty());
+    assert!(first_row[dead_in_first_row * cell..].starts_with(&format!("| {:>max_len$} ", "1:1")));
+    let last = format!("1:{}", 1200 - 2 * dead_in_first_row);
+    assert!(first_row.ends_with(&format!("| {last:>max_len$} |{}", " ".repeat(dead_in_first_row * cell - 1))));
+}
+
+#[test]
+fn test_text_knights() {
+    let graph = fixtures::paths(3, 3, &[&RING[..5], &RING[5..]]);
+    let expected = [
+        "+-----+-----+-----+",
+        "| 1:1 | 1:4 | 2:2 |",
+        "+-----+-----+-----+",
+        "| 2:1 |     | 1:2 |",
+        "+-----+-----+-----+",
+        "| 1:3 | 2:3 | 1:5 |",
+        "+-----+-----+-----+",
+        "",
+    ].join("\n");
+
+    assert_eq!(render_to_string(&graph), expected);
+}
+
+#[test]
+fn test_text_colored() {
+    let graph = fixtures::open(3, 3, &RING);
+    let mut buf = Vec::new();
+    render_text_colored(&mut buf, &Tour::from_graph(&graph).unwrap()).unwrap();
+    let colored = String::from_utf8(buf).unwrap();
+
+    // the first move is blue, the last one red, and the center square isn't colored
+    assert!(colored.contains("| \x1b[38;5;21m1\x1b[0m |"), "{colored:?}");
+    assert!(colored.contains("| \x1b[38;5;196m8\x1b[0m |"), "{colored:?}");
+    assert_eq!(colored.matches("\x1b[0m").count(), 8, "{colored:?}");
+
+    // without the colors, it's the same grid
+    let plain = colored.split("\x1b[").map(|part| part.split_once('m').map_or(part, |(_, rest)| rest)).collect::<String>();
+    assert_eq!(plain, render_to_string(&graph));
+}
+
+#[test]
+fn test_gradient() {
+    assert_eq!((0..6).map(|idx| gradient(idx, 6)).collect::<Vec<_>>(), GRADIENT);
+    assert_eq!((gradient(0, 100), gradient(49, 100), gradient(99, 100)), (21, 91, 196));
+    assert_eq!(gradient(0, 1), 21);
+}
+
+#[test]
+fn test_degree_map() {
+    let dead = std::collections::HashSet::from([BoardPos::new(1, 1)]);
+    let map = DegreeMap::new(crate::board_size::BoardSize::new(4, 3), &dead, crate::args::Wrap::None, crate::leaper::Leaper::KNIGHT);
+    let mut buf = Vec::new();
+    render_degree_map(&mut buf, &map).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), "2331\n2 22\n2331\n");
+}
+
+#[test]
+fn test_plan_table() {
+    let mut buf = Vec::new();
+    render_plan(&mut buf, &crate::divide_and_conquer::plan(crate::board_size::BoardSize::new(12, 12))).unwrap();
+    let table = String::from_utf8(buf).unwrap();
+    let lines: Vec<_> = table.lines().collect();
+    assert_eq!(lines[0], "sector  offset  size  mode         merge", "{table}");
+    assert_eq!(lines[1], "0       A1      6x6   closed       -", "{table}");
+    assert_eq!(lines[2], "1       G1      6x6   stretched-h  horizontal", "{table}");
+    assert_eq!(lines[3], "2       A7      6x6   stretched-v  vertical", "{table}");
+    assert_eq!(lines.len(), 5, "{table}");
+}
@@ -0,0 +1,247 @@
+use crate::{
+    aliases::BoardIndexOverflow as IdxMath,
+    args::Wrap,
+    bitset::BitSet,
+    board::Board,
+    board_pos::BoardPos,
+    board_size::BoardSize,
+    leaper::Leaper,
+    move_graph::MoveGraph,
+    validation::Defect,
+};
+
+/// The squares the solvers visited, in order, one path per knight. This is what gets rendered and checked,
+/// the [MoveGraph] the tour was found in stays with the solvers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tour {
+    size: BoardSize,
+    wrap: Wrap,
+    leaper: Leaper,
+    paths: Vec<Vec<BoardPos>>,
+    closed: bool,
+    complete: bool,
+    // the path and the index within it of every visited square, row by row
+    visits: Vec<Option<(usize, usize)>>,
+}
+
+impl Tour {
+    /// Walks every path through the graph, checking the same as [crate::validation::validate] does for a single tour:
+    /// every move is a move of the piece, links are consistent in both directions and every linked square is visited exactly once
+    pub fn from_graph(graph: &MoveGraph) -> Result<Self, Defect> {
+        let size = graph.size();
+        let starts: Vec<_> = graph.paths().iter().filter_map(|path| path.start()).collect();
+        if starts.is_empty() {
+            return Err(Defect::Empty);
+        }
+
+        let mut visited = BitSet::new(size);
+        let mut paths = Vec::with_capacity(starts.len());
+        let mut closed = false;
+        for start in starts {
+            if !visited.insert(start) {
+                return Err(Defect::Revisited(start));
+            }
+
+            let mut path = vec![start];
+            let mut pos = start;
+            // the start of a warnsdorff path is marked by pointing back to itself
+            while let Some(next) = graph.node(pos).next().filter(|&next| next != pos) {
+                if !graph.is_move(pos, next) {
+                    return Err(Defect::IllegalMove(pos, next));
+                }
+
+                if graph.node(next).prev() != Some(pos) {
+                    return Err(Defect::BrokenLink(pos, next));
+                }
+
+                if next == start {
+                    closed = true;
+                    break;
+                }
+
+                if !visited.insert(next) {
+                    return Err(Defect::Revisited(next));
+                }
+
+                path.push(next);
+                pos = next;
+            }
+
+            paths.push(path);
+        }
+
+        // squares that link somewhere but weren't reached by walking the paths
+        for node in graph.nodes() {
+            let linked = node.next().is_some() || node.prev().is_some();
+            if linked && !visited.contains(node.pos()) {
+                return Err(Defect::Unvisited(node.pos()));
+            }
+        }
+
+        let mut visits = vec![None; size.width() as usize * size.height() as usize];
+        for (path_idx, path) in paths.iter().enumerate() {
+            for (idx, &pos) in path.iter().enumerate() {
+                visits[pos.row() as usize * size.width() as usize + pos.col() as usize] = Some((path_idx, idx));
+            }
+        }
+
+        // only a single path can lead back to where it started, the start of every other one isn't linked from anywhere
+        let closed = closed && paths.len() == 1;
+        Ok(Self { size, wrap: graph.wrap(), leaper: graph.leaper(), paths, closed, complete: true, visits })
+    }
+
+    /// Marks the tour as the longest path a search got to without finding a tour (--best-effort), or as a tour
+    pub fn with_complete(self, complete: bool) -> Self {
+        Self { complete, ..self }
+    }
+
+    /// Whether this is a tour rather than the longest path found instead of one. The squares left out are dead all the same
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn size(&self) -> BoardSize {
+        self.size
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// The piece that moved along the tour
+    pub fn leaper(&self) -> Leaper {
+        self.leaper
+    }
+
+    /// The squares in the order they are visited, one path after the other
+    pub fn iter(&self) -> impl Iterator<Item = BoardPos> + '_ {
+        self.paths.iter().flatten().copied()
+    }
+
+    /// The path of every knight, ordered row by row by the square it starts on
+    pub fn paths(&self) -> &[Vec<BoardPos>] {
+        &self.paths
+    }
+
+    /// Whether the last square is a move away from the first and is linked back to it. A cover by several knights never is
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// The number of squares visited
+    pub fn len(&self) -> usize {
+        self.paths.iter().map(Vec::len).sum()
+    }
+
+    /// Never the case, [Tour::from_graph] rejects graphs without any path
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// The number of moves made, which for a closed tour includes the one back to the start
+    pub fn moves(&self) -> usize {
+        self.len() - self.paths.len() + usize::from(self.closed)
+    }
+
+    /// The first square of the first path
+    pub fn start(&self) -> BoardPos {
+        self.paths[0][0]
+    }
+
+    /// The last square of the first path
+    pub fn end(&self) -> BoardPos {
+        *self.paths[0].last().unwrap()
+    }
+
+    /// The path the square is on and how many squares of it come before the square, or nothing for squares that weren't visited
+    pub fn visit(&self, pos: BoardPos) -> Option<(usize, usize)> {
+        self.visits.get(pos.row() as usize * self.size.width() as usize + pos.col() as usize).copied().flatten()
+    }
+
+    /// The square visited after the given one. The last square of a closed tour is followed by the first one
+    pub fn next(&self, pos: BoardPos) -> Option<BoardPos> {
+        let (path, idx) = self.visit(pos)?;
+        let path = &self.paths[path];
+        path.get(idx + 1).copied().or_else(|| self.closed.then_some(path[0]))
+    }
+
+    pub fn is_dead(&self, pos: BoardPos) -> bool {
+        self.visit(pos).is_none()
+    }
+
+    /// The squares of the board the tour doesn't visit, row by row
+    pub fn dead_squares(&self) -> impl Iterator<Item = BoardPos> + '_ {
+        let width = self.size.width();
+        (0..self.size.height()).flat_map(move |row| (0..width).map(move |col| BoardPos::new(col, row))).filter(|&pos| self.is_dead(pos))
+    }
+
+    /// The move of the piece leading from one square to the other, taking wrapped edges into account
+    pub fn move_offset(&self, from: BoardPos, to: BoardPos) -> Option<(IdxMath, IdxMath)> {
+        from.move_offset_to(to, self.size, self.wrap, self.leaper)
+    }
+
+    /// Numbers the squares in the order they are visited, counting on across the paths of several knights
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new(self.size.width(), self.size.height(), 0).with_dead_squares(self.dead_squares().collect());
+        for (number, pos) in (1..).zip(self.iter()) {
+            *board.at_mut(pos) = number;
+        }
+
+        board
+    }
+}
+
+#[cfg(test)]
+use crate::move_graph::fixtures::{self, CYLINDER, RING, SIX_BY_SIX};
+
+#[test]
+fn test_from_graph() {
+    let graph = fixtures::open(6, 6, &SIX_BY_SIX);
+    let tour = Tour::from_graph(&graph).unwrap();
+    assert_eq!(tour.iter().collect::<Vec<_>>(), SIX_BY_SIX.map(BoardPos::from));
+    assert_eq!((tour.len(), tour.moves(), tour.is_closed()), (36, 35, false));
+    assert_eq!((tour.start(), tour.end()), (BoardPos::new(0, 0), BoardPos::new(2, 3)));
+    assert_eq!(tour.next(BoardPos::new(2, 3)), None);
+    assert_eq!(tour.to_board(), graph.to_board());
+
+    let graph = fixtures::closed(3, 3, &RING);
+    let tour = Tour::from_graph(&graph).unwrap();
+    assert_eq!((tour.len(), tour.moves(), tour.is_closed()), (8, 8, true));
+    assert_eq!(tour.next(BoardPos::new(1, 2)), Some(BoardPos::new(0, 0)));
+    assert_eq!(tour.dead_squares().collect::<Vec<_>>(), [BoardPos::new(1, 1)]);
+    assert_eq!(tour.to_board(), graph.to_board());
+
+    let cylinder = fixtures::closed(5, 6, &CYLINDER).with_wrap(Wrap::Horizontal);
+    let tour = Tour::from_graph(&cylinder).unwrap();
+    assert_eq!((tour.len(), tour.wrap()), (30, Wrap::Horizontal));
+    assert_eq!(tour.move_offset(BoardPos::new(4, 0), BoardPos::new(1, 1)), Some((2, 1)));
+}
+
+#[test]
+fn test_from_graph_knights() {
+    let tour = Tour::from_graph(&fixtures::paths(3, 3, &[&RING[..5], &RING[5..]])).unwrap();
+    assert_eq!(tour.paths().len(), 2);
+    assert_eq!((tour.len(), tour.moves(), tour.is_closed()), (8, 6, false));
+    assert_eq!(tour.visit(BoardPos::new(0, 1)), Some((1, 0)));
+    assert_eq!(tour.next(BoardPos::new(2, 2)), None);
+    assert!(tour.is_dead(BoardPos::new(1, 1)));
+}
+
+#[test]
+fn test_from_graph_defects() {
+    assert_eq!(Tour::from_graph(&MoveGraph::new(4, 4)), Err(Defect::Empty));
+
+    // the same moves aren't a tour on the plain board
+    let illegal = Defect::IllegalMove(BoardPos::new(4, 0), BoardPos::new(1, 1));
+    assert_eq!(Tour::from_graph(&fixtures::closed(5, 6, &CYLINDER)), Err(illegal));
+
+    let mut graph = fixtures::open(6, 6, &SIX_BY_SIX);
+    *graph.node_mut(BoardPos::new(4, 0)).prev_mut() = None;
+    assert_eq!(Tour::from_graph(&graph), Err(crate::validation::validate(&graph).unwrap_err()));
+
+    // a ring on its own, without a start for any path to reach it from
+    let mut graph = fixtures::closed(6, 3, &RING);
+    *graph.node_mut(BoardPos::new(3, 0)).next_mut() = Some(BoardPos::new(5, 1));
+    *graph.node_mut(BoardPos::new(5, 1)).prev_mut() = Some(BoardPos::new(3, 0));
+    assert_eq!(Tour::from_graph(&graph), Err(Defect::Unvisited(BoardPos::new(0, 0))));
+}
@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use crate::{bitset::BitSet, board_pos::BoardPos, board_size::BoardSize, move_graph::MoveGraph};
+
+/// Summary of a valid tour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TourSummary {
+    pub squares: usize,
+    pub start: BoardPos,
+    pub end: BoardPos,
+    pub closed: bool,
+}
+
+/// The first problem found while walking a tour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Defect {
+    Empty,
+    IllegalMove(BoardPos, BoardPos),
+    BrokenLink(BoardPos, BoardPos),
+    Revisited(BoardPos),
+    Unvisited(BoardPos),
+}
+
+impl Display for Defect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the tour doesn't visit any squares"),
+            Self::IllegalMove(from, to) => write!(f, "{from} -> {to} is not a legal move"),
+            Self::BrokenLink(from, to) => write!(f, "{from} -> {to} is not linked back from {to}"),
+            Self::Revisited(pos) => write!(f, "{pos} is visited more than once"),
+            Self::Unvisited(pos) => write!(f, "{pos} is part of the graph but not of the tour"),
+        }
+    }
+}
+
+/// Walks the tour once, keeping only a single bit per square to track visited squares,
+/// and checks that every move is a move of the piece, that links are consistent in both directions
+/// and that every linked square is visited exactly once.
+pub fn validate(graph: &MoveGraph) -> Result<TourSummary, Defect> {
+    let size = BoardSize::new(graph.width(), graph.height());
+    let start = graph.tour().start().ok_or(Defect::Empty)?;
+
+    let mut visited = BitSet::new(size);
+    visited.insert(start);
+    let mut squares = 1;
+    let mut pos = start;
+    let mut closed = false;
+    while let Some(next) = graph.node(pos).next() {
+        if !graph.is_move(pos, next) {
+            return Err(Defect::IllegalMove(pos, next));
+        }
+
+        if graph.node(next).prev() != Some(pos) {
+            return Err(Defect::BrokenLink(pos, next));
+        }
+
+        if next == start {
+            closed = true;
+            break;
+        }
+
+        if !visited.insert(next) {
+            return Err(Defect::Revisited(next));
+        }
+
+        squares += 1;
+        pos = next;
+    }
+
+    // squares that link somewhere but weren't reached by walking the tour
+    for node in graph.nodes() {
+        let linked = node.next().is_some() || node.prev().is_some();
+        if linked && !visited.contains(node.pos()) {
+            return Err(Defect::Unvisited(node.pos()));
+        }
+    }
+
+    Ok(TourSummary { squares, start, end: pos, closed })
+}
+
+/// Straightforward implementation on top of a [std::collections::HashSet] to check the bitset based one against
+#[cfg(test)]
+fn validate_reference(graph: &MoveGraph) -> Result<TourSummary, Defect> {
+    use std::collections::HashSet;
+
+    let start = graph.tour().start().ok_or(Defect::Empty)?;
+    let mut visited = HashSet::from([start]);
+    let mut moves = vec![start];
+    let mut closed = false;
+    while let Some(next) = graph.node(*moves.last().unwrap()).next() {
+        let pos = *moves.last().unwrap();
+        if !graph.is_move(pos, next) {
+            return Err(Defect::IllegalMove(pos, next));
+        }
+
+        if graph.node(next).prev() != Some(pos) {
+            return Err(Defect::BrokenLink(pos, next));
+        }
+
+        if next == start {
+            closed = true;
+            break;
+        }
+
+        if !visited.insert(next) {
+            return Err(Defect::Revisited(next));
+        }
+
+        moves.push(next);
+    }
+
+    if let Some(node) = graph.nodes().find(|node| (node.next().is_some() || node.prev().is_some()) && !visited.contains(&node.pos())) {
+        return Err(Defect::Unvisited(node.pos()));
+    }
+
+    Ok(TourSummary { squares: moves.len(), start, end: *moves.last().unwrap(), closed })
+}
+
+#[cfg(test)]
+use crate::{args::Wrap, move_graph::fixtures::{self, CYLINDER, RING, SIX_BY_SIX}};
+
+#[test]
+fn test_valid_tours() {
+    let open = fixtures::open(6, 6, &SIX_BY_SIX);
+    let expected = TourSummary { squares: 36, start: BoardPos::new(0, 0), end: BoardPos::new(2, 3), closed: false };
+    assert_eq!(validate(&open), Ok(expected));
+    assert_eq!(validate_reference(&open), Ok(expected));
+
+    let closed = fixtures::closed(3, 3, &RING);
+    let expected = TourSummary { squares: 8, start: BoardPos::new(0, 0), end: BoardPos::new(1, 2), closed: true };
+    assert_eq!(validate(&closed), Ok(expected));
+    assert_eq!(validate_reference(&closed), Ok(expected));
+
+    assert_eq!(validate(&MoveGraph::new(4, 4)), Err(Defect::Empty));
+}
+
+#[test]
+fn test_cylinder_tour() {
+    let expected = TourSummary { squares: 30, start: BoardPos::new(0, 0), end: BoardPos::new(1, 2), closed: true };
+    let cylinder = fixtures::closed(5, 6, &CYLINDER).with_wrap(Wrap::Horizontal);
+    assert_eq!(validate(&cylinder), Ok(expected));
+    assert_eq!(validate_reference(&cylinder), Ok(expected));
+
+    // the same moves in the same orientation don't make a tour on the plain board, nor on a board wrapping the other way
+    let illegal = Err(Defect::IllegalMove(BoardPos::new(4, 0), BoardPos::new(1, 1)));
+    for wrap in [Wrap::None, Wrap::Vertical] {
+        let graph = fixtures::closed(5, 6, &CYLINDER).with_wrap(wrap);
+        assert_eq!(validate(&graph), illegal, "{wrap:?}");
+        assert_eq!(validate_reference(&graph), illegal, "{wrap:?}");
+    }
+}
+
+#[test]
+fn test_defects_match_reference() {
+    let pos = |col, row| BoardPos::new(col, row);
+    type Corruption = fn(&mut MoveGraph);
+    let corruptions: [(&str, Corruption); 4] = [
+        ("illegal move", |graph| {
+            // (2, 1) -> (4, 0) becomes (2, 1) -> (4, 1)
+            *graph.node_mut(BoardPos::new(2, 1)).next_mut() = Some(BoardPos::new(4, 1));
+        }),
+        ("broken link", |graph| {
+            *graph.node_mut(BoardPos::new(4, 0)).prev_mut() = None;
+        }),
+        ("loop back into the tour", |graph| {
+            // (0, 4) -> (1, 2) becomes (0, 4) -> (2, 5), which was visited two moves before.
+            // This is caught as soon as the original move to (2, 5) is checked, before ever reaching it twice
+            *graph.node_mut(BoardPos::new(0, 4)).next_mut() = Some(BoardPos::new(2, 5));
+            *graph.node_mut(BoardPos::new(2, 5)).prev_mut() = Some(BoardPos::new(0, 4));
+        }),
+        ("unvisited", |graph| {
+            // cut the tour short, leaving the rest of the squares dangling
+            *graph.node_mut(BoardPos::new(5, 3)).next_mut() = None;
+            *graph.node_mut(BoardPos::new(3, 2)).prev_mut() = None;
+        }),
+    ];
+
+    let expected = [
+        Defect::IllegalMove(pos(2, 1), pos(4, 1)),
+        Defect::BrokenLink(pos(2, 1), pos(4, 0)),
+        Defect::BrokenLink(pos(4, 4), pos(2, 5)),
+        Defect::Unvisited(pos(1, 0)),
+    ];
+
+    for ((name, corrupt), expected) in corruptions.into_iter().zip(expected) {
+        let mut graph = fixtures::open(6, 6, &SIX_BY_SIX);
+        corrupt(&mut graph);
+        assert_eq!(validate(&graph), Err(expected), "{name}");
+        assert_eq!(validate_reference(&graph), Err(expected), "{name}");
+    }
+}
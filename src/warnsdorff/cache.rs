@@ -1,18 +1,217 @@
-use std::{collections::HashMap, sync::OnceLock};
+use std::{collections::HashSet, fs, io, path::{Path, PathBuf}};
 
-use crate::{board_size::BoardSize, move_graph::{Direction, MoveGraph}};
+use crate::{
+    board_pos::{parse_board_pos, BoardPos},
+    board_size::BoardSize,
+    context::SolverContext,
+    divide_and_conquer::contract::{check_stretched, StretchedContract},
+    dprintln,
+    move_graph::{Direction, MoveGraph},
+};
 
-static mut STRETCHED_CACHE: OnceLock<HashMap<(BoardSize, Direction), MoveGraph>> = OnceLock::new();
+/// Bumped whenever the layout of the files changes, so that files of an older layout are solved again
+const FORMAT_VERSION: u32 = 1;
 
+/// Looks the sector up in the cache of the current [SolverContext], and on a miss in the --cache-dir, if there is one
 pub fn get_stretched_cached<'a>(size: BoardSize, direction: Direction) -> Option<&'a MoveGraph<'a>> {
-    let cache = unsafe { STRETCHED_CACHE.get()? };
-    cache.get(&(size, direction))
+    let cached = get_stretched_in_memory(size, direction);
+    if cached.is_some() {
+        return cached;
+    }
+
+    let path = SolverContext::with_current(|context| context.cache_dir())?.join(file_name(size, direction));
+    let graph = match load(&path, size, direction) {
+        Ok(Some(graph)) => graph,
+        Ok(None) => return None,
+        Err(reason) => {
+            dprintln!(1 => "Ignoring the cached sector in {}: {reason}.", path.display());
+            return None;
+        },
+    };
+
+    let graph = leak(graph);
+    SolverContext::with_current(|context| context.stretched_cache().lock().unwrap().insert((size, direction), graph));
+    Some(graph)
+}
+
+/// Looks the sector up in the cache of the current [SolverContext] only, for sectors that must not be taken from a file
+pub fn get_stretched_in_memory<'a>(size: BoardSize, direction: Direction) -> Option<&'a MoveGraph<'a>> {
+    SolverContext::with_current(|context| context.stretched_cache().lock().unwrap().get(&(size, direction)).copied())
 }
 
+/// Only graphs keeping the [StretchedContract] can be cached, as every later stretched sector of the same size and direction is taken from here.
+/// With a --cache-dir, the graph is written there as well for later runs to find
 pub fn insert_stretched_cache(size: BoardSize, direction: Direction, graph: MoveGraph<'static>) {
-    let cache = unsafe {
-        STRETCHED_CACHE.get_or_init(HashMap::new);
-        STRETCHED_CACHE.get_mut().unwrap()
+    if let Err(violation) = check_stretched(&graph, &StretchedContract::new(size, direction)) {
+        panic!("Cannot cache the {size} {direction:?} sector, it breaks its contract: {violation}\n{graph:?}");
+    }
+
+    if let Some(dir) = SolverContext::with_current(|context| context.cache_dir()) {
+        let path = dir.join(file_name(size, direction));
+        if let Err(e) = store(&path, size, direction, &graph) {
+            eprintln!("Could not write the {size} sector to the cache in {}: {e}", dir.display());
+        }
+    }
+
+    let graph = leak(graph);
+    SolverContext::with_current(|context| context.stretched_cache().lock().unwrap().insert((size, direction), graph));
+}
+
+// cached graphs are leaked so that references handed out stay valid for the remainder of the program,
+// regardless of how many more entries get inserted afterwards
+fn leak(graph: MoveGraph<'static>) -> &'static MoveGraph<'static> {
+    Box::leak(Box::new(graph))
+}
+
+/// Each version of the program has files of its own, as a different solver may have found different sectors
+fn file_name(size: BoardSize, direction: Direction) -> String {
+    format!("stretched-{size}-{}-{}.txt", direction_name(direction), env!("CARGO_PKG_VERSION"))
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    if direction.is_horizontal() { "horizontal" } else { "vertical" }
+}
+
+/// The first line names the layout, the version of the program and the sector, and ends in the checksum of the second line.
+/// The second line lists the squares in the order they are visited, as --prefix-moves reads them
+fn contents(size: BoardSize, direction: Direction, squares: &str) -> String {
+    format!(
+        "knights_tour-cache {FORMAT_VERSION} {} stretched {size} {} {:08x}\n{squares}\n",
+        env!("CARGO_PKG_VERSION"),
+        direction_name(direction),
+        checksum(squares.as_bytes()),
+    )
+}
+
+/// CRC-32, as used by zip and png
+fn checksum(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 })
+    })
+}
+
+/// Writes the sector to a temporary file first, so that other runs sharing the directory never read half a file.
+/// Sectors are only solved when the file is missing or was rejected by [load], so an existing file is replaced
+fn store(path: &Path, size: BoardSize, direction: Direction, graph: &MoveGraph) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let squares: Vec<_> = graph.tour().map(|pos| pos.to_string()).collect();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".tmp-{}", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents(size, direction, &squares.join(",")))?;
+    fs::rename(&tmp, path)
+}
+
+/// Reads a sector written by [store]. Anything that isn't a complete path keeping the contract of the sector is rejected,
+/// so that a damaged or outdated file is solved again instead of breaking the tour
+fn load(path: &Path, size: BoardSize, direction: Direction) -> Result<Option<MoveGraph<'static>>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let (header, squares) = text.split_once('\n').ok_or("it ends in the header")?;
+    let squares = squares.strip_suffix('\n').ok_or("it ends in the squares")?;
+    let fields: Vec<_> = header.split(' ').collect();
+    let [magic, format, version, kind, sector_size, sector_direction, sum] = fields[..] else {
+        return Err("it is not a cached sector".to_string());
     };
-    cache.insert((size, direction), graph);
-}
\ No newline at end of file
+    if magic != "knights_tour-cache" {
+        return Err("it is not a cached sector".to_string());
+    }
+    if format != FORMAT_VERSION.to_string() {
+        return Err(format!("it is in format {format}, expected {FORMAT_VERSION}"));
+    }
+    if version != env!("CARGO_PKG_VERSION") {
+        return Err(format!("it was written by version {version}"));
+    }
+    if (kind, sector_size, sector_direction) != ("stretched", size.to_string().as_str(), direction_name(direction)) {
+        return Err("it is for a different sector".to_string());
+    }
+    if u32::from_str_radix(sum, 16) != Ok(checksum(squares.as_bytes())) {
+        return Err("its checksum doesn't match the squares".to_string());
+    }
+
+    let squares = squares
+        .split(',')
+        .map(parse_board_pos)
+        .collect::<Result<Vec<BoardPos>, _>>()?;
+    let distinct: HashSet<_> = squares.iter().collect();
+    if squares.len() != size.area() as usize || distinct.len() != squares.len() || !squares.iter().all(|pos| size.fits(*pos)) {
+        return Err("it doesn't visit every square once".to_string());
+    }
+
+    let graph = MoveGraph::from_tour(size.width(), size.height(), squares);
+    check_stretched(&graph, &StretchedContract::new(size, direction)).map_err(|violation| violation.to_string())?;
+    Ok(Some(graph))
+}
+
+#[cfg(test)]
+use crate::temp_dir::TempDir;
+
+#[test]
+fn test_disk_cache() {
+    use super::{solve_internal, Mode, StructureMode};
+
+    let dir = TempDir::new("cache");
+    let (size, direction) = (BoardSize::new(6, 6), Direction::Horizontal);
+    let with_dir = || SolverContext::new().with_cache_dir(Some(dir.to_path_buf()));
+
+    let solve = || with_dir().run(|| solve_internal(size, Mode::Structured(StructureMode::Stretched(direction)))).unwrap();
+
+    // solved by one run, found by the next
+    let (solved, _) = solve();
+    let path = dir.join(format!("stretched-6x6-horizontal-{}.txt", env!("CARGO_PKG_VERSION")));
+    let text = fs::read_to_string(&path).unwrap();
+    let prefix = format!("knights_tour-cache 1 {} stretched 6x6 horizontal ", env!("CARGO_PKG_VERSION"));
+    assert!(text.starts_with(&prefix), "{text}");
+    let loaded = with_dir().run(|| get_stretched_cached(size, direction).map(|graph| graph.tour().collect::<Vec<_>>()));
+    assert_eq!(loaded, Some(solved.tour().collect()));
+
+    // without the directory, or for a different sector, there is nothing to find
+    assert!(SolverContext::new().run(|| get_stretched_cached(size, direction)).is_none());
+    assert!(with_dir().run(|| get_stretched_cached(size, Direction::Vertical)).is_none());
+
+    // damaged files are ignored, and replaced once the sector is solved again
+    let (header, squares) = text.split_once('\n').unwrap();
+    let squares = squares.trim_end();
+    let (first, rest) = squares.split_once(',').unwrap();
+    let (second, rest) = rest.split_once(',').unwrap();
+    let flipped = |offset: usize| {
+        let mut bytes = text.clone().into_bytes();
+        bytes[offset] ^= 1;
+        String::from_utf8(bytes).unwrap()
+    };
+    let mut damaged: Vec<_> = [0, 1, prefix.len(), header.len() - 1, header.len(), header.len() + 1, text.len() / 2, text.len() - 1]
+        .into_iter()
+        .map(|len| text[..len].to_string())
+        .collect();
+    damaged.extend([header.len() + 1, header.len() + 4, text.len() / 2, text.len() - 2].map(flipped));
+    damaged.extend([
+        text.replacen("knights_tour-cache 1", "knights_tour-cache 2", 1),
+        text.replacen(env!("CARGO_PKG_VERSION"), "0.0.0-old", 1),
+        text.replacen("6x6", "6x8", 1),
+        text.replacen("horizontal", "vertical", 1),
+        // with a matching checksum, only checking the squares finds these
+        contents(size, direction, &format!("{second},{first},{rest}")),
+        contents(size, direction, &format!("{first},{first},{rest}")),
+        contents(size, direction, &squares[..squares.len() / 2]),
+        contents(size, direction, "A1,??"),
+    ]);
+    for damaged in damaged {
+        fs::write(&path, &damaged).unwrap();
+        assert!(with_dir().run(|| get_stretched_cached(size, direction)).is_none(), "{damaged}");
+        solve();
+        assert_eq!(fs::read_to_string(&path).unwrap(), text, "{damaged}");
+    }
+}
+
+#[test]
+fn test_checksum() {
+    assert_eq!(checksum(b""), 0);
+    assert_eq!(checksum(b"123456789"), 0xCBF4_3926);
+}
@@ -0,0 +1,215 @@
+use std::{collections::HashSet, time::{Duration, Instant}};
+
+use crate::{
+    aliases::BoardIndexOverflow as IdxMath,
+    args::{InputArgs, TieBreak, Wrap},
+    board_pos::BoardPos,
+    board_size::BoardSize,
+    dprintln,
+    knight::Knight,
+    leaper::Leaper,
+    move_graph::MoveGraph,
+    summary,
+};
+
+use super::{degrees::Degrees, effective_mask, live_squares};
+
+/// How often covering the board may backtrack before it is given up on
+const COVER_BACKTRACK_LIMIT: usize = 100_000;
+
+/// Covers the board with the paths of several knights that don't share any squares. The knights take turns moving,
+/// each picking its move with the Warnsdorff heuristic, and a knight that can't move any more sits out until backtracking frees it
+pub fn solve<'a>(args: &InputArgs, knights: usize) -> Option<(Duration, MoveGraph<'a>)> {
+    let Some((dead_squares, size)) = effective_mask(args) else {
+        summary::record(|metrics| metrics.input_error = true);
+        return None;
+    };
+
+    let warnsdorff = args.warnsdorff.as_ref()?;
+    let cover = Cover {
+        size,
+        dead_squares: &dead_squares,
+        wrap: warnsdorff.wrap,
+        leaper: warnsdorff.piece,
+        lookahead: warnsdorff.lookahead,
+        tie_break: warnsdorff.tie_break,
+    };
+
+    let now = Instant::now();
+    let starts = cover.spread_starts(knights);
+    if starts.len() < knights {
+        println!("Cannot place {knights} {}s, the board only has {} accessible squares.", cover.leaper, starts.len());
+        return None;
+    }
+
+    let Some(graph) = cover.search(&starts) else {
+        println!("No cover of this board by {knights} {}s found.", cover.leaper);
+        return None;
+    };
+
+    Some((now.elapsed(), graph))
+}
+
+struct Cover<'a> {
+    size: BoardSize,
+    dead_squares: &'a HashSet<BoardPos>,
+    wrap: Wrap,
+    leaper: Leaper,
+    lookahead: u8,
+    tie_break: TieBreak,
+}
+
+impl Cover<'_> {
+    /// Picks the squares the knights start on: each one as far from the earlier ones as possible, then like --starting-pos auto
+    /// does, the one with the fewest onward moves, topmost and leftmost. Returns fewer squares if there are fewer accessible ones
+    fn spread_starts(&self, knights: usize) -> Vec<BoardPos> {
+        let mut degrees = Degrees::new(self.size, self.wrap, self.leaper, |pos| self.dead_squares.contains(&pos));
+        let mut starts: Vec<BoardPos> = Vec::with_capacity(knights);
+        let distance = |a: BoardPos, b: BoardPos| {
+            let (col, row) = (a.col() as IdxMath - b.col() as IdxMath, a.row() as IdxMath - b.row() as IdxMath);
+            col * col + row * row
+        };
+
+        while starts.len() < knights {
+            let Some(next) = live_squares(self.size, self.dead_squares)
+                .filter(|pos| !starts.contains(pos))
+                .min_by_key(|&pos| (-starts.iter().map(|&start| distance(pos, start)).min().unwrap_or(0), degrees.get(pos))) else {
+                break;
+            };
+
+            degrees.visit(next);
+            starts.push(next);
+        }
+
+        starts
+    }
+
+    fn search<'a>(&self, starts: &[BoardPos]) -> Option<MoveGraph<'a>> {
+        let knights = starts.len();
+        let mut graph = MoveGraph::new(self.size.width(), self.size.height()).with_wrap(self.wrap).with_leaper(self.leaper);
+        let mut degrees = Degrees::new(self.size, self.wrap, self.leaper, |pos| self.dead_squares.contains(&pos));
+        let mut heads: Vec<_> = starts.iter().map(|&start| Knight::new(start, self.size, self.wrap, self.leaper)).collect();
+        for &start in starts {
+            *graph.node_mut(start).prev_mut() = Some(start); // mark start as visited and start
+            degrees.visit(start);
+        }
+
+        let alive = live_squares(self.size, self.dead_squares).count();
+        let mut visited = knights;
+
+        // the knight that made each move, and which of the options it was
+        let mut moves: Vec<(usize, usize)> = Vec::new();
+        let mut skip = 0;
+        let mut count: usize = 0;
+        let mut backtracks: usize = 0;
+        let mut result = None;
+        while backtracks <= COVER_BACKTRACK_LIMIT {
+            if visited == alive {
+                result = Some(graph);
+                break;
+            }
+
+            count += 1;
+            let stranded = moves.last().is_some_and(|&(knight, _)| self.strands_squares(&graph, &degrees, &heads, knight));
+            let turn = moves.last().map_or(0, |&(knight, _)| (knight + 1) % knights);
+            let next_move = if stranded { None } else { self.options(&graph, &degrees, &heads, turn).nth(skip) };
+
+            if let Some((knight, next)) = next_move {
+                let from = heads[knight].position();
+                *graph.node_mut(from).next_mut() = Some(next);
+                *graph.node_mut(next).prev_mut() = Some(from);
+                heads[knight].update_position(next);
+                degrees.visit(next);
+                visited += 1;
+                moves.push((knight, skip));
+                skip = 0;
+                continue;
+            }
+
+            let Some((knight, option)) = moves.pop() else {
+                break;
+            };
+
+            backtracks += 1;
+            let pos = heads[knight].position();
+            let prev = graph.node(pos).prev().unwrap();
+            *graph.node_mut(pos).prev_mut() = None;
+            *graph.node_mut(prev).next_mut() = None;
+            heads[knight].update_position(prev);
+            degrees.leave(pos);
+            visited -= 1;
+            skip = option + 1;
+            dprintln!(3 => { count, knight = knight + 1, pos = %prev } "Move #{count}: knight {} returns to {prev}", knight + 1);
+        }
+
+        if result.is_none() && backtracks > COVER_BACKTRACK_LIMIT {
+            dprintln!(1 => { backtracks } "Giving up on covering the board after {backtracks} backtracks.");
+        }
+
+        summary::record(|metrics| {
+            metrics.iterations += count as u64;
+            metrics.backtracks += backtracks as u64;
+        });
+
+        result
+    }
+
+    /// The moves of all knights, the knight whose turn it is first and the others in the order they take their turns
+    fn options<'b>(&'b self, graph: &'b MoveGraph, degrees: &'b Degrees, heads: &'b [Knight], turn: usize) -> impl Iterator<Item = (usize, BoardPos)> + 'b {
+        let reachable = |_: BoardPos, pos: BoardPos| !self.dead_squares.contains(&pos) && graph.node(pos).prev().is_none();
+        (0..heads.len()).map(move |offset| (turn + offset) % heads.len()).flat_map(move |knight| {
+            let moves = heads[knight].get_possible_moves(&reachable, |pos, moves_ahead| degrees.onward_moves(pos, moves_ahead), self.lookahead, self.tie_break);
+            moves.into_iter().map(move |pos| (knight, pos))
+        })
+    }
+
+    /// Whether the last move of the knight left squares behind that no knight can get to any more:
+    /// unvisited squares next to the square it came from, with no unvisited squares or knights next to them
+    fn strands_squares(&self, graph: &MoveGraph, degrees: &Degrees, heads: &[Knight], knight: usize) -> bool {
+        let neighbors = |pos: BoardPos| self.leaper.moves().filter_map(move |(col, row)| pos.try_translate_wrapping(col, row, self.size, self.wrap));
+        let Some(from) = graph.node(heads[knight].position()).prev() else {
+            return false;
+        };
+
+        neighbors(from)
+            .filter(|pos| !self.dead_squares.contains(pos) && graph.node(*pos).prev().is_none())
+            .any(|pos| degrees.get(pos) == 0 && !neighbors(pos).any(|neighbor| heads.iter().any(|head| head.position() == neighbor)))
+    }
+}
+
+#[cfg(test)]
+fn cover(args: &[&str]) -> Option<MoveGraph<'static>> {
+    let args = crate::args::Args::parse_from(["knights_tour", "-w"].iter().chain(args));
+    let knights = args.input.warnsdorff.as_ref().unwrap().knights;
+    solve(&args.input, knights).map(|(_, graph)| graph)
+}
+
+#[test]
+fn test_two_knights_cover_the_board() {
+    let graph = cover(&["-s", "8", "--knights", "2"]).unwrap();
+    let paths: Vec<Vec<_>> = graph.paths().into_iter().map(|path| path.collect()).collect();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(paths.iter().map(Vec::len).sum::<usize>(), 64);
+
+    let mut seen = HashSet::new();
+    for path in &paths {
+        assert!(path.iter().all(|pos| seen.insert(*pos)), "{graph:?}");
+        assert!(path.windows(2).all(|pair| graph.is_move(pair[0], pair[1])), "{graph:?}");
+    }
+}
+
+#[test]
+fn test_spread_starts() {
+    let dead = HashSet::new();
+    let cover = Cover { size: BoardSize::new(8, 8), dead_squares: &dead, wrap: Wrap::None, leaper: Leaper::KNIGHT, lookahead: 1, tie_break: TieBreak::None };
+
+    // the first knight starts where --starting-pos auto would, the second one in the opposite corner
+    assert_eq!(cover.spread_starts(2), [BoardPos::ZERO, BoardPos::new(7, 7)]);
+    assert_eq!(cover.spread_starts(4), [BoardPos::ZERO, BoardPos::new(7, 7), BoardPos::new(7, 0), BoardPos::new(0, 7)]);
+    assert_eq!(cover.spread_starts(65).len(), 64);
+}
+
+#[test]
+fn test_not_enough_squares() {
+    assert!(cover(&["-s", "2", "--knights", "5"]).is_none());
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use crate::{args::Wrap, board::matrix2d::Matrix2D, board_pos::BoardPos, board_size::BoardSize, leaper::Leaper};
+
+/// The number of unvisited squares a move of the piece away from each square, updated as squares are visited and left again,
+/// so that ordering the moves doesn't need to look at the neighbors of every candidate
+pub struct Degrees {
+    counts: Matrix2D<u8>,
+    // squares that are never counted, even if the knight does end up on them
+    excluded: Matrix2D<bool>,
+    visited: Matrix2D<bool>,
+    size: BoardSize,
+    wrap: Wrap,
+    leaper: Leaper,
+}
+
+impl Degrees {
+    /// Counts the neighbors of every square, leaving out the squares that can never be moved to
+    pub fn new(size: BoardSize, wrap: Wrap, leaper: Leaper, excluded: impl Fn(BoardPos) -> bool) -> Self {
+        let mut res = Degrees {
+            counts: Matrix2D::new(size.width(), size.height(), || 0),
+            excluded: Matrix2D::new(size.width(), size.height(), || false),
+            visited: Matrix2D::new(size.width(), size.height(), || false),
+            size,
+            wrap,
+            leaper,
+        };
+        for col in 0..size.width() {
+            for row in 0..size.height() {
+                let pos = BoardPos::new(col, row);
+                *res.excluded.at_mut(pos) = excluded(pos);
+            }
+        }
+
+        for col in 0..size.width() {
+            for row in 0..size.height() {
+                let pos = BoardPos::new(col, row);
+                let count = res.neighbors(pos).filter(|neighbor| !res.excluded.at(*neighbor)).count();
+                *res.counts.at_mut(pos) = count as u8;
+            }
+        }
+
+        res
+    }
+
+    pub fn get(&self, pos: BoardPos) -> usize {
+        *self.counts.at(pos) as usize
+    }
+
+    pub fn visit(&mut self, pos: BoardPos) {
+        *self.visited.at_mut(pos) = true;
+        if *self.excluded.at(pos) {
+            return;
+        }
+
+        for neighbor in self.neighbors(pos).collect::<Vec<_>>() {
+            *self.counts.at_mut(neighbor) -= 1;
+        }
+    }
+
+    pub fn leave(&mut self, pos: BoardPos) {
+        *self.visited.at_mut(pos) = false;
+        if *self.excluded.at(pos) {
+            return;
+        }
+
+        for neighbor in self.neighbors(pos).collect::<Vec<_>>() {
+            *self.counts.at_mut(neighbor) += 1;
+        }
+    }
+
+    /// The number of ways to make `moves_ahead` moves from the square onto unvisited squares, not taking into account
+    /// that the earlier moves would visit squares themselves. 1 move ahead is the degree of the square
+    pub fn onward_moves(&self, pos: BoardPos, moves_ahead: u8) -> usize {
+        if moves_ahead <= 1 {
+            return self.get(pos);
+        }
+
+        // squares the knight could be on after each move, with the number of ways to get there
+        let mut frontier = HashMap::from([(pos, 1)]);
+        for _ in 1..moves_ahead {
+            let mut next = HashMap::new();
+            for (pos, ways) in frontier {
+                for neighbor in self.neighbors(pos).filter(|neighbor| !self.excluded.at(*neighbor) && !self.visited.at(*neighbor)) {
+                    *next.entry(neighbor).or_insert(0) += ways;
+                }
+            }
+            frontier = next;
+        }
+
+        frontier.into_iter().map(|(pos, ways)| ways * self.get(pos)).sum()
+    }
+
+    fn neighbors(&self, pos: BoardPos) -> impl Iterator<Item = BoardPos> {
+        let (size, wrap) = (self.size, self.wrap);
+        self.leaper.moves().filter_map(move |(col, row)| pos.try_translate_wrapping(col, row, size, wrap))
+    }
+}
+
+#[test]
+fn test_degrees() {
+    let size = BoardSize::new(5, 5);
+    let mut degrees = Degrees::new(size, Wrap::None, Leaper::KNIGHT, |pos| pos == BoardPos::new(1, 2));
+    assert_eq!(degrees.get(BoardPos::new(0, 0)), 1);
+    assert_eq!(degrees.get(BoardPos::new(2, 2)), 8);
+    // B3 is excluded
+    assert_eq!(degrees.get(BoardPos::new(2, 0)), 3);
+
+    degrees.visit(BoardPos::new(0, 0));
+    assert_eq!(degrees.get(BoardPos::new(2, 1)), 5);
+    assert_eq!(degrees.get(BoardPos::new(1, 2)), 5);
+
+    // visiting an excluded square doesn't change anything, as it was never counted
+    degrees.visit(BoardPos::new(1, 2));
+    assert_eq!(degrees.get(BoardPos::new(0, 0)), 1);
+
+    degrees.leave(BoardPos::new(0, 0));
+    assert_eq!(degrees.get(BoardPos::new(2, 1)), 6);
+
+    // on a torus, every square has all eight neighbors
+    let degrees = Degrees::new(size, Wrap::Both, Leaper::KNIGHT, |_| false);
+    assert_eq!(degrees.get(BoardPos::new(0, 0)), 8);
+}
+
+#[test]
+fn test_onward_moves() {
+    let size = BoardSize::new(5, 5);
+    let mut degrees = Degrees::new(size, Wrap::None, Leaper::KNIGHT, |_| false);
+    assert_eq!(degrees.onward_moves(BoardPos::new(0, 0), 1), 2);
+    // B3 has 6 neighbors and C2 has 6 as well
+    assert_eq!(degrees.onward_moves(BoardPos::new(0, 0), 2), 12);
+
+    degrees.visit(BoardPos::new(0, 0));
+    assert_eq!(degrees.onward_moves(BoardPos::new(0, 0), 2), 10);
+    // B3 is no longer an option, C2 doesn't neighbor it
+    degrees.visit(BoardPos::new(1, 2));
+    assert_eq!(degrees.onward_moves(BoardPos::new(0, 0), 2), 5);
+}
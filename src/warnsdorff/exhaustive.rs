@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+
+use crate::{
+    args::Wrap,
+    board_pos::BoardPos,
+    board_size::BoardSize,
+    dprintln,
+    leaper::Leaper,
+    move_graph::MoveGraph,
+    summary,
+};
+
+use super::live_squares;
+
+/// How many steps the exhaustive search may take before it is given up on
+pub const EXHAUSTIVE_STEP_LIMIT: usize = 50_000_000;
+
+pub enum Outcome<'a> {
+    Found(MoveGraph<'a>),
+    /// Every path was tried, there is no tour
+    Proven,
+    /// The search ran out of steps before trying every path
+    OutOfBudget,
+}
+
+/// A depth first search trying the moves of every square in the same fixed order, without any heuristics or pruning,
+/// so that not finding a tour proves that there is none
+pub struct Exhaustive<'a> {
+    pub size: BoardSize,
+    pub dead_squares: &'a HashSet<BoardPos>,
+    pub wrap: Wrap,
+    pub leaper: Leaper,
+}
+
+impl Exhaustive<'_> {
+    /// Searches for a tour starting with the given squares, which are never taken back
+    pub fn search<'a>(&self, prefix: &[BoardPos], closed: bool, budget: usize) -> Outcome<'a> {
+        let Some(&start) = prefix.first() else {
+            return Outcome::Proven;
+        };
+
+        let mut graph = MoveGraph::new(self.size.width(), self.size.height()).with_wrap(self.wrap).with_leaper(self.leaper);
+        *graph.node_mut(start).prev_mut() = Some(start); // mark start as visited and start
+        for pair in prefix.windows(2) {
+            *graph.node_mut(pair[0]).next_mut() = Some(pair[1]);
+            *graph.node_mut(pair[1]).prev_mut() = Some(pair[0]);
+        }
+
+        let moves: Vec<_> = self.leaper.moves().collect();
+        let alive = live_squares(self.size, self.dead_squares).count();
+        let is_move = |from: BoardPos, to: BoardPos| from.move_offset_to(to, self.size, self.wrap, self.leaper).is_some();
+
+        let mut path = prefix.to_vec();
+        // the next of the moves to try from each square of the path
+        let mut options = vec![0; path.len()];
+        let mut steps: usize = 0;
+        let mut backtracks: usize = 0;
+        let outcome = loop {
+            let current = *path.last().unwrap();
+            if path.len() == alive && (!closed || is_move(current, start)) {
+                if closed {
+                    *graph.node_mut(current).next_mut() = Some(start);
+                    *graph.node_mut(start).prev_mut() = Some(current);
+                }
+
+                break Outcome::Found(graph);
+            }
+
+            steps += 1;
+            if steps > budget {
+                break Outcome::OutOfBudget;
+            }
+
+            let option = options.last_mut().unwrap();
+            let next = moves[*option..].iter().enumerate().find_map(|(idx, &(col, row))| {
+                current.try_translate_wrapping(col, row, self.size, self.wrap)
+                    .filter(|pos| !self.dead_squares.contains(pos) && graph.node(*pos).prev().is_none())
+                    .map(|pos| (idx, pos))
+            });
+
+            if let Some((idx, next)) = next {
+                *option += idx + 1;
+                *graph.node_mut(current).next_mut() = Some(next);
+                *graph.node_mut(next).prev_mut() = Some(current);
+                path.push(next);
+                options.push(0);
+                continue;
+            }
+
+            if path.len() == prefix.len() {
+                break Outcome::Proven;
+            }
+
+            backtracks += 1;
+            path.pop();
+            options.pop();
+            *graph.node_mut(current).prev_mut() = None;
+            *graph.node_mut(*path.last().unwrap()).next_mut() = None;
+        };
+
+        if let Outcome::OutOfBudget = outcome {
+            dprintln!(1 => { steps } "Giving up on the exhaustive search after {steps} steps.");
+        }
+
+        summary::record(|metrics| {
+            metrics.iterations += steps as u64;
+            metrics.backtracks += backtracks as u64;
+        });
+
+        outcome
+    }
+}
+
+/// The most accessible squares --count-solutions takes on. Counting the tours of a board takes time exponential in its squares,
+/// 6x6 already takes a while
+pub const COUNT_LIMIT: usize = 40;
+
+/// Which tours --count-solutions counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CountedTours {
+    /// Every tour, open or closed, once for each square it starts on and each direction it runs in
+    /// (1728 on 5x5, 6637920 on 6x6)
+    All,
+    /// Closed tours, once each no matter where they start or which way round they run (9862 on 6x6)
+    Closed,
+}
+
+/// Counts the tours of the board by trying every path, without skipping any of the moves Warnsdorff would.
+/// Boards with more than [COUNT_LIMIT] accessible squares are the caller's to reject
+pub fn count_tours(size: BoardSize, dead_squares: &HashSet<BoardPos>, wrap: Wrap, leaper: Leaper, tours: CountedTours) -> u64 {
+    let squares: Vec<_> = live_squares(size, dead_squares).collect();
+    // the moves between the accessible squares, by index. Wrapping can make two moves land on the same square on small boards
+    let neighbors: Vec<Vec<usize>> = squares.iter().map(|&pos| {
+        let mut targets: Vec<_> = leaper.moves()
+            .filter_map(|(col, row)| pos.try_translate_wrapping(col, row, size, wrap))
+            .filter_map(|target| squares.iter().position(|&square| square == target))
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        targets
+    }).collect();
+
+    let mut counter = Counter { neighbors: &neighbors, visited: vec![false; squares.len()], steps: 0 };
+    let count = match tours {
+        CountedTours::All => (0..squares.len()).map(|start| counter.count(start, start, 1, false)).sum(),
+        // every closed tour passes the first square, in both directions
+        CountedTours::Closed if !squares.is_empty() => counter.count(0, 0, 1, true) / 2,
+        CountedTours::Closed => 0,
+    };
+
+    summary::record(|metrics| metrics.iterations += counter.steps);
+    count
+}
+
+struct Counter<'a> {
+    neighbors: &'a [Vec<usize>],
+    visited: Vec<bool>,
+    steps: u64,
+}
+
+impl Counter<'_> {
+    /// Counts the ways to visit every square not yet visited, continuing the path of the given length from `current`
+    fn count(&mut self, start: usize, current: usize, len: usize, closed: bool) -> u64 {
+        self.steps += 1;
+        if len == self.visited.len() {
+            return u64::from(!closed || self.neighbors[current].contains(&start));
+        }
+
+        self.visited[current] = true;
+        let mut count = 0;
+        for idx in 0..self.neighbors[current].len() {
+            let next = self.neighbors[current][idx];
+            if !self.visited[next] {
+                count += self.count(start, next, len + 1, closed);
+            }
+        }
+        self.visited[current] = false;
+        count
+    }
+}
+
+#[cfg(test)]
+fn search(size: BoardSize, start: BoardPos, closed: bool) -> Outcome<'static> {
+    let dead = HashSet::new();
+    let exhaustive = Exhaustive { size, dead_squares: &dead, wrap: Wrap::None, leaper: Leaper::KNIGHT };
+    exhaustive.search(&[start], closed, EXHAUSTIVE_STEP_LIMIT)
+}
+
+#[test]
+fn test_exhaustive_search() {
+    use crate::validation::validate;
+
+    assert!(matches!(search(BoardSize::new(4, 4), BoardPos::ZERO, false), Outcome::Proven));
+    assert!(matches!(search(BoardSize::new(4, 4), BoardPos::new(1, 1), false), Outcome::Proven));
+
+    // 13 squares of the 5x5 board share the color of the corners, so a tour has to start and end on one of them
+    assert!(matches!(search(BoardSize::new(5, 5), BoardPos::new(1, 0), false), Outcome::Proven));
+    let Outcome::Found(graph) = search(BoardSize::new(5, 5), BoardPos::ZERO, false) else { panic!("no tour from A1") };
+    let tour = validate(&graph).unwrap();
+    assert_eq!((tour.squares, tour.start, tour.closed), (25, BoardPos::ZERO, false));
+
+    // 3x7 has open tours, but no closed one
+    assert!(matches!(search(BoardSize::new(3, 7), BoardPos::ZERO, true), Outcome::Proven));
+    let Outcome::Found(graph) = search(BoardSize::new(3, 7), BoardPos::ZERO, false) else { panic!("no open tour on 3x7") };
+    assert_eq!(validate(&graph).unwrap().squares, 21);
+
+    let Outcome::Found(graph) = search(BoardSize::new(3, 4), BoardPos::ZERO, false) else { panic!("no open tour on 3x4") };
+    assert_eq!(validate(&graph).unwrap().squares, 12);
+}
+
+#[test]
+fn test_exhaustive_budget() {
+    let dead = HashSet::new();
+    let exhaustive = Exhaustive { size: BoardSize::new(5, 5), dead_squares: &dead, wrap: Wrap::None, leaper: Leaper::KNIGHT };
+    assert!(matches!(exhaustive.search(&[BoardPos::new(1, 0)], false, 100), Outcome::OutOfBudget));
+}
+
+#[test]
+fn test_count_tours() {
+    let count = |width, height, tours| count_tours(BoardSize::new(width, height), &HashSet::new(), Wrap::None, Leaper::KNIGHT, tours);
+    assert_eq!(count(4, 4, CountedTours::All), 0);
+    assert_eq!(count(5, 5, CountedTours::All), 1728);
+    assert_eq!(count(5, 5, CountedTours::Closed), 0);
+    assert_eq!(count(3, 4, CountedTours::All), 16);
+    assert_eq!(count(3, 10, CountedTours::Closed), 16);
+    assert_eq!(count(1, 1, CountedTours::All), 1);
+}
@@ -0,0 +1,63 @@
+use std::fmt::Display;
+
+use crate::board_pos::BoardPos;
+
+/// Reasons why a tour can't possibly be found, independent of the actual layout of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Infeasible {
+    NoKnights,
+    TooFewSquares { alive: usize, required: usize },
+}
+
+impl Display for Infeasible {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoKnights => write!(f, "at least one knight is required"),
+            Self::TooFewSquares { alive, required } => write!(f, "{alive} accessible squares, but at least {required} are required"),
+        }
+    }
+}
+
+/// The number of moves needed to visit every one of `alive` squares.
+///
+/// Every knight occupies its starting square without moving there. A closed tour (one that ends where it started)
+/// needs one additional move per knight to return to its starting square, which in turn requires a cycle of at least
+/// four squares because the knight's move graph is bipartite.
+pub fn expected_moves(alive: usize, start: BoardPos, end: Option<BoardPos>, knights: usize) -> Result<usize, Infeasible> {
+    if knights == 0 {
+        return Err(Infeasible::NoKnights);
+    }
+
+    let closed = end == Some(start);
+    let required = if closed { 4 * knights } else { knights };
+    let too_few = Infeasible::TooFewSquares { alive, required };
+    if alive < required {
+        return Err(too_few);
+    }
+
+    let moves = alive.checked_sub(knights).ok_or(too_few)?;
+    if closed {
+        moves.checked_add(knights).ok_or(too_few)
+    } else {
+        Ok(moves)
+    }
+}
+
+#[test]
+fn test_expected_moves() {
+    let start = BoardPos::new(0, 0);
+    let other = BoardPos::new(1, 2);
+
+    assert_eq!(expected_moves(0, start, None, 1), Err(Infeasible::TooFewSquares { alive: 0, required: 1 }));
+    assert_eq!(expected_moves(0, start, Some(start), 1), Err(Infeasible::TooFewSquares { alive: 0, required: 4 }));
+    assert_eq!(expected_moves(1, start, None, 1), Ok(0));
+    assert_eq!(expected_moves(1, start, Some(other), 1), Ok(0));
+    assert_eq!(expected_moves(1, start, Some(start), 1), Err(Infeasible::TooFewSquares { alive: 1, required: 4 }));
+    assert_eq!(expected_moves(64, start, None, 1), Ok(63));
+    assert_eq!(expected_moves(64, start, Some(other), 1), Ok(63));
+    assert_eq!(expected_moves(64, start, Some(start), 1), Ok(64));
+    assert_eq!(expected_moves(64, start, None, 2), Ok(62));
+    assert_eq!(expected_moves(64, start, Some(start), 2), Ok(64));
+    assert_eq!(expected_moves(1, start, None, 2), Err(Infeasible::TooFewSquares { alive: 1, required: 2 }));
+    assert_eq!(expected_moves(64, start, None, 0), Err(Infeasible::NoKnights));
+}
@@ -0,0 +1,44 @@
+use std::fmt::Display;
+
+use crate::{board_pos::BoardPos, validation::Defect};
+
+use super::expected_moves::Infeasible;
+
+/// Why the search came back without a tour. The callers report it, as only they know whether the board is the one
+/// the user asked for or a sector of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// The board couldn't be set up, either because of the input (which is reported on stderr right away)
+    /// or because a constrained tour would have to start or end on a dead square
+    Unusable,
+    Infeasible(Infeasible),
+    /// The prefix moves are a tour of their own, just not of the whole board
+    PrefixIncomplete { visited: usize, alive: usize },
+    PrefixInvalid(Defect),
+    Unreachable { squares: usize, start: BoardPos },
+    /// Every move was taken back without finding a tour
+    Exhausted,
+    /// The search backtracked more often than it may
+    GaveUp { backtracks: usize },
+    /// The exhaustive search tried every path
+    Proven { start: BoardPos, prefix: bool },
+    OutOfBudget { start: BoardPos, steps: usize },
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unusable => write!(f, "the board could not be set up"),
+            Self::Infeasible(reason) => write!(f, "{reason}"),
+            Self::PrefixIncomplete { visited, alive } => write!(f, "the prefix moves only visit {visited} of the {alive} accessible squares"),
+            Self::PrefixInvalid(defect) => write!(f, "the prefix moves don't form a tour: {defect}"),
+            Self::Unreachable { squares, start } => write!(f, "{squares} accessible squares can't be reached from {start}"),
+            Self::Exhausted => write!(f, "every move was tried"),
+            Self::GaveUp { backtracks } => write!(f, "gave up after {backtracks} backtracks"),
+            Self::Proven { start, prefix } => {
+                write!(f, "proven that no open tour exists from {start}{}", if *prefix { " with the given prefix moves" } else { "" })
+            },
+            Self::OutOfBudget { start, steps } => write!(f, "none found from {start}, the exhaustive search gave up after {steps} steps"),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use crate::{aliases::BoardIndex as Idx, board_pos::BoardPos, board_size::BoardSize};
+
+/// Removes the rows and columns along the edges of the board that contain no accessible squares, moving the remaining squares up and left.
+/// A board without any accessible squares is returned unchanged
+pub fn trim(dead_squares: &HashSet<BoardPos>, size: BoardSize) -> (HashSet<BoardPos>, BoardSize) {
+    let mut live = (0..size.height()).flat_map(|row| (0..size.width()).map(move |col| BoardPos::new(col, row)))
+        .filter(|pos| !dead_squares.contains(pos));
+    let Some(first) = live.next() else {
+        return (dead_squares.clone(), size);
+    };
+
+    let (mut left, mut top, mut right, mut bottom) = (first.col(), first.row(), first.col(), first.row());
+    for pos in live {
+        left = left.min(pos.col());
+        right = right.max(pos.col());
+        top = top.min(pos.row());
+        bottom = bottom.max(pos.row());
+    }
+
+    let trimmed = BoardSize::new(right - left + 1, bottom - top + 1);
+    let dead = dead_squares.iter()
+        .filter(|pos| (left..=right).contains(&pos.col()) && (top..=bottom).contains(&pos.row()))
+        .map(|pos| BoardPos::new(pos.col() - left, pos.row() - top))
+        .collect();
+
+    (dead, trimmed)
+}
+
+/// Surrounds the board with `rings` rings of inaccessible squares, moving the existing squares down and right
+pub fn pad(dead_squares: &HashSet<BoardPos>, size: BoardSize, rings: Idx) -> (HashSet<BoardPos>, BoardSize) {
+    let padded = BoardSize::new(size.width() + 2 * rings, size.height() + 2 * rings);
+    let mut dead: HashSet<_> = dead_squares.iter().map(|pos| BoardPos::new(pos.col() + rings, pos.row() + rings)).collect();
+    for row in 0..padded.height() {
+        for col in 0..padded.width() {
+            let inside = (rings..rings + size.width()).contains(&col) && (rings..rings + size.height()).contains(&row);
+            if !inside {
+                dead.insert(BoardPos::new(col, row));
+            }
+        }
+    }
+
+    (dead, padded)
+}
+
+#[cfg(test)]
+fn mask(rows: &[&str]) -> (HashSet<BoardPos>, BoardSize) {
+    let size = BoardSize::new(rows[0].len() as Idx, rows.len() as Idx);
+    let dead = rows.iter().enumerate()
+        .flat_map(|(row, line)| line.chars().enumerate().filter(|(_, ch)| *ch == '.').map(move |(col, _)| BoardPos::new(col as Idx, row as Idx)))
+        .collect();
+    (dead, size)
+}
+
+#[test]
+fn test_trim() {
+    let (dead, size) = mask(&[
+        ".....",
+        "..#..",
+        ".##..",
+        ".....",
+    ]);
+    assert_eq!(trim(&dead, size), mask(&[
+        ".#",
+        "##",
+    ]));
+
+    // nothing to trim
+    let (dead, size) = mask(&["#.", ".#"]);
+    assert_eq!(trim(&dead, size), (dead.clone(), size));
+
+    // a board without accessible squares has no content to trim down to
+    let (dead, size) = mask(&["..", ".."]);
+    assert_eq!(trim(&dead, size), (dead.clone(), size));
+}
+
+#[test]
+fn test_pad() {
+    let (dead, size) = mask(&[
+        "#.",
+        "##",
+    ]);
+    assert_eq!(pad(&dead, size, 1), mask(&[
+        "....",
+        ".#..",
+        ".##.",
+        "....",
+    ]));
+    assert_eq!(pad(&dead, size, 0), (dead.clone(), size));
+
+    // padding and trimming again gives back the original board
+    let (padded, padded_size) = pad(&dead, size, 3);
+    assert_eq!(trim(&padded, padded_size), (dead, size));
+}
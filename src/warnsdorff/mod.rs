@@ -1,57 +1,219 @@
-use std::{collections::{HashMap, HashSet}, error::Error, io::BufRead, path::PathBuf, time::{Duration, Instant}};
+use std::{collections::{BTreeMap, BTreeSet, HashMap, HashSet}, error::Error, io::BufRead, path::Path, time::{Duration, Instant}};
 
 use crate::{
     aliases::BoardIndex as Idx,
-    args::{BoardFileType, ImageMode, InputArgs},
+    args::{BoardFileType, ImageFilter, ImageMode, InputArgs, SearchMode, StartPos, TieBreak, Wrap},
+    board::matrix2d::Matrix2D,
     board_pos::BoardPos,
     board_size::BoardSize,
+    context::SolverContext,
     dprint,
+    divide_and_conquer::contract::StretchedContract,
     dprintln,
+    feasibility::{self, Feasibility},
     knight::Knight,
-    move_graph::{Direction, MoveGraph}
+    leaper::Leaper,
+    move_graph::{Direction, MoveGraph},
+    random::Random,
+    summary,
+    validation,
 };
 
 mod mode;
 mod move_tracker;
 mod cache;
+mod expected_moves;
+mod degrees;
+mod margins;
+mod cover;
+mod exhaustive;
+mod failure;
+mod resume;
 use move_tracker::MoveTracker;
+pub use degrees::Degrees;
+use expected_moves::expected_moves;
+use exhaustive::{Exhaustive, Outcome, EXHAUSTIVE_STEP_LIMIT};
+pub use exhaustive::{count_tours, CountedTours, COUNT_LIMIT};
 pub use mode::*;
-pub use cache::{get_stretched_cached, insert_stretched_cache};
-use image::{Rgba, GenericImageView};
+pub use failure::Failure;
+pub use cache::{get_stretched_cached, get_stretched_in_memory, insert_stretched_cache};
+use image::{imageops::FilterType, Rgba, GenericImageView};
 
 pub fn solve<'a>(args: InputArgs) -> Option<(Duration, MoveGraph<'a>)> {
-    let result = solve_internal_impl(args.board_size, Mode::Basic(args))?;
-    Some((result.1, result.0))
+    if let Some(knights) = args.warnsdorff.as_ref().map(|w| w.knights).filter(|&knights| knights > 1) {
+        return cover::solve(&args, knights);
+    }
+
+    if let Some(Feasibility::Impossible(reason)) = feasibility::of_input(&args) {
+        println!("No knight's tour possible for a {} board: {reason}.", args.board_size.unwrap());
+        // --best-effort still wants the longest path there is, which only the search finds
+        if !SolverContext::with_current(|context| context.best_effort()) {
+            return None;
+        }
+    }
+
+    let leaper = args.warnsdorff.as_ref().map(|w| w.piece).unwrap_or_default();
+    let mode = match args.warnsdorff.as_ref().map(|w| w.mode).unwrap_or_default() {
+        SearchMode::Basic => Mode::Basic(Box::new(args.clone())),
+        // a closed tour of an odd number of squares can't exist, so the corner is left out as for the sectors
+        SearchMode::Closed => Mode::Structured(StructureMode::Closed(args.board_size.is_some_and(|size| size.area() % 2 != 0))),
+        SearchMode::StretchedH => Mode::Structured(StructureMode::Stretched(Direction::Horizontal)),
+        SearchMode::StretchedV => Mode::Structured(StructureMode::Stretched(Direction::Vertical)),
+    };
+    let reversed = reversed_args(&args);
+    let started = Instant::now();
+    let mut result = solve_internal_impl(args.board_size, mode);
+    if let (Err(failure @ (Failure::GaveUp { .. } | Failure::Exhausted)), Some((reversed, start, end))) = (&result, reversed) {
+        let first_attempt = started.elapsed();
+        let longest = SolverContext::with_current(|context| context.take_longest_path());
+        dprintln!(1 => "No tour found from {start} to {end}: {failure}. Searching from {end} to {start} instead.");
+        result = solve_internal_impl(reversed.board_size, Mode::Basic(Box::new(reversed)))
+            .map(|(mut graph, duration, dead_squares)| {
+                graph.reverse_in_place();
+                dprintln!(1 => "Found the tour searching the other way round.");
+                (graph, first_attempt + duration, dead_squares)
+            })
+            // the failure of the search the user asked for is the one reported, and its path the one kept
+            .or(result);
+        if let (Err(_), Some(longest)) = (&result, longest) {
+            SolverContext::with_current(|context| context.record_longest_path(longest));
+        }
+    }
+
+    match result {
+        Ok((graph, duration, _)) => Some((duration, graph)),
+        // already reported where the input was read
+        Err(Failure::Unusable) => None,
+        Err(failure) => {
+            println!("No {leaper}'s tour for this board configuration: {failure}.");
+            longest_path(started.elapsed())
+        },
+    }
 }
 
-pub fn solve_internal<'a>(size: BoardSize, mode: Mode) -> Option<(MoveGraph<'a>, Duration)> {
+/// The longest path the failed search reached, if --best-effort asked for it to be kept
+fn longest_path<'a>(elapsed: Duration) -> Option<(Duration, MoveGraph<'a>)> {
+    let graph = SolverContext::with_current(|context| context.take_longest_path())?;
+    summary::record(|metrics| metrics.partial = true);
+    Some((elapsed, graph))
+}
+
+/// The arguments to search the tour from its ending position to its starting position instead, if both are fixed,
+/// along with both positions
+fn reversed_args(args: &InputArgs) -> Option<(InputArgs, BoardPos, BoardPos)> {
+    let warnsdorff = args.warnsdorff.as_ref().filter(|w| w.mode == SearchMode::Basic)?;
+    let (StartPos::Fixed(start), Some(end)) = (warnsdorff.starting_pos.unwrap_or(StartPos::Fixed(BoardPos::ZERO)), warnsdorff.ending_pos) else {
+        return None;
+    };
+
+    let mut reversed = args.clone();
+    let warnsdorff = reversed.warnsdorff.as_mut().unwrap();
+    (warnsdorff.starting_pos, warnsdorff.ending_pos) = (Some(StartPos::Fixed(end)), Some(start));
+    Some((reversed, start, end))
+}
+
+pub fn solve_internal<'a>(size: BoardSize, mode: Mode) -> Result<(MoveGraph<'a>, Duration), Failure> {
     solve_internal_impl(Some(size), mode).map(|(graph, duration, _)|(graph, duration))
 }
 
+/// How often a constrained tour may backtrack before it is given up on, unless --backtrack-limit says otherwise for basic tours
+const CONSTRAINED_BACKTRACK_LIMIT: usize = 100_000;
+/// How often Warnsdorff may backtrack on a board small enough for the exhaustive search before handing it over
+const SMALL_BOARD_BACKTRACK_LIMIT: usize = 10_000;
+
 struct SolveParams {
     dead_squares: HashSet<BoardPos>,
     end_point: Option<BoardPos>,
     pos: BoardPos,
+    prefix: Vec<BoardPos>,
+    resume: Vec<BoardPos>,
     cache: bool,
     direction: Direction,
-    size: BoardSize
+    size: BoardSize,
+    wrap: Wrap,
+    leaper: Leaper,
+    lookahead: u8,
+    tie_break: TieBreak,
 }
 
 fn parse_mode(mode: &Mode, mut size: Option<BoardSize>) -> Option<SolveParams> {
-    let end_point;
+    let mut end_point;
     let mut dead_squares = HashSet::new();
-    let pos;
-    let cache;
+    let mut pos;
+    let mut prefix = Vec::new();
+    let mut resume = Vec::new();
+    let mut cache;
     let mut direction = Direction::Horizontal;
+    let mut wrap = Wrap::None;
+    let mut leaper = Leaper::KNIGHT;
+    let mut lookahead = 1;
+    let mut tie_break = TieBreak::None;
     match mode {
         Mode::Basic(ref args) => {
             end_point = None;
-            size = Some(populate_dead_squares(&mut dead_squares, &args)?);
+            let Some(populated) = populate_dead_squares(&mut dead_squares, args) else {
+                summary::record(|metrics| metrics.input_error = true);
+                return None;
+            };
+            size = Some(populated);
+
+            wrap = args.warnsdorff.as_ref().map(|w|w.wrap).unwrap_or_default();
+            leaper = args.warnsdorff.as_ref().map(|w|w.piece).unwrap_or_default();
+            lookahead = args.warnsdorff.as_ref().map_or(1, |w|w.lookahead);
+            tie_break = args.warnsdorff.as_ref().map(|w|w.tie_break).unwrap_or_default();
+            if let Some(prefix_moves) = args.warnsdorff.as_ref().and_then(|w|w.prefix_moves.as_ref()) {
+                if let Err(problem) = validate_prefix(&prefix_moves.0, size?, &dead_squares, wrap, leaper) {
+                    eprintln!("Invalid --prefix-moves: {problem}.");
+                    summary::record(|metrics| metrics.input_error = true);
+                    return None;
+                }
 
-            pos = args.warnsdorff.as_ref().map(|w|w.starting_pos).flatten().unwrap_or(BoardPos::ZERO);
+                prefix = prefix_moves.0[1..].to_vec();
+            }
+
+            if let Some(path) = args.warnsdorff.as_ref().and_then(|w|w.resume.as_ref()) {
+                match resume::load(path, size?, &dead_squares, wrap, leaper) {
+                    Ok(squares) => resume = squares,
+                    Err(problem) => {
+                        eprintln!("Cannot resume from {}: {problem}.", path.display());
+                        summary::record(|metrics| metrics.input_error = true);
+                        return None;
+                    },
+                }
+            }
+
+            let requested = match args.warnsdorff.as_ref().and_then(|w|w.prefix_moves.as_ref()) {
+                Some(prefix_moves) => StartPos::Fixed(prefix_moves.0[0]),
+                None if !resume.is_empty() => StartPos::Fixed(resume.remove(0)),
+                None => args.warnsdorff.as_ref().and_then(|w|w.starting_pos).unwrap_or(StartPos::Fixed(BoardPos::ZERO)),
+            };
+            let requested = match requested {
+                StartPos::Fixed(pos) => pos,
+                StartPos::Random => {
+                    let seed = args.warnsdorff.as_ref().and_then(|w|w.random_seed).unwrap_or_else(Random::seed_from_time);
+                    dprintln!(1 => { seed } "Random seed: {seed}");
+                    summary::record(|metrics| metrics.seed = Some(seed));
+                    random_live_square(size?, &dead_squares, seed)?
+                },
+                StartPos::Auto => most_constrained_square(size?, &dead_squares, wrap, leaper)?,
+            };
+            let snap = args.warnsdorff.as_ref().is_some_and(|w|w.snap_start);
+            let Some(valid) = validate_starting_pos(requested, size?, &dead_squares, snap) else {
+                summary::record(|metrics| metrics.input_error = true);
+                return None;
+            };
+            pos = valid;
             cache = false;
+
+            if let Some(end) = args.warnsdorff.as_ref().and_then(|w|w.ending_pos) {
+                let Some(valid) = validate_ending_pos(end, pos, size?, &dead_squares) else {
+                    summary::record(|metrics| metrics.input_error = true);
+                    return None;
+                };
+                end_point = Some(valid);
+            }
         },
-        Mode::Structured(StructureMode::Closed(skip_corner)) => {
+        Mode::Structured(StructureMode::Closed(skip_corner)) | Mode::Constrained(StructureMode::Closed(skip_corner), _) => {
             cache = false;
             if *skip_corner {
                 dead_squares.insert(BoardPos::new(0, 0));
@@ -62,42 +224,71 @@ fn parse_mode(mode: &Mode, mut size: Option<BoardSize>) -> Option<SolveParams> {
 
             end_point = Some(pos);
         },
-        Mode::Structured(StructureMode::Stretched(dir)) => {
+        Mode::Structured(StructureMode::Stretched(dir)) | Mode::Constrained(StructureMode::Stretched(dir), _) => {
             direction = *dir;
-            end_point = if matches!(direction, Direction::Horizontal)  { Some(BoardPos::new(0, 1)) } else { Some(BoardPos::new(1, 0)) };
+            let contract = StretchedContract::new(size?, direction);
+            end_point = Some(contract.end);
             cache = true;
-            pos = BoardPos::new(0, 0);
+            pos = contract.start;
         },
         Mode::Freeform /* very small board, no structured/closed tour possible */ => {
-            cache = true;
+            // the tour may end anywhere, which would break the contract of the stretched sectors sharing the cache
+            cache = false;
             end_point = None;
             pos = BoardPos::new(0, 0);
         },
     }
 
+    if let Mode::Constrained(structure, constrained) = mode {
+        dead_squares.extend(constrained);
+        cache = false;
+
+        // closed tours can start anywhere, the other ends are fixed
+        if matches!(structure, StructureMode::Closed(false)) && dead_squares.contains(&pos) {
+            pos = live_squares(size?, &dead_squares).next()?;
+            end_point = Some(pos);
+        }
+        if dead_squares.contains(&pos) || end_point.is_some_and(|end| dead_squares.contains(&end)) {
+            return None;
+        }
+    }
+
     Some(SolveParams {
         dead_squares,
         end_point,
         pos,
+        prefix,
+        resume,
         cache,
         direction,
         size: size?,
+        wrap,
+        leaper,
+        lookahead,
+        tie_break,
     })
 }
 
-pub fn solve_internal_impl<'a>(size: Option<BoardSize>, mode: Mode) -> Option<(MoveGraph<'a>, Duration, HashSet<BoardPos>)> {
+/// Searches for a tour, leaving it to the caller to report why there is none
+pub fn solve_internal_impl<'a>(size: Option<BoardSize>, mode: Mode) -> Result<(MoveGraph<'a>, Duration, HashSet<BoardPos>), Failure> {
     let SolveParams {
         dead_squares,
         end_point,
         pos: start_pos,
+        prefix,
+        resume,
         cache,
         direction,
-        size
-    } = parse_mode(&mode, size)?;
+        size,
+        wrap,
+        leaper,
+        lookahead,
+        tie_break,
+    } = parse_mode(&mode, size).ok_or(Failure::Unusable)?;
 
     if cache {
         if let Some(cached) = get_stretched_cached(size, direction) {
-            return Some((MoveGraph::ref_to(cached), Duration::ZERO, HashSet::new()));
+            return Ok((MoveGraph::ref_to(cached), Duration::ZERO, HashSet::new()));
         }
 
         if let Some(cached) = get_stretched_cached(size.flip(), direction.opposite()) {
@@ -105,116 +296,233 @@ pub fn solve_internal_impl<'a>(size: Option<BoardSize>, mode: Mode) -> Option<(M
             let result = cached.flip();
             let duration = now.elapsed();
             insert_stretched_cache(size, direction, result);
-            return Some((MoveGraph::ref_to(get_stretched_cached(size, direction).unwrap()), duration, HashSet::new()));
+            return Ok((MoveGraph::ref_to(get_stretched_cached(size, direction).unwrap()), duration, HashSet::new()));
         }
     }
 
-    let mut graph = MoveGraph::new(size.width(), size.height());
+    let mut graph = MoveGraph::new(size.width(), size.height()).with_wrap(wrap).with_leaper(leaper);
     *graph.node_mut(start_pos).prev_mut() = Some(start_pos); // mark start as visited and start
-    let mut knight = Knight::new(start_pos);
+    let mut knight = Knight::new(start_pos, size, wrap, leaper);
+
+    let mut predetermined_moves = preconnect_corners(&graph, &mode, size);
+    if let Mode::Constrained(..) = mode {
+        // only the structure around the dead squares is kept
+        predetermined_moves.retain(|pos, _| !dead_squares.contains(pos));
+        for targets in predetermined_moves.values_mut() {
+            targets.retain(|pos| !dead_squares.contains(pos));
+        }
+        predetermined_moves.retain(|_, targets| !targets.is_empty());
+    }
 
-    let predetermined_moves = preconnect_corners(&graph, &mode, size);
+    // the end point only counts as a neighbor once the knight is allowed to move there
+    let mut degrees = Degrees::new(size, wrap, leaper, |pos| dead_squares.contains(&pos) || Some(pos) == end_point);
+    degrees.visit(start_pos);
+
+    let alive = usize::try_from(size.area()).unwrap_or(usize::MAX).saturating_sub(dead_squares.len());
+    let expected_move_count = expected_moves(alive, start_pos, end_point, 1).map_err(Failure::Infeasible)?;
+    dprintln!(2 => { expected_move_count } "Expected move count: {expected_move_count}.");
+
+    // a prefix visiting every square leaves nothing to search
+    if prefix.len() >= expected_move_count {
+        let now = Instant::now();
+        let mut prev = start_pos;
+        for &next_move in &prefix {
+            graph.link(prev, next_move);
+            prev = next_move;
+        }
 
-    let expected_move_count = (graph.width() * graph.height() - dead_squares.len() as Idx) as usize
-        - if end_point.is_some() && end_point == Some(start_pos) { 0 } else { 1 };
-    dprintln!(2 => "Expected move count: {expected_move_count}.");
+        return match validation::validate(&graph) {
+            Ok(tour) if tour.squares == alive => Ok((graph, now.elapsed(), dead_squares)),
+            Ok(tour) => Err(Failure::PrefixIncomplete { visited: tour.squares, alive }),
+            Err(defect) => Err(Failure::PrefixInvalid(defect)),
+        };
+    }
+
+    // backtracking would take forever to find out that some squares can never be reached
+    if let Mode::Basic(_) = mode {
+        let unreachable = unreachable_squares(start_pos, size, &dead_squares, wrap, leaper);
+        if unreachable > 0 {
+            return Err(Failure::Unreachable { squares: unreachable, start: start_pos });
+        }
+    }
+
+    // small boards get a definitive answer from the exhaustive search if Warnsdorff gets stuck
+    let exhaustive = match mode {
+        // it doesn't know about the ending position
+        Mode::Basic(ref args) => end_point.is_none() && alive <= args.warnsdorff.as_ref().map_or(0, |w| w.exhaustive_limit),
+        _ => false,
+    };
+    let backtrack_limit = match mode {
+        Mode::Basic(ref args) => args.warnsdorff.as_ref().and_then(|w| w.backtrack_limit),
+        Mode::Constrained(..) => Some(CONSTRAINED_BACKTRACK_LIMIT),
+        _ => None,
+    };
 
-    let mut moves = vec![ 0 ];
+    // the moves Warnsdorff tries from each square of the path, in order, and how many of them were taken back. Nothing
+    // the order depends on changes before the knight backtracks past the square, so they are only sorted once it gets there
+    let mut moves: Vec<(Option<Vec<BoardPos>>, usize)> = vec![(None, 0)];
 
     let now = Instant::now();
     let mut count: usize = 0;
+    let mut backtracks: usize = 0;
     let mut move_tracker = MoveTracker::new(expected_move_count);
     move_tracker.push(start_pos);
+    // how often a move was taken back on each square, only counted for --heatmap
+    let mut retractions = SolverContext::with_current(|context| context.heatmap_enabled())
+        .then(|| Matrix2D::new(size.width(), size.height(), || 0u32));
+
+    // the prefix is walked like any other moves, except that it is never taken back
+    for &next_move in &prefix {
+        graph.link(knight.position(), next_move);
+        knight.update_position(next_move);
+        degrees.visit(next_move);
+        move_tracker.push(next_move);
+        moves.push((None, 0));
+    }
 
-    while moves.len() <= expected_move_count {
-        count += 1;
-        let skip = moves.last().copied().unwrap();
+    // the resumed squares are taken back like any other moves, so they are walked by the search itself below
+    let mut replay = resume.iter().copied();
 
-        let target = if moves.len() == expected_move_count { end_point } else { None };
+    while moves.len() <= expected_move_count {
+        if backtrack_limit.is_some_and(|limit| backtracks > limit) {
+            dprintln!(1 => { backtracks } "Giving up on {size} {mode} after {backtracks} backtracks.");
+            break;
+        }
 
-        let checker = ReachabilityChecker {
-            target,
-            end_point,
-            dead_squares: &dead_squares,
-            graph: &graph,
-            start: start_pos,
-            predetermined_moves: &predetermined_moves,
-            move_to_end_allowed: expected_move_count - moves.len() < 3,
-        };
-        let reachable = |from, to| checker.reachable(from, to);
+        if exhaustive && backtracks > SMALL_BOARD_BACKTRACK_LIMIT {
+            dprintln!(1 => { backtracks } "Handing {size} over to the exhaustive search after {backtracks} backtracks.");
+            break;
+        }
 
-        let possible_moves = knight.get_possible_moves(&reachable);
+        count += 1;
+        let depth = moves.len();
+        let (possible_moves, skip) = moves.last_mut().unwrap();
+        let possible_moves = possible_moves.get_or_insert_with(|| {
+            let target = if depth == expected_move_count { end_point } else { None };
+
+            let checker = ReachabilityChecker {
+                target,
+                end_point,
+                dead_squares: &dead_squares,
+                graph: &graph,
+                start: start_pos,
+                predetermined_moves: &predetermined_moves,
+                move_to_end_allowed: expected_move_count - depth < 3,
+            };
+            let reachable = |from, to| checker.reachable(from, to);
+
+            knight.get_possible_moves(&reachable, |pos, moves_ahead| degrees.onward_moves(pos, moves_ahead), lookahead, tie_break)
+        });
+
+        // the move is picked as if Warnsdorff had made it, so that taking it back tries the moves it would have tried after it
+        if let Some(resumed) = replay.next() {
+            let Some(idx) = possible_moves.iter().position(|&pos| pos == resumed) else {
+                panic!("The resumed move from {} to {resumed} was checked, but isn't possible!", knight.position());
+            };
+            *skip = idx;
+        }
 
-        let next_move = possible_moves.iter()
-            .skip(skip as usize)
-            .next()
-            .copied();
+        let next_move = possible_moves.get(*skip).copied();
 
         if let Some(next_move) = next_move {
-            moves.push(0);
-
-            let current_node = graph.node_mut(knight.position());
-            *current_node.next_mut() = Some(next_move);
-
-            let next_node = graph.node_mut(next_move);
-            *next_node.prev_mut() = Some(knight.position());
+            moves.push((None, 0));
 
+            graph.link(knight.position(), next_move);
             knight.update_position(next_move);
+            degrees.visit(next_move);
             move_tracker.push(next_move);
-            dprintln!(3 => "Move #{count}:");
+            dprintln!(3 => { count, pos = %knight.position() } "Move #{count}:");
             dprintln!(3 => "{move_tracker}");
             dprintln!(3 => "{graph:?}");
             dprintln!(3 => );
-        } else if moves.len() > 1 {
+        } else if moves.len() > prefix.len() + 1 {
             // undo the last move
+            backtracks += 1;
+            if let Some(ref mut retractions) = retractions {
+                *retractions.at_mut(knight.position()) += 1;
+            }
             moves.pop();
             move_tracker.pop();
-            let prev_move = moves.last_mut().unwrap();
             // skip the last move
-            *prev_move += 1;
+            moves.last_mut().unwrap().1 += 1;
 
-            let current_node = graph.node_mut(knight.position());
-            if let Some(prev_pos) = current_node.prev_mut().take(){
-                let prev_node = graph.node_mut(prev_pos);
-                *prev_node.next_mut() = None;
+            if let Some(prev_pos) = graph.node(knight.position()).prev() {
+                graph.unlink(prev_pos);
+                degrees.leave(knight.position());
                 knight.update_position(prev_pos);
             }
             else {
-                dprintln!(3 => "Move #{count}: return from {}", knight.position());
-                dprintln!(3 => "{graph:?}");
+                dprintln!(3 => { count, pos = %knight.position() } "Move #{count}: return from {}", knight.position());
+                dprintln!(3 => "{}", graph.dump_around(knight.position(), 3));
                 dprintln!(3 => );
 
                 panic!("No previous move found for {}!", knight.position());
             }
 
-            dprintln!(3 => "Move #{count}: return to {}", knight.position());
+            dprintln!(3 => { count, pos = %knight.position() } "Move #{count}: return to {}", knight.position());
             dprintln!(3 => "{move_tracker}");
             dprintln!(3 => "{graph:?}");
             dprintln!(3 => );
         }
         else {
-            println!("No knight's tour possible for this board configuration ({size} {mode}).");
             break;
         }
     }
 
-    if cache {
+    summary::record(|metrics| {
+        metrics.iterations += count as u64;
+        metrics.backtracks += backtracks as u64;
+    });
+    if let Some(retractions) = retractions {
+        SolverContext::with_current(|context| context.record_heatmap(retractions));
+    }
+
+    if exhaustive && moves.len() <= expected_move_count {
+        let chain: Vec<_> = [start_pos].into_iter().chain(prefix.iter().copied()).collect();
+        let search = Exhaustive { size, dead_squares: &dead_squares, wrap, leaper };
+        return match search.search(&chain, false, EXHAUSTIVE_STEP_LIMIT) {
+            Outcome::Found(graph) => Ok((graph, now.elapsed(), dead_squares)),
+            Outcome::Proven => {
+                summary::record(|metrics| metrics.proven_no_tour = true);
+                // the path Warnsdorff got stuck on before handing over
+                move_tracker.report(&graph, &dead_squares);
+                Err(Failure::Proven { start: start_pos, prefix: !prefix.is_empty() })
+            },
+            Outcome::OutOfBudget => {
+                move_tracker.report(&graph, &dead_squares);
+                Err(Failure::OutOfBudget { start: start_pos, steps: EXHAUSTIVE_STEP_LIMIT })
+            },
+        };
+    }
+
+    if moves.len() <= expected_move_count {
+        // the search either gave up or ran out of moves to take back
+        move_tracker.report(&graph, &dead_squares);
+        return Err(match mode {
+            _ if backtrack_limit.is_some_and(|limit| backtracks > limit) => Failure::GaveUp { backtracks },
+            _ => Failure::Exhausted,
+        });
+    }
+
+    // an unfinished search doesn't end where the stretched sectors have to
+    if cache && moves.len() > expected_move_count {
         insert_stretched_cache(size, direction, graph.clone());
     }
 
+    debug_assert_eq!(graph.check_consistency(), Ok(()));
     dprintln!(3 => "{graph:?}");
 
     let duration = now.elapsed();
-    Some((graph, duration, dead_squares))
+    Ok((graph, duration, dead_squares))
 }
 
 fn preconnect_corners(graph: &MoveGraph, mode: &Mode, size: BoardSize) -> HashMap<BoardPos, HashSet<BoardPos>> {
     let top_left = match mode {
         Mode::Basic(_) => return HashMap::new(),
-        Mode::Structured(StructureMode::Closed(skip_corner)) => {
+        Mode::Structured(StructureMode::Closed(skip_corner)) | Mode::Constrained(StructureMode::Closed(skip_corner), _) => {
             (true, !skip_corner, None)
         },
-        Mode::Structured(StructureMode::Stretched(direction)) => {
+        Mode::Structured(StructureMode::Stretched(direction)) | Mode::Constrained(StructureMode::Stretched(direction), _) => {
             (false, false, Some(direction))
         },
         _ => (false, false, None)
@@ -230,7 +538,7 @@ fn preconnect_corners(graph: &MoveGraph, mode: &Mode, size: BoardSize) -> HashMa
             vec.insert(to);
         }
         else {
-            res.insert(from, vec![to].iter().copied().collect());
+            res.insert(from, [to].iter().copied().collect());
         }
     };
 
@@ -261,15 +569,21 @@ fn preconnect_corners(graph: &MoveGraph, mode: &Mode, size: BoardSize) -> HashMa
         add(pos, prev);
     }
 
-    if let Some(direction) = top_left.2 {
+    // the line to the end point would run through the dead squares of constrained tours
+    if let (Some(direction), Mode::Structured(_)) = (top_left.2, mode) {
         preconnect_end_point(&mut res, *direction, size);
     }
 
-    dprintln!(3 => "Preconnected moves: {res:?}");
+    dprintln!(3 => "Preconnected moves: {:?}", res.iter().map(|(pos, targets)| (pos, sorted(targets))).collect::<BTreeMap<_, _>>());
 
     res
 }
 
+/// The squares in the order they are written in, so that the debug output lists them the same way every time
+fn sorted(squares: &HashSet<BoardPos>) -> BTreeSet<BoardPos> {
+    squares.iter().copied().collect()
+}
+
 fn preconnect_end_point(preconnected_corners: &mut HashMap<BoardPos, HashSet<BoardPos>>, direction: Direction, size: BoardSize) {
     let half_size = size.width().max(size.height()) / 2;
     let half_size = half_size.min(size.width()).min(size.height());
@@ -285,16 +599,12 @@ fn preconnect_end_point(preconnected_corners: &mut HashMap<BoardPos, HashSet<Boa
     };
 
     let mut prev = start;
-    loop {
-        if let Some(next) = prev.try_translate(offset.0, offset.1) {
-            preconnected_corners.entry(prev).or_insert_with(HashSet::new).insert(next);
-            preconnected_corners.entry(next).or_insert_with(HashSet::new).insert(prev);
-            prev = next;
-            if prev.col() >= half_size && prev.row() >= half_size {
-                break;
-            }
-        }
-        else {
+    // long strips run out of board before the line gets to the middle
+    while let Some(next) = prev.try_translate(offset.0, offset.1).filter(|&next| size.fits(next)) {
+        preconnected_corners.entry(prev).or_default().insert(next);
+        preconnected_corners.entry(next).or_default().insert(prev);
+        prev = next;
+        if prev.col() >= half_size && prev.row() >= half_size {
             break;
         }
     }
@@ -358,7 +668,7 @@ impl<'a> ReachabilityChecker<'a> {
                 dprintln!(3 => "predetermined move -> true");
                 return true;
             } else if len > 1 {
-                dprintln!(3 => "unrelated square to the middle of two chained predetermined move {next:?} -> false");
+                dprintln!(3 => "unrelated square to the middle of two chained predetermined move {:?} -> false", sorted(&next));
                 return false;
             } else if let Some(end_point) = self.end_point {
                 if !self.move_to_end_allowed & next.contains(&end_point) {
@@ -371,7 +681,7 @@ impl<'a> ReachabilityChecker<'a> {
         if let Some(prev) = self.predetermined_moves.get(&from) {
             let res = prev.iter().all(|pos|is_occupied(*pos));
             const BOOLS: [&str; 2] = ["false", "true"];
-            dprintln!(3 => "from a predetermined move {prev:?} -> {}", BOOLS[res as usize]);
+            dprintln!(3 => "from a predetermined move {:?} -> {}", sorted(prev), BOOLS[res as usize]);
             res
         }
         else {
@@ -381,32 +691,164 @@ impl<'a> ReachabilityChecker<'a> {
     }
 }
 
-fn populate_dead_squares(dead_squares: &mut HashSet<BoardPos>, args: &InputArgs) -> Option<BoardSize> {
-    if let Some(ref path) = args.warnsdorff.as_ref().map(|w|w.board_file.as_ref()).flatten() {
-        populate_dead_squares_from_file(dead_squares, path, args)
+fn validate_starting_pos(pos: BoardPos, size: BoardSize, dead_squares: &HashSet<BoardPos>, snap: bool) -> Option<BoardPos> {
+    let problem = if !size.fits(pos) {
+        format!("Starting position {pos} is outside of the {size} board")
+    } else if dead_squares.contains(&pos) {
+        format!("Starting position {pos} is not an accessible square")
+    } else {
+        return Some(pos);
+    };
+
+    if !snap {
+        eprintln!("{problem}. Choose a different starting position or use --snap-start.");
+        return None;
     }
-    else {
-        populate_dead_squares_from_corner_radius(dead_squares, args);
-        args.board_size
+
+    if let Some(nearest) = nearest_live_square(pos, size, dead_squares) {
+        eprintln!("{problem}, starting at {nearest} instead.");
+        Some(nearest)
+    } else {
+        eprintln!("{problem} and the board has no accessible squares.");
+        None
+    }
+}
+
+fn validate_ending_pos(end: BoardPos, start: BoardPos, size: BoardSize, dead_squares: &HashSet<BoardPos>) -> Option<BoardPos> {
+    let problem = if !size.fits(end) {
+        format!("Ending position {end} is outside of the {size} board")
+    } else if dead_squares.contains(&end) {
+        format!("Ending position {end} is not an accessible square")
+    } else if end == start {
+        format!("Ending position {end} is the starting position, use --mode closed for a closed tour")
+    } else {
+        return Some(end);
+    };
+
+    eprintln!("{problem}.");
+    None
+}
+
+/// Checks that the squares are accessible, visited once each and a knight's move apart,
+/// naming the first offending square or step (the move from one square to the next, starting at 1)
+fn validate_prefix(prefix: &[BoardPos], size: BoardSize, dead_squares: &HashSet<BoardPos>, wrap: Wrap, leaper: Leaper) -> Result<(), String> {
+    let mut visited = HashSet::new();
+    for (idx, &pos) in prefix.iter().enumerate() {
+        let square = idx + 1;
+        if !size.fits(pos) {
+            return Err(format!("square {square} ({pos}) is outside of the {size} board"));
+        }
+
+        if dead_squares.contains(&pos) {
+            return Err(format!("square {square} ({pos}) is not an accessible square"));
+        }
+
+        if !visited.insert(pos) {
+            return Err(format!("square {square} ({pos}) is visited twice"));
+        }
+
+        if idx > 0 && prefix[idx - 1].move_offset_to(pos, size, wrap, leaper).is_none() {
+            return Err(format!("step {idx} ({} -> {pos}) is not a {leaper}'s move", prefix[idx - 1]));
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the accessible square closest to the given position by manhattan distance.
+/// Ties are broken by picking the topmost, then leftmost square
+fn nearest_live_square(pos: BoardPos, size: BoardSize, dead_squares: &HashSet<BoardPos>) -> Option<BoardPos> {
+    live_squares(size, dead_squares)
+        .min_by_key(|candidate| candidate.col().abs_diff(pos.col()) as u64 + candidate.row().abs_diff(pos.row()) as u64)
+}
+
+fn live_squares(size: BoardSize, dead_squares: &HashSet<BoardPos>) -> impl Iterator<Item = BoardPos> + '_ {
+    (0..size.height())
+        .flat_map(move |row| (0..size.width()).map(move |col| BoardPos::new(col, row)))
+        .filter(|pos| !dead_squares.contains(pos))
+}
+
+fn random_live_square(size: BoardSize, dead_squares: &HashSet<BoardPos>, seed: u64) -> Option<BoardPos> {
+    let live: Vec<_> = live_squares(size, dead_squares).collect();
+    if live.is_empty() {
+        eprintln!("Cannot pick a random starting position, the board has no accessible squares.");
+        return None;
     }
+
+    Some(live[Random::new(seed).below(live.len())])
 }
 
-fn populate_dead_squares_from_corner_radius(dead_squares: &mut HashSet<BoardPos>, args: &InputArgs) {
-    let radius = if let Some(radius) = args.warnsdorff.as_ref().map(|w|w.corner_radius.as_ref()).flatten() { radius } else { return };
-    let size = args.board_size.unwrap();
-    let w = size.width();
-    let h = size.height();
+/// Finds the accessible square with the fewest moves away from it, as judged by the solver on an empty board.
+/// Ties are broken by picking the topmost, then leftmost square
+fn most_constrained_square(size: BoardSize, dead_squares: &HashSet<BoardPos>, wrap: Wrap, leaper: Leaper) -> Option<BoardPos> {
+    let graph = MoveGraph::new(size.width(), size.height()).with_wrap(wrap).with_leaper(leaper);
+    let predetermined_moves = HashMap::new();
+    let res = live_squares(size, dead_squares).min_by_key(|&pos| {
+        let checker = ReachabilityChecker {
+            target: None,
+            end_point: None,
+            dead_squares,
+            graph: &graph,
+            start: pos,
+            predetermined_moves: &predetermined_moves,
+            move_to_end_allowed: true,
+        };
+        Knight::new(pos, size, wrap, leaper).possible_moves_count(&|from, to| checker.reachable(from, to), 1)
+    });
+
+    if res.is_none() {
+        eprintln!("Cannot pick a starting position, the board has no accessible squares.");
+    }
+
+    res
+}
 
-    for (i, j) in (0..w).flat_map(|i| (0..h).map(move |j| (i, j))) {
-        if radius.is_in_range(BoardPos::new(i, j), size) {
-            dead_squares.insert(BoardPos::new(i, j));
+/// The number of accessible squares the piece can't get to from the start, no matter which way it goes
+fn unreachable_squares(start: BoardPos, size: BoardSize, dead_squares: &HashSet<BoardPos>, wrap: Wrap, leaper: Leaper) -> usize {
+    let mut reached = HashSet::from([start]);
+    let mut queue = vec![start];
+    while let Some(pos) = queue.pop() {
+        for (col, row) in leaper.moves() {
+            let Some(next) = pos.try_translate_wrapping(col, row, size, wrap) else { continue };
+            if !dead_squares.contains(&next) && reached.insert(next) {
+                queue.push(next);
+            }
         }
     }
+
+    live_squares(size, dead_squares).filter(|pos| !reached.contains(pos)).count()
+}
+
+/// The size of the board and its inaccessible squares, as the solvers see them after reading the input
+pub fn effective_mask(args: &InputArgs) -> Option<(HashSet<BoardPos>, BoardSize)> {
+    let mut dead_squares = HashSet::new();
+    let size = populate_dead_squares(&mut dead_squares, args)?;
+    Some((dead_squares, size))
+}
+
+fn populate_dead_squares(dead_squares: &mut HashSet<BoardPos>, args: &InputArgs) -> Option<BoardSize> {
+    if let Some(path) = args.warnsdorff.as_ref().and_then(|w|w.board_file.as_ref()) {
+        let mut size = populate_dead_squares_from_file(dead_squares, path, args)?;
+        let warnsdorff = args.warnsdorff.as_ref()?;
+        if warnsdorff.trim_board {
+            (*dead_squares, size) = margins::trim(dead_squares, size);
+        }
+        if let Some(rings) = warnsdorff.pad_board {
+            (*dead_squares, size) = margins::pad(dead_squares, size, rings);
+        }
+
+        Some(size)
+    }
+    else {
+        let size = args.board_size?;
+        dead_squares.extend(args.dead_squares(size));
+        Some(size)
+    }
 }
 
 fn populate_dead_squares_from_file(
     dead_squares: &mut HashSet<BoardPos>,
-    path: &PathBuf,
+    path: &Path,
     args: &InputArgs
 ) -> Option<BoardSize> {
     let warnsdorff = args.warnsdorff.as_ref()?;
@@ -417,7 +859,7 @@ fn populate_dead_squares_from_file(
             Some(osstr) if osstr.eq_ignore_ascii_case("txt") => { BoardFileType::Text },
             Some(osstr) if image::ImageFormat::from_extension(osstr).is_some() => { BoardFileType::Image },
             _ => {
-                eprintln!("Unknown file type. Please provide the board file type explicitly.");
+                eprintln!("Unknown file type of {}. Please provide the board file type explicitly.", path.display());
                 return None;
             }
         }
@@ -429,24 +871,28 @@ fn populate_dead_squares_from_file(
             dead_squares,
             path,
             warnsdorff.image_mode.unwrap(),
-            warnsdorff.threshold.unwrap_or(128)
-        ).map(|s|Some(s)).unwrap_or(None),
+            warnsdorff.threshold.unwrap_or(128),
+            warnsdorff.invert_image_mode,
+            warnsdorff.image_board_size.map(|size| (size, warnsdorff.image_filter)),
+        ).map_err(|e| eprintln!("Could not read board image {}: {e}", path.display())).ok(),
     }
 }
 
-fn populate_dead_squares_from_text_file(dead_squares: &mut HashSet<BoardPos>, path: &PathBuf) -> Option<BoardSize> {
-    let file = std::fs::File::open(path).map(|f|Some(f)).unwrap_or(None)?;
+fn populate_dead_squares_from_text_file(dead_squares: &mut HashSet<BoardPos>, path: &Path) -> Option<BoardSize> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| eprintln!("Could not open board file {}: {e}", path.display()))
+        .ok()?;
     let mut lines = Vec::new();
     let mut max_len = 0;
     for line in std::io::BufReader::new(file).lines() {
-        let str = line.map(|f|Some(f)).unwrap_or(None)?;
+        let str = line.map_err(|e| eprintln!("Could not read board file {}: {e}", path.display())).ok()?;
         max_len =max_len.max(str.len());
         lines.push(str);
     }
 
     let size = BoardSize::new(max_len as Idx, lines.len() as Idx);
-    let mut row = 0;
-    for line in lines {
+    for (row, line) in lines.into_iter().enumerate() {
+        let row = row as Idx;
         let mut col = 0;
         for ch in line.chars() {
             if ch.is_whitespace() || ch.is_control() {
@@ -460,44 +906,381 @@ fn populate_dead_squares_from_text_file(dead_squares: &mut HashSet<BoardPos>, pa
             dead_squares.insert(BoardPos::new(col, row));
             col += 1;
         }
-
-        row += 1;
     }
 
     Some(size)
 }
 
-fn populate_dead_squares_from_image_file(dead_squares: &mut HashSet<BoardPos>, path: &PathBuf, image_mode: ImageMode, threshold: u8) -> Result<BoardSize, Box<dyn Error + 'static>> {
-    let image = image::open(path)?;
+fn luminance(pixel: Rgba<u8>) -> u8 {
+    let [r, g, b, _] = pixel.0;
+    let (r, g, b) = (r as u16, g as u16, b as u16);
+    let r = r * 30;
+    let g = g * 59;
+    let b = b * 11;
+    let sum = r + g + b;
+    (sum / 100) as u8
+}
+
+/// Pixels that are neither pure black nor pure white in black-white mode
+#[derive(Default)]
+struct ImpurePixels {
+    count: usize,
+    top_left: Option<BoardPos>,
+    bottom_right: Option<BoardPos>,
+}
+
+impl ImpurePixels {
+    fn add(&mut self, pos: BoardPos) {
+        self.count += 1;
+        let (top_left, bottom_right) = match (self.top_left, self.bottom_right) {
+            (Some(tl), Some(br)) => (
+                BoardPos::new(tl.col().min(pos.col()), tl.row().min(pos.row())),
+                BoardPos::new(br.col().max(pos.col()), br.row().max(pos.row())),
+            ),
+            _ => (pos, pos),
+        };
+        (self.top_left, self.bottom_right) = (Some(top_left), Some(bottom_right));
+    }
+
+    /// The error for the impure pixels, if there are any. Luminance mode keeps the bright pixels, black-white mode the
+    /// black ones, so the suggested options flip --invert-image-mode to keep the same squares
+    fn into_error(self, luminance_alive: usize, invert: bool) -> Option<String> {
+        let (top_left, bottom_right) = (self.top_left?, self.bottom_right?);
+        let invert = if invert { " without --invert-image-mode" } else { " --invert-image-mode" };
+        Some(format!(
+            "{} pixels between {top_left} and {bottom_right} are neither pure black nor pure white, which usually means the image has anti-aliased edges. \
+            Re-export it without anti-aliasing, or use --image-mode luminance --threshold 128{invert} (which for this image would mark {luminance_alive} squares alive).",
+            self.count,
+        ))
+    }
+}
 
+fn populate_dead_squares_from_image_file(
+    dead_squares: &mut HashSet<BoardPos>,
+    path: &Path,
+    image_mode: ImageMode,
+    threshold: u8,
+    invert: bool,
+    resize: Option<(BoardSize, ImageFilter)>,
+) -> Result<BoardSize, Box<dyn Error + 'static>> {
+    let mut image = image::open(path)?;
+    if let Some((size, filter)) = resize {
+        let filter = match filter {
+            ImageFilter::Nearest => FilterType::Nearest,
+            ImageFilter::Triangle => FilterType::Triangle,
+            ImageFilter::CatmullRom => FilterType::CatmullRom,
+            ImageFilter::Gaussian => FilterType::Gaussian,
+            ImageFilter::Lanczos3 => FilterType::Lanczos3,
+        };
+        image = image.resize_exact(size.width(), size.height(), filter);
+    }
+
+    // black-white mode scans the whole image before failing, so that the error can describe all offending pixels
+    let mut impure = ImpurePixels::default();
+    let mut luminance_alive = 0;
     for (x, y, pixel) in image.pixels() {
-        let visible = match image_mode {
+        let pos = BoardPos::new(x as Idx, y as Idx);
+        let accessible = match image_mode {
             ImageMode::Alpha => pixel.0[3] >= threshold,
             ImageMode::BlackWhite =>  {
+                // dark pixels are alive like black ones, unless inverted
+                luminance_alive += ((luminance(pixel) < 128) != invert) as usize;
                 if pixel == Rgba([255, 255, 255, 255]) {
                     false
                 } else if pixel == Rgba([0, 0, 0, 255]) {
                     true
                 } else {
-                    return Err("Only black and white pixels are supported. Try the mode \"luminance\" or \"alpha\" instead.".into());
+                    impure.add(pos);
+                    false
                 }
             },
-            ImageMode::Luminance => {
-                let [r, g, b, _] = pixel.0;
-                let (r, g, b) = (r as u16, g as u16, b as u16);
-                let r = r * 30;
-                let g = g * 59;
-                let b = b * 11;
-                let sum = r + g + b;
-                let lum = (sum / 100) as u8;
-                lum >= threshold
-            },
+            ImageMode::Luminance => luminance(pixel) >= threshold,
         };
 
-        if visible {
-            dead_squares.insert(BoardPos::new(x as Idx, y as Idx));
+        if accessible == invert {
+            dead_squares.insert(pos);
         }
     }
 
+    if let Some(error) = impure.into_error(luminance_alive, invert) {
+        return Err(error.into());
+    }
+
     Ok(BoardSize::new(image.width() as Idx, image.height() as Idx))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+use crate::temp_dir::TempDir;
+
+#[cfg(unix)]
+#[test]
+fn test_board_file_with_non_utf8_name() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let dir = TempDir::new("test");
+    let path = dir.join(OsStr::from_bytes(b"board_\xff\xfe.txt"));
+    assert!(path.to_str().is_none());
+    std::fs::write(&path, "xxx\nx x\nxxx\n").unwrap();
+
+    let mut dead_squares = HashSet::new();
+    let size = populate_dead_squares_from_text_file(&mut dead_squares, &path);
+    drop(dir);
+
+    assert_eq!(size, Some(BoardSize::new(3, 3)));
+    assert_eq!(dead_squares, HashSet::from([BoardPos::new(1, 1)]));
+
+    // a missing file is reported instead of panicking on the name
+    assert_eq!(populate_dead_squares_from_text_file(&mut dead_squares, &path), None);
+}
+
+#[test]
+fn test_validate_starting_pos() {
+    let size = BoardSize::new(5, 4);
+    let dead_squares = HashSet::from([BoardPos::new(0, 0), BoardPos::new(1, 0), BoardPos::new(0, 1)]);
+
+    assert_eq!(validate_starting_pos(BoardPos::new(2, 2), size, &dead_squares, false), Some(BoardPos::new(2, 2)));
+    assert_eq!(validate_starting_pos(BoardPos::new(0, 0), size, &dead_squares, false), None);
+    assert_eq!(validate_starting_pos(BoardPos::new(5, 0), size, &dead_squares, false), None);
+
+    // (2, 0) and (1, 1) are both two squares away from (0, 0), the top one wins
+    assert_eq!(validate_starting_pos(BoardPos::new(0, 0), size, &dead_squares, true), Some(BoardPos::new(2, 0)));
+    assert_eq!(validate_starting_pos(BoardPos::new(0, 1), size, &dead_squares, true), Some(BoardPos::new(1, 1)));
+    assert_eq!(validate_starting_pos(BoardPos::new(9, 9), size, &dead_squares, true), Some(BoardPos::new(4, 3)));
+
+    let all_dead: HashSet<_> = (0..5).flat_map(|col| (0..4).map(move |row| BoardPos::new(col, row))).collect();
+    assert_eq!(validate_starting_pos(BoardPos::new(0, 0), size, &all_dead, true), None);
+}
+
+#[test]
+fn test_anti_aliased_black_white_image() {
+    let dir = TempDir::new("image_test");
+    let load = |name: &str, image: image::RgbaImage| {
+        let path = dir.join(name);
+        image.save(&path).unwrap();
+        populate_dead_squares_from_image_file(&mut HashSet::new(), &path, ImageMode::BlackWhite, 128, false, None).map_err(|e| e.to_string())
+    };
+
+    let (black, white, gray) = (Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255]), Rgba([128, 128, 128, 255]));
+
+    // a 6x5 image with a gray border around a white first row and black everywhere else.
+    // the border (18 pixels) is as bright as the white row (4 pixels) for luminance mode, leaving the 8 black ones dark
+    let bordered = image::RgbaImage::from_fn(6, 5, |x, y| match (x, y) {
+        (0 | 5, _) | (_, 0 | 4) => gray,
+        (_, 1) => white,
+        _ => black,
+    });
+    let error = load("bordered.png", bordered).unwrap_err();
+    assert!(error.starts_with("18 pixels between A1 and F5 are neither pure black nor pure white"), "{error}");
+    assert!(error.contains("Re-export it without anti-aliasing, or use --image-mode luminance --threshold 128 --invert-image-mode (which for this image would mark 8 squares alive)"), "{error}");
+
+    // the area only covers the offending pixels
+    let speckled = image::RgbaImage::from_fn(6, 5, |x, y| match (x, y) {
+        (2, 1) | (3, 3) => gray,
+        _ => white,
+    });
+    let error = load("speckled.png", speckled).unwrap_err();
+    assert!(error.starts_with("2 pixels between C2 and D4 "), "{error}");
+    assert!(error.contains("would mark 0 squares alive"), "{error}");
+
+    let pure = image::RgbaImage::from_fn(6, 5, |x, _| if x % 2 == 0 { black } else { white });
+    assert_eq!(load("pure.png", pure), Ok(BoardSize::new(6, 5)));
+}
+
+#[test]
+fn test_invert_image_mode() {
+    let dir = TempDir::new("invert_test");
+    let load = |name: &str, image: &image::RgbaImage, mode: ImageMode, invert: bool| {
+        let path = dir.join(name);
+        image.save(&path).unwrap();
+        let mut dead_squares = HashSet::new();
+        populate_dead_squares_from_image_file(&mut dead_squares, &path, mode, 128, invert, None).map(|_| dead_squares).map_err(|e| e.to_string())
+    };
+
+    let all: HashSet<_> = (0..3).flat_map(|col| (0..2).map(move |row| BoardPos::new(col, row))).collect();
+    let images = [
+        ("alpha.png", ImageMode::Alpha, image::RgbaImage::from_fn(3, 2, |x, _| Rgba([9, 9, 9, [0, 127, 128][x as usize]]))),
+        ("luminance.png", ImageMode::Luminance, image::RgbaImage::from_fn(3, 2, |x, y| { let v = (x * 100 + y * 50) as u8; Rgba([v, v, v, 255]) })),
+        ("black_white.png", ImageMode::BlackWhite, image::RgbaImage::from_fn(3, 2, |x, y| if (x + y) % 2 == 0 { Rgba([0, 0, 0, 255]) } else { Rgba([255; 4]) })),
+    ];
+
+    for (name, mode, image) in images {
+        let plain = load(name, &image, mode, false).unwrap();
+        let inverted = load(name, &image, mode, true).unwrap();
+        assert!(!plain.is_empty() && plain != all, "{name} should have squares of both kinds");
+        assert_eq!(inverted, all.difference(&plain).copied().collect(), "{name}");
+    }
+
+    // impure pixels are still an error in black-white mode, inverted or not
+    let gray = image::RgbaImage::from_fn(3, 2, |_, _| Rgba([128, 128, 128, 255]));
+    let error = load("gray.png", &gray, ImageMode::BlackWhite, true).unwrap_err();
+    assert!(error.starts_with("6 pixels between A1 and C2 "), "{error}");
+    assert!(error.contains("--threshold 128 without --invert-image-mode (which for this image would mark 6 squares alive)"), "{error}");
+}
+
+#[test]
+fn test_image_accessibility() {
+    let dir = TempDir::new("accessibility_test");
+    let dead = |name: &str, pixels: [Rgba<u8>; 3], mode: ImageMode, threshold: u8| {
+        let path = dir.join(name);
+        image::RgbaImage::from_fn(3, 1, |x, _| pixels[x as usize]).save(&path).unwrap();
+        let mut dead_squares = HashSet::new();
+        populate_dead_squares_from_image_file(&mut dead_squares, &path, mode, threshold, false, None).unwrap();
+        let mut dead: Vec<_> = dead_squares.into_iter().map(|pos| pos.col()).collect();
+        dead.sort();
+        dead
+    };
+
+    // alpha >= threshold is accessible
+    let alpha = [Rgba([0, 0, 0, 0]), Rgba([0, 0, 0, 99]), Rgba([0, 0, 0, 100])];
+    assert_eq!(dead("alpha.png", alpha, ImageMode::Alpha, 100), [0, 1]);
+
+    // luminance >= threshold is accessible, transparency doesn't matter
+    let luminance = [Rgba([255, 255, 255, 0]), Rgba([127, 127, 127, 255]), Rgba([128, 128, 128, 255])];
+    assert_eq!(dead("luminance.png", luminance, ImageMode::Luminance, 128), [1]);
+
+    // black is accessible, white is not
+    let black_white = [Rgba([255, 255, 255, 255]), Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255])];
+    assert_eq!(dead("black_white.png", black_white, ImageMode::BlackWhite, 128), [0, 2]);
+}
+
+#[test]
+fn test_image_board_size() {
+    let dir = TempDir::new("image_size_test");
+    let path = dir.join("half.png");
+
+    // a 100x100 image whose left half is transparent
+    image::RgbaImage::from_fn(100, 100, |x, _| Rgba([0, 0, 0, if x < 50 { 0 } else { 255 }])).save(&path).unwrap();
+    let load = |filter: ImageFilter, threshold: u8| {
+        let mut dead_squares = HashSet::new();
+        let size = populate_dead_squares_from_image_file(&mut dead_squares, &path, ImageMode::Alpha, threshold, false, Some((BoardSize::new(10, 10), filter))).unwrap();
+        assert_eq!(size, BoardSize::new(10, 10));
+        dead_squares
+    };
+
+    for filter in [ImageFilter::Nearest, ImageFilter::Triangle, ImageFilter::Lanczos3] {
+        let dead = load(filter, 128);
+        assert_eq!(dead.len(), 50, "{filter:?}");
+        assert!(dead.iter().all(|pos| pos.col() < 5), "{filter:?}");
+    }
+
+    // the threshold applies to the averaged pixels: a 3x3 board straddles the edge in its middle column
+    let mut dead_squares = HashSet::new();
+    populate_dead_squares_from_image_file(&mut dead_squares, &path, ImageMode::Alpha, 200, false, Some((BoardSize::new(3, 3), ImageFilter::Triangle))).unwrap();
+    assert_eq!(dead_squares, (0..2).flat_map(|col| (0..3).map(move |row| BoardPos::new(col, row))).collect());
+}
+
+#[test]
+fn test_generated_starting_pos() {
+    let size = BoardSize::new(5, 5);
+    let dead_squares = HashSet::from([BoardPos::new(0, 0)]);
+
+    // the corners only have two moves, but with A1 gone B3 and C2 lose one of theirs
+    assert_eq!(most_constrained_square(size, &HashSet::new(), Wrap::None, Leaper::KNIGHT), Some(BoardPos::new(0, 0)));
+    assert_eq!(most_constrained_square(size, &dead_squares, Wrap::None, Leaper::KNIGHT), Some(BoardPos::new(4, 0)));
+    // on a torus every square has all eight moves, except for those leading to A1. C2 is the first of them
+    assert_eq!(most_constrained_square(size, &dead_squares, Wrap::Both, Leaper::KNIGHT), Some(BoardPos::new(2, 1)));
+
+    for seed in 0..50 {
+        let pos = random_live_square(size, &dead_squares, seed).unwrap();
+        assert!(size.fits(pos) && pos != BoardPos::ZERO);
+        assert_eq!(random_live_square(size, &dead_squares, seed), Some(pos));
+    }
+
+    let all_dead: HashSet<_> = live_squares(size, &HashSet::new()).collect();
+    assert_eq!(random_live_square(size, &all_dead, 0), None);
+    assert_eq!(most_constrained_square(size, &all_dead, Wrap::None, Leaper::KNIGHT), None);
+}
+
+#[test]
+fn test_lookahead() {
+    for lookahead in ["1", "2"] {
+        let args = crate::args::Args::parse_from(["knights_tour", "-w", "-s", "30", "--lookahead", lookahead]);
+        let (_, graph) = solve(args.input).unwrap();
+        let summary = crate::validation::validate(&graph).unwrap();
+        assert_eq!(summary.squares, 900, "lookahead {lookahead}");
+    }
+}
+
+#[test]
+fn test_pohl_tie_break_without_backtracking() {
+    let args = crate::args::Args::parse_from(["knights_tour", "-w", "-s", "8", "--tie-break", "pohl"]);
+    let (graph, metrics) = crate::context::SolverContext::new().run(|| (solve(args.input), summary::take()));
+    let (_, graph) = graph.unwrap();
+    assert_eq!(crate::validation::validate(&graph).unwrap().squares, 64);
+    assert_eq!(metrics.backtracks, 0);
+}
+
+#[test]
+fn test_leaper_tours() {
+    for (piece, size) in [("giraffe", "10"), ("camel", "11"), ("2,3", "12x9")] {
+        let args = crate::args::Args::parse_from(["knights_tour", "-s", size, "--wrap", "both", "--piece", piece]);
+        let (_, graph) = solve(args.input).unwrap();
+        let summary = crate::validation::validate(&graph).unwrap();
+        assert_eq!(summary.squares as u64, graph.size().area() as u64, "{piece}");
+        assert!(graph.tour().zip(graph.tour().skip(1)).all(|(from, to)| graph.move_offset(from, to).is_some_and(|(col, row)| graph.leaper().is_move(col, row))));
+    }
+
+    // a camel never leaves the color of its starting square
+    let size = BoardSize::new(8, 8);
+    assert_eq!(unreachable_squares(BoardPos::ZERO, size, &HashSet::new(), Wrap::None, Leaper::CAMEL), 32);
+    assert_eq!(unreachable_squares(BoardPos::ZERO, size, &HashSet::new(), Wrap::None, Leaper::KNIGHT), 0);
+    let args = crate::args::Args::parse_from(["knights_tour", "-w", "-s", "8", "--piece", "camel"]);
+    assert!(solve(args.input).is_none());
+}
+
+#[test]
+fn test_prefix_moves() {
+    let prefix = "D4,E6,G7,H5,G3,E2,C1,A2,B4,C6,A7";
+    let args = crate::args::Args::parse_from(["knights_tour", "-w", "-s", "8", "--prefix-moves", prefix]);
+    let (_, graph) = solve(args.input).unwrap();
+    assert_eq!(crate::validation::validate(&graph).unwrap().squares, 64);
+
+    let expected: Vec<BoardPos> = prefix.split(',').map(|square| square.try_into().unwrap()).collect();
+    assert_eq!(graph.tour().take(expected.len()).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_full_prefix() {
+    let prefix = "A1,C2,E1,D3,E5,C4,A5,B3,D4,B5,A3,B1,D2,E4,C5,A4,B2,D1,E3,D5,B4,A2,C3,E2,C1";
+    let args = crate::args::Args::parse_from(["knights_tour", "-w", "-s", "5", "--prefix-moves", prefix]);
+    let (graph, metrics) = crate::context::SolverContext::new().run(|| (solve(args.input), summary::take()));
+    let (_, graph) = graph.unwrap();
+
+    let expected: Vec<BoardPos> = prefix.split(',').map(|square| square.try_into().unwrap()).collect();
+    assert_eq!(graph.tour().collect::<Vec<_>>(), expected);
+    assert_eq!(crate::validation::validate(&graph).unwrap().squares, 25);
+    assert_eq!((metrics.iterations, metrics.backtracks), (0, 0));
+}
+
+#[test]
+fn test_validate_prefix() {
+    let size = BoardSize::new(8, 8);
+    let squares = |list: &str| -> Vec<BoardPos> { list.split(',').map(|square| square.try_into().unwrap()).collect() };
+    let dead_squares = [BoardPos::new(7, 7)].into_iter().collect();
+    let validate = |list| validate_prefix(&squares(list), size, &dead_squares, Wrap::None, Leaper::KNIGHT);
+
+    assert_eq!(validate("A1,C2,E1"), Ok(()));
+    assert_eq!(validate("A1,C2,E4").unwrap_err(), "step 2 (C2 -> E4) is not a knight's move");
+    assert_eq!(validate("A1,C2,A1").unwrap_err(), "square 3 (A1) is visited twice");
+    assert_eq!(validate("F7,H8").unwrap_err(), "square 2 (H8) is not an accessible square");
+    assert_eq!(validate("H7,J8").unwrap_err(), "square 2 (J8) is outside of the 8x8 board");
+
+    // moves across a wrapped edge only count on wrapping boards
+    assert!(validate_prefix(&squares("H1,B2"), size, &HashSet::new(), Wrap::None, Leaper::KNIGHT).is_err());
+    assert_eq!(validate_prefix(&squares("H1,B2"), size, &HashSet::new(), Wrap::Horizontal, Leaper::KNIGHT), Ok(()));
+}
+
+#[test]
+fn test_constrained_path_keeps_its_ends() {
+    let size = BoardSize::new(8, 6);
+    let dead = HashSet::from([BoardPos::new(4, 3), BoardPos::new(5, 3)]);
+    let (graph, _) = solve_internal(size, Mode::Constrained(StructureMode::Stretched(Direction::Horizontal), dead.clone())).unwrap();
+
+    let tour = crate::validation::validate(&graph).unwrap();
+    assert_eq!((tour.squares, tour.start, tour.end), (46, BoardPos::new(0, 0), BoardPos::new(0, 1)));
+    assert!(dead.iter().all(|pos| graph.node(*pos).next().is_none() && graph.node(*pos).prev().is_none()));
+
+    // the ends of the path can't be dead
+    let dead = HashSet::from([BoardPos::new(0, 1), BoardPos::new(1, 1)]);
+    assert!(solve_internal(size, Mode::Constrained(StructureMode::Stretched(Direction::Horizontal), dead)).is_err());
+}
@@ -1,10 +1,12 @@
-use std::fmt::Display;
+use std::{collections::HashSet, fmt::Display};
 
-use crate::{args::InputArgs, move_graph::Direction};
+use crate::{args::InputArgs, board_pos::BoardPos, move_graph::Direction};
 
 pub enum Mode {
-    Basic(InputArgs),
+    Basic(Box<InputArgs>),
     Structured(StructureMode),
+    /// A structured tour around squares that can't be visited. Gives up instead of searching for too long
+    Constrained(StructureMode, HashSet<BoardPos>),
     Freeform,
 }
 
@@ -14,6 +16,7 @@ impl Display for Mode{
             Self::Basic(_) => write!(f, "basic"),
             Self::Structured(StructureMode::Closed(_)) => write!(f, "closed"),
             Self::Structured(StructureMode::Stretched(d)) => write!(f, "stretched {}", if d.is_vertical() { "vertical" } else { "horizontal" }),
+            Self::Constrained(structure, _) => write!(f, "constrained {}", Self::Structured(*structure)),
             Self::Freeform => write!(f, "Freeform mode"),
         }
     }
@@ -1,28 +1,77 @@
-use std::fmt::Display;
+use std::{collections::HashSet, fmt::Display, fs::File, io::{BufWriter, Write}, path::Path};
 
-use crate::{board_pos::BoardPos, debug_output};
+use crate::{board_pos::BoardPos, board_size::BoardSize, context::SolverContext, debug_output, mask, move_graph::MoveGraph};
 
+/// The path the search is currently on. It is only recorded for the debug output at verbosity 3, --failure-dump and --best-effort
 pub struct MoveTracker{
-    data: Vec<BoardPos>
+    data: Vec<BoardPos>,
+    // the longest path taken back so far, which is where the search got stuck
+    deepest: Vec<BoardPos>,
+    enabled: bool,
 }
 
 impl MoveTracker{
     pub fn new(capacity: usize) -> Self{
-        let capacity = if debug_output::is_enabled(3) { capacity } else { 0 };
+        let enabled = debug_output::is_enabled(3) || SolverContext::with_current(|context| context.failure_dump().is_some() || context.best_effort());
+        let capacity = if enabled { capacity } else { 0 };
         MoveTracker{
-            data: Vec::with_capacity(capacity)
+            data: Vec::with_capacity(capacity),
+            deepest: Vec::new(),
+            enabled,
         }
     }
 
     pub fn push(&mut self, pos: BoardPos){
-        if debug_output::is_enabled(3) {
+        if self.enabled {
             self.data.push(pos);
         }
     }
 
     pub fn pop(&mut self) -> Option<BoardPos>{
+        // the path only gets longer this way once per square, so this copies at most one path per square
+        if self.data.len() > self.deepest.len() {
+            self.deepest.clone_from(&self.data);
+        }
         self.data.pop()
     }
+
+    /// Hands the longest path the search reached over to --best-effort, as a graph like the one searched, and writes it
+    /// and the board to the --failure-dump file, if there is one. Failing to write it is reported, but doesn't change the outcome of the search
+    pub fn report(&self, graph: &MoveGraph, dead_squares: &HashSet<BoardPos>) {
+        let size = graph.size();
+        if SolverContext::with_current(|context| context.best_effort()) && !self.longest().is_empty() {
+            let longest = MoveGraph::from_tour(size.width(), size.height(), self.longest().iter().copied())
+                .with_wrap(graph.wrap())
+                .with_leaper(graph.leaper());
+            SolverContext::with_current(|context| context.record_longest_path(longest));
+        }
+
+        let Some(path) = SolverContext::with_current(|context| context.failure_dump()) else {
+            return;
+        };
+
+        if let Err(e) = self.write_dump(&path, size, dead_squares) {
+            eprintln!("Could not write failure dump to {}: {e}", path.display());
+        }
+    }
+
+    fn write_dump(&self, path: &Path, size: BoardSize, dead_squares: &HashSet<BoardPos>) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_moves(&mut writer, size, dead_squares)?;
+        writer.flush()
+    }
+
+    fn write_moves(&self, writer: &mut impl Write, size: BoardSize, dead_squares: &HashSet<BoardPos>) -> std::io::Result<()> {
+        let moves: Vec<_> = self.longest().iter().map(ToString::to_string).collect();
+        writeln!(writer, "{}", moves.join(","))?;
+        writeln!(writer)?;
+        mask::write_text_mask(writer, dead_squares, size)
+    }
+
+    /// The longest path the search reached, which is the current one if it hasn't backtracked from there yet
+    fn longest(&self) -> &[BoardPos] {
+        if self.data.len() > self.deepest.len() { &self.data } else { &self.deepest }
+    }
 }
 
 impl Display for MoveTracker {
@@ -40,3 +89,25 @@ impl Display for MoveTracker {
         Ok(())
     }
 }
+
+#[test]
+fn test_write_moves() {
+    let context = SolverContext::new().with_failure_dump(Some("unused".into()));
+    let mut tracker = context.run(|| MoveTracker::new(4));
+    for pos in [BoardPos::new(0, 0), BoardPos::new(2, 1), BoardPos::new(0, 2)] {
+        tracker.push(pos);
+    }
+    tracker.pop();
+    tracker.pop();
+    assert_eq!(tracker.to_string(), "A1");
+
+    // the path the search got furthest on is kept after backtracking
+    let mut buf = Vec::new();
+    tracker.write_moves(&mut buf, BoardSize::new(3, 3), &HashSet::from([BoardPos::new(1, 1)])).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), "A1,C2,A3\n\n###\n# #\n###\n");
+
+    // without the dump or debug output, nothing is recorded
+    let mut tracker = SolverContext::new().run(|| MoveTracker::new(4));
+    tracker.push(BoardPos::new(0, 0));
+    assert_eq!(tracker.to_string(), "");
+}
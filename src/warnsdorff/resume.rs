@@ -0,0 +1,49 @@
+use std::{collections::HashSet, path::Path};
+
+use crate::{
+    aliases::BoardIndex as Idx,
+    args::Wrap,
+    board_pos::{parse_board_pos_list, BoardPos},
+    board_size::BoardSize,
+    leaper::Leaper,
+};
+
+use super::validate_prefix;
+
+/// Reads the squares to continue the search from out of a --failure-dump file and checks that they are a path through the board.
+/// If the file ends in the board it was written for, that board has to be the size of this one
+pub fn load(path: &Path, size: BoardSize, dead_squares: &HashSet<BoardPos>, wrap: Wrap, leaper: Leaper) -> Result<Vec<BoardPos>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("could not read it: {e}"))?;
+    let (squares, dumped_size) = parse(&text)?;
+    if let Some(dumped_size) = dumped_size.filter(|&dumped_size| dumped_size != size) {
+        return Err(format!("it was written for a {dumped_size} board, not a {size} one"));
+    }
+
+    validate_prefix(&squares, size, dead_squares, wrap, leaper)?;
+    Ok(squares)
+}
+
+/// The squares on the first lines and the size of the board after the empty line, if there is one
+fn parse(text: &str) -> Result<(Vec<BoardPos>, Option<BoardSize>), String> {
+    let (moves, mask) = text.split_once("\n\n").unwrap_or((text, ""));
+    let squares = parse_board_pos_list(moves)?;
+    if squares.is_empty() {
+        return Err("it doesn't list any squares".to_string());
+    }
+
+    let rows: Vec<_> = mask.lines().collect();
+    let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    let size = (!rows.is_empty()).then(|| BoardSize::new(width as Idx, rows.len() as Idx));
+    Ok((squares, size))
+}
+
+#[test]
+fn test_parse() {
+    let squares = |list: &str| list.split(',').map(|square| BoardPos::try_from(square).unwrap()).collect::<Vec<_>>();
+    assert_eq!(parse("A1,C2,E1\n\n#####\n# ###\n#####\n"), Ok((squares("A1,C2,E1"), Some(BoardSize::new(5, 3)))));
+    // rows ending in inaccessible squares keep their trailing spaces
+    assert_eq!(parse("A1,C2\n\n### \n##  \n"), Ok((squares("A1,C2"), Some(BoardSize::new(4, 2)))));
+    assert_eq!(parse("A1 C2\nE1\n"), Ok((squares("A1,C2,E1"), None)));
+    assert!(parse("\n\n###\n").is_err());
+    assert!(parse("A1,??\n").is_err());
+}
@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
-use syn::{parenthesized, parse::Parse, parse_macro_input, token::Paren, Error, Ident, LitStr, Token};
+use syn::{parenthesized, parse::Parse, parse_macro_input, token::Paren, Error, Expr, Ident, LitStr, Token};
 
 struct SvgInput {
     writer: Ident,
@@ -26,14 +26,25 @@ struct XmlDoc {
 }
 
 enum XmlDocChild {
-    Repeat(String),
-    Raw{
-        value: String,
-        is_ref: bool
-    },
+    /// `#(#iter)*` or `#(expr)*`, every item on a line of its own, written unescaped with its Display implementation. Items
+    /// go straight into the writer, so large documents don't need to be formatted into strings first
+    Repeat(Expr),
+    Raw(Value),
+    /// `#raw(expr)`, written as it is, line by line at the indent of the surrounding children
+    Block(Expr),
     Doc(XmlDoc),
 }
 
+/// An attribute name or value, or a text child
+enum Value {
+    /// An identifier or a string literal, written as it is
+    Name(String),
+    /// `#ident`, written with its Display implementation
+    Ref(Ident),
+    /// `#(expr)`, written with its Display implementation
+    Expr(Expr),
+}
+
 #[derive(PartialEq, Eq)]
 enum XmlDocStyle {
     Ref,
@@ -44,7 +55,11 @@ impl Parse for XmlDoc {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         input.parse::<Token![<]>()?;
 
-        let (name, is_ref) = parse_str(input, None)?;
+        let (name, is_ref) = match parse_value(input, None)? {
+            Value::Name(name) => (name, false),
+            Value::Ref(ident) => (ident.to_string(), true),
+            Value::Expr(expr) => return Err(Error::new_spanned(expr, "Tag names can't be expressions, bind them to a variable first")),
+        };
         let style = if is_ref {
             XmlDocStyle::Ref
         } else {
@@ -105,31 +120,32 @@ fn parse_attributes(input: syn::parse::ParseStream) -> syn::Result<Vec<XmlAttrib
 }
 
 fn parse_attribute(input: syn::parse::ParseStream) -> syn::Result<XmlAttribute> {
-    let (value, value_is_ref);
-    let (name, mut name_is_ref) = input.call(|s|parse_str(s, None))?;
+    let name = input.call(|s|parse_value(s, None))?;
 
-    if !input.peek(Token![=]) {
-        value = name.clone();
-        value_is_ref = name_is_ref;
-        name_is_ref = false;
-    } else {
+    if input.peek(Token![=]) {
         input.parse::<Token![=]>()?;
-        (value, value_is_ref) = input.call(|s|parse_str(s, None))?;
+        let value = input.call(|s|parse_value(s, None))?;
+        return Ok(XmlAttribute { name, value });
     }
 
-    Ok(XmlAttribute {
-        name,
-        name_is_ref,
-        value,
-        value_is_ref,
-    })
+    // a lone #ident is named after the variable
+    match name {
+        Value::Ref(ident) => Ok(XmlAttribute { name: Value::Name(ident.to_string()), value: Value::Ref(ident) }),
+        Value::Expr(expr) => Err(Error::new_spanned(expr, "Expected '=' and a value, an expression can't name the attribute by itself")),
+        Value::Name(name) => Ok(XmlAttribute { name: Value::Name(name.clone()), value: Value::Name(name) }),
+    }
 }
 
-fn parse_str(input: syn::parse::ParseStream, expected_style: Option<XmlDocStyle>) -> syn::Result<(String, bool)> {
+fn parse_value(input: syn::parse::ParseStream, expected_style: Option<XmlDocStyle>) -> syn::Result<Value> {
     if input.peek(Token![#]) && (expected_style.is_none() || expected_style == Some(XmlDocStyle::Ref)) {
         input.parse::<Token![#]>()?;
-        let name: Ident = input.parse()?;
-        Ok((name.to_string(), true))
+        if input.peek(Paren) {
+            let content;
+            parenthesized!(content in input);
+            return Ok(Value::Expr(content.parse()?));
+        }
+
+        Ok(Value::Ref(input.parse()?))
     } else if input.peek(Ident) && (expected_style.is_none() || expected_style == Some(XmlDocStyle::Name)) {
         let ident: Ident = input.parse()?;
         let mut name = ident.to_string();
@@ -140,18 +156,18 @@ fn parse_str(input: syn::parse::ParseStream, expected_style: Option<XmlDocStyle>
             name = format!("{}-{}", name, ident);
         }
 
-        Ok((name, false))
+        Ok(Value::Name(name))
     } else if input.peek(LitStr) && (expected_style.is_none() || expected_style == Some(XmlDocStyle::Name)) {
         let lit: LitStr = input.parse()?;
-        Ok((lit.value(), false))
+        Ok(Value::Name(lit.value()))
     } else {
         if let Some(style) = expected_style {
-            return match style {
+            match style {
                 XmlDocStyle::Name => Err(input.error("Expected an identifier or a string literal")),
                 XmlDocStyle::Ref => Err(input.error("Expected a #variable")),
-            };
+            }
         } else {
-            return Err(input.error("Expected a #variable, an identifier or a string literal"));
+            Err(input.error("Expected a #variable, an identifier or a string literal"))
         }
     }
 }
@@ -164,16 +180,33 @@ fn parse_children(input: syn::parse::ParseStream) -> syn::Result<Vec<XmlDocChild
         } else if input.peek(Token![<]) {
             res.push(XmlDocChild::Doc(input.parse::<XmlDoc>()?));
         } else if input.peek(Token![#]) {
-            if input.peek2(Paren) {
+            if is_raw_block(input) {
+                input.parse::<Token![#]>()?;
+                input.parse::<Ident>()?;
+                let content;
+                parenthesized!(content in input);
+                res.push(XmlDocChild::Block(content.parse()?));
+            } else if input.peek2(Paren) {
+                // #(#iter)* and #(expr)* repeat, #(expr) without the star is a single value
                 input.parse::<Token![#]>()?;
                 let content;
                 parenthesized!(content in input);
-                let (iter, _) = parse_str(&content, Some(XmlDocStyle::Ref))?;
-                res.push(XmlDocChild::Repeat(iter));
-                input.parse::<Token![*]>()?;
+                let expr: Expr = if content.peek(Token![#]) {
+                    content.parse::<Token![#]>()?;
+                    let ident: Ident = content.parse()?;
+                    syn::parse_quote!(#ident)
+                } else {
+                    content.parse()?
+                };
+
+                if input.peek(Token![*]) {
+                    input.parse::<Token![*]>()?;
+                    res.push(XmlDocChild::Repeat(expr));
+                } else {
+                    res.push(XmlDocChild::Raw(Value::Expr(expr)));
+                }
             } else {
-                let (item, is_ref) = parse_str(input, None)?;
-                res.push(XmlDocChild::Raw{ value: item, is_ref });
+                res.push(XmlDocChild::Raw(parse_value(input, None)?));
             }
         } else {
             return Err(input.error("Expected a child element"));
@@ -181,11 +214,17 @@ fn parse_children(input: syn::parse::ParseStream) -> syn::Result<Vec<XmlDocChild
     }
 }
 
+/// Whether the input continues with `#raw(`, as opposed to a `#raw` variable
+fn is_raw_block(input: syn::parse::ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![#]>().is_ok()
+        && fork.parse::<Ident>().is_ok_and(|ident| ident == "raw")
+        && fork.peek(Paren)
+}
+
 struct XmlAttribute {
-    name: String,
-    name_is_ref: bool,
-    value: String,
-    value_is_ref: bool,
+    name: Value,
+    value: Value,
 }
 
 #[proc_macro]
@@ -212,21 +251,21 @@ fn quote_doc(doc: &XmlDoc, writer: &Ident) -> proc_macro2::TokenStream {
     };
 
     let attributes;
-    let attributes_fmt = if doc.attributes.len() == 0 {
+    let attributes_fmt = if doc.attributes.is_empty() {
         attributes = quote!{ };
         String::new()
     } else {
         let attr_iter: Vec<_> = doc.attributes.iter().map(map_attribute).collect();
         let (attr_fmts, attr_values): (Vec<_>, Vec<_>) = attr_iter.into_iter().unzip();
-        let attr_values = attr_values.into_iter().filter(|v|v.is_some()).map(|v|v.unwrap());
+        let attr_values = attr_values.into_iter().flatten();
         attributes = quote! { #(, #attr_values)* };
         attr_fmts.join("")
     };
 
-    let open_tag_format = format!("{}{}{}>", "{: >indent$}<{}", attributes_fmt, if doc.is_self_closed { "/" } else { "" });
+    let open_tag_format = format!("{}{}{}>", "{:indent$}<{}", attributes_fmt, if doc.is_self_closed { "/" } else { "" });
 
     let mut res = Vec::new();
-    res.push(quote! { writeln!(#writer, #open_tag_format, ' ', #title #attributes)?; });
+    res.push(quote! { writeln!(#writer, #open_tag_format, "", #title #attributes)?; });
     if !doc.is_self_closed {
         let children: Vec<_> = doc.children.iter().map(|child|quote_child(child, writer)).collect();
         res.push(quote!{
@@ -234,7 +273,7 @@ fn quote_doc(doc: &XmlDoc, writer: &Ident) -> proc_macro2::TokenStream {
                 let indent =  indent + 4;
                 #(#children)*
             }
-            writeln!(#writer, "{: >indent$}</{}>", ' ', #title)?;
+            writeln!(#writer, "{:indent$}</{}>", "", #title)?;
         });
     }
 
@@ -246,40 +285,58 @@ fn quote_doc(doc: &XmlDoc, writer: &Ident) -> proc_macro2::TokenStream {
 fn quote_child(child: &XmlDocChild, writer: &Ident) -> proc_macro2::TokenStream {
     match child {
         XmlDocChild::Repeat(iter) => {
-            let ident = Ident::new(iter, Span::call_site());
             quote!{
-                for #ident in #ident {
-                    writeln!(#writer, "{: >indent$}{}", ' ', #ident)?;
+                for item in #iter {
+                    writeln!(#writer, "{:indent$}{}", "", item)?;
                 }
             }
         },
-        XmlDocChild::Raw{ value, is_ref } => {
-            if *is_ref {
-                let ident = Ident::new(value, Span::call_site());
-                quote!{ writeln!(#writer, "{: >indent$}{}", ' ', #ident)?; }
-            } else {
-                let lit = LitStr::new(value, Span::call_site());
-                quote!{ writeln!(#writer, "{: >indent$}{}", ' ', #lit)?; }
+        XmlDocChild::Raw(value) => {
+            let value = match value {
+                Value::Name(name) => {
+                    let lit = LitStr::new(name, Span::call_site());
+                    quote!{ #lit }
+                },
+                Value::Ref(ident) => quote_escaped(quote!{ #ident }),
+                Value::Expr(expr) => quote_escaped(quote!{ (#expr) }),
+            };
+            quote!{ writeln!(#writer, "{:indent$}{}", "", #value)?; }
+        },
+        XmlDocChild::Block(expr) => quote!{
+            for line in ::std::string::ToString::to_string(&(#expr)).lines() {
+                // no trailing whitespace on empty lines
+                if line.is_empty() {
+                    writeln!(#writer)?;
+                } else {
+                    writeln!(#writer, "{:indent$}{}", "", line)?;
+                }
             }
         },
         XmlDocChild::Doc(doc) => quote_doc(doc, writer),
     }
 }
 
-fn map_attribute(attr: &XmlAttribute) -> (String, Option<proc_macro2::TokenStream>) {
-    let (name_format, name_args) = if attr.name_is_ref {
-        let ident = Ident::new(&attr.name, Span::call_site());
-        (String::from(" {}="), Some(quote! { #ident }))
-    } else {
-        (format!(" {}=", attr.name), None)
-    };
+/// Dynamic text and attribute values go through `crate::svg::xml_escape`, which the crate using the macro has to provide.
+/// Literals are the macro author's own markup and are written as they are
+fn quote_escaped(value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote!{ crate::svg::xml_escape(&::std::string::ToString::to_string(&#value)) }
+}
 
-    let (value_format, value_args) = if attr.value_is_ref {
-        let ident = Ident::new(&attr.value, Span::call_site());
-        (String::from("\"{}\""), Some(quote!{ #ident }))
-    } else {
-        (format!("\"{}\"", attr.value), None)
-    };
+/// The format string for the value and the argument it takes, if any
+fn map_value(value: &Value, escape: bool) -> (String, Option<proc_macro2::TokenStream>) {
+    let arg = |value: proc_macro2::TokenStream| if escape { quote_escaped(value) } else { value };
+    match value {
+        // literals become part of the format string
+        Value::Name(name) => (name.replace('{', "{{").replace('}', "}}"), None),
+        Value::Ref(ident) => (String::from("{}"), Some(arg(quote!{ #ident }))),
+        Value::Expr(expr) => (String::from("{}"), Some(arg(quote!{ (#expr) }))),
+    }
+}
+
+fn map_attribute(attr: &XmlAttribute) -> (String, Option<proc_macro2::TokenStream>) {
+    let (name_format, name_args) = map_value(&attr.name, false);
+    let (value_format, value_args) = map_value(&attr.value, true);
+    let (name_format, value_format) = (format!(" {name_format}="), format!("\"{value_format}\""));
 
     let tt = match (name_args, value_args) {
         (Some(name), Some(val)) => Some(quote!{ #name, #val }),
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{run, temp_dir::TempDir};
+
+#[test]
+fn test_longest_path_instead_of_nothing() {
+    // Warnsdorff gives up on A1 -> C1 long before it could prove that the squares are of the same color
+    let args = ["-w", "-s", "6x6", "-p", "A1", "--ending-pos", "C1", "--backtrack-limit", "200"];
+    let (stdout, stderr, code) = run(&args);
+    assert_eq!(code, Some(3), "{stderr}");
+    assert!(!stdout.contains("Partial tour") && !stdout.contains("|  1 |"), "{stdout}");
+
+    let (stdout, stderr, code) = run(&[&args[..], &["--best-effort"]].concat());
+    assert_eq!(code, Some(3), "{stderr}");
+    assert!(stdout.contains("No knight's tour for this board configuration: gave up after 201 backtracks."), "{stdout}");
+    assert!(stdout.contains("Partial tour: no full tour was found, this is the longest path of 34 squares"), "{stdout}");
+    assert!(stdout.contains("|  1 |") && stdout.contains("| 34 |") && !stdout.contains("| 35 |"), "{stdout}");
+    assert!(stderr.contains("result=partial size=6x6 solver=warnsdorff closed=false complete=false squares=34 "), "{stderr}");
+}
+
+#[test]
+fn test_partial_svg() {
+    let dir = TempDir::new("best_effort");
+    let board = dir.join("board.txt");
+    std::fs::write(&board, "####\n####\n## #\n####\n").unwrap();
+    let (stdout, stderr, code) = run(&["-f", board.to_str().unwrap(), "-b", "text", "--best-effort", "-O", "svg", "--verify"]);
+
+    // the exhaustive search proves that there is no tour, the path is the longest one Warnsdorff took before handing over
+    assert_eq!(code, Some(3), "{stderr}");
+    assert!(stdout.contains("proven that no open tour exists from A1."), "{stdout}");
+    assert!(stdout.contains("Partial knight's tour on a 4\u{d7}4 board, 12 moves, no full tour found"), "{stdout}");
+    assert!(stdout.contains("complete=false"), "{stdout}");
+    assert!(stderr.contains("result=partial "), "{stderr}");
+}
+
+#[test]
+fn test_longest_path_on_a_board_without_tours() {
+    // the shortcut for boards known to have no tour would leave nothing to write
+    let (stdout, stderr, code) = run(&["-w", "-s", "4"]);
+    assert_eq!(code, Some(3), "{stderr}");
+    assert!(stdout.contains("No knight's tour possible for a 4x4 board"), "{stdout}");
+    assert!(stderr.contains("result=infeasible "), "{stderr}");
+
+    let (stdout, stderr, code) = run(&["-w", "-s", "4", "--best-effort", "--verify"]);
+    assert_eq!(code, Some(3), "{stderr}");
+    assert!(stdout.contains("No knight's tour possible for a 4x4 board"), "{stdout}");
+    assert!(stdout.contains("Partial tour: no full tour was found, this is the longest path of 15 squares"), "{stdout}");
+    assert!(stderr.contains("result=partial size=4x4 solver=warnsdorff closed=false complete=false squares=15 "), "{stderr}");
+}
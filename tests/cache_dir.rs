@@ -0,0 +1,38 @@
+mod common;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> (String, bool) {
+    let (stdout, _, code) = common::run(&[args, &["--no-summary", "--verify"]].concat());
+    // everything but the time it took
+    let tour = stdout.lines().filter(|line| !line.contains("Elapsed time")).collect::<Vec<_>>().join("\n");
+    (tour, code == Some(0))
+}
+
+#[test]
+fn test_cached_sectors_are_reused_or_ignored() {
+    let dir = TempDir::new("cache_dir_test");
+    let dir_arg = dir.to_str().unwrap();
+
+    let (first, success) = run(&["-s", "37x29", "--cache-dir", dir_arg]);
+    assert!(success);
+    let files: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    assert!(!files.is_empty());
+
+    // the sectors read back make for the same tour
+    let (second, success) = run(&["-s", "37x29", "--cache-dir", dir_arg]);
+    assert!(success);
+    assert_eq!(first, second);
+
+    // damaged files are solved again and written anew
+    let texts: Vec<_> = files.iter().map(|file| std::fs::read_to_string(file).unwrap()).collect();
+    for (file, text) in files.iter().zip(&texts) {
+        std::fs::write(file, &text[..text.len() / 2]).unwrap();
+    }
+    let (third, success) = run(&["-s", "37x29", "--cache-dir", dir_arg]);
+    assert!(success);
+    assert_eq!(first, third);
+    for (file, text) in files.iter().zip(&texts) {
+        assert_eq!(&std::fs::read_to_string(file).unwrap(), text, "{}", file.display());
+    }
+}
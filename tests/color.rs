@@ -0,0 +1,30 @@
+mod common;
+
+fn run(args: &[&str], no_color: Option<&str>) -> String {
+    let (stdout, _, code) = common::run_with(&[args, &["--no-summary"]].concat(), |command| {
+        match no_color {
+            Some(value) => command.env("NO_COLOR", value),
+            None => command.env_remove("NO_COLOR"),
+        };
+    });
+
+    assert_eq!(code, Some(0));
+    // everything but the time it took
+    stdout.lines().filter(|line| !line.contains("Elapsed time")).collect::<Vec<_>>().join("\n")
+}
+
+#[test]
+fn test_color_only_where_asked_for() {
+    // stdout is a pipe here, so --color on its own leaves the board alone
+    let plain = run(&["-s", "6"], None);
+    assert!(!plain.contains('\x1b'), "{plain}");
+    assert!(!run(&["-s", "6", "--color"], None).contains('\x1b'));
+
+    let colored = run(&["-s", "6", "--color", "always"], None);
+    assert_eq!(colored.matches("\x1b[0m").count(), 36, "{colored}");
+    assert_eq!(run(&["-s", "6", "--color", "always"], Some("1")), colored);
+    assert!(!run(&["-s", "6", "--color", "never"], None).contains('\x1b'));
+
+    // other formats aren't colored
+    assert!(!run(&["-s", "6", "--color", "always", "-O", "arrows"], None).contains('\x1b'));
+}
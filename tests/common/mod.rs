@@ -0,0 +1,119 @@
+//! Shared by the integration tests: running the binary, directories to write files to, and checks for the tests that
+//! call the solvers directly.
+//! The checks only rely on the squares of the tour, not on how the solvers link them up
+
+// every test crate including this uses only some of it
+#![allow(dead_code)]
+
+use std::{collections::HashSet, process::Command};
+
+use knights_tour::{aliases::BoardIndex as Idx, BoardPos, BoardSize, Tour};
+
+// the same directories the unit tests use
+#[path = "../../src/temp_dir.rs"]
+pub mod temp_dir;
+
+/// Runs the binary, returning what it wrote to stdout and stderr and the code it exited with
+pub fn run(args: &[&str]) -> (String, String, Option<i32>) {
+    run_with(args, |_| {})
+}
+
+/// Like [run], letting the test change the command before it runs, e.g. its environment
+pub fn run_with(args: &[&str], setup: impl FnOnce(&mut Command)) -> (String, String, Option<i32>) {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_knights_tour"));
+    command.args(args);
+    setup(&mut command);
+    let output = command.output().expect("failed to run knights_tour");
+    (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap(), output.status.code())
+}
+
+/// Whether the knight can jump from one square to the other
+fn is_knight_move(from: BoardPos, to: BoardPos) -> bool {
+    let (cols, rows) = (from.col().abs_diff(to.col()), from.row().abs_diff(to.row()));
+    matches!((cols, rows), (1, 2) | (2, 1))
+}
+
+/// Checks that the tour visits every square of the board exactly once, that every move is a knight's move
+/// and, if `closed`, that the last square is a move away from the first and the tour says it is closed
+pub fn verify_tour(tour: &Tour, size: BoardSize, closed: bool) -> Result<(), String> {
+    if tour.size() != size {
+        return Err(format!("the tour is of a {} board, not a {size} one", tour.size()));
+    }
+
+    let squares: Vec<_> = tour.iter().collect();
+    if squares.len() != size.area() as usize {
+        return Err(format!("{} squares visited, the board has {}", squares.len(), size.area()));
+    }
+
+    let mut visited = HashSet::new();
+    for &pos in &squares {
+        if !size.fits(pos) {
+            return Err(format!("{pos} is outside of the board"));
+        }
+
+        if !visited.insert(pos) {
+            return Err(format!("{pos} is visited twice"));
+        }
+    }
+
+    if let Some(pair) = squares.windows(2).find(|pair| !is_knight_move(pair[0], pair[1])) {
+        return Err(format!("{} -> {} is not a knight's move", pair[0], pair[1]));
+    }
+
+    if closed && !(tour.is_closed() && is_knight_move(squares[squares.len() - 1], squares[0])) {
+        return Err(format!("the tour from {} to {} isn't closed", squares[0], squares[squares.len() - 1]));
+    }
+
+    Ok(())
+}
+
+/// Whether an open tour starting on the square exists, by trying every path. Only meant for boards of up to 30 squares,
+/// the moves are tried in the order Warnsdorff's rule suggests so that tours are found quickly where there are any
+pub fn tour_exists(size: BoardSize, start: BoardPos) -> bool {
+    let (width, height) = (size.width() as usize, size.height() as usize);
+    assert!(width * height <= 30, "{size} is too large to search exhaustively");
+
+    let mut visited = vec![false; width * height];
+    visited[start.row() as usize * width + start.col() as usize] = true;
+    search(size, &mut visited, start, 1)
+}
+
+fn neighbors(size: BoardSize, pos: BoardPos) -> impl Iterator<Item = BoardPos> {
+    const OFFSETS: [(i64, i64); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+    OFFSETS.into_iter().filter_map(move |(cols, rows)| {
+        let col = pos.col() as i64 + cols;
+        let row = pos.row() as i64 + rows;
+        let inside = (0..size.width() as i64).contains(&col) && (0..size.height() as i64).contains(&row);
+        inside.then(|| BoardPos::new(col as Idx, row as Idx))
+    })
+}
+
+fn search(size: BoardSize, visited: &mut [bool], pos: BoardPos, count: usize) -> bool {
+    if count == visited.len() {
+        return true;
+    }
+
+    let width = size.width() as usize;
+    let free = |visited: &[bool], pos: BoardPos| !visited[pos.row() as usize * width + pos.col() as usize];
+    // a square neither the knight nor any other free square can jump to is never reached
+    let stranded = (0..visited.len())
+        .map(|idx| BoardPos::new((idx % width) as Idx, (idx / width) as Idx))
+        .filter(|&square| free(visited, square))
+        .any(|square| neighbors(size, square).all(|from| from != pos && !free(visited, from)));
+    if stranded {
+        return false;
+    }
+
+    let mut next: Vec<_> = neighbors(size, pos).filter(|&next| free(visited, next)).collect();
+    next.sort_by_key(|&next| neighbors(size, next).filter(|&onward| free(visited, onward)).count());
+
+    for next in next {
+        visited[next.row() as usize * width + next.col() as usize] = true;
+        if search(size, visited, next, count + 1) {
+            return true;
+        }
+        visited[next.row() as usize * width + next.col() as usize] = false;
+    }
+
+    false
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use common::run;
+
+fn numbers(line: &str) -> Vec<usize> {
+    line.split('|').filter_map(|cell| cell.trim().parse().ok()).collect()
+}
+
+#[test]
+fn test_warnsdorff_cuts_off_the_corners() {
+    // a radius of 4 cuts a single square off each corner of an 8x8 board
+    let (stdout, stderr, code) = run(&["-w", "-s", "8", "--corner-radius", "4", "-p", "D4", "--no-summary"]);
+    assert_eq!(code, Some(0), "{stderr}");
+
+    let rows: Vec<_> = stdout.lines().filter(|line| line.contains("| ")).map(numbers).collect();
+    assert_eq!(rows.iter().map(Vec::len).collect::<Vec<_>>(), [6, 8, 8, 8, 8, 8, 8, 6], "{stdout}");
+    assert_eq!(rows[3][3], 1, "{stdout}");
+
+    let mut squares: Vec<_> = rows.concat();
+    squares.sort();
+    assert_eq!(squares, (1..=60).collect::<Vec<_>>(), "{stdout}");
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{run, temp_dir::TempDir};
+
+#[test]
+fn test_counts() {
+    let (stdout, stderr, code) = run(&["-s", "3x4", "--count-solutions"]);
+    assert_eq!(code, Some(0));
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("16 tours"));
+    assert!(lines.next().unwrap().contains("Elapsed time"), "{stdout}");
+    assert!(stderr.contains("result=counted size=3x4 solver=count tours=16 counted=all"), "{stderr}");
+
+    let (stdout, _, code) = run(&["-s", "3x10", "--count-solutions", "closed", "--no-summary"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("16 closed tours\n"), "{stdout}");
+
+    // inaccessible squares are left out
+    let dir = TempDir::new("count_solutions");
+    let path = dir.join("board.txt");
+    std::fs::write(&path, "### \n####\n####\n####\n####\n").unwrap();
+    let (stdout, _, code) = run(&["-f", path.to_str().unwrap(), "-b", "text", "--count-solutions", "--no-summary"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("288 tours\n"), "{stdout}");
+}
+
+#[test]
+fn test_large_boards_are_rejected() {
+    let (stdout, stderr, code) = run(&["-s", "7", "--count-solutions"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("49 squares"), "{stderr}");
+    assert!(stderr.contains("result=error reason=too-large"), "{stderr}");
+
+    let (_, _, code) = run(&["-s", "3x4", "--count-solutions", "-o", "tours.txt"]);
+    assert_eq!(code, Some(2));
+}
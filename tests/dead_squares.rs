@@ -0,0 +1,36 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn test_listed_squares_are_left_out() {
+    for solver in [&[][..], &["-w"]] {
+        let (stdout, stderr, code) = run(&[&["-s", "8", "--dead-squares", "C3, D5", "--verify"][..], solver].concat());
+        assert_eq!(code, Some(0), "{stderr}");
+        // 62 squares are numbered, C3 and D5 are blank
+        assert!(stdout.contains("| 62 |"), "{stdout}");
+        assert!(!stdout.contains("| 63 |"), "{stdout}");
+        let rows: Vec<_> = stdout.lines().filter(|line| line.starts_with('|')).collect();
+        assert_eq!(rows[2].split('|').nth(3), Some("    "), "{stdout}");
+        assert_eq!(rows[4].split('|').nth(4), Some("    "), "{stdout}");
+    }
+}
+
+#[test]
+fn test_unbalanced_squares_are_infeasible() {
+    // A1 and C1 are both light, which leaves two more dark squares than light ones
+    let (_, stderr, code) = run(&["-s", "8", "--dead-squares", "A1,C1"]);
+    assert_eq!(code, Some(3));
+    assert!(stderr.contains("result=infeasible reason=color-imbalance"), "{stderr}");
+}
+
+#[test]
+fn test_squares_off_the_board() {
+    let (_, stderr, code) = run(&["-s", "5", "--dead-squares", "A1,F2,B9"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("F2, B9"), "{stderr}");
+
+    // the squares are checked against --board-size, which is required
+    let (_, _, code) = run(&["--dead-squares", "A1"]);
+    assert_eq!(code, Some(2));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+fn run(args: &[&str]) -> (String, String, bool) {
+    let (stdout, stderr, code) = common::run(args);
+    (stdout, stderr, code == Some(0))
+}
+
+#[test]
+fn test_dry_run_prints_the_degree_map() {
+    let (stdout, stderr, success) = run(&["-s", "8", "--degree-map", "--dry-run"]);
+    assert!(success, "{stderr}");
+    assert_eq!(stdout.lines().next(), Some("23444432"), "{stdout}");
+    assert_eq!(stdout.lines().nth(3), Some("46888864"), "{stdout}");
+    assert_eq!(stdout.lines().count(), 8, "{stdout}");
+    assert!(stderr.starts_with("result=dry-run "), "{stderr}");
+
+    // the cut off corners have no degree
+    let (stdout, _, success) = run(&["-s", "12x9", "-w", "-c", "6 4 (2,5) 0", "--degree-map", "--dry-run"]);
+    assert!(success);
+    assert_eq!(stdout.lines().take(2).collect::<Vec<_>>(), ["  344444443 ", " 45566666543"], "{stdout}");
+}
+
+#[test]
+fn test_degree_map_beneath_the_tour() {
+    let (stdout, stderr, success) = run(&["-s", "6", "--degree-map", "-O", "svg", "--no-summary"]);
+    assert!(success, "{stderr}");
+    assert_eq!(stdout.matches("data-degree=").count(), 36, "{stdout}");
+    assert!(stdout.rfind("data-degree=").unwrap() < stdout.find("<line ").unwrap(), "{stdout}");
+
+    let (stdout, _, success) = run(&["-s", "6", "--degree-map", "--no-summary"]);
+    assert!(success);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines[2], "234432", "{stdout}");
+    assert_eq!(lines[8], "", "{stdout}");
+    assert!(lines[9].starts_with('+'), "{stdout}");
+}
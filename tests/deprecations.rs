@@ -0,0 +1,49 @@
+mod common;
+
+fn run(args: &[&str], suppress: bool) -> (String, bool) {
+    let (_, stderr, code) = common::run_with(&[args, &["-s", "8", "-q", "--no-summary"]].concat(), |command| {
+        command.env_remove("KT_NO_DEPRECATION_WARNINGS");
+        if suppress {
+            command.env("KT_NO_DEPRECATION_WARNINGS", "1");
+        }
+    });
+    (stderr, code == Some(0))
+}
+
+#[test]
+fn test_old_forms_still_work() {
+    for args in [&["--wrap", "none", "--snap-start", "--random-seed", "5"][..], &["--wrap", "none", "--lookahead", "2"]] {
+        let (stderr, success) = run(args, false);
+        assert!(success, "{args:?}: {stderr}");
+        assert_eq!(stderr.matches("is deprecated").count(), 1, "{args:?}: {stderr}");
+        assert!(stderr.contains("Add '--use-warnsdorff'"), "{stderr}");
+    }
+}
+
+#[test]
+fn test_each_warning_fires_once() {
+    let (stderr, success) = run(&["--wrap", "none", "--prefix-moves", "B2", "--snap-start", "--lookahead", "2", "--tie-break", "pohl"], false);
+    assert!(success);
+    assert_eq!(stderr.lines().filter(|line| line.starts_with("warning:")).count(), 2, "{stderr}");
+}
+
+#[test]
+fn test_warnings_can_be_turned_off() {
+    let (stderr, success) = run(&["--wrap", "none", "--snap-start"], true);
+    assert!(success);
+    assert_eq!(stderr, "");
+
+    // neither the current forms nor the errors they replace are affected
+    let (stderr, success) = run(&["-w", "--wrap", "none", "-p", "B2"], false);
+    assert!(success);
+    assert_eq!(stderr, "");
+    let (_, success) = run(&["--random-seed", "5"], true);
+    assert!(!success);
+}
+
+#[test]
+fn test_starting_pos_without_warnsdorff_is_rejected() {
+    let (stderr, success) = run(&["--wrap", "none", "-p", "B2"], true);
+    assert!(!success);
+    assert!(stderr.contains("'--starting-pos' requires '--use-warnsdorff', divide and conquer decides where its tours start."), "{stderr}");
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> (String, String, bool) {
+    let (stdout, stderr, code) = common::run(args);
+    (stdout, stderr, code == Some(0))
+}
+
+#[test]
+fn test_dry_run_prints_the_sectors() {
+    let (stdout, stderr, success) = run(&["-s", "20x13", "--dry-run"]);
+    assert!(success, "{stderr}");
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines[0], "sector  offset  size  mode         merge", "{stdout}");
+    assert_eq!(lines[1], "0       A1      10x7  closed       -", "{stdout}");
+    assert_eq!(lines[3], "2       Q1      4x7   stretched-h  horizontal", "{stdout}");
+    assert_eq!(lines[4], "3       A8      10x3  stretched-v  vertical", "{stdout}");
+    assert_eq!(lines.len(), 8, "{stdout}");
+    assert!(stderr.starts_with("result=dry-run solver=dnc sectors=7"), "{stderr}");
+
+    // Warnsdorff doesn't cut the board up, and boards without a tour aren't cut up either
+    for args in [&["-s", "20x13", "--dry-run", "-w"][..], &["-s", "3", "--dry-run"]] {
+        let (stdout, stderr, success) = run(args);
+        assert!(success, "{stderr}");
+        assert_eq!(stdout, "", "{args:?}");
+        assert!(!stderr.contains("sectors="), "{stderr}");
+    }
+}
+
+#[test]
+fn test_dry_run_draws_the_sectors() {
+    let dir = TempDir::new("dry_run_test");
+    let path = dir.join("plan.svg");
+
+    let (stdout, stderr, success) = run(&["-s", "20x13", "--dry-run", "-o", path.to_str().unwrap()]);
+    let svg = std::fs::read_to_string(&path).unwrap();
+    assert!(success, "{stderr}");
+    assert_eq!(stdout, "");
+    assert_eq!(svg.matches("data-sector=").count(), 7, "{svg}");
+    assert_eq!(svg.matches("data-mode=\"closed\"").count(), 1, "{svg}");
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use std::path::Path;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> bool {
+    common::run(&[args, &["--no-summary", "-q"]].concat()).2 == Some(0)
+}
+
+fn path(dir: &Path, name: &str) -> String {
+    dir.join(name).to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_emitted_mask_round_trips() {
+    let dir = TempDir::new("emit_mask_test");
+    let (text, again, image, from_image) = (path(&dir, "mask.txt"), path(&dir, "again.txt"), path(&dir, "mask.png"), path(&dir, "from_image.txt"));
+
+    // A1 is cut off, so none of these get to solving, but the mask is written before that
+    run(&["-s", "12x9", "-w", "-c", "6 4 (2,5) 0", "--emit-mask", &text]);
+    run(&["-f", &text, "-b", "text", "--emit-mask", &again]);
+    run(&["-f", &text, "-b", "text", "--emit-mask", &image]);
+    run(&["-f", &image, "-b", "image", "-i", "black-white", "--emit-mask", &from_image]);
+    let read = |path: &str| std::fs::read_to_string(path).unwrap();
+    let (text, again, from_image) = (read(&text), read(&again), read(&from_image));
+
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 9, "{text}");
+    assert!(lines.iter().all(|line| line.len() == 12), "{text}");
+    assert_eq!(lines[0], "  ######### ");
+    assert_eq!(lines[1], " ###########");
+    assert_eq!(lines[8], "############");
+    assert_eq!(again, text);
+    assert_eq!(from_image, text);
+}
+
+#[test]
+fn test_mask_is_written_without_a_tour() {
+    let dir = TempDir::new("emit_mask_unsolved_test");
+    let mask = path(&dir, "mask.txt");
+
+    let success = run(&["-s", "3", "--emit-mask", &mask]);
+    let text = std::fs::read_to_string(&mask);
+
+    assert!(!success);
+    assert_eq!(text.unwrap(), "###\n###\n###\n");
+}
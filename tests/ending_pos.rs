@@ -0,0 +1,42 @@
+mod common;
+
+fn run(args: &[&str]) -> (String, String, Option<i32>) {
+    common::run(&[args, &["--no-summary"]].concat())
+}
+
+#[test]
+fn test_searches_the_other_way_round() {
+    // from B1, Warnsdorff finds its way to A1 within the budget, but not the other way round
+    let (stdout, stderr, code) = run(&["-w", "-s", "6x6", "-p", "B1", "--ending-pos", "A1", "--backtrack-limit", "200", "-v"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(!stderr.contains("other way round"), "{stderr}");
+    assert!(stdout.contains("|  1 |"), "{stdout}");
+
+    let (stdout, stderr, code) = run(&["-w", "-s", "6x6", "-p", "A1", "--ending-pos", "B1", "--backtrack-limit", "200", "--verify", "-v"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(stderr.contains("No tour found from A1 to B1: gave up after 201 backtracks. Searching from B1 to A1 instead."), "{stderr}");
+    assert!(stderr.contains("Found the tour searching the other way round."), "{stderr}");
+    // the tour found from B1 is reversed, so that it still starts on A1 and ends on B1
+    assert!(stdout.contains("|  1 | 36 |"), "{stdout}");
+}
+
+#[test]
+fn test_no_tour_either_way() {
+    // both squares are white, a tour of the 36 squares ends on a square of the other color
+    let (stdout, stderr, code) = run(&["-w", "-s", "6x6", "-p", "A1", "--ending-pos", "C1", "--backtrack-limit", "200", "-v"]);
+    assert_eq!(code, Some(3), "{stderr}");
+    assert!(stderr.contains("Searching from C1 to A1 instead."), "{stderr}");
+    // the search that was asked for is the one reported
+    assert!(stdout.contains("No knight's tour for this board configuration: gave up after 201 backtracks."), "{stdout}");
+}
+
+#[test]
+fn test_invalid_ending_pos() {
+    let (_, stderr, code) = run(&["-w", "-s", "6x6", "-p", "C2", "--ending-pos", "C2"]);
+    assert_eq!(code, Some(1));
+    assert!(stderr.contains("Ending position C2 is the starting position, use --mode closed for a closed tour."), "{stderr}");
+
+    let (_, stderr, code) = run(&["-w", "-s", "6x6", "--dead-squares", "F6", "--ending-pos", "F6"]);
+    assert_eq!(code, Some(1));
+    assert!(stderr.contains("Ending position F6 is not an accessible square."), "{stderr}");
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::run;
+
+fn exit_code(args: &[&str]) -> Option<i32> {
+    run(args).2
+}
+
+#[test]
+fn test_fail_on_open() {
+    // boards with an odd number of squares only have open tours
+    assert_eq!(exit_code(&["-s", "7", "-q", "--fail-on-open"]), Some(5));
+    assert_eq!(exit_code(&["-s", "8", "-q", "--fail-on-open"]), Some(0));
+}
+
+#[test]
+fn test_fail_on_closed() {
+    assert_eq!(exit_code(&["-s", "8", "-q", "--fail-on-closed"]), Some(5));
+    assert_eq!(exit_code(&["-s", "5", "-q", "--fail-on-closed"]), Some(0));
+}
+
+#[test]
+fn test_conflicting_assertions() {
+    assert_eq!(exit_code(&["-s", "8", "-q", "--fail-on-open", "--fail-on-closed"]), Some(2));
+}
+
+#[test]
+fn test_no_tour() {
+    // no tour exists on a 4x4 board
+    assert_eq!(exit_code(&["-s", "4", "-q"]), Some(3));
+    assert_eq!(exit_code(&["-s", "4", "-q", "-w"]), Some(3));
+    assert_eq!(exit_code(&["-s", "8", "-q"]), Some(0));
+}
+
+#[test]
+fn test_input_errors() {
+    assert_eq!(exit_code(&["-q", "--board-file", "does/not/exist.txt", "--board-file-format", "text"]), Some(1));
+    assert_eq!(exit_code(&["--batch", "does/not/exist.txt"]), Some(1));
+    assert_eq!(exit_code(&["-s", "8", "-q", "-w", "--starting-pos", "A1", "--prefix-moves", "A1,C3"]), Some(2));
+    assert_eq!(exit_code(&["-s", "8", "-q", "-w", "--prefix-moves", "A1,A2"]), Some(1));
+    assert_eq!(exit_code(&["-s", "8", "-o", "does/not/exist/tour.txt"]), Some(1));
+}
+
+#[test]
+fn test_random_seed_requires_warnsdorff() {
+    // divide and conquer doesn't use the seed, so accepting it would suggest tours it never produces
+    let (stdout, stderr, code) = run(&["-s", "100", "--random-seed", "7"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("'--random-seed' requires '--use-warnsdorff' or '--solve-order random', divide and conquer otherwise finds the same tour"), "{stderr}");
+    assert!(stdout.is_empty());
+
+    assert_eq!(exit_code(&["-s", "6", "-w", "-q", "--no-summary", "-p", "random", "--random-seed", "7"]), Some(0));
+    assert_eq!(exit_code(&["-s", "6", "--wrap", "both", "-q", "--no-summary", "--random-seed", "7"]), Some(0));
+    // the seed of the order divide and conquer solves the sectors in
+    assert_eq!(exit_code(&["-s", "100", "-q", "--no-summary", "--solve-order", "random", "--random-seed", "7"]), Some(0));
+}
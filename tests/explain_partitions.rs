@@ -0,0 +1,25 @@
+//! Compares the --explain-partitions narrative with the files in tests/golden, so changes to the partition tables show up in review.
+//! After an intended change, regenerate them with e.g. `knights_tour -s 50 -q --no-summary --explain-partitions 2> tests/golden/explain_partitions_50x50.txt`
+
+mod common;
+
+fn explain(args: &[&str]) -> String {
+    let (_, stderr, code) = common::run(&[args, &["-q", "--no-summary", "--explain-partitions"]].concat());
+    assert_eq!(code, Some(0), "{args:?} failed");
+    stderr
+}
+
+#[test]
+fn test_explain_50x50() {
+    assert_eq!(explain(&["-s", "50"]), include_str!("golden/explain_partitions_50x50.txt"));
+}
+
+#[test]
+fn test_explain_37x41() {
+    assert_eq!(explain(&["-s", "37x41"]), include_str!("golden/explain_partitions_37x41.txt"));
+}
+
+#[test]
+fn test_explain_ignored_for_warnsdorff() {
+    assert_eq!(explain(&["-s", "6", "-w"]), "");
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> bool {
+    common::run(&[args, &["--no-summary", "-q"]].concat()).2 == Some(0)
+}
+
+#[test]
+fn test_dump_on_failure() {
+    let dir = TempDir::new("failure_dump");
+    let (board, dump) = (dir.join("board.txt"), dir.join("dump.txt"));
+    std::fs::write(&board, "####\n####\n## #\n####\n").unwrap();
+    let success = run(&["-f", board.to_str().unwrap(), "-b", "text", "--exhaustive-limit", "0", "--failure-dump", dump.to_str().unwrap()]);
+    let contents = std::fs::read_to_string(&dump).unwrap();
+
+    assert!(!success);
+    let (moves, mask) = contents.split_once("\n\n").unwrap();
+    assert_eq!(mask, "####\n####\n## #\n####\n");
+
+    // the moves are a path through the board that --prefix-moves accepts
+    let squares: Vec<_> = moves.split(',').collect();
+    assert_eq!(squares[0], "A1");
+    assert!(squares.len() > 1 && squares.len() < 15, "{moves}");
+    let success = run(&["-f", board.to_str().unwrap(), "-b", "text", "--exhaustive-limit", "0", "--prefix-moves", moves]);
+    assert!(!success);
+}
+
+#[test]
+fn test_no_dump_on_success() {
+    let dir = TempDir::new("failure_dump_unused");
+    let dump = dir.join("unused.txt");
+    assert!(run(&["-w", "-s", "8", "--failure-dump", dump.to_str().unwrap()]));
+    assert!(!dump.exists());
+}
+
+#[test]
+fn test_dump_of_failed_sector() {
+    // the 10x3 sector at K18 fails around the cut off corner before it is solved another way
+    let dir = TempDir::new("failure_dump_sector");
+    let dump = dir.join("sector.txt");
+    assert!(run(&["-s", "20", "-c", "0 0 10 0", "--failure-dump", dump.to_str().unwrap()]));
+    let contents = std::fs::read_to_string(&dump).unwrap();
+
+    let (_, mask) = contents.split_once("\n\n").unwrap();
+    assert_eq!(mask.lines().count(), 3, "{mask}");
+    assert!(mask.lines().all(|line| line.len() == 10), "{mask}");
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> bool {
+    common::run(&[args, &["--no-summary", "-q"]].concat()).2 == Some(0)
+}
+
+#[test]
+fn test_heatmap_with_tour() {
+    // Warnsdorff takes back tens of thousands of moves before finding the tour of this board from C3
+    let dir = TempDir::new("heatmap_solved");
+    let path = dir.join("solved.svg");
+    assert!(run(&["-w", "-s", "6x5", "-p", "C3", "--exhaustive-limit", "0", "--heatmap", path.to_str().unwrap()]));
+    let svg = std::fs::read_to_string(&path).unwrap();
+
+    roxmltree::Document::parse(&svg).unwrap();
+    assert!(svg.contains("63436 moves taken back on a 6\u{d7}5 board"), "{svg}");
+    assert!(svg.contains("data-retractions="), "{svg}");
+    assert_eq!(svg.matches("<line").count(), 29, "{svg}");
+}
+
+#[test]
+fn test_heatmap_without_tour() {
+    let dir = TempDir::new("heatmap_failed");
+    let (board, path) = (dir.join("board.txt"), dir.join("failed.svg"));
+    std::fs::write(&board, "####\n####\n## #\n####\n").unwrap();
+    let success = run(&["-f", board.to_str().unwrap(), "-b", "text", "--exhaustive-limit", "0", "--heatmap", path.to_str().unwrap()]);
+    let svg = std::fs::read_to_string(&path).unwrap();
+
+    assert!(!success);
+    assert!(svg.contains("data-retractions="), "{svg}");
+    assert!(!svg.contains("<line"), "{svg}");
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use knights_tour::{solve_warnsdorff, Args, BoardPos, BoardSize, Tour};
+
+/// Solves the board with Warnsdorff from the given square, which hands boards this small over to its exhaustive search
+/// when it gets stuck, so that it gives a definitive answer
+fn warnsdorff_tour(size: BoardSize, start: BoardPos) -> Option<Tour> {
+    let args = Args::parse_from(["knights_tour", "-w", "-s", &size.to_string(), "-p", &start.to_string()]);
+    let (_, graph) = solve_warnsdorff(args.input)?;
+    Some(Tour::from_graph(&graph).expect("the solver returned a broken tour"))
+}
+
+#[test]
+fn test_warnsdorff_agrees_with_oracle() {
+    for width in 1..=5 {
+        for height in 1..=6 {
+            let size = BoardSize::new(width, height);
+            // the other starting squares are mirror images of these. Proving that there is no tour means trying every path,
+            // which takes both searches a while from the squares of the larger boards that have none, so those only start in the corner
+            let quadrant = (0..width.div_ceil(2)).flat_map(|col| (0..height.div_ceil(2)).map(move |row| BoardPos::new(col, row)));
+            let starts: Vec<_> = if size.area() <= 20 { quadrant.collect() } else { vec![BoardPos::ZERO] };
+            for start in starts {
+                let expected = common::tour_exists(size, start);
+                match warnsdorff_tour(size, start) {
+                    Some(tour) => {
+                        assert!(expected, "{size} from {start}: found a tour where the oracle finds none");
+                        common::verify_tour(&tour, size, false).unwrap_or_else(|e| panic!("{size} from {start}: {e}"));
+                        assert_eq!(tour.start(), start, "{size}");
+                    },
+                    None => assert!(!expected, "{size} from {start}: the oracle finds a tour, Warnsdorff doesn't"),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_oracle() {
+    // the boards with and without tours are well known
+    assert!(common::tour_exists(BoardSize::new(1, 1), BoardPos::ZERO));
+    assert!(common::tour_exists(BoardSize::new(5, 5), BoardPos::ZERO));
+    assert!(common::tour_exists(BoardSize::new(3, 4), BoardPos::ZERO));
+    assert!(!common::tour_exists(BoardSize::new(4, 4), BoardPos::ZERO));
+    assert!(!common::tour_exists(BoardSize::new(3, 5), BoardPos::new(1, 2)));
+    // the knight alternates colors, so on an odd board it has to start on the color there is more of
+    assert!(!common::tour_exists(BoardSize::new(5, 5), BoardPos::new(1, 0)));
+}
@@ -0,0 +1,182 @@
+//! Solves a few boards once per output format and checks that all formats describe the same tour.
+//! Every output format should be part of this comparison
+
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+type Square = (i64, i64);
+type Move = (Square, Square);
+
+struct Case {
+    args: &'static [&'static str],
+    width: i64,
+    height: i64,
+    wrap: bool,
+}
+
+const CASES: [Case; 6] = [
+    Case { args: &["-s", "8"], width: 8, height: 8, wrap: false },
+    Case { args: &["-s", "9x10"], width: 9, height: 10, wrap: false },
+    Case { args: &["-s", "5", "-w"], width: 5, height: 5, wrap: false },
+    Case { args: &["-s", "12", "--corner-radius", "5"], width: 12, height: 12, wrap: false },
+    Case { args: &["-s", "7x6", "--wrap", "both"], width: 7, height: 6, wrap: true },
+    // two columns across the board or across the edge lead to the same square
+    Case { args: &["-s", "4x5", "--wrap", "both"], width: 4, height: 5, wrap: true },
+];
+
+// single arrows are steep moves (two rows, one column), double arrows are shallow moves (one row, two columns)
+const GLYPHS: [(char, Square); 8] = [
+    ('↗', (1, -2)), ('⇗', (2, -1)), ('⇘', (2, 1)), ('↘', (1, 2)),
+    ('↙', (-1, 2)), ('⇙', (-2, 1)), ('⇖', (-2, -1)), ('↖', (-1, -2)),
+];
+
+fn render(case: &Case, format: &str) -> String {
+    let (stdout, _, code) = common::run(&[case.args, &["-O", format, "--no-summary"]].concat());
+    assert_eq!(code, Some(0), "{:?} -O {format} failed", case.args);
+    stdout
+}
+
+/// The number in each square of the text board, None for squares left blank
+fn parse_text(text: &str, case: &Case) -> HashMap<Square, Option<usize>> {
+    let lines: Vec<&str> = text.lines().filter(|line| line.contains(['|', '+'])).collect();
+    let stride = lines.iter().map(|line| line.len()).max().unwrap() as i64 / case.width;
+    let rows: Vec<&str> = lines.into_iter().filter(|line| line.contains('|')).collect();
+    assert_eq!(rows.len() as i64, case.height, "{text}");
+
+    let mut res = HashMap::new();
+    for (row, line) in (0..).zip(rows) {
+        let line = format!("{line:<0$}", (stride * case.width) as usize);
+        for col in 0..case.width {
+            let cell = line[(col * stride) as usize + 1..((col + 1) * stride) as usize].trim();
+            res.insert((col, row), (!cell.is_empty()).then(|| cell.parse().unwrap_or_else(|_| panic!("{cell:?} in {line}"))));
+        }
+    }
+
+    res
+}
+
+/// The glyph of each square of the arrows grid
+fn parse_arrows(text: &str, case: &Case) -> HashMap<Square, char> {
+    let rows: Vec<&str> = text.lines().skip_while(|line| !line.starts_with("S start")).skip(1).collect();
+    assert_eq!(rows.len() as i64, case.height, "{text}");
+
+    let mut res = HashMap::new();
+    for (row, line) in (0..).zip(rows) {
+        let mut glyphs = line.chars();
+        for col in 0..case.width {
+            res.insert((col, row), glyphs.next().unwrap_or(' '));
+        }
+        assert_eq!(glyphs.next(), None, "{line}");
+    }
+
+    res
+}
+
+/// The segments of the SVG: the move they belong to, their end points in squares and whether they are half of a move across a seam
+fn parse_svg(svg: &str) -> Vec<(Move, Move, bool)> {
+    let attr = |line: &str, name: &str| -> String {
+        let start = line.find(&format!(" {name}=\"")).unwrap_or_else(|| panic!("no {name} in {line}")) + name.len() + 3;
+        line[start..].split('"').next().unwrap().to_string()
+    };
+    // squares are 10px, their centers 15px from the left edge and 25px from the top edge of the document
+    let square = |line: &str, x: &str, y: &str| {
+        let (x, y): (i64, i64) = (attr(line, x).parse().unwrap(), attr(line, y).parse().unwrap());
+        assert_eq!(((x - 15) % 10, (y - 25) % 10), (0, 0), "{line}");
+        ((x - 15) / 10, (y - 25) / 10)
+    };
+
+    svg.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("<line "))
+        .map(|line| {
+            let name = attr(line, "data-move");
+            let (from, to) = name.split_once('-').unwrap();
+            let seam = line.contains("stroke-dasharray");
+            ((parse_square(from), parse_square(to)), (square(line, "x1", "y1"), square(line, "x2", "y2")), seam)
+        })
+        .collect()
+}
+
+/// Reads a square as written in the output, e.g. "AA12"
+fn parse_square(name: &str) -> Square {
+    let split = name.find(|c: char| c.is_ascii_digit()).unwrap();
+    let col = name[..split].bytes().fold(0, |col, letter| col * 26 + (letter - b'A') as i64 + 1);
+    (col - 1, name[split..].parse::<i64>().unwrap() - 1)
+}
+
+/// The offsets of the knight's moves from one square to the other. A move across the board comes first, and
+/// is the only one returned, as it is preferred over moves across an edge that lead to the same square
+fn knight_offsets(from: Square, to: Square, case: &Case) -> Vec<Square> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let is_move = |(dx, dy): &Square| matches!((dx.abs(), dy.abs()), (1, 2) | (2, 1));
+    if is_move(&(dx, dy)) {
+        return vec![(dx, dy)];
+    }
+
+    let candidates = |d: i64, len: i64| if case.wrap { vec![d, d - len, d + len] } else { vec![d] };
+    candidates(dx, case.width).into_iter()
+        .flat_map(|dx| candidates(dy, case.height).into_iter().map(move |dy| (dx, dy)))
+        .filter(is_move)
+        .collect()
+}
+
+#[test]
+fn test_formats_agree() {
+    for case in &CASES {
+        let context = format!("{:?}", case.args);
+
+        // the text board numbers the squares of the tour
+        let numbers = parse_text(&render(case, "text"), case);
+        let mut tour: Vec<(usize, Square)> = numbers.iter().filter_map(|(square, number)| number.map(|number| (number, *square))).collect();
+        tour.sort();
+        let tour: Vec<Square> = tour.into_iter().enumerate()
+            .map(|(idx, (number, square))| {
+                assert_eq!(number, idx + 1, "{context}: numbers skipped or repeated");
+                square
+            })
+            .collect();
+        let dead: HashSet<Square> = numbers.iter().filter(|(_, number)| number.is_none()).map(|(square, _)| *square).collect();
+        assert_eq!(tour.len() + dead.len(), (case.width * case.height) as usize, "{context}");
+        let (start, end) = (tour[0], tour[tour.len() - 1]);
+
+        // one segment per move, or two for moves across a seam
+        let svg = render(case, "svg");
+        let segments = parse_svg(&svg);
+        let moves: HashSet<Move> = segments.iter().map(|(mv, _, _)| *mv).collect();
+        let closed = moves.contains(&(end, start));
+        let expected: HashSet<Move> = tour.windows(2).map(|pair| (pair[0], pair[1])).chain(closed.then_some((end, start))).collect();
+        assert_eq!(moves, expected, "{context}: the SVG draws different moves than the text board numbers");
+        for ((from, to), (first, second), seam) in &segments {
+            assert!(!dead.contains(from) && !dead.contains(to), "{context}: {from:?} -> {to:?} touches a blank square");
+            if *seam {
+                assert!(knight_offsets(*from, *to, &Case { wrap: false, ..*case }).is_empty(), "{context}: {from:?} -> {to:?} is drawn across a seam, but stays on the board");
+                assert!(first == from || second == to, "{context}: neither end of the seam segment {first:?} -> {second:?} is on {from:?} -> {to:?}");
+            } else {
+                assert_eq!((first, second), (from, to), "{context}: segment drawn in the wrong place");
+            }
+        }
+        let filled: HashSet<Square> = svg.split(" data-dead=\"").skip(1).map(|rest| parse_square(rest.split('"').next().unwrap())).collect();
+        assert_eq!(filled, dead, "{context}: the SVG fills in different squares than the text board leaves blank");
+
+        // the arrows point along the same moves, with the start and (for open tours) the end marked
+        let glyphs = parse_arrows(&render(case, "arrows"), case);
+        for (idx, square) in tour.iter().enumerate() {
+            let glyph = glyphs[square];
+            let next = tour.get(idx + 1).copied().or(closed.then_some(start));
+            match (idx, next) {
+                (0, _) => assert_eq!(glyph, 'S', "{context}: {square:?}"),
+                (_, None) => assert_eq!(glyph, 'E', "{context}: {square:?}"),
+                (_, Some(next)) => {
+                    let offsets = knight_offsets(*square, next, case);
+                    assert!(!offsets.is_empty(), "{context}: {square:?} -> {next:?} is no knight's move");
+                    let offset = GLYPHS.iter().find(|(g, _)| *g == glyph).map(|(_, o)| *o);
+                    assert!(offset.is_some_and(|offset| offsets.contains(&offset)), "{context}: {glyph} on {square:?}, expected one of {offsets:?}");
+                },
+            }
+        }
+        for square in &dead {
+            assert_eq!(glyphs[square], ' ', "{context}: {square:?}");
+        }
+    }
+}
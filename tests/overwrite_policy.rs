@@ -0,0 +1,106 @@
+mod common;
+
+use common::{run, temp_dir::TempDir};
+
+#[test]
+fn test_clobber_is_the_default() {
+    let dir = TempDir::new("overwrite_policy_clobber");
+    let file = dir.join("tour.txt");
+    std::fs::write(&file, "old").unwrap();
+    let (_, _, code) = run(&["-s", "8", "-o", file.to_str().unwrap()]);
+    let contents = std::fs::read_to_string(&file).unwrap();
+
+    assert_eq!(code, Some(0));
+    assert!(contents.contains("Elapsed time"), "{contents}");
+}
+
+#[test]
+fn test_error_refuses_before_solving() {
+    let dir = TempDir::new("overwrite_policy_error");
+    let file = dir.join("tour.txt");
+    std::fs::write(&file, "old").unwrap();
+    let (stdout, stderr, code) = run(&["-s", "8", "-o", file.to_str().unwrap(), "--overwrite-policy", "error"]);
+    let contents = std::fs::read_to_string(&file).unwrap();
+
+    assert_eq!(code, Some(1));
+    assert_eq!(contents, "old");
+    assert!(stderr.contains("already exists"), "{stderr}");
+    // nothing was solved: no iterations in the summary and no elapsed time
+    assert!(stderr.contains("result=error reason=output-exists\n"), "{stderr}");
+    assert_eq!(stdout, "");
+
+    // a new file is written as usual
+    let new_file = dir.join("new.txt");
+    let (_, _, code) = run(&["-s", "8", "-o", new_file.to_str().unwrap(), "--overwrite-policy", "error"]);
+    let written = new_file.exists();
+    assert_eq!(code, Some(0));
+    assert!(written);
+}
+
+#[test]
+fn test_backup_keeps_the_old_file() {
+    let dir = TempDir::new("overwrite_policy_backup");
+    let file = dir.join("tour.txt");
+    std::fs::write(&file, "old").unwrap();
+    std::fs::write(dir.join("tour.txt.bak-1"), "older").unwrap();
+    let (_, _, code) = run(&["-s", "8", "-o", file.to_str().unwrap(), "--overwrite-policy", "backup"]);
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+    let (older, old, new) = (read("tour.txt.bak-1"), read("tour.txt.bak-2"), read("tour.txt"));
+
+    assert_eq!(code, Some(0));
+    assert_eq!((older.as_str(), old.as_str()), ("older", "old"));
+    assert!(new.contains("Elapsed time"), "{new}");
+}
+
+#[test]
+fn test_skip_solves_nothing() {
+    let dir = TempDir::new("overwrite_policy_skip");
+    let file = dir.join("tour.txt");
+    std::fs::write(&file, "old").unwrap();
+    let (stdout, stderr, code) = run(&["-s", "8", "-o", file.to_str().unwrap(), "--overwrite-policy", "skip"]);
+    let contents = std::fs::read_to_string(&file).unwrap();
+
+    assert_eq!(code, Some(0));
+    assert_eq!(contents, "old");
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("already exists"), "{stderr}");
+    assert!(stderr.contains("result=skipped reason=output-exists\n"), "{stderr}");
+}
+
+#[test]
+fn test_unwritable_file_fails_before_solving() {
+    let dir = TempDir::new("overwrite_policy_unwritable");
+    let file = dir.join("missing").join("tour.txt");
+    let (stdout, stderr, code) = run(&["-s", "8", "-o", file.to_str().unwrap()]);
+
+    assert_eq!(code, Some(1));
+    assert!(stderr.contains(&format!("Could not create {}: ", file.display())), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+    // nothing was solved
+    assert!(stderr.contains("result=error reason=output-file\n"), "{stderr}");
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_batch_policies() {
+    let dir = TempDir::new("overwrite_policy_batch");
+    let (batch, out) = (dir.join("sizes.txt"), dir.join("out"));
+    std::fs::write(&batch, "8\n10\n").unwrap();
+    std::fs::create_dir_all(&out).unwrap();
+    std::fs::write(out.join("10x10.txt"), "old").unwrap();
+    let args = |policy: &'static str| ["--batch", batch.to_str().unwrap(), "--output-dir", out.to_str().unwrap(), "--overwrite-policy", policy];
+
+    // error checks every file before solving the first board
+    let (_, stderr, code) = run(&args("error"));
+    assert_eq!(code, Some(1));
+    assert!(stderr.contains("10x10.txt already exists"), "{stderr}");
+    assert!(!out.join("8x8.txt").exists());
+
+    let (_, stderr, code) = run(&args("skip"));
+    let (written, kept) = (out.join("8x8.txt").exists(), std::fs::read_to_string(out.join("10x10.txt")).unwrap());
+    assert_eq!(code, Some(0));
+    assert!(written);
+    assert_eq!(kept, "old");
+    assert!(stderr.contains("10x10: "), "{stderr}");
+    assert!(stderr.contains("result=solved solver=dnc boards=2 solved=1 skipped=1 "), "{stderr}");
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use knights_tour::{solve_divide_and_conquer, tour_exists, Args, BoardSize, Feasibility, Random, Tour};
+
+fn solve(size: BoardSize) -> Tour {
+    solve_with(size, &[])
+}
+
+fn solve_with(size: BoardSize, extra: &[&str]) -> Tour {
+    let size_arg = size.to_string();
+    let args = Args::parse_from([&["knights_tour", "-s", &size_arg][..], extra].concat());
+    let (_, graph) = solve_divide_and_conquer(args.input).unwrap_or_else(|| panic!("no tour of the {size} board"));
+    Tour::from_graph(&graph).unwrap_or_else(|defect| panic!("{size}: {defect}"))
+}
+
+#[test]
+fn test_divide_and_conquer_tours() {
+    // the same sizes every run, so that a failure can be reproduced
+    let mut random = Random::new(0x6b6e_6967_6874);
+    for _ in 0..24 {
+        let size = BoardSize::new(5 + random.below(8) as u32, 5 + random.below(8) as u32);
+        // every board this size with a closed tour gets one
+        let closed = matches!(tour_exists(size, true), Feasibility::Possible);
+        common::verify_tour(&solve(size), size, closed).unwrap_or_else(|e| panic!("{size}: {e}"));
+    }
+}
+
+#[test]
+fn test_divide_and_conquer_sizes() {
+    // boards with one side short enough to be a single sector and the other long enough to be cut
+    for (width, height) in [(5, 11), (6, 12), (12, 7), (11, 10), (12, 12)] {
+        let size = BoardSize::new(width, height);
+        let closed = matches!(tour_exists(size, true), Feasibility::Possible);
+        common::verify_tour(&solve(size), size, closed).unwrap_or_else(|e| panic!("{size}: {e}"));
+    }
+}
+
+#[test]
+fn test_divide_and_conquer_solve_orders() {
+    for size in [BoardSize::new(30, 26), BoardSize::new(37, 29)] {
+        let closed = matches!(tour_exists(size, true), Feasibility::Possible);
+        for order in ["default", "spiral", "center-out", "random"] {
+            let tour = solve_with(size, &["--solve-order", order]);
+            common::verify_tour(&tour, size, closed).unwrap_or_else(|e| panic!("{size} {order}: {e}"));
+        }
+
+        // the random order is the same for the same seed
+        let seeded = || solve_with(size, &["--solve-order", "random", "--random-seed", "7"]).iter().collect::<Vec<_>>();
+        assert_eq!(seeded(), seeded(), "{size}");
+    }
+}
+
@@ -0,0 +1,31 @@
+mod common;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> (String, bool) {
+    let (stdout, _, code) = common::run(&[args, &["--no-summary"]].concat());
+    (stdout, code == Some(0))
+}
+
+#[test]
+fn test_quiet_still_writes_output_file() {
+    let dir = TempDir::new("quiet_test");
+    let file = dir.join("tour.svg");
+
+    let (stdout, success) = run(&["-s", "8", "-q", "-o", file.to_str().unwrap()]);
+    let svg = std::fs::read_to_string(&file);
+
+    assert!(success);
+    assert!(svg.unwrap().contains("<svg"));
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "{stdout}");
+    assert!(lines[0].contains("Elapsed time"), "{stdout}");
+}
+
+#[test]
+fn test_quiet_without_output_file() {
+    let (stdout, success) = run(&["-s", "8", "-q"]);
+    assert!(success);
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    assert!(stdout.contains("Elapsed time"), "{stdout}");
+}
@@ -0,0 +1,47 @@
+//! The same input has to give the same output byte for byte, run after run, so that it can be compared with golden files.
+//! Sets and maps of squares hash differently every time, anything listing them has to put them in order first. After an
+//! intended change to the SVG output, regenerate the files in tests/golden with `render` for the arguments of `test_svg_golden`
+
+mod common;
+
+use std::time::Duration;
+
+use knights_tour::{render_svg, solve_divide_and_conquer, solve_warnsdorff, Args, Precision, SvgStyle, Tour};
+
+/// The tour as SVG, without the time it took to find
+fn render(args: &[&str]) -> String {
+    let input = Args::parse_from(["knights_tour"].iter().chain(args)).input;
+    let solve = if input.use_warnsdorff { solve_warnsdorff } else { solve_divide_and_conquer };
+    let (_, graph) = solve(input).unwrap_or_else(|| panic!("no tour for {args:?}"));
+    let mut buf = Vec::new();
+    render_svg(&mut buf, &Tour::from_graph(&graph).unwrap(), Duration::ZERO, Precision::Seconds, None, &SvgStyle::default(), None).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_svg() {
+    for args in [&["-s", "12", "--corner-radius", "5"][..], &["-s", "16", "--dead-squares", "C3,D5,H8"], &["-w", "-s", "8", "--dead-squares", "C3,D5"]] {
+        assert_eq!(render(args), render(args), "{args:?}");
+    }
+}
+
+#[test]
+fn test_svg_golden() {
+    // moves crossing the seams of the torus are drawn in two dashed halves
+    assert_eq!(render(&["--wrap", "both", "-s", "6"]), include_str!("golden/svg_6x6_torus.svg"));
+    assert_eq!(render(&["-w", "-s", "8", "--dead-squares", "C3,D5"]), include_str!("golden/svg_8x8_dead_squares.svg"));
+}
+
+#[test]
+fn test_debug_output() {
+    // lists the moves stretched tours have to keep, and which of them each move is checked against
+    let debug_output = || {
+        let (_, stderr, code) = common::run(&["-w", "-s", "8", "--mode", "stretched-h", "-vvv", "--no-summary"]);
+        assert_eq!(code, Some(0));
+        stderr
+    };
+
+    let first = debug_output();
+    assert!(first.contains("Preconnected moves: {BoardPos(5, 0): {BoardPos(7, 1)}, "), "{first}");
+    assert_eq!(first, debug_output());
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> (String, bool) {
+    let (_, stderr, code) = common::run(&[args, &["--no-summary", "-q"]].concat());
+    (stderr, code == Some(0))
+}
+
+#[test]
+fn test_resume_after_giving_up() {
+    // Warnsdorff takes back tens of thousands of moves before finding the tour of this board from C3
+    let dir = TempDir::new("resume");
+    let dump = dir.join("dump.txt");
+    let dump_arg = dump.to_str().unwrap();
+    let (_, success) = run(&["-w", "-s", "6x5", "-p", "C3", "--exhaustive-limit", "0", "--backtrack-limit", "1000", "--failure-dump", dump_arg]);
+    assert!(!success);
+    assert!(std::fs::read_to_string(&dump).unwrap().starts_with("C3,"));
+
+    // the resumed moves may be taken back, so the search still finds the tour
+    let (stderr, success) = run(&["-w", "-s", "6x5", "--exhaustive-limit", "0", "--resume", dump_arg, "--verify"]);
+    assert!(success, "{stderr}");
+
+    // the dump doesn't fit other boards
+    let (stderr, success) = run(&["-w", "-s", "6", "--resume", dump_arg]);
+    assert!(!success);
+    assert!(stderr.contains("it was written for a 6x5 board, not a 6x6 one"), "{stderr}");
+
+    let (stderr, success) = run(&["-w", "-s", "6x5", "--dead-squares", "A1", "--resume", dump_arg]);
+    assert!(!success);
+    assert!(stderr.contains("(A1) is not an accessible square"), "{stderr}");
+}
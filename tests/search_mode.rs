@@ -0,0 +1,44 @@
+mod common;
+
+fn run(args: &[&str]) -> (String, String, Option<i32>) {
+    common::run(&[args, &["--no-summary"]].concat())
+}
+
+/// The rows of the arrows output, without the elapsed time and the legend
+fn arrows(args: &[&str]) -> Vec<String> {
+    let (stdout, stderr, code) = run(&[args, &["-w", "-O", "arrows", "--verify"]].concat());
+    assert_eq!(code, Some(0), "{stderr}");
+    stdout.lines().skip(3).map(str::to_string).collect()
+}
+
+#[test]
+fn test_structured_tours() {
+    // the ends the sectors of divide and conquer are merged through
+    let rows = arrows(&["-s", "40x6", "--mode", "stretched-h"]);
+    assert!(rows[0].starts_with('S') && rows[1].starts_with('E'), "{rows:?}");
+    let rows = arrows(&["-s", "10x6", "--mode", "stretched-v"]);
+    assert!(rows[0].starts_with("SE"), "{rows:?}");
+
+    let (_, stderr, code) = run(&["-w", "-s", "6", "--mode", "closed", "--fail-on-open"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    // an odd number of squares leaves out the corner
+    let rows = arrows(&["-s", "5", "--mode", "closed", "--fail-on-open"]);
+    assert!(rows[0].starts_with(' '), "{rows:?}");
+}
+
+#[test]
+fn test_structured_conflicts() {
+    for extra in [&["-p", "B2"][..], &["-c", "2"], &["--wrap", "both"], &["--piece", "camel"], &["--lookahead", "2"]] {
+        let (_, stderr, code) = run(&[&["-w", "-s", "8", "--mode", "stretched-h"][..], extra].concat());
+        assert_eq!(code, Some(2), "{extra:?}");
+        assert!(stderr.contains("can't be combined"), "{stderr}");
+    }
+
+    let (_, stderr, code) = run(&["-w", "--mode", "closed"]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("requires '--board-size'"), "{stderr}");
+
+    // the explicit default is fine
+    let (_, _, code) = run(&["-w", "-s", "8", "--mode", "basic", "-p", "B2", "-q"]);
+    assert_eq!(code, Some(0));
+}
@@ -0,0 +1,24 @@
+//! Compares tours Warnsdorff only finds after backtracking a lot with the files in tests/golden, so that changes to the order
+//! in which it tries its moves show up in review. After an intended change, regenerate them with e.g.
+//! `knights_tour -w -s 6x5 -p C3 --exhaustive-limit 0 --no-summary | tail -n +3 > tests/golden/warnsdorff_6x5_c3.txt`
+
+mod common;
+
+/// The text board, without the elapsed time and the empty line after it
+fn tour(args: &[&str]) -> String {
+    let (stdout, _, code) = common::run(&[args, &["-w", "--exhaustive-limit", "0", "--no-summary"]].concat());
+    assert_eq!(code, Some(0), "{args:?} failed");
+    stdout.lines().skip(2).map(|line| format!("{line}\n")).collect()
+}
+
+#[test]
+fn test_6x5_from_c3() {
+    // 63436 backtracks
+    assert_eq!(tour(&["-s", "6x5", "-p", "C3"]), include_str!("golden/warnsdorff_6x5_c3.txt"));
+}
+
+#[test]
+fn test_8x5_from_b3_looking_ahead() {
+    // 13144 backtracks
+    assert_eq!(tour(&["-s", "8x5", "-p", "B3", "--lookahead", "2"]), include_str!("golden/warnsdorff_8x5_b3_lookahead_2.txt"));
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::temp_dir::TempDir;
+
+fn run(args: &[&str]) -> (String, String, bool) {
+    let (stdout, stderr, code) = common::run(&[args, &["--no-summary"]].concat());
+    (stdout, stderr, code == Some(0))
+}
+
+#[test]
+fn test_failed_sector_keeps_stdout_clean() {
+    // the 10x3 sector at K18 can't be solved around the cut off corner, so it is solved as if the corner were there
+    let (stdout, _, success) = run(&["-s", "20", "-c", "0 0 10 0"]);
+    assert!(success);
+    assert!(stdout.starts_with("\u{1F4A9} Elapsed time"), "{stdout}");
+    assert!(!stdout.contains("No knight's tour"), "{stdout}");
+
+    // the failure is still there for those who ask, naming the sector rather than the board
+    let (_, stderr, success) = run(&["-s", "20", "-c", "0 0 10 0", "-v", "-q"]);
+    assert!(success);
+    assert!(stderr.contains("Could not solve the 10x3 sector at K18 around its dead squares: every move was tried."), "{stderr}");
+}
+
+#[test]
+fn test_failed_board_is_reported() {
+    // the user's own board still gets its message on stdout, and running out of moves is no longer passed off as a tour
+    let dir = TempDir::new("sector_failures");
+    let path = dir.join("board.txt");
+    std::fs::write(&path, "####\n####\n## #\n####\n").unwrap();
+    let (stdout, _, success) = run(&["-f", path.to_str().unwrap(), "-b", "text", "--exhaustive-limit", "0"]);
+
+    assert!(!success);
+    assert!(stdout.starts_with("No knight's tour for this board configuration: every move was tried."), "{stdout}");
+}
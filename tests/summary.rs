@@ -0,0 +1,63 @@
+mod common;
+
+use std::collections::HashMap;
+
+/// Runs the program and splits the last line on stderr into its key=value pairs
+fn run(args: &[&str]) -> HashMap<String, String> {
+    let (_, stderr, _) = common::run(args);
+    let line = stderr.lines().last().unwrap_or_else(|| panic!("no summary line for {args:?}"));
+
+    line.split(' ')
+        .map(|field| {
+            let (key, value) = field.split_once('=').unwrap_or_else(|| panic!("not a key=value pair: {field}"));
+            (key.to_string(), value.to_string())
+        })
+        .collect()
+}
+
+fn assert_number(summary: &HashMap<String, String>, key: &str) {
+    let value = summary.get(key).unwrap_or_else(|| panic!("{key} missing from {summary:?}"));
+    assert!(value.parse::<u64>().is_ok(), "{key}={value} is not a number");
+}
+
+#[test]
+fn test_solved() {
+    let summary = run(&["-s", "8", "-q"]);
+    assert_eq!(summary["result"], "solved");
+    assert_eq!(summary["size"], "8x8");
+    assert_eq!(summary["solver"], "dnc");
+    assert_eq!(summary["closed"], "true");
+    for key in ["duration_ms", "iterations", "backtracks"] {
+        assert_number(&summary, key);
+    }
+
+    let summary = run(&["-s", "6", "-w", "-p", "random", "--random-seed", "42", "-q"]);
+    assert_eq!(summary["solver"], "warnsdorff");
+    assert_eq!(summary["seed"], "42");
+}
+
+#[test]
+fn test_infeasible() {
+    let summary = run(&["-s", "4", "-q"]);
+    assert_eq!(summary["result"], "infeasible");
+    assert_eq!(summary["reason"], "small-board");
+    assert_number(&summary, "iterations");
+
+    // 5x5 isn't ruled out by its size, only by the color of the starting square
+    let summary = run(&["-s", "5", "-w", "-p", "B1", "-q"]);
+    assert_eq!(summary["result"], "infeasible");
+    assert_eq!(summary["reason"], "exhaustive-search");
+}
+
+#[test]
+fn test_no_summary() {
+    let (_, stderr, _) = common::run(&["-s", "8", "-q", "--no-summary"]);
+    assert!(stderr.is_empty());
+}
+
+#[test]
+fn test_prefer_closed() {
+    assert_eq!(run(&["-s", "8", "-w", "-q", "--verify"])["closed"], "false");
+    assert_eq!(run(&["-s", "8", "-w", "-q", "--verify", "--prefer-closed"])["closed"], "true");
+    assert_eq!(run(&["-s", "5", "-w", "-q", "--verify", "--prefer-closed"])["closed"], "false");
+}
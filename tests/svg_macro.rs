@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use svg_macro::svg;
+
+// the generated code escapes dynamic values with crate::svg::xml_escape, which the main crate provides
+mod svg {
+    pub fn xml_escape(value: &str) -> std::borrow::Cow<'_, str> {
+        value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").into()
+    }
+}
+
+fn render(f: impl FnOnce(&mut String) -> std::fmt::Result) -> String {
+    let mut buf = String::new();
+    f(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_inline_attribute_expression() {
+    let w = 3;
+    let svg = render(|writer| {
+        svg! { writer =>
+            <rect width=#(w * 10 + 2) height=#w />
+        };
+        Ok(())
+    });
+
+    assert_eq!(svg.trim(), "<rect width=\"32\" height=\"3\"/>");
+}
+
+#[test]
+fn test_expression_name_and_text() {
+    let names = ["x", "y"];
+    let svg = render(|writer| {
+        svg! { writer =>
+            <text #(names[1])="5" data-len=#(names.len())>#(names.join("+"))</text>
+        };
+        Ok(())
+    });
+
+    let lines: Vec<_> = svg.lines().map(str::trim).collect();
+    assert_eq!(lines, ["<text y=\"5\" data-len=\"2\">", "x+y", "</text>"]);
+}
+
+#[test]
+fn test_repeat_stays_a_repeat() {
+    let items = ["<a/>", "<b/>"];
+    let svg = render(|writer| {
+        svg! { writer =>
+            <g>
+                #(#items)*
+                #(items.iter().rev())*
+                #(items.len())
+            </g>
+        };
+        Ok(())
+    });
+
+    let lines: Vec<_> = svg.lines().map(str::trim).collect();
+    assert_eq!(lines, ["<g>", "<a/>", "<b/>", "<b/>", "<a/>", "2", "</g>"]);
+}
+
+#[test]
+fn test_repeat_any_display() {
+    struct Dot(u32);
+
+    impl std::fmt::Display for Dot {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "<circle r=\"{}\"/>", self.0)
+        }
+    }
+
+    let svg = render(|writer| {
+        svg! { writer =>
+            <g>
+                #((1..=2).map(Dot))*
+            </g>
+        };
+        Ok(())
+    });
+
+    let lines: Vec<_> = svg.lines().map(str::trim).collect();
+    assert_eq!(lines, ["<g>", "<circle r=\"1\"/>", "<circle r=\"2\"/>", "</g>"]);
+}
+
+#[test]
+fn test_values_are_escaped() {
+    let (title, class, markup) = ("a < b & c", "\"x\"", ["<!-- raw -->"]);
+    let svg = render(|writer| {
+        svg! { writer =>
+            <g class=#class data-len=#(title.len())>#title #(#markup)*</g>
+        };
+        Ok(())
+    });
+
+    let lines: Vec<_> = svg.lines().map(str::trim).collect();
+    assert_eq!(lines, ["<g class=\"&quot;x&quot;\" data-len=\"9\">", "a &lt; b &amp; c", "<!-- raw -->", "</g>"]);
+}
+
+#[test]
+fn test_raw_blocks() {
+    let block = |depth: usize| (0..depth).map(|n| format!("<step n=\"{n}\" />")).collect::<Vec<_>>().join("\n");
+    let raw = 1;
+    let svg = render(|writer| {
+        svg! { writer =>
+            <svg>
+                #raw(block(2))
+                <g>
+                    #raw("<!--\n\ncomment\n-->")
+                    <path />
+                </g>
+                #raw(String::new())
+                #raw
+                <g />
+            </svg>
+        };
+        Ok(())
+    });
+
+    let expected = [
+        "<svg>",
+        "    <step n=\"0\" />",
+        "    <step n=\"1\" />",
+        "    <g>",
+        "        <!--",
+        "",
+        "        comment",
+        "        -->",
+        "        <path/>",
+        "    </g>",
+        "    1",
+        "    <g/>",
+        "</svg>",
+        "",
+    ].join("\n");
+    assert_eq!(svg, expected);
+}
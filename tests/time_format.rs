@@ -0,0 +1,57 @@
+mod common;
+
+fn stdout(args: &[&str]) -> String {
+    let (stdout, _, code) = common::run(&[args, &["-s", "8", "--no-summary"]].concat());
+    assert_eq!(code, Some(0), "{args:?} failed");
+    stdout
+}
+
+#[test]
+fn test_text_board() {
+    let seconds = stdout(&[]);
+    assert!(seconds.lines().next().unwrap().ends_with(" seconds \u{1F4A9}"), "{seconds}");
+
+    let millis = stdout(&["--time-format", "millis"]);
+    assert!(millis.lines().next().unwrap().ends_with(" ms \u{1F4A9}"), "{millis}");
+}
+
+/// Whether the time is written the way --time-format human writes it, e.g. "42µs", "2.5ms", "1.5s" or "1h 2m 3.4s"
+fn is_human(time: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let tenths = |s: &str| s.split_once('.').is_some_and(|(whole, tenth)| digits(whole) && tenth.len() == 1 && digits(tenth));
+    let (units, last) = time.rsplit_once(' ').map_or((None, time), |(units, last)| (Some(units), last));
+    let last = match (last.strip_suffix("µs"), last.strip_suffix("ms"), last.strip_suffix('s')) {
+        (Some(micros), _, _) => digits(micros),
+        (_, Some(millis), _) => tenths(millis),
+        (_, _, Some(secs)) => tenths(secs),
+        _ => false,
+    };
+
+    // hours and minutes only come before seconds
+    last && units.is_none_or(|units| {
+        !time.ends_with("ms") && units.split(' ').all(|unit| unit.strip_suffix(['h', 'm']).is_some_and(digits))
+    })
+}
+
+#[test]
+fn test_is_human() {
+    for time in ["42µs", "0.1ms", "999.9ms", "1.0s", "1m 23.4s", "25h 1m 1.9s"] {
+        assert!(is_human(time), "{time}");
+    }
+
+    for time in ["", "ms", "0.12ms", "4.2µs", "1.5 seconds", "1m 2.0ms", "1x 2.0s", "1.0"] {
+        assert!(!is_human(time), "{time}");
+    }
+}
+
+#[test]
+fn test_quiet_and_svg() {
+    let quiet = stdout(&["-q", "--time-format", "human"]);
+    let time = quiet.trim_end().strip_prefix("\u{1F4A9} Elapsed time: ").and_then(|rest| rest.strip_suffix(" \u{1F4A9}"));
+    assert!(time.is_some_and(is_human), "{quiet}");
+
+    let svg = stdout(&["-O", "svg", "--time-format", "human"]);
+    let title = svg.lines().find(|line| line.contains("Elapsed time")).unwrap();
+    assert!(title.trim().strip_prefix("Elapsed time: ").is_some_and(is_human), "{svg}");
+    assert!(!svg.contains('\u{1F4A9}'), "{svg}");
+}